@@ -0,0 +1,55 @@
+#!/bin/bash
+
+# Integration test for the Enonomics provider: serve a snapshot from the stand-in
+# server, load it into the module as exports, then sync markets and trade routes
+DB_NAME="${DB_NAME:-worldsim}"
+STUB_PORT="${STUB_PORT:-8765}"
+STUB_URL="http://127.0.0.1:$STUB_PORT"
+
+echo "=== Testing Enonomics Integration ==="
+echo ""
+
+echo "1. Starting Enonomics stand-in server..."
+python3 world-simulation/enonomics-stub/server.py --port "$STUB_PORT" &
+STUB_PID=$!
+trap 'kill $STUB_PID 2>/dev/null' EXIT
+sleep 1
+
+load_export() {
+    local data_type="$1" identifier="$2" path="$3"
+    local json
+    json=$(curl -sf "$STUB_URL/$path") || { echo "   FAILED to fetch $path"; exit 1; }
+    docker-compose exec -T spacetimedb spacetime call $DB_NAME load_enonomics_export \
+        "\"$data_type\"" "\"$identifier\"" "$(printf '%s' "$json" | python3 -c 'import json,sys; print(json.dumps(sys.stdin.read()))')" 2>&1 | grep -v "WARNING"
+}
+
+echo ""
+echo "2. Loading city exports..."
+for city_id in 1 2 3 4 5; do
+    load_export city "$city_id" "city/$city_id"
+done
+
+echo ""
+echo "3. Loading region and trade route exports..."
+load_export region eno region/eno
+load_export trade_routes global trade_routes
+
+echo ""
+echo "4. Rejecting an export that breaks the schema..."
+docker-compose exec -T spacetimedb spacetime call $DB_NAME load_enonomics_export \
+    '"trade_routes"' '"global"' '"[{\"from\":\"1\",\"to\":\"1\",\"resource_type\":\"food\",\"volume\":1.0,\"importance\":0.5}]"' 2>&1 | grep -v "WARNING"
+
+echo ""
+echo "5. Syncing city 1 markets..."
+docker-compose exec -T spacetimedb spacetime call $DB_NAME sync_market_with_enonomics 1 1 2>&1 | grep -v "WARNING"
+
+echo ""
+echo "6. Generating trade routes..."
+docker-compose exec -T spacetimedb spacetime call $DB_NAME generate_trade_routes_from_enonomics 1 2>&1 | grep -v "WARNING"
+
+echo ""
+echo "7. Checking logs..."
+docker-compose exec -T spacetimedb spacetime logs $DB_NAME 2>&1 | grep -i "enonomics" | tail -20
+
+echo ""
+echo "=== Enonomics test complete ==="
@@ -0,0 +1,69 @@
+#!/usr/bin/env python3
+"""Stand-in for the Enonomics API, for integration tests.
+
+Serves an Enonomics snapshot over HTTP with the same shapes the module
+expects from exports:
+
+    GET /city/<id>       -> EnonomicsCity
+    GET /region/<id>     -> EnonomicsRegion
+    GET /trade_routes    -> [TradeConnection]
+
+Usage: server.py [--port 8765] [--snapshot ../data/enonomics/snapshot.json]
+"""
+
+import argparse
+import json
+import os
+from http.server import BaseHTTPRequestHandler, HTTPServer
+
+DEFAULT_SNAPSHOT = os.path.join(os.path.dirname(__file__), "..", "data", "enonomics", "snapshot.json")
+
+
+def make_handler(snapshot):
+    cities = {c["id"]: c for c in snapshot["cities"]}
+    regions = {r["id"]: r for r in snapshot["regions"]}
+
+    class Handler(BaseHTTPRequestHandler):
+        def do_GET(self):
+            parts = [p for p in self.path.split("/") if p]
+            body = None
+            if parts == ["trade_routes"]:
+                body = snapshot["trade_routes"]
+            elif len(parts) == 2 and parts[0] == "city":
+                body = cities.get(parts[1])
+            elif len(parts) == 2 and parts[0] == "region":
+                body = regions.get(parts[1])
+
+            if body is None:
+                self.send_error(404, "No Enonomics data at %s" % self.path)
+                return
+
+            payload = json.dumps(body).encode("utf-8")
+            self.send_response(200)
+            self.send_header("Content-Type", "application/json")
+            self.send_header("Content-Length", str(len(payload)))
+            self.end_headers()
+            self.wfile.write(payload)
+
+        def log_message(self, fmt, *args):
+            print("enonomics-stub: " + fmt % args)
+
+    return Handler
+
+
+def main():
+    parser = argparse.ArgumentParser(description=__doc__.splitlines()[0])
+    parser.add_argument("--port", type=int, default=8765)
+    parser.add_argument("--snapshot", default=DEFAULT_SNAPSHOT)
+    args = parser.parse_args()
+
+    with open(args.snapshot) as f:
+        snapshot = json.load(f)
+
+    server = HTTPServer(("127.0.0.1", args.port), make_handler(snapshot))
+    print("enonomics-stub: serving %s on port %d" % (args.snapshot, args.port))
+    server.serve_forever()
+
+
+if __name__ == "__main__":
+    main()
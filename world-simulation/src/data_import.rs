@@ -89,6 +89,19 @@ pub fn import_eno_cities_subset(
         // Create the city
         let city_id = create_city_from_eno(ctx, &eno_city, current_hour)?;
         imported_cities += 1;
+
+        // Temples become holy sites where faiths can be founded
+        if eno_city.temple {
+            crate::political::religion::consecrate_temple(
+                ctx,
+                city_id,
+                &eno_city.name,
+                eno_city.latitude as f32,
+                eno_city.longitude as f32,
+                current_hour,
+            )?;
+            imported_buildings += 1;
+        }
         
         // Create districts as building clusters
         let mut district_buildings = Vec::new();
@@ -226,8 +239,10 @@ fn create_city_from_eno(
     let city = City {
         id,
         name: eno_city.name.clone(),
-        founded_hour: current_hour - (eno_city.founded_in as u64 * 24 * 365), // Rough conversion
+        founded_hour: current_hour - (eno_city.founded_in as u64 * crate::natural::climate::HOURS_PER_CYCLE), // Rough conversion
         population: 0, // Will be set when individuals are created
+        latitude: eno_city.latitude as f32,
+        longitude: eno_city.longitude as f32,
         
         // Infrastructure based on Eno characteristics
         public_works: infrastructure.min(100.0),
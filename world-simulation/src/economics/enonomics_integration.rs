@@ -3,7 +3,12 @@
 use spacetimedb::{ReducerContext, Table};
 use serde::{Serialize, Deserialize};
 use log;
-use crate::economics::{ResourceType, Market};
+use std::collections::HashSet;
+use crate::economics::{ResourceType, Market, market, trade_route};
+use crate::tables::city::city;
+
+const CACHE_TTL_MS: i64 = 60 * 60 * 1000; // Cache for 1 hour
+const BUNDLED_SNAPSHOT: &str = include_str!("../../data/enonomics/snapshot.json");
 
 // Enonomics data structures
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -39,6 +44,85 @@ pub struct TradeConnection {
     pub importance: f64,
 }
 
+// A full Enonomics export, as bundled with the module or served by the stand-in server
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EnonomicsSnapshot {
+    pub cities: Vec<EnonomicsCity>,
+    pub regions: Vec<EnonomicsRegion>,
+    pub trade_routes: Vec<TradeConnection>,
+}
+
+impl EnonomicsCity {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.id.is_empty() || self.name.is_empty() {
+            return Err("city needs an id and a name".to_string());
+        }
+        if self.population == 0 {
+            return Err(format!("city {} has no population", self.id));
+        }
+        if !self.gdp.is_finite() || self.gdp < 0.0 {
+            return Err(format!("city {} has an invalid gdp", self.id));
+        }
+        if !(0.0..=100.0).contains(&self.unemployment_rate) {
+            return Err(format!("city {} unemployment_rate must be 0-100", self.id));
+        }
+        if !self.trade_volume.is_finite() || self.trade_volume < 0.0 {
+            return Err(format!("city {} has an invalid trade_volume", self.id));
+        }
+        if !(0.0..=1.0).contains(&self.wealth_index) {
+            return Err(format!("city {} wealth_index must be 0-1", self.id));
+        }
+        Ok(())
+    }
+}
+
+impl EnonomicsRegion {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.id.is_empty() || self.name.is_empty() {
+            return Err("region needs an id and a name".to_string());
+        }
+        if self.cities.is_empty() {
+            return Err(format!("region {} lists no cities", self.id));
+        }
+        for connection in &self.trade_routes {
+            connection.validate()
+                .map_err(|e| format!("region {}: {}", self.id, e))?;
+        }
+        Ok(())
+    }
+}
+
+impl TradeConnection {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.from.is_empty() || self.to.is_empty() || self.from == self.to {
+            return Err(format!("trade route {} -> {} needs two different ends", self.from, self.to));
+        }
+        if self.resource().is_none() {
+            return Err(format!("trade route {} -> {} has unknown resource_type {}", self.from, self.to, self.resource_type));
+        }
+        if !self.volume.is_finite() || self.volume < 0.0 {
+            return Err(format!("trade route {} -> {} has an invalid volume", self.from, self.to));
+        }
+        if !(0.0..=1.0).contains(&self.importance) {
+            return Err(format!("trade route {} -> {} importance must be 0-1", self.from, self.to));
+        }
+        Ok(())
+    }
+
+    pub fn resource(&self) -> Option<ResourceType> {
+        match self.resource_type.as_str() {
+            "food" => Some(ResourceType::Food),
+            "materials" => Some(ResourceType::RawMaterials),
+            "goods" => Some(ResourceType::ProcessedGoods),
+            "luxury" => Some(ResourceType::Luxury),
+            "knowledge" => Some(ResourceType::Knowledge),
+            "energy" => Some(ResourceType::Energy),
+            "military" => Some(ResourceType::Military),
+            _ => None,
+        }
+    }
+}
+
 // Cache for Enonomics data
 #[spacetimedb::table(name = enonomics_cache)]
 pub struct EnonomicsCache {
@@ -47,145 +131,213 @@ pub struct EnonomicsCache {
     pub cache_key: String, // City ID, region ID, or "global"
     pub data_type: String, // "city", "region", "trade_routes"
     pub data_json: String,
+    pub source: String,    // Provider the data came from
     pub last_updated_ms: i64,
     pub expires_ms: i64,
 }
 
-// Fetch Enonomics data (simulated - in real implementation would call API)
-#[spacetimedb::reducer]
-pub fn fetch_enonomics_data(
-    ctx: &ReducerContext,
-    data_type: String,
-    identifier: String,
-) -> Result<String, String> {
-    // Check cache first
-    if let Some(cached) = ctx.db.enonomics_cache()
-        .iter()
-        .find(|c| c.cache_key == identifier && c.data_type == data_type) {
+// Enonomics exports pushed into the module from files on disk
+#[spacetimedb::table(name = enonomics_export)]
+pub struct EnonomicsExport {
+    #[primary_key]
+    pub id: u32,
+    pub identifier: String,
+    pub data_type: String,
+    pub data_json: String,
+    pub loaded_ms: i64,
+}
 
-        let now = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .map_err(|e| format!("Time error: {}", e))?
-            .as_millis() as i64;
+// Where Enonomics data comes from; reducers cannot reach the network, so every provider
+// reads data that is already inside the module
+pub trait EnonomicsProvider {
+    fn name(&self) -> &'static str;
+    fn fetch(&self, ctx: &ReducerContext, data_type: &str, identifier: &str) -> Result<Option<String>, String>;
+}
 
-        if cached.expires_ms > now {
-            log::info!("Retrieved cached Enonomics data for {} {}", data_type, identifier);
-            return Ok(cached.data_json.clone());
-        }
+// Exports loaded through `load_enonomics_export`
+pub struct ExportProvider;
+
+impl EnonomicsProvider for ExportProvider {
+    fn name(&self) -> &'static str {
+        "export"
+    }
+
+    fn fetch(&self, ctx: &ReducerContext, data_type: &str, identifier: &str) -> Result<Option<String>, String> {
+        Ok(ctx.db.enonomics_export()
+            .iter()
+            .find(|e| e.data_type == data_type && e.identifier == identifier)
+            .map(|e| e.data_json))
     }
+}
 
-    // Fetch fresh data (simulated)
-    let data = match data_type.as_str() {
-        "city" => fetch_city_data(&identifier)?,
-        "region" => fetch_region_data(&identifier)?,
-        "trade_routes" => fetch_trade_routes_data()?,
-        _ => return Err("Unknown data type".to_string()),
-    };
+// The snapshot compiled into the module from data/enonomics
+pub struct SnapshotProvider;
 
-    // Cache the data
-    cache_enonomics_data(ctx, data_type, identifier, data.clone())?;
+impl EnonomicsProvider for SnapshotProvider {
+    fn name(&self) -> &'static str {
+        "snapshot"
+    }
 
-    Ok(data)
+    fn fetch(&self, _ctx: &ReducerContext, data_type: &str, identifier: &str) -> Result<Option<String>, String> {
+        let snapshot: EnonomicsSnapshot = serde_json::from_str(BUNDLED_SNAPSHOT)
+            .map_err(|e| format!("Bundled Enonomics snapshot is malformed: {}", e))?;
+
+        let data = match data_type {
+            "city" => snapshot.cities.iter()
+                .find(|c| c.id == identifier)
+                .map(serde_json::to_string),
+            "region" => snapshot.regions.iter()
+                .find(|r| r.id == identifier)
+                .map(serde_json::to_string),
+            "trade_routes" => Some(serde_json::to_string(&snapshot.trade_routes)),
+            _ => None,
+        };
+        data.transpose().map_err(|e| e.to_string())
+    }
 }
 
-// Simulated city data fetch
-fn fetch_city_data(city_id: &str) -> Result<String, String> {
-    // In real implementation, this would call Enonomics API
-    let city = EnonomicsCity {
-        id: city_id.to_string(),
-        name: format!("City {}", city_id),
-        population: 10000 + (city_id.len() as u32 * 5000),
-        gdp: 50000000.0 + (city_id.len() as f64 * 10000000.0),
-        unemployment_rate: 5.0 + (city_id.len() as f64 % 10.0),
-        trade_volume: 1000000.0 + (city_id.len() as f64 * 500000.0),
-        primary_industries: vec!["Manufacturing".to_string(), "Agriculture".to_string()],
-        trade_partners: vec!["partner1".to_string(), "partner2".to_string()],
-        wealth_index: 0.6 + (city_id.len() as f64 % 5.0) / 10.0,
-    };
-
-    serde_json::to_string(&city).map_err(|e| e.to_string())
+// Providers in order of preference
+fn providers() -> [&'static dyn EnonomicsProvider; 2] {
+    [&ExportProvider, &SnapshotProvider]
 }
 
-// Simulated region data fetch
-fn fetch_region_data(region_id: &str) -> Result<String, String> {
-    let region = EnonomicsRegion {
-        id: region_id.to_string(),
-        name: format!("Region {}", region_id),
-        cities: vec!["city1".to_string(), "city2".to_string(), "city3".to_string()],
-        total_population: 100000,
-        climate: "Temperate".to_string(),
-        natural_resources: vec!["Iron".to_string(), "Coal".to_string(), "Timber".to_string()],
-        trade_routes: vec![
-            TradeConnection {
-                from: "city1".to_string(),
-                to: "city2".to_string(),
-                resource_type: "food".to_string(),
-                volume: 1000.0,
-                importance: 0.8,
-            },
-        ],
-    };
-
-    serde_json::to_string(&region).map_err(|e| e.to_string())
+/// Check a payload against the schema for its data type
+pub fn validate_enonomics_data(data_type: &str, data_json: &str) -> Result<(), String> {
+    match data_type {
+        "city" => serde_json::from_str::<EnonomicsCity>(data_json)
+            .map_err(|e| format!("Malformed city data: {}", e))?
+            .validate(),
+        "region" => serde_json::from_str::<EnonomicsRegion>(data_json)
+            .map_err(|e| format!("Malformed region data: {}", e))?
+            .validate(),
+        "trade_routes" => {
+            let connections: Vec<TradeConnection> = serde_json::from_str(data_json)
+                .map_err(|e| format!("Malformed trade route data: {}", e))?;
+            connections.iter().try_for_each(|c| c.validate())
+        },
+        _ => Err(format!("Unknown data type {}", data_type)),
+    }
 }
 
-// Simulated trade routes data fetch
-fn fetch_trade_routes_data() -> Result<String, String> {
-    let routes = vec![
-        TradeConnection {
-            from: "city1".to_string(),
-            to: "city2".to_string(),
-            resource_type: "food".to_string(),
-            volume: 1000.0,
-            importance: 0.8,
+// Load an Enonomics export file; replaces any earlier export for the same key
+#[spacetimedb::reducer]
+pub fn load_enonomics_export(
+    ctx: &ReducerContext,
+    data_type: String,
+    identifier: String,
+    data_json: String,
+) -> Result<(), String> {
+    validate_enonomics_data(&data_type, &data_json)?;
+    let now = ctx.timestamp.to_micros_since_unix_epoch() / 1000;
+
+    match ctx.db.enonomics_export()
+        .iter()
+        .find(|e| e.data_type == data_type && e.identifier == identifier)
+    {
+        Some(mut export) => {
+            export.data_json = data_json;
+            export.loaded_ms = now;
+            ctx.db.enonomics_export().id().update(export);
         },
-        TradeConnection {
-            from: "city2".to_string(),
-            to: "city3".to_string(),
-            resource_type: "materials".to_string(),
-            volume: 500.0,
-            importance: 0.6,
+        None => {
+            let id = ctx.db.enonomics_export().iter().count() as u32 + 1;
+            ctx.db.enonomics_export().insert(EnonomicsExport {
+                id,
+                identifier: identifier.clone(),
+                data_type: data_type.clone(),
+                data_json,
+                loaded_ms: now,
+            });
         },
-    ];
+    }
 
-    serde_json::to_string(&routes).map_err(|e| e.to_string())
+    // New data supersedes whatever is cached
+    if let Some(mut cached) = ctx.db.enonomics_cache()
+        .iter()
+        .find(|c| c.cache_key == identifier && c.data_type == data_type)
+    {
+        cached.expires_ms = now;
+        ctx.db.enonomics_cache().id().update(cached);
+    }
+
+    log::info!("Loaded Enonomics {} export for {}", data_type, identifier);
+    Ok(())
 }
 
-// Cache Enonomics data
-fn cache_enonomics_data(
+/// Enonomics data from the cache, refreshed from the providers once it expires.
+/// Stale data is served if no provider has anything newer.
+pub fn fetch_enonomics_data(
     ctx: &ReducerContext,
     data_type: String,
     identifier: String,
-    data: String,
-) -> Result<(), String> {
-    let now = std::time::SystemTime::now()
-        .duration_since(std::time::UNIX_EPOCH)
-        .map_err(|e| format!("Time error: {}", e))?
-        .as_millis() as i64;
-
-    let cache_id = ctx.db.enonomics_cache().iter().count() as u32 + 1;
-    let expires_ms = now + (60 * 60 * 1000); // Cache for 1 hour
+) -> Result<String, String> {
+    let now = ctx.timestamp.to_micros_since_unix_epoch() / 1000;
 
-    // Remove old cache entry if exists
-    if let Some(old_cache) = ctx.db.enonomics_cache()
+    // Check cache first
+    let cached = ctx.db.enonomics_cache()
         .iter()
-        .find(|c| c.cache_key == identifier && c.data_type == data_type) {
-        // In a real implementation, you'd delete the old entry
-        log::info!("Updating existing cache for {} {}", data_type, identifier);
+        .find(|c| c.cache_key == identifier && c.data_type == data_type);
+    if let Some(cached) = &cached {
+        if cached.expires_ms > now {
+            log::info!("Retrieved cached Enonomics data for {} {}", data_type, identifier);
+            return Ok(cached.data_json.clone());
+        }
     }
 
-    let cache_entry = EnonomicsCache {
-        id: cache_id,
-        cache_key: identifier,
-        data_type,
-        data_json: data,
-        last_updated_ms: now,
-        expires_ms,
-    };
+    for provider in providers() {
+        let data = match provider.fetch(ctx, &data_type, &identifier)? {
+            Some(data) => data,
+            None => continue,
+        };
+        if let Err(e) = validate_enonomics_data(&data_type, &data) {
+            log::warn!("Rejected {} data from Enonomics {} provider: {}", data_type, provider.name(), e);
+            continue;
+        }
+
+        cache_enonomics_data(ctx, cached, data_type, identifier, data.clone(), provider.name(), now);
+        return Ok(data);
+    }
 
-    ctx.db.enonomics_cache().insert(cache_entry);
+    match cached {
+        Some(stale) => {
+            log::warn!("Serving expired Enonomics data for {} {}", data_type, identifier);
+            Ok(stale.data_json)
+        },
+        None => Err(format!("No Enonomics data for {} {}", data_type, identifier)),
+    }
+}
 
-    Ok(())
+// Cache Enonomics data, reusing the row for the same key
+fn cache_enonomics_data(
+    ctx: &ReducerContext,
+    existing: Option<EnonomicsCache>,
+    data_type: String,
+    identifier: String,
+    data: String,
+    source: &str,
+    now: i64,
+) {
+    match existing {
+        Some(mut cache_entry) => {
+            cache_entry.data_json = data;
+            cache_entry.source = source.to_string();
+            cache_entry.last_updated_ms = now;
+            cache_entry.expires_ms = now + CACHE_TTL_MS;
+            ctx.db.enonomics_cache().id().update(cache_entry);
+        },
+        None => {
+            let cache_id = ctx.db.enonomics_cache().iter().count() as u32 + 1;
+            ctx.db.enonomics_cache().insert(EnonomicsCache {
+                id: cache_id,
+                cache_key: identifier,
+                data_type,
+                data_json: data,
+                source: source.to_string(),
+                last_updated_ms: now,
+                expires_ms: now + CACHE_TTL_MS,
+            });
+        },
+    }
 }
 
 // Update market data based on Enonomics information
@@ -209,13 +361,12 @@ pub fn sync_market_with_enonomics(
     let markets: Vec<Market> = ctx.db.market()
         .iter()
         .filter(|m| m.world_id == world_id && m.city_id == city_id)
-        .cloned()
         .collect();
 
     for mut market in markets {
         // Adjust supply and demand based on Enonomics indicators
         let gdp_factor = (city_data.gdp / 100000000.0) as f32;
-        let unemployment_factor = (100.0 - city_data.unemployment_rate) / 100.0;
+        let unemployment_factor = (100.0 - city_data.unemployment_rate as f32) / 100.0;
         let wealth_factor = city_data.wealth_index as f32;
 
         // Update demand based on wealth and population
@@ -244,19 +395,19 @@ pub fn sync_market_with_enonomics(
         market.price_volatility = (0.3 + trade_factor * 0.4).clamp(0.1, 1.0);
 
         // Update the market
-        ctx.db.market().id().update(market.id, market);
+        ctx.db.market().id().update(market);
     }
 
     log::info!("Synced markets for city {} with Enonomics data", city_id);
     Ok(())
 }
 
-// Generate trade routes based on Enonomics data
+// Generate trade routes based on Enonomics data; routes already known are refreshed
 #[spacetimedb::reducer]
 pub fn generate_trade_routes_from_enonomics(
     ctx: &ReducerContext,
     world_id: u32,
-) -> Result<Vec<u32>, String> {
+) -> Result<(), String> {
     let trade_data_json = fetch_enonomics_data(
         ctx,
         "trade_routes".to_string(),
@@ -266,44 +417,89 @@ pub fn generate_trade_routes_from_enonomics(
     let trade_connections: Vec<TradeConnection> = serde_json::from_str(&trade_data_json)
         .map_err(|e| format!("Failed to parse trade data: {}", e))?;
 
-    let mut route_ids = Vec::new();
+    let mut created = 0;
+    let mut refreshed = 0;
 
     for connection in trade_connections {
-        let from_city_id: u32 = connection.from.parse()
-            .unwrap_or_else(|_| connection.from.len() as u32);
-        let to_city_id: u32 = connection.to.parse()
-            .unwrap_or_else(|_| connection.to.len() as u32);
-
-        let resource_type = match connection.resource_type.as_str() {
-            "food" => ResourceType::Food,
-            "materials" => ResourceType::RawMaterials,
-            "luxury" => ResourceType::Luxury,
-            _ => ResourceType::ProcessedGoods,
+        let (from_city_id, to_city_id) = match (resolve_city(ctx, &connection.from), resolve_city(ctx, &connection.to)) {
+            (Some(from), Some(to)) => (from, to),
+            _ => {
+                log::warn!("Skipping Enonomics trade route {} -> {}: unknown city", connection.from, connection.to);
+                continue;
+            },
         };
-
-        let route_id = ctx.db.trade_route().iter().count() as u32 + 1;
-
-        let trade_route = crate::economics::TradeRoute {
-            id: route_id,
-            world_id,
-            from_city_id,
-            to_city_id,
-            resource_type,
-            volume: connection.volume as f32,
-            frequency_hours: 24, // Daily trade
-            profitability: connection.importance as f32,
-            safety: 80.0, // Base safety level
-            merchant_count: (connection.volume / 100.0) as u32,
-            last_trade_hour: 0,
-            is_active: true,
+        let resource_type = match connection.resource() {
+            Some(resource_type) => resource_type,
+            None => continue,
         };
 
-        ctx.db.trade_route().insert(trade_route);
-        route_ids.push(route_id);
+        let existing = ctx.db.trade_route()
+            .iter()
+            .find(|r| r.world_id == world_id && r.from_city_id == from_city_id &&
+                r.to_city_id == to_city_id && r.resource_type == resource_type);
+
+        match existing {
+            Some(mut route) => {
+                route.volume = connection.volume as f32;
+                route.is_active = true;
+                ctx.db.trade_route().id().update(route);
+                refreshed += 1;
+            },
+            None => {
+                let route_id = ctx.db.trade_route().iter().count() as u32 + 1;
+
+                let trade_route = crate::economics::TradeRoute {
+                    id: route_id,
+                    world_id,
+                    from_city_id,
+                    to_city_id,
+                    resource_type,
+                    volume: connection.volume as f32,
+                    frequency_hours: 24, // Daily trade
+                    profitability: connection.importance as f32,
+                    safety: 80.0, // Base safety level
+                    merchant_count: 0, // Merchants sign up as the route proves itself
+                    last_trade_hour: 0,
+                    is_active: true,
+                };
+
+                ctx.db.trade_route().insert(trade_route);
+                created += 1;
+            },
+        }
+    }
+
+    log::info!("Generated {} trade routes from Enonomics data ({} refreshed)", created, refreshed);
+    Ok(())
+}
+
+// Enonomics refers to cities by simulation ID or by name
+fn resolve_city(ctx: &ReducerContext, key: &str) -> Option<u32> {
+    if let Ok(id) = key.parse::<u32>() {
+        return ctx.db.city().id().find(&id).map(|c| c.id);
     }
+    ctx.db.city()
+        .iter()
+        .find(|c| c.name.eq_ignore_ascii_case(key))
+        .map(|c| c.id)
+}
 
-    log::info!("Generated {} trade routes from Enonomics data", route_ids.len());
-    Ok(route_ids)
+/// Natural resources Enonomics lists for the region holding a city, from loaded exports or the bundled snapshot
+pub(crate) fn enonomics_natural_resources(ctx: &ReducerContext, city_id: u32) -> Vec<String> {
+    let exported: Vec<EnonomicsRegion> = ctx.db.enonomics_export()
+        .iter()
+        .filter(|e| e.data_type == "region")
+        .filter_map(|e| serde_json::from_str(&e.data_json).ok())
+        .collect();
+    let bundled = serde_json::from_str::<EnonomicsSnapshot>(BUNDLED_SNAPSHOT)
+        .map(|s| s.regions)
+        .unwrap_or_default();
+
+    exported.into_iter()
+        .chain(bundled)
+        .find(|r| r.cities.iter().any(|c| resolve_city(ctx, c) == Some(city_id)))
+        .map(|r| r.natural_resources)
+        .unwrap_or_default()
 }
 
 // Periodic sync with Enonomics (should be called regularly)
@@ -313,12 +509,10 @@ pub fn periodic_enonomics_sync(
     world_id: u32,
 ) -> Result<(), String> {
     // Get all cities in the world
-    let cities: Vec<u32> = ctx.db.market()
+    let cities: HashSet<u32> = ctx.db.market()
         .iter()
         .filter(|m| m.world_id == world_id)
         .map(|m| m.city_id)
-        .collect::<std::collections::HashSet<u32>>()
-        .into_iter()
         .collect();
 
     // Sync each city with Enonomics data
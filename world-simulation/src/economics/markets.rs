@@ -0,0 +1,707 @@
+// Order-book markets: traders post bids and asks, and each tick clears them at one price
+
+use spacetimedb::{ReducerContext, Table, SpacetimeType};
+use serde::{Serialize, Deserialize};
+use log;
+use std::collections::HashMap;
+use crate::economics::{Market, ResourceType, EconomicEventType, base_price, create_economic_event, market, merchant, trade_route};
+use crate::economics::trade_routes::{cargo_of, cargo_cost, cargo_weight, load_cargo, unload_cargo, unit_weight, CAPITAL_SHARE};
+use crate::political::faction;
+use crate::tables::building::{building, workplace_data};
+use crate::tables::individual::individual;
+use crate::tables::city::city;
+use crate::tables::agriculture::food_security;
+use crate::types::BuildingType;
+use crate::systems::modifiers::{farming, thresholds};
+use crate::reducers::storage::{deposit_stock, withdraw_stock};
+use crate::reducers::budget::{tax_rates, accrue_revenue};
+use crate::tables::budget::RevenueLine;
+
+const QUOTE_SPREAD: f32 = 0.1;              // How far agents quote from the last price, scaled by volatility
+const BOOK_PRESSURE: f32 = 0.5;             // Share of the spread the last book's excess supply or demand moves quotes
+const HOUSEHOLD_GOODS_NEED: f32 = 0.4;      // Goods are wanted but never urgent
+const HOUSEHOLD_GOODS_PER_DAY: f32 = 0.1;   // Per resident
+const RESTAURANT_FOOD_PER_DAY: f32 = 20.0;  // Market units
+const FACTION_ORDER_HOURS: u64 = 24;
+const MANIPULATION_SHARE: f32 = 0.4;        // One faction's share of a side that moves the market
+
+// Standing bid or ask in a city's market for one resource
+#[spacetimedb::table(name = market_order)]
+pub struct MarketOrder {
+    #[primary_key]
+    pub id: u32,
+    pub world_id: u32,
+    pub city_id: u32,
+    pub resource_type: ResourceType,
+    pub side: OrderSide,
+    pub trader: Trader,
+    pub quantity: f32,
+    pub remaining: f32,
+    pub limit_price: f32,
+    pub placed_hour: u64,
+    pub expires_hour: u64,
+    pub is_open: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, SpacetimeType)]
+pub enum OrderSide {
+    Bid,
+    Ask,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, SpacetimeType)]
+pub enum Trader {
+    Workplace(u32),  // Building ID
+    Household(u32),  // Home building ID
+    Restaurant(u32), // Building ID
+    Merchant(u32),
+    Faction(u32),
+    City(u32),       // The city granary
+}
+
+// One match between a bid and an ask
+#[spacetimedb::table(name = market_fill)]
+pub struct MarketFill {
+    #[primary_key]
+    pub id: u32,
+    pub world_id: u32,
+    pub city_id: u32,
+    pub resource_type: ResourceType,
+    pub bid_order_id: u32,
+    pub ask_order_id: u32,
+    pub quantity: f32,
+    pub price: f32,
+    pub hour: u64,
+}
+
+// Goods a faction has bought and holds, ready to sell again
+#[spacetimedb::table(name = faction_holding)]
+pub struct FactionHolding {
+    #[primary_key]
+    pub id: u32,
+    pub faction_id: u32,
+    pub resource_type: ResourceType,
+    pub quantity: f32,
+}
+
+// Cleared price and volume per market per tick
+#[spacetimedb::table(name = price_point)]
+pub struct PricePoint {
+    #[primary_key]
+    pub id: u32,
+    pub world_id: u32,
+    pub city_id: u32,
+    pub resource_type: ResourceType,
+    pub hour: u64,
+    pub price: f32,
+    pub volume: f32,
+    pub bid_volume: f32,
+    pub ask_volume: f32,
+    pub best_bid: Option<f32>,
+    pub best_ask: Option<f32>,
+}
+
+// Post a bid or ask on behalf of a faction
+#[spacetimedb::reducer]
+pub fn place_faction_order(
+    ctx: &ReducerContext,
+    faction_id: u32,
+    city_id: u32,
+    resource_type: ResourceType,
+    side: OrderSide,
+    quantity: f32,
+    limit_price: f32,
+    hour: u64,
+) -> Result<(), String> {
+    let faction = ctx.db.faction().id().find(&faction_id)
+        .ok_or("Faction not found")?;
+
+    if quantity <= 0.0 || limit_price <= 0.0 {
+        return Err("Orders need a positive quantity and price".to_string());
+    }
+    if side == OrderSide::Bid && faction.treasury < quantity * limit_price {
+        return Err("Faction cannot cover the order".to_string());
+    }
+    if side == OrderSide::Ask && holding_of(ctx, faction_id, resource_type) <= 0.0 {
+        return Err("Faction holds none of that resource".to_string());
+    }
+
+    place_order(ctx, faction.world_id, city_id, resource_type, side, Trader::Faction(faction_id),
+        quantity, limit_price, hour, hour + FACTION_ORDER_HOURS);
+    Ok(())
+}
+
+// Clear every market in a world: agents post orders, books match, prices are recorded
+#[spacetimedb::reducer]
+pub fn clear_markets(
+    ctx: &ReducerContext,
+    world_id: u32,
+    hour: u64,
+) -> Result<(), String> {
+    let markets: Vec<Market> = ctx.db.market()
+        .iter()
+        .filter(|m| m.world_id == world_id)
+        .collect();
+
+    for mut market in markets {
+        post_agent_orders(ctx, &market, hour);
+
+        let faction_volumes = new_faction_volumes(ctx, &market);
+        let point = match_book(ctx, &market, hour);
+        detect_manipulation(ctx, &market, &point, faction_volumes, hour)?;
+        close_orders(ctx, &market, hour);
+
+        if point.volume > 0.0 {
+            market.price = point.price;
+        }
+        market.supply = point.ask_volume;
+        market.demand = point.bid_volume;
+        market.last_update_hour = hour;
+        ctx.db.price_point().insert(point);
+        ctx.db.market().id().update(market);
+    }
+
+    Ok(())
+}
+
+/// Post an order, taking sellers' goods into escrow until it fills or closes
+pub(crate) fn place_order(
+    ctx: &ReducerContext,
+    world_id: u32,
+    city_id: u32,
+    resource_type: ResourceType,
+    side: OrderSide,
+    trader: Trader,
+    quantity: f32,
+    limit_price: f32,
+    hour: u64,
+    expires_hour: u64,
+) -> u32 {
+    let quantity = if side == OrderSide::Ask {
+        take_goods(ctx, &trader, city_id, resource_type, quantity)
+    } else {
+        quantity
+    };
+
+    let id = ctx.db.market_order().iter().count() as u32 + 1;
+    if quantity <= 0.0 {
+        return id;
+    }
+
+    ctx.db.market_order().insert(MarketOrder {
+        id,
+        world_id,
+        city_id,
+        resource_type,
+        side,
+        trader,
+        quantity,
+        remaining: quantity,
+        limit_price,
+        placed_hour: hour,
+        expires_hour,
+        is_open: true,
+    });
+    id
+}
+
+/// How much of a resource a faction holds
+pub(crate) fn holding_of(ctx: &ReducerContext, faction_id: u32, resource_type: ResourceType) -> f32 {
+    ctx.db.faction_holding().iter()
+        .find(|h| h.faction_id == faction_id && h.resource_type == resource_type)
+        .map_or(0.0, |h| h.quantity)
+}
+
+/// Add to (or, with a negative quantity, draw from) a faction's holding; returns the change made
+fn adjust_holding(ctx: &ReducerContext, faction_id: u32, resource_type: ResourceType, quantity: f32) -> f32 {
+    match ctx.db.faction_holding().iter()
+        .find(|h| h.faction_id == faction_id && h.resource_type == resource_type)
+    {
+        Some(mut holding) => {
+            let change = quantity.max(-holding.quantity);
+            holding.quantity += change;
+            ctx.db.faction_holding().id().update(holding);
+            change
+        },
+        None if quantity > 0.0 => {
+            let id = ctx.db.faction_holding().iter().count() as u32 + 1;
+            ctx.db.faction_holding().insert(FactionHolding { id, faction_id, resource_type, quantity });
+            quantity
+        },
+        None => 0.0,
+    }
+}
+
+/// Most recent cleared prices for a market, oldest first
+pub fn recent_prices(ctx: &ReducerContext, city_id: u32, resource_type: ResourceType, count: usize) -> Vec<f32> {
+    let mut points: Vec<PricePoint> = ctx.db.price_point()
+        .iter()
+        .filter(|p| p.city_id == city_id && p.resource_type == resource_type && p.volume > 0.0)
+        .collect();
+    points.sort_by_key(|p| (p.hour, p.id));
+
+    let skip = points.len().saturating_sub(count);
+    points.into_iter().skip(skip).map(|p| p.price).collect()
+}
+
+/// Map a building-level resource onto the traded market resource
+pub fn market_resource(resource_type: &crate::types::ResourceType) -> Option<ResourceType> {
+    match resource_type {
+        crate::types::ResourceType::Food => Some(ResourceType::Food),
+        crate::types::ResourceType::Goods => Some(ResourceType::ProcessedGoods),
+        crate::types::ResourceType::Culture => Some(ResourceType::Luxury),
+        crate::types::ResourceType::Science => Some(ResourceType::Knowledge),
+        crate::types::ResourceType::RawMaterials => Some(ResourceType::RawMaterials),
+        crate::types::ResourceType::Energy => Some(ResourceType::Energy),
+        _ => None,
+    }
+}
+
+fn city_resource(resource_type: ResourceType) -> Option<crate::types::ResourceType> {
+    match resource_type {
+        ResourceType::Food => Some(crate::types::ResourceType::Food),
+        ResourceType::ProcessedGoods => Some(crate::types::ResourceType::Goods),
+        ResourceType::Luxury => Some(crate::types::ResourceType::Culture),
+        ResourceType::Knowledge => Some(crate::types::ResourceType::Science),
+        ResourceType::RawMaterials => Some(crate::types::ResourceType::RawMaterials),
+        ResourceType::Energy => Some(crate::types::ResourceType::Energy),
+        _ => None,
+    }
+}
+
+// Producers, households, restaurants and merchants quote reservation prices around the last price:
+// full sellers undercut, needy buyers outbid, and the previous book's imbalance shifts everyone
+fn post_agent_orders(ctx: &ReducerContext, market: &Market, hour: u64) {
+    let last_price = if market.price > 0.0 { market.price } else { base_price(market.resource_type) };
+    let spread = QUOTE_SPREAD * (1.0 + market.price_volatility);
+    let imbalance = if market.supply + market.demand > 0.0 {
+        (market.demand - market.supply) / (market.supply + market.demand)
+    } else {
+        0.0
+    };
+    let quote = Quote { last_price, spread, pressure: spread * imbalance * BOOK_PRESSURE };
+    let days = (hour.saturating_sub(market.last_update_hour).clamp(1, 168)) as f32 / 24.0;
+    let granary_need = granary_need(ctx, market.city_id);
+
+    let buildings: Vec<_> = ctx.db.building()
+        .iter()
+        .filter(|b| b.city_id == market.city_id && !b.destroyed)
+        .collect();
+
+    for building in &buildings {
+        match &building.building_type {
+            // Farms deliver to the granary, which trades on their behalf
+            BuildingType::Workplace(_) => {
+                if let Some(output) = ctx.db.workplace_data().building_id().find(&building.id) {
+                    if output.resource_type != crate::types::ResourceType::Food &&
+                        output.inventory > 0.0 &&
+                        market_resource(&output.resource_type) == Some(market.resource_type) {
+                        let fill = output.inventory / output.max_inventory.max(1.0);
+                        place_order(ctx, market.world_id, market.city_id, market.resource_type, OrderSide::Ask,
+                            Trader::Workplace(building.id), output.inventory, quote.ask(fill), hour, hour);
+                    }
+                    // Workshops buy in raw materials to keep their stockpile going, harder as it runs down
+                    if market.resource_type == ResourceType::RawMaterials && output.consumption_rate > 0.0 {
+                        let need = 1.0 - output.stockpile / output.max_stockpile.max(1.0);
+                        let bid_price = quote.bid(need);
+                        let quantity = (output.max_stockpile - output.stockpile).min(building.revenue.max(0.0) / bid_price);
+                        if quantity > 0.0 {
+                            place_order(ctx, market.world_id, market.city_id, market.resource_type, OrderSide::Bid,
+                                Trader::Workplace(building.id), quantity, bid_price, hour, hour);
+                        }
+                    }
+                }
+            },
+            BuildingType::Home(_) => {
+                let residents: Vec<_> = ctx.db.individual().iter()
+                    .filter(|i| i.home_id == Some(building.id))
+                    .collect();
+                if residents.is_empty() {
+                    continue;
+                }
+                let budget: f32 = residents.iter().map(|i| i.income.max(0.0)).sum();
+                let (wanted, need) = match market.resource_type {
+                    // Hungry households pay more, and more again when the granary is running low
+                    ResourceType::Food => {
+                        let hunger = residents.iter()
+                            .map(|i| 1.0 - i.food_water / thresholds::NEED_MAX)
+                            .sum::<f32>() / residents.len() as f32;
+                        (residents.len() as f32 * farming::MEALS_PER_PERSON_PER_DAY / farming::MEALS_PER_MARKET_UNIT,
+                            (hunger + granary_need) / 2.0)
+                    },
+                    ResourceType::ProcessedGoods => (residents.len() as f32 * HOUSEHOLD_GOODS_PER_DAY, HOUSEHOLD_GOODS_NEED),
+                    _ => (0.0, 0.0),
+                };
+                let bid_price = quote.bid(need);
+                let quantity = (wanted * days).min(budget / bid_price);
+                if quantity > 0.0 {
+                    place_order(ctx, market.world_id, market.city_id, market.resource_type, OrderSide::Bid,
+                        Trader::Household(building.id), quantity, bid_price, hour, hour);
+                }
+            },
+            BuildingType::Restaurant if market.resource_type == ResourceType::Food => {
+                place_order(ctx, market.world_id, market.city_id, market.resource_type, OrderSide::Bid,
+                    Trader::Restaurant(building.id), RESTAURANT_FOOD_PER_DAY * days, quote.bid(granary_need), hour, hour);
+            },
+            _ => {},
+        }
+    }
+
+    // Merchants in town sell away from where they bought, and load up at their route's origin
+    let merchants: Vec<_> = ctx.db.merchant()
+        .iter()
+        .filter(|m| m.is_active && m.current_city_id == market.city_id && m.destination_city_id.is_none())
+        .collect();
+
+    for trader in merchants {
+        let origin = trader.trade_route_id
+            .and_then(|id| ctx.db.trade_route().id().find(&id))
+            .filter(|r| r.from_city_id == market.city_id);
+        let cargo = cargo_of(ctx, trader.id, market.resource_type);
+
+        // A full hold sells cheap and an empty one buys eagerly
+        let fill = cargo_weight(ctx, trader.id) / trader.cargo_capacity.max(1.0);
+        match origin {
+            None if cargo > 0.0 => {
+                place_order(ctx, market.world_id, market.city_id, market.resource_type, OrderSide::Ask,
+                    Trader::Merchant(trader.id), cargo, quote.ask(fill), hour, hour);
+            },
+            Some(route) if route.resource_type == market.resource_type && trader.capital > 0.0 => {
+                let bid_price = quote.bid(1.0 - fill);
+                let room = (trader.cargo_capacity - cargo_weight(ctx, trader.id)).max(0.0) / unit_weight(market.resource_type);
+                let quantity = (trader.capital * CAPITAL_SHARE / bid_price).min(room);
+                if quantity > 0.0 {
+                    place_order(ctx, market.world_id, market.city_id, market.resource_type, OrderSide::Bid,
+                        Trader::Merchant(trader.id), quantity, bid_price, hour, hour);
+                }
+            },
+            _ => {},
+        }
+    }
+}
+
+// Where one market's traders start from this tick
+struct Quote {
+    last_price: f32,
+    spread: f32,
+    pressure: f32, // Share of the last price added when the previous book was short of goods
+}
+
+impl Quote {
+    /// Asking price for a seller whose stores are `fill` (0-1) full
+    fn ask(&self, fill: f32) -> f32 {
+        self.last_price * (1.0 + self.pressure + self.spread * (1.0 - 2.0 * fill.clamp(0.0, 1.0)))
+    }
+
+    /// Bid for a buyer with `need` (0-1) for the goods
+    fn bid(&self, need: f32) -> f32 {
+        self.last_price * (1.0 + self.pressure + self.spread * (2.0 * need.clamp(0.0, 1.0) - 1.0))
+    }
+}
+
+/// How short the city granary is of its winter target (0-1); cities without one are taken as half stocked
+fn granary_need(ctx: &ReducerContext, city_id: u32) -> f32 {
+    ctx.db.food_security().city_id().find(&city_id)
+        .filter(|s| s.winter_target > 0.0)
+        .map_or(0.5, |s| (1.0 - s.days_of_supply * s.daily_consumption / s.winter_target).clamp(0.0, 1.0))
+}
+
+// Match best bids against best asks; ties go to the older order, then the lower ID
+fn match_book(ctx: &ReducerContext, market: &Market, hour: u64) -> PricePoint {
+    let open: Vec<MarketOrder> = ctx.db.market_order()
+        .iter()
+        .filter(|o| o.is_open && o.city_id == market.city_id && o.resource_type == market.resource_type)
+        .collect();
+
+    let (mut bids, mut asks): (Vec<MarketOrder>, Vec<MarketOrder>) = open.into_iter()
+        .partition(|o| o.side == OrderSide::Bid);
+    bids.sort_by(|a, b| b.limit_price.partial_cmp(&a.limit_price).unwrap()
+        .then(a.placed_hour.cmp(&b.placed_hour))
+        .then(a.id.cmp(&b.id)));
+    asks.sort_by(|a, b| a.limit_price.partial_cmp(&b.limit_price).unwrap()
+        .then(a.placed_hour.cmp(&b.placed_hour))
+        .then(a.id.cmp(&b.id)));
+
+    let bid_volume: f32 = bids.iter().map(|o| o.remaining).sum();
+    let ask_volume: f32 = asks.iter().map(|o| o.remaining).sum();
+    let best_bid = bids.first().map(|o| o.limit_price);
+    let best_ask = asks.first().map(|o| o.limit_price);
+
+    // Walk the book to find the matches and the marginal pair that sets the price
+    let mut matches: Vec<(usize, usize, f32)> = Vec::new();
+    let mut bid_left: Vec<f32> = bids.iter().map(|o| o.remaining).collect();
+    let mut ask_left: Vec<f32> = asks.iter().map(|o| o.remaining).collect();
+    let (mut b, mut a) = (0, 0);
+    let mut clearing_price = market.price;
+    while b < bids.len() && a < asks.len() && bids[b].limit_price >= asks[a].limit_price {
+        let quantity = bid_left[b].min(ask_left[a]);
+        matches.push((b, a, quantity));
+        clearing_price = (bids[b].limit_price + asks[a].limit_price) / 2.0;
+        bid_left[b] -= quantity;
+        ask_left[a] -= quantity;
+        if bid_left[b] <= 0.0 { b += 1; }
+        if ask_left[a] <= 0.0 { a += 1; }
+    }
+
+    // Everything clears at the one price
+    let mut volume = 0.0;
+    for (b, a, quantity) in matches {
+        let fill_id = ctx.db.market_fill().iter().count() as u32 + 1;
+        ctx.db.market_fill().insert(MarketFill {
+            id: fill_id,
+            world_id: market.world_id,
+            city_id: market.city_id,
+            resource_type: market.resource_type,
+            bid_order_id: bids[b].id,
+            ask_order_id: asks[a].id,
+            quantity,
+            price: clearing_price,
+            hour,
+        });
+        settle_purchase(ctx, &bids[b].trader, market, quantity, clearing_price);
+        settle_sale(ctx, &asks[a].trader, market, quantity, clearing_price, hour);
+        volume += quantity;
+    }
+
+    for (order, left) in bids.into_iter().zip(bid_left).chain(asks.into_iter().zip(ask_left)) {
+        if order.remaining != left {
+            let mut order = order;
+            order.remaining = left;
+            ctx.db.market_order().id().update(order);
+        }
+    }
+
+    let id = ctx.db.price_point().iter().count() as u32 + 1;
+    PricePoint {
+        id,
+        world_id: market.world_id,
+        city_id: market.city_id,
+        resource_type: market.resource_type,
+        hour,
+        price: clearing_price,
+        volume,
+        bid_volume,
+        ask_volume,
+        best_bid,
+        best_ask,
+    }
+}
+
+// Volume each faction has newly put on either side of the book, keyed by (faction, buying)
+fn new_faction_volumes(ctx: &ReducerContext, market: &Market) -> HashMap<(u32, bool), f32> {
+    let mut volumes = HashMap::new();
+    for order in ctx.db.market_order().iter()
+        .filter(|o| o.is_open && o.city_id == market.city_id && o.resource_type == market.resource_type)
+        .filter(|o| o.placed_hour > market.last_update_hour)
+    {
+        if let Trader::Faction(faction_id) = order.trader {
+            *volumes.entry((faction_id, order.side == OrderSide::Bid)).or_insert(0.0) += order.remaining;
+        }
+    }
+    volumes
+}
+
+// A faction holding a large share of one side of the book is manipulating the price
+fn detect_manipulation(
+    ctx: &ReducerContext,
+    market: &Market,
+    point: &PricePoint,
+    faction_volumes: HashMap<(u32, bool), f32>,
+    hour: u64,
+) -> Result<(), String> {
+    for ((faction_id, buying), quantity) in faction_volumes {
+        let side_volume = if buying { point.bid_volume } else { point.ask_volume };
+        if side_volume <= 0.0 || quantity / side_volume < MANIPULATION_SHARE {
+            continue;
+        }
+
+        let name = ctx.db.faction().id().find(&faction_id)
+            .map(|f| f.name)
+            .unwrap_or_else(|| format!("Faction {}", faction_id));
+        let action = if buying { "cornering" } else { "dumping" };
+        create_economic_event(
+            ctx,
+            market.world_id,
+            EconomicEventType::PriceManipulation,
+            Some(market.resource_type),
+            vec![market.city_id],
+            hour,
+            format!("{} is {} the {:?} market in city {} ({:.0}% of the book)",
+                name, action, market.resource_type, market.city_id, quantity / side_volume * 100.0),
+        )?;
+        log::info!("Faction {} {} {:?} in city {}", faction_id, action, market.resource_type, market.city_id);
+    }
+
+    Ok(())
+}
+
+// Close filled and expired orders, returning unsold goods to their sellers
+fn close_orders(ctx: &ReducerContext, market: &Market, hour: u64) {
+    let closing: Vec<MarketOrder> = ctx.db.market_order()
+        .iter()
+        .filter(|o| o.is_open && o.city_id == market.city_id && o.resource_type == market.resource_type)
+        .filter(|o| o.remaining <= 0.0 || o.expires_hour <= hour)
+        .collect();
+
+    for mut order in closing {
+        if order.side == OrderSide::Ask && order.remaining > 0.0 {
+            return_goods(ctx, &order.trader, market, order.remaining);
+        }
+        order.is_open = false;
+        ctx.db.market_order().id().update(order);
+    }
+}
+
+fn take_goods(ctx: &ReducerContext, trader: &Trader, city_id: u32, resource_type: ResourceType, quantity: f32) -> f32 {
+    match trader {
+        Trader::Workplace(building_id) => {
+            match ctx.db.workplace_data().building_id().find(building_id) {
+                Some(mut output) => {
+                    let taken = quantity.min(output.inventory).max(0.0);
+                    output.inventory -= taken;
+                    ctx.db.workplace_data().building_id().update(output);
+                    taken
+                },
+                None => 0.0,
+            }
+        },
+        Trader::City(_) => {
+            let unit = units_per_market_unit(resource_type);
+            city_resource(resource_type)
+                .and_then(|r| withdraw_stock(ctx, city_id, &r, quantity * unit))
+                .map(|taken| taken / unit)
+                .unwrap_or(0.0)
+        },
+        Trader::Merchant(merchant_id) => unload_cargo(ctx, *merchant_id, resource_type, quantity),
+        // Factions sell only what they have bought
+        Trader::Faction(faction_id) => -adjust_holding(ctx, *faction_id, resource_type, -quantity),
+        Trader::Household(_) | Trader::Restaurant(_) => 0.0,
+    }
+}
+
+fn return_goods(ctx: &ReducerContext, trader: &Trader, market: &Market, quantity: f32) {
+    match trader {
+        Trader::Workplace(building_id) => {
+            if let Some(mut output) = ctx.db.workplace_data().building_id().find(building_id) {
+                output.inventory += quantity;
+                ctx.db.workplace_data().building_id().update(output);
+            }
+        },
+        Trader::City(city_id) => {
+            if let Some(resource) = city_resource(market.resource_type) {
+                deposit_stock(ctx, *city_id, resource, quantity * units_per_market_unit(market.resource_type), market.last_update_hour);
+            }
+        },
+        Trader::Merchant(merchant_id) => load_cargo(ctx, *merchant_id, market.resource_type, quantity, None),
+        Trader::Faction(faction_id) => {
+            adjust_holding(ctx, *faction_id, market.resource_type, quantity);
+        },
+        Trader::Household(_) | Trader::Restaurant(_) => {},
+    }
+}
+
+fn settle_purchase(ctx: &ReducerContext, trader: &Trader, market: &Market, quantity: f32, price: f32) {
+    let cost = quantity * price;
+    match trader {
+        // Households pay from their residents' income and draw their share through the granary
+        Trader::Household(home_id) => {
+            let residents: Vec<_> = ctx.db.individual().iter()
+                .filter(|i| i.home_id == Some(*home_id))
+                .collect();
+            let share = cost / residents.len().max(1) as f32;
+            for mut resident in residents {
+                resident.income = (resident.income - share).max(0.0);
+                ctx.db.individual().id().update(resident);
+            }
+            if let Some(resource) = city_resource(market.resource_type) {
+                deposit_stock(ctx, market.city_id, resource, quantity * units_per_market_unit(market.resource_type), market.last_update_hour);
+            }
+        },
+        Trader::Restaurant(building_id) => {
+            if let Some(mut restaurant) = ctx.db.building().id().find(building_id) {
+                restaurant.revenue -= cost;
+                ctx.db.building().id().update(restaurant);
+            }
+            if let Some(resource) = city_resource(market.resource_type) {
+                deposit_stock(ctx, market.city_id, resource, quantity * units_per_market_unit(market.resource_type), market.last_update_hour);
+            }
+        },
+        Trader::City(city_id) => {
+            if let Some(mut buyer) = ctx.db.city().id().find(city_id) {
+                buyer.tax_reserve -= cost;
+                ctx.db.city().id().update(buyer);
+            }
+            if let Some(resource) = city_resource(market.resource_type) {
+                deposit_stock(ctx, *city_id, resource, quantity * units_per_market_unit(market.resource_type), market.last_update_hour);
+            }
+        },
+        Trader::Merchant(merchant_id) => {
+            if let Some(mut buyer) = ctx.db.merchant().id().find(merchant_id) {
+                buyer.capital -= cost;
+                ctx.db.merchant().id().update(buyer);
+            }
+            load_cargo(ctx, *merchant_id, market.resource_type, quantity, Some(price));
+        },
+        // Factions hoard what they buy
+        Trader::Faction(faction_id) => {
+            if let Some(mut buyer) = ctx.db.faction().id().find(faction_id) {
+                buyer.treasury = (buyer.treasury - cost).max(0.0);
+                ctx.db.faction().id().update(buyer);
+            }
+            adjust_holding(ctx, *faction_id, market.resource_type, quantity);
+        },
+        Trader::Workplace(building_id) => {
+            if let Some(mut buyer) = ctx.db.building().id().find(building_id) {
+                buyer.revenue -= cost;
+                ctx.db.building().id().update(buyer);
+            }
+            if let Some(mut input) = ctx.db.workplace_data().building_id().find(building_id) {
+                input.stockpile = (input.stockpile + quantity).min(input.max_stockpile);
+                ctx.db.workplace_data().building_id().update(input);
+            }
+        },
+    }
+}
+
+// Sellers other than the city itself pay sales tax out of the proceeds
+fn settle_sale(ctx: &ReducerContext, trader: &Trader, market: &Market, quantity: f32, price: f32, hour: u64) {
+    let sales_tax = match trader {
+        Trader::City(_) => 0.0,
+        _ => quantity * price * tax_rates(ctx, market.city_id).sales,
+    };
+    accrue_revenue(ctx, market.city_id, RevenueLine::SalesTax, sales_tax, hour);
+    let proceeds = quantity * price - sales_tax;
+    match trader {
+        Trader::Workplace(building_id) => {
+            if let Some(mut seller) = ctx.db.building().id().find(building_id) {
+                seller.revenue += proceeds;
+                ctx.db.building().id().update(seller);
+            }
+        },
+        Trader::City(city_id) => {
+            if let Some(mut seller) = ctx.db.city().id().find(city_id) {
+                seller.tax_reserve += proceeds;
+                ctx.db.city().id().update(seller);
+            }
+        },
+        Trader::Merchant(merchant_id) => {
+            if let Some(mut seller) = ctx.db.merchant().id().find(merchant_id) {
+                seller.capital += proceeds;
+                seller.profit_this_cycle += proceeds - quantity * cargo_cost(ctx, *merchant_id, market.resource_type);
+                ctx.db.merchant().id().update(seller);
+            }
+        },
+        Trader::Faction(faction_id) => {
+            if let Some(mut seller) = ctx.db.faction().id().find(faction_id) {
+                seller.treasury += proceeds;
+                ctx.db.faction().id().update(seller);
+            }
+        },
+        Trader::Household(_) | Trader::Restaurant(_) => {},
+    }
+}
+
+// City stores count food in meals; markets trade it in larger units
+fn units_per_market_unit(resource_type: ResourceType) -> f32 {
+    if resource_type == ResourceType::Food { farming::MEALS_PER_MARKET_UNIT } else { 1.0 }
+}
\ No newline at end of file
@@ -30,9 +30,8 @@ pub struct Market {
     pub resource_type: ResourceType,
     pub supply: f32,
     pub demand: f32,
-    pub price: f32,
-    pub price_volatility: f32,
-    pub price_history: String, // JSON array of recent prices
+    pub price: f32,            // Last clearing price; history lives in price_point
+    pub price_volatility: f32, // Widens the spread agents quote around the last price
     pub last_update_hour: u64,
 }
 
@@ -67,8 +66,11 @@ pub struct Merchant {
     pub reputation: f32,
     pub specialization: ResourceType,
     pub trade_route_id: Option<u32>,
-    pub goods_carried: String, // JSON of resource quantities
-    pub profit_this_cycle: f32,
+    pub cargo_capacity: f32,           // Weight the merchant can haul; goods live in merchant_cargo
+    pub destination_city_id: Option<u32>, // Set while on the road
+    pub arrival_hour: u64,
+    pub profit_this_cycle: f32,        // Realised gains since the last laden departure
+    pub is_active: bool,               // False once retired or bankrupt
 }
 
 // Economic event types
@@ -128,16 +130,17 @@ pub fn calculate_supply_demand(
     (base_supply, base_demand)
 }
 
-// Calculate price based on supply and demand
-pub fn calculate_price(supply: f32, demand: f32, base_price: f32, volatility: f32) -> f32 {
-    if supply <= 0.0 {
-        return base_price * 10.0; // Extreme scarcity
+// Reference price before a market has cleared any trades
+pub fn base_price(resource_type: ResourceType) -> f32 {
+    match resource_type {
+        ResourceType::Food => 10.0,
+        ResourceType::RawMaterials => 20.0,
+        ResourceType::ProcessedGoods => 50.0,
+        ResourceType::Luxury => 200.0,
+        ResourceType::Knowledge => 100.0,
+        ResourceType::Energy => 30.0,
+        ResourceType::Military => 500.0,
     }
-
-    let ratio = demand / supply;
-    let price_multiplier = ratio.powf(volatility);
-
-    (base_price * price_multiplier).clamp(base_price * 0.1, base_price * 10.0)
 }
 
 // Initialize markets for a city
@@ -167,16 +170,6 @@ pub fn initialize_city_markets(
             resource_type,
         );
 
-        let base_price = match resource_type {
-            ResourceType::Food => 10.0,
-            ResourceType::RawMaterials => 20.0,
-            ResourceType::ProcessedGoods => 50.0,
-            ResourceType::Luxury => 200.0,
-            ResourceType::Knowledge => 100.0,
-            ResourceType::Energy => 30.0,
-            ResourceType::Military => 500.0,
-        };
-
         let market = Market {
             id: market_id,
             world_id,
@@ -184,9 +177,8 @@ pub fn initialize_city_markets(
             resource_type,
             supply,
             demand,
-            price: base_price,
+            price: base_price(resource_type),
             price_volatility: 0.5,
-            price_history: format!("[{}]", base_price),
             last_update_hour: 0,
         };
 
@@ -197,116 +189,6 @@ pub fn initialize_city_markets(
     Ok(())
 }
 
-// Update market prices based on supply and demand
-#[spacetimedb::reducer]
-pub fn update_market_prices(
-    ctx: &ReducerContext,
-    world_id: u32,
-    hour: u64,
-) -> Result<(), String> {
-    let markets: Vec<Market> = ctx.db.market()
-        .iter()
-        .filter(|m| m.world_id == world_id)
-        .cloned()
-        .collect();
-
-    for mut market in markets {
-        // Calculate new price
-        let base_price = match market.resource_type {
-            ResourceType::Food => 10.0,
-            ResourceType::RawMaterials => 20.0,
-            ResourceType::ProcessedGoods => 50.0,
-            ResourceType::Luxury => 200.0,
-            ResourceType::Knowledge => 100.0,
-            ResourceType::Energy => 30.0,
-            ResourceType::Military => 500.0,
-        };
-
-        let new_price = calculate_price(
-            market.supply,
-            market.demand,
-            base_price,
-            market.price_volatility,
-        );
-
-        // Update price history (keep last 10 prices)
-        let mut price_history: Vec<f32> = serde_json::from_str(&market.price_history)
-            .unwrap_or_else(|_| vec![market.price]);
-
-        price_history.push(new_price);
-        if price_history.len() > 10 {
-            price_history.remove(0);
-        }
-
-        market.price = new_price;
-        market.price_history = serde_json::to_string(&price_history).unwrap();
-        market.last_update_hour = hour;
-
-        // Update the market
-        ctx.db.market().id().update(market.id, market);
-    }
-
-    Ok(())
-}
-
-// Process trade between cities
-#[spacetimedb::reducer]
-pub fn process_trade_routes(
-    ctx: &ReducerContext,
-    world_id: u32,
-    hour: u64,
-) -> Result<(), String> {
-    let trade_routes: Vec<TradeRoute> = ctx.db.trade_route()
-        .iter()
-        .filter(|tr| tr.world_id == world_id && tr.is_active)
-        .filter(|tr| hour - tr.last_trade_hour >= tr.frequency_hours as u64)
-        .cloned()
-        .collect();
-
-    for mut route in trade_routes {
-        // Get source and destination markets
-        let source_market = ctx.db.market()
-            .iter()
-            .find(|m| m.city_id == route.from_city_id && m.resource_type == route.resource_type);
-
-        let dest_market = ctx.db.market()
-            .iter()
-            .find(|m| m.city_id == route.to_city_id && m.resource_type == route.resource_type);
-
-        if let (Some(mut source), Some(mut dest)) = (source_market, dest_market) {
-            // Calculate trade volume based on price differential
-            let price_diff = dest.price - source.price;
-            let trade_volume = if price_diff > 0.0 {
-                (route.volume * (price_diff / source.price).min(2.0)).min(source.supply * 0.1)
-            } else {
-                0.0
-            };
-
-            if trade_volume > 0.0 {
-                // Execute trade
-                source.supply -= trade_volume;
-                dest.supply += trade_volume * (route.safety / 100.0); // Some loss due to safety
-
-                // Update markets
-                ctx.db.market().id().update(source.id, source);
-                ctx.db.market().id().update(dest.id, dest);
-
-                // Calculate profitability
-                route.profitability = (price_diff * trade_volume) / route.volume;
-
-                log::info!("Trade route {} moved {} units of {:?} from city {} to {}",
-                    route.id, trade_volume, route.resource_type,
-                    route.from_city_id, route.to_city_id);
-            }
-        }
-
-        route.last_trade_hour = hour;
-        ctx.db.trade_route().id().update(route.id, route);
-    }
-
-    Ok(())
-}
-
 // Generate economic events based on market conditions
 #[spacetimedb::reducer]
 pub fn generate_economic_events(
@@ -324,8 +206,7 @@ pub fn generate_economic_events(
         .collect();
 
     for market in markets {
-        let price_history: Vec<f32> = serde_json::from_str(&market.price_history)
-            .unwrap_or_else(|_| vec![market.price]);
+        let price_history = markets::recent_prices(ctx, market.city_id, market.resource_type, 10);
 
         if price_history.len() >= 3 {
             let recent_avg = price_history.iter().rev().take(3).sum::<f32>() / 3.0;
@@ -380,7 +261,7 @@ pub fn generate_economic_events(
 }
 
 // Helper to create economic events
-fn create_economic_event(
+pub(crate) fn create_economic_event(
     ctx: &ReducerContext,
     world_id: u32,
     event_type: EconomicEventType,
@@ -0,0 +1,546 @@
+// Merchant agents: pick the most profitable route, haul cargo between city markets and risk the road
+
+use spacetimedb::{ReducerContext, Table, SpacetimeType};
+use serde::{Serialize, Deserialize};
+use log;
+use rand::Rng;
+use crate::economics::{Merchant, TradeRoute, ResourceType, EconomicEventType, base_price, create_economic_event, market, merchant, trade_route};
+use crate::tables::individual::individual;
+use crate::tables::building::building;
+use crate::types::{IndividualStatus, StatusData};
+use crate::systems::calculate_distance;
+use crate::systems::modifiers::relocation;
+use crate::reducers::city::{city_residents, local_weather};
+use crate::reducers::migration::city_centre;
+use crate::reducers::budget::{tax_rates, accrue_revenue};
+use crate::tables::budget::RevenueLine;
+
+const CARGO_CAPACITY: f32 = 100.0;          // Weight one merchant can haul
+const MIN_MERCHANT_SAVINGS: f32 = 100.0;    // Savings a resident needs to take up trading
+const STAKE_SHARE: f32 = 0.5;               // Share of savings put into the business
+const VOLUME_PER_MERCHANT: f32 = 100.0;     // Route volume that keeps one merchant busy
+pub(crate) const CAPITAL_SHARE: f32 = 0.5;             // Share of capital committed to one cargo
+const UPKEEP_PER_DAY: f32 = 2.0;            // Food, lodging and tolls on the road
+const SPECIALIST_BONUS: f32 = 1.2;          // Merchants favour the goods they know
+const BANDITRY_RISK_PER_DAY: f32 = 0.1;     // Chance of an ambush per day on a route with no safety
+const ROBBED_CARGO_SHARE: f32 = 0.5;
+const ROBBED_CAPITAL_SHARE: f32 = 0.1;
+const BANDIT_INJURY: f32 = 15.0;
+const ROBBERY_SAFETY_LOSS: f32 = 2.0;       // Word of an ambush makes the route more dangerous
+const DELIVERY_REPUTATION: f32 = 2.0;
+const FAILED_DELIVERY_REPUTATION: f32 = 5.0;
+const PROFITABILITY_SMOOTHING: f32 = 0.3;
+const UNPATROLLED_SAFETY: f32 = 50.0;       // Safety of a road no trade route runs along
+
+// Goods a merchant is carrying, one row per resource
+#[spacetimedb::table(name = merchant_cargo)]
+pub struct MerchantCargo {
+    #[primary_key]
+    pub id: u32,
+    pub merchant_id: u32,
+    pub resource_type: ResourceType,
+    pub quantity: f32,
+    pub unit_cost: f32, // Average price paid, kept when the hold is empty
+}
+
+// One leg of a merchant's travels
+#[spacetimedb::table(name = merchant_journey)]
+pub struct MerchantJourney {
+    #[primary_key]
+    pub id: u32,
+    pub world_id: u32,
+    pub merchant_id: u32,
+    pub trade_route_id: Option<u32>, // None when travelling empty to a route's origin
+    pub from_city_id: u32,
+    pub to_city_id: u32,
+    pub cargo_weight: f32,
+    pub departed_hour: u64,
+    pub arrival_hour: u64,
+    pub outcome: JourneyOutcome,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, SpacetimeType)]
+pub enum JourneyOutcome {
+    EnRoute,
+    Delivered,
+    Robbed,
+}
+
+// Recruit merchants for busy routes, move them along the road and send idle ones off again
+#[spacetimedb::reducer]
+pub fn process_trade_routes(
+    ctx: &ReducerContext,
+    world_id: u32,
+    hour: u64,
+) -> Result<(), String> {
+    let routes: Vec<TradeRoute> = ctx.db.trade_route()
+        .iter()
+        .filter(|r| r.world_id == world_id && r.is_active)
+        .collect();
+
+    for route in &routes {
+        recruit_merchant(ctx, route, hour);
+    }
+
+    let merchants: Vec<Merchant> = ctx.db.merchant()
+        .iter()
+        .filter(|m| m.world_id == world_id && m.is_active)
+        .collect();
+
+    for trader in merchants {
+        // Merchants leave the trade with the person behind them
+        if ctx.db.individual().id().find(&trader.individual_id).is_none() {
+            retire_merchant(ctx, trader);
+            continue;
+        }
+        // With no coin and nothing left to sell, the business is finished
+        if trader.destination_city_id.is_none() && trader.capital <= 0.0 && cargo_weight(ctx, trader.id) <= 0.0 {
+            bankrupt_merchant(ctx, trader, hour);
+            continue;
+        }
+
+        match trader.destination_city_id {
+            Some(_) if hour >= trader.arrival_hour => arrive(ctx, trader, hour)?,
+            Some(_) => {},
+            None => plan_next_leg(ctx, trader, &routes, hour),
+        }
+    }
+
+    // Keep the route tallies in step with the merchants actually working them
+    for mut route in routes {
+        route.merchant_count = ctx.db.merchant().iter()
+            .filter(|m| m.trade_route_id == Some(route.id))
+            .count() as u32;
+        ctx.db.trade_route().id().update(route);
+    }
+
+    Ok(())
+}
+
+// Register a resident as a merchant, staking part of their savings
+#[spacetimedb::reducer]
+pub fn register_merchant(
+    ctx: &ReducerContext,
+    world_id: u32,
+    individual_id: u32,
+    specialization: ResourceType,
+) -> Result<(), String> {
+    let person = ctx.db.individual().id().find(&individual_id)
+        .ok_or("Individual not found")?;
+
+    if ctx.db.merchant().iter().any(|m| m.individual_id == individual_id && m.is_active) {
+        return Err("Individual is already a merchant".to_string());
+    }
+    if person.income < MIN_MERCHANT_SAVINGS {
+        return Err("Individual cannot afford to start trading".to_string());
+    }
+
+    let city_id = person.home_id
+        .and_then(|home_id| ctx.db.building().id().find(&home_id))
+        .map(|b| b.city_id)
+        .ok_or("Individual has no home city")?;
+
+    create_merchant(ctx, world_id, individual_id, city_id, specialization);
+    Ok(())
+}
+
+/// Quantity of a resource a merchant has in the hold
+pub(crate) fn cargo_of(ctx: &ReducerContext, merchant_id: u32, resource_type: ResourceType) -> f32 {
+    ctx.db.merchant_cargo().iter()
+        .find(|c| c.merchant_id == merchant_id && c.resource_type == resource_type)
+        .map(|c| c.quantity)
+        .unwrap_or(0.0)
+}
+
+/// Average price the merchant paid for what they carry of a resource
+pub(crate) fn cargo_cost(ctx: &ReducerContext, merchant_id: u32, resource_type: ResourceType) -> f32 {
+    ctx.db.merchant_cargo().iter()
+        .find(|c| c.merchant_id == merchant_id && c.resource_type == resource_type)
+        .map(|c| c.unit_cost)
+        .unwrap_or(0.0)
+}
+
+/// Total weight in a merchant's hold
+pub(crate) fn cargo_weight(ctx: &ReducerContext, merchant_id: u32) -> f32 {
+    ctx.db.merchant_cargo().iter()
+        .filter(|c| c.merchant_id == merchant_id)
+        .map(|c| c.quantity * unit_weight(c.resource_type))
+        .sum()
+}
+
+/// Load goods into the hold; `unit_cost` is None when goods come back unsold
+pub(crate) fn load_cargo(ctx: &ReducerContext, merchant_id: u32, resource_type: ResourceType, quantity: f32, unit_cost: Option<f32>) {
+    let existing = ctx.db.merchant_cargo().iter()
+        .find(|c| c.merchant_id == merchant_id && c.resource_type == resource_type);
+
+    match existing {
+        Some(mut hold) => {
+            if let Some(cost) = unit_cost {
+                let total = hold.quantity + quantity;
+                if total > 0.0 {
+                    hold.unit_cost = (hold.quantity * hold.unit_cost + quantity * cost) / total;
+                }
+            }
+            hold.quantity += quantity;
+            ctx.db.merchant_cargo().id().update(hold);
+        },
+        None => {
+            let id = ctx.db.merchant_cargo().iter().count() as u32 + 1;
+            ctx.db.merchant_cargo().insert(MerchantCargo {
+                id,
+                merchant_id,
+                resource_type,
+                quantity,
+                unit_cost: unit_cost.unwrap_or(0.0),
+            });
+        },
+    }
+}
+
+/// Take up to `quantity` out of the hold; returns what was taken
+pub(crate) fn unload_cargo(ctx: &ReducerContext, merchant_id: u32, resource_type: ResourceType, quantity: f32) -> f32 {
+    match ctx.db.merchant_cargo().iter()
+        .find(|c| c.merchant_id == merchant_id && c.resource_type == resource_type)
+    {
+        Some(mut hold) => {
+            let taken = quantity.min(hold.quantity).max(0.0);
+            hold.quantity -= taken;
+            ctx.db.merchant_cargo().id().update(hold);
+            taken
+        },
+        None => 0.0,
+    }
+}
+
+/// Weight of one market unit
+pub fn unit_weight(resource_type: ResourceType) -> f32 {
+    match resource_type {
+        ResourceType::Food => 1.0,
+        ResourceType::RawMaterials => 2.0,
+        ResourceType::ProcessedGoods => 1.0,
+        ResourceType::Luxury => 0.2,
+        ResourceType::Knowledge => 0.1,
+        ResourceType::Energy => 1.5,
+        ResourceType::Military => 3.0,
+    }
+}
+
+/// Hours on the road between two cities, slowed by the weather at the start
+pub(crate) fn travel_hours(ctx: &ReducerContext, from_city_id: u32, to_city_id: u32) -> u64 {
+    let from = city_centre(ctx, from_city_id);
+    let to = city_centre(ctx, to_city_id);
+    let distance = calculate_distance(from.0, from.1, to.0, to.1);
+    let multiplier = local_weather(ctx, from_city_id, true).travel_multiplier();
+    ((distance * relocation::TRAVEL_HOURS_PER_UNIT * multiplier) as u64).max(relocation::MIN_TRAVEL_HOURS)
+}
+
+fn market_price(ctx: &ReducerContext, city_id: u32, resource_type: ResourceType) -> f32 {
+    ctx.db.market().iter()
+        .find(|m| m.city_id == city_id && m.resource_type == resource_type)
+        .map(|m| m.price)
+        .unwrap_or_else(|| base_price(resource_type))
+}
+
+fn banditry_chance(safety: f32, hours: u64) -> f32 {
+    let danger = 1.0 - (safety / 100.0).clamp(0.0, 1.0);
+    (danger * BANDITRY_RISK_PER_DAY * hours as f32 / 24.0).min(0.9)
+}
+
+// The trade route running along the road between two cities, either way; the merchant's own route first
+fn route_along(ctx: &ReducerContext, from_city_id: u32, to_city_id: u32, own_route_id: Option<u32>) -> Option<TradeRoute> {
+    let connects = |r: &TradeRoute| (r.from_city_id == from_city_id && r.to_city_id == to_city_id) ||
+        (r.from_city_id == to_city_id && r.to_city_id == from_city_id);
+    own_route_id
+        .and_then(|id| ctx.db.trade_route().id().find(&id))
+        .filter(|r| connects(r))
+        .or_else(|| ctx.db.trade_route().iter()
+            .filter(|r| r.is_active && connects(r))
+            .max_by(|a, b| a.safety.total_cmp(&b.safety).then(b.id.cmp(&a.id))))
+}
+
+// Margin on a full hold after expected losses to bandits and the cost of the road
+fn expected_profit(ctx: &ReducerContext, trader: &Merchant, route: &TradeRoute) -> f32 {
+    let buy = market_price(ctx, route.from_city_id, route.resource_type);
+    let sell = market_price(ctx, route.to_city_id, route.resource_type);
+    if buy <= 0.0 {
+        return 0.0;
+    }
+
+    let quantity = (trader.capital * CAPITAL_SHARE / buy)
+        .min(trader.cargo_capacity / unit_weight(route.resource_type));
+    // Cargo is only at risk on the route itself; getting to its origin costs upkeep
+    let laden_hours = travel_hours(ctx, route.from_city_id, route.to_city_id);
+    let mut hours = laden_hours;
+    if trader.current_city_id != route.from_city_id {
+        hours += travel_hours(ctx, trader.current_city_id, route.from_city_id);
+    }
+
+    let arrives = 1.0 - banditry_chance(route.safety, laden_hours) * ROBBED_CARGO_SHARE;
+    let margin = quantity * (sell * arrives - buy) - UPKEEP_PER_DAY * hours as f32 / 24.0;
+    if route.resource_type == trader.specialization && margin > 0.0 {
+        margin * SPECIALIST_BONUS
+    } else {
+        margin
+    }
+}
+
+// Idle merchants with cargo set off from the origin; with an empty hold they pick the best route
+fn plan_next_leg(ctx: &ReducerContext, mut trader: Merchant, routes: &[TradeRoute], hour: u64) {
+    let current = trader.trade_route_id
+        .and_then(|id| routes.iter().find(|r| r.id == id));
+
+    // Cargo anywhere but the origin waits for buyers in this city's market
+    if cargo_weight(ctx, trader.id) > 0.0 {
+        if let Some(route) = current {
+            if trader.current_city_id == route.from_city_id {
+                depart(ctx, trader, Some(route.id), route.to_city_id, hour);
+            }
+        }
+        return;
+    }
+
+    let best = routes.iter()
+        .map(|r| (r, expected_profit(ctx, &trader, r)))
+        .filter(|(_, profit)| *profit > 0.0)
+        .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap().then(b.0.id.cmp(&a.0.id)));
+
+    let (route, profit) = match best {
+        Some(best) => best,
+        None => {
+            if trader.trade_route_id.is_some() {
+                trader.trade_route_id = None;
+                ctx.db.merchant().id().update(trader);
+            }
+            return;
+        },
+    };
+
+    if trader.trade_route_id != Some(route.id) {
+        log::info!("Merchant {} takes up route {} ({:?}, expecting {:.1})",
+            trader.id, route.id, route.resource_type, profit);
+        trader.trade_route_id = Some(route.id);
+    }
+
+    // At the origin the merchant bids in the next clearing; elsewhere they travel there empty
+    if trader.current_city_id == route.from_city_id {
+        ctx.db.merchant().id().update(trader);
+    } else {
+        depart(ctx, trader, None, route.from_city_id, hour);
+    }
+}
+
+fn depart(ctx: &ReducerContext, mut trader: Merchant, route_id: Option<u32>, to_city_id: u32, hour: u64) {
+    let hours = travel_hours(ctx, trader.current_city_id, to_city_id);
+    let arrival_hour = hour + hours;
+
+    let id = ctx.db.merchant_journey().iter().count() as u32 + 1;
+    ctx.db.merchant_journey().insert(MerchantJourney {
+        id,
+        world_id: trader.world_id,
+        merchant_id: trader.id,
+        trade_route_id: route_id,
+        from_city_id: trader.current_city_id,
+        to_city_id,
+        cargo_weight: cargo_weight(ctx, trader.id),
+        departed_hour: hour,
+        arrival_hour,
+        outcome: JourneyOutcome::EnRoute,
+    });
+
+    if let Some(mut person) = ctx.db.individual().id().find(&trader.individual_id) {
+        person.status = IndividualStatus::InTransit(StatusData {
+            until_hour: arrival_hour,
+            target_location: None,
+            target_building: None,
+        });
+        ctx.db.individual().id().update(person);
+    }
+
+    // A cycle runs from one laden departure to the next
+    if route_id.is_some() {
+        trader.profit_this_cycle = 0.0;
+    }
+    let upkeep = UPKEEP_PER_DAY * hours as f32 / 24.0;
+    trader.capital -= upkeep;
+    trader.profit_this_cycle -= upkeep;
+    trader.destination_city_id = Some(to_city_id);
+    trader.arrival_hour = arrival_hour;
+    ctx.db.merchant().id().update(trader);
+}
+
+fn arrive(ctx: &ReducerContext, mut trader: Merchant, hour: u64) -> Result<(), String> {
+    let journey = ctx.db.merchant_journey().iter()
+        .filter(|j| j.merchant_id == trader.id && j.outcome == JourneyOutcome::EnRoute)
+        .max_by_key(|j| j.id);
+    let route = trader.trade_route_id.and_then(|id| ctx.db.trade_route().id().find(&id));
+    let to_city_id = trader.destination_city_id.unwrap_or(trader.current_city_id);
+
+    let mut outcome = JourneyOutcome::Delivered;
+    let laden = journey.as_ref().map_or(false, |j| j.trade_route_id.is_some());
+
+    // Bandits work the road actually travelled, as safe as the route along it, if any
+    if let Some(leg) = &journey {
+        let hours = leg.arrival_hour - leg.departed_hour;
+        let road = route_along(ctx, leg.from_city_id, leg.to_city_id, leg.trade_route_id);
+        let safety = road.as_ref().map_or(UNPATROLLED_SAFETY, |r| r.safety);
+
+        if rand::thread_rng().gen::<f32>() < banditry_chance(safety, hours) {
+            outcome = JourneyOutcome::Robbed;
+            rob(ctx, &mut trader);
+            if let Some(mut road) = road {
+                road.safety = (road.safety - ROBBERY_SAFETY_LOSS).max(0.0);
+                ctx.db.trade_route().id().update(road);
+            }
+            create_economic_event(
+                ctx,
+                trader.world_id,
+                EconomicEventType::TradeDisruption,
+                route.as_ref().filter(|_| laden).map(|r| r.resource_type),
+                vec![leg.from_city_id, leg.to_city_id],
+                hour,
+                format!("Bandits ambushed a merchant between city {} and city {}", leg.from_city_id, leg.to_city_id),
+            )?;
+            trader.reputation = (trader.reputation - FAILED_DELIVERY_REPUTATION).max(0.0);
+        } else if laden {
+            trader.reputation = (trader.reputation + DELIVERY_REPUTATION).min(100.0);
+        }
+    }
+
+    // Re-read the route, which an ambush may have just made less safe
+    let route = route.and_then(|r| ctx.db.trade_route().id().find(&r.id));
+    if let (Some(mut route), true) = (route, laden) {
+        // Cargo that gets through pays the destination's tariff
+        let value = cargo_of(ctx, trader.id, route.resource_type) * market_price(ctx, to_city_id, route.resource_type);
+        let tariff = (value * tax_rates(ctx, to_city_id).tariff).min(trader.capital.max(0.0));
+        trader.capital -= tariff;
+        trader.profit_this_cycle -= tariff;
+        accrue_revenue(ctx, to_city_id, RevenueLine::Tariff, tariff, hour);
+
+        // Route profitability follows the margin on what actually arrives
+        let margin = market_price(ctx, to_city_id, route.resource_type) - cargo_cost(ctx, trader.id, route.resource_type);
+        route.profitability = route.profitability * (1.0 - PROFITABILITY_SMOOTHING) + margin * PROFITABILITY_SMOOTHING;
+        route.last_trade_hour = hour;
+        ctx.db.trade_route().id().update(route);
+    }
+
+    if let Some(mut journey) = journey {
+        journey.outcome = outcome;
+        ctx.db.merchant_journey().id().update(journey);
+    }
+
+    if let Some(mut person) = ctx.db.individual().id().find(&trader.individual_id) {
+        if outcome == JourneyOutcome::Robbed {
+            person.health = (person.health - BANDIT_INJURY).max(0.0);
+        }
+        person.status = IndividualStatus::Idle;
+        ctx.db.individual().id().update(person);
+    }
+
+    log::info!("Merchant {} reached city {} ({:?})", trader.id, to_city_id, outcome);
+    trader.current_city_id = to_city_id;
+    trader.destination_city_id = None;
+    ctx.db.merchant().id().update(trader);
+    Ok(())
+}
+
+// Bandits take part of the hold and some coin; the loss counts against this cycle
+fn rob(ctx: &ReducerContext, trader: &mut Merchant) {
+    let holds: Vec<MerchantCargo> = ctx.db.merchant_cargo().iter()
+        .filter(|c| c.merchant_id == trader.id && c.quantity > 0.0)
+        .collect();
+
+    for mut hold in holds {
+        let stolen = hold.quantity * ROBBED_CARGO_SHARE;
+        hold.quantity -= stolen;
+        trader.profit_this_cycle -= stolen * hold.unit_cost;
+        ctx.db.merchant_cargo().id().update(hold);
+    }
+
+    let taken = trader.capital.max(0.0) * ROBBED_CAPITAL_SHARE;
+    trader.capital -= taken;
+    trader.profit_this_cycle -= taken;
+}
+
+// Residents with savings and no job take up a route that needs more hands
+fn recruit_merchant(ctx: &ReducerContext, route: &TradeRoute, hour: u64) {
+    let working = ctx.db.merchant().iter()
+        .filter(|m| m.trade_route_id == Some(route.id))
+        .count() as f32;
+    if working >= (route.volume / VOLUME_PER_MERCHANT).ceil().max(1.0) {
+        return;
+    }
+
+    let recruit = city_residents(ctx, route.from_city_id).into_iter()
+        .filter(|i| i.age >= 18 && i.workplace_id.is_none() && i.income >= MIN_MERCHANT_SAVINGS)
+        .filter(|i| !matches!(i.status, IndividualStatus::InTransit(_)))
+        .filter(|i| !ctx.db.merchant().iter().any(|m| m.individual_id == i.id && m.is_active))
+        .max_by(|a, b| a.income.partial_cmp(&b.income).unwrap().then(b.id.cmp(&a.id)));
+
+    if let Some(person) = recruit {
+        let merchant_id = create_merchant(ctx, route.world_id, person.id, route.from_city_id, route.resource_type);
+        if let Some(mut trader) = ctx.db.merchant().id().find(&merchant_id) {
+            trader.trade_route_id = Some(route.id);
+            ctx.db.merchant().id().update(trader);
+        }
+        log::info!("Resident {} became a merchant on route {} at hour {}", person.id, route.id, hour);
+    }
+}
+
+fn create_merchant(ctx: &ReducerContext, world_id: u32, individual_id: u32, city_id: u32, specialization: ResourceType) -> u32 {
+    let mut capital = 0.0;
+    if let Some(mut person) = ctx.db.individual().id().find(&individual_id) {
+        capital = person.income * STAKE_SHARE;
+        person.income -= capital;
+        ctx.db.individual().id().update(person);
+    }
+
+    let id = ctx.db.merchant().iter().count() as u32 + 1;
+    ctx.db.merchant().insert(Merchant {
+        id,
+        world_id,
+        individual_id,
+        home_city_id: city_id,
+        current_city_id: city_id,
+        capital,
+        reputation: 50.0,
+        specialization,
+        trade_route_id: None,
+        cargo_capacity: CARGO_CAPACITY,
+        destination_city_id: None,
+        arrival_hour: 0,
+        profit_this_cycle: 0.0,
+        is_active: true,
+    });
+    id
+}
+
+// The person behind the business is gone; the hold is abandoned and the route left free
+fn retire_merchant(ctx: &ReducerContext, mut trader: Merchant) {
+    let holds: Vec<MerchantCargo> = ctx.db.merchant_cargo().iter()
+        .filter(|c| c.merchant_id == trader.id && c.quantity > 0.0)
+        .collect();
+    for mut hold in holds {
+        hold.quantity = 0.0;
+        ctx.db.merchant_cargo().id().update(hold);
+    }
+
+    trader.trade_route_id = None;
+    trader.destination_city_id = None;
+    trader.is_active = false;
+    ctx.db.merchant().id().update(trader);
+}
+
+// A merchant who can neither buy nor sell gives up the trade; their debts fall on them personally
+fn bankrupt_merchant(ctx: &ReducerContext, mut trader: Merchant, hour: u64) {
+    if let Some(mut person) = ctx.db.individual().id().find(&trader.individual_id) {
+        person.income = (person.income + trader.capital).max(0.0);
+        ctx.db.individual().id().update(person);
+    }
+    log::info!("Merchant {} went bankrupt in city {} at hour {} owing {:.1}",
+        trader.id, trader.current_city_id, hour, (-trader.capital).max(0.0));
+
+    trader.capital = 0.0;
+    trader.trade_route_id = None;
+    trader.is_active = false;
+    ctx.db.merchant().id().update(trader);
+}
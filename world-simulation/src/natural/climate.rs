@@ -0,0 +1,347 @@
+// Climate model: regional weather computed from a zone and latitude baseline, seasonal and daily curves, and mean-reverting anomalies
+
+use rand::Rng;
+use crate::world::ClimateZone;
+use crate::natural::{ClimateState, WeatherPattern};
+
+pub(crate) const DAYS_PER_CYCLE: f32 = 360.0;
+pub(crate) const HOURS_PER_CYCLE: u64 = DAYS_PER_CYCLE as u64 * 24;
+const WARMEST_DAY: f32 = 150.0;             // Late summer; the coldest day is half a cycle later
+const WARMEST_HOUR: f32 = 15.0;
+const LATITUDE_LAPSE: f32 = 0.4;            // Degrees Celsius cooler per degree of latitude poleward
+const RAIN_COOLING: f32 = 2.0;              // Degrees while it rains
+
+const TEMPERATURE_ANOMALY_SD: f32 = 3.0;    // Degrees
+const TEMPERATURE_ANOMALY_HOURS: f32 = 72.0; // Time for an anomaly to fade to 1/e
+const HUMIDITY_ANOMALY_SD: f32 = 8.0;       // Percentage points
+const HUMIDITY_ANOMALY_HOURS: f32 = 48.0;
+const MOISTURE_ANOMALY_HOURS: f32 = 12.0;   // Moisture anomaly is in standard deviations
+const ANOMALY_LIMIT: f32 = 4.0;             // Standard deviations; beyond this is not weather
+
+const RAIN_HUMIDITY: f32 = 15.0;            // Percentage points added while raining
+const DIURNAL_HUMIDITY: f32 = 10.0;         // Relative humidity swing over the day
+const BASE_WIND: f32 = 8.0;                 // km/h
+const GUST_PER_SIGMA: f32 = 0.8;            // Share of base wind added per moisture sigma
+const STORM_SIGMA: f32 = 2.0;               // Moisture anomaly where storms begin
+const STORM_WIND_PER_SIGMA: f32 = 30.0;
+const SEA_LEVEL_PRESSURE: f32 = 1013.25;    // hPa
+const PRESSURE_PER_SIGMA: f32 = 12.0;       // Lows bring rain
+
+pub(crate) const DEFAULT_LATITUDE: f32 = 45.0; // For cities placed without coordinates
+pub(crate) const TEMPERATURE_RANGE: (f32, f32) = (-60.0, 55.0);
+pub(crate) const PRECIPITATION_MAX: f32 = 50.0; // mm/hour
+pub(crate) const HUMIDITY_RANGE: (f32, f32) = (5.0, 100.0);
+pub(crate) const PRESSURE_RANGE: (f32, f32) = (950.0, 1060.0);
+pub(crate) const WIND_MAX: f32 = 150.0;
+
+/// The long-run climate of a region
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct ClimateBaseline {
+    pub mean_temperature: f32,
+    pub seasonal_amplitude: f32,  // Half the gap between the warmest and coldest days
+    pub diurnal_range: f32,       // Gap between afternoon high and pre-dawn low
+    pub humidity: f32,
+    pub rain_threshold: f32,      // Moisture anomaly (sigma) above which it rains
+    pub rain_intensity: f32,      // mm/hour per sigma above the threshold
+    pub wet_season: f32,          // -1 wettest in winter .. +1 wettest in summer
+    pub southern: bool,           // Seasons run opposite
+}
+
+/// Weather anomalies carried from one update to the next
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct ClimateAnomaly {
+    pub temperature: f32,
+    pub humidity: f32,
+    pub moisture: f32,
+}
+
+/// Conditions at one moment
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct ClimateSample {
+    pub temperature: f32,
+    pub humidity: f32,
+    pub precipitation: f32,
+    pub wind_speed: f32,
+    pub pressure: f32,
+}
+
+/// Baseline for a zone at a latitude: the zone sets the character, latitude shifts it warmer or colder
+pub(crate) fn baseline(zone: ClimateZone, latitude: f32) -> ClimateBaseline {
+    // (mean temperature at the reference latitude, reference latitude, seasonal amplitude, diurnal range,
+    //  humidity, rain threshold, rain intensity, wet season)
+    let (mean, reference, amplitude, diurnal, humidity, threshold, intensity, wet) = match zone {
+        ClimateZone::Arctic => (-12.0, 70.0, 18.0, 6.0, 75.0, 0.8, 1.5, 0.3),
+        ClimateZone::Temperate => (10.0, 48.0, 10.0, 9.0, 72.0, 0.5, 3.0, 0.0),
+        ClimateZone::Tropical => (27.0, 10.0, 2.0, 8.0, 80.0, 0.3, 8.0, 0.7),
+        ClimateZone::Arid => (24.0, 25.0, 9.0, 16.0, 30.0, 2.0, 4.0, 0.2),
+        ClimateZone::Mediterranean => (17.0, 38.0, 8.0, 10.0, 62.0, 0.8, 4.0, -0.7),
+    };
+    let abs_latitude = latitude.abs().min(90.0);
+    // Seasons grow stronger toward the poles
+    let seasonality = (abs_latitude / reference).clamp(0.5, 1.5);
+
+    ClimateBaseline {
+        mean_temperature: mean + LATITUDE_LAPSE * (reference - abs_latitude),
+        seasonal_amplitude: amplitude * seasonality,
+        diurnal_range: diurnal,
+        humidity,
+        rain_threshold: threshold,
+        rain_intensity: intensity,
+        wet_season: wet,
+        southern: latitude < 0.0,
+    }
+}
+
+/// -1 on the coldest day of the cycle, +1 on the warmest
+pub(crate) fn seasonal_curve(day_of_cycle: f32, southern: bool) -> f32 {
+    let curve = (2.0 * std::f32::consts::PI * (day_of_cycle - WARMEST_DAY) / DAYS_PER_CYCLE).cos();
+    if southern { -curve } else { curve }
+}
+
+/// -1 before dawn, +1 in mid-afternoon
+pub(crate) fn diurnal_curve(hour_of_day: f32) -> f32 {
+    (2.0 * std::f32::consts::PI * (hour_of_day - WARMEST_HOUR) / 24.0).cos()
+}
+
+/// Advance the anomalies by `hours`: each decays toward zero and takes a random kick sized so its spread stays constant
+pub(crate) fn evolve_anomaly(anomaly: ClimateAnomaly, hours: f32, rng: &mut impl Rng) -> ClimateAnomaly {
+    ClimateAnomaly {
+        temperature: mean_revert(anomaly.temperature, TEMPERATURE_ANOMALY_SD, TEMPERATURE_ANOMALY_HOURS, hours, rng),
+        humidity: mean_revert(anomaly.humidity, HUMIDITY_ANOMALY_SD, HUMIDITY_ANOMALY_HOURS, hours, rng),
+        moisture: mean_revert(anomaly.moisture, 1.0, MOISTURE_ANOMALY_HOURS, hours, rng),
+    }
+}
+
+/// One exact step of an Ornstein-Uhlenbeck process with stationary standard deviation `sd`
+fn mean_revert(value: f32, sd: f32, decay_hours: f32, hours: f32, rng: &mut impl Rng) -> f32 {
+    let retained = (-hours.max(0.0) / decay_hours).exp();
+    let kick = sd * (1.0 - retained * retained).sqrt() * standard_normal(rng);
+    (value * retained + kick).clamp(-ANOMALY_LIMIT * sd, ANOMALY_LIMIT * sd)
+}
+
+fn standard_normal(rng: &mut impl Rng) -> f32 {
+    // Box-Muller
+    let u1: f32 = rng.gen_range(f32::EPSILON..1.0);
+    let u2: f32 = rng.gen();
+    (-2.0 * u1.ln()).sqrt() * (2.0 * std::f32::consts::PI * u2).cos()
+}
+
+/// Conditions for a baseline at a time of cycle and day, given the current anomalies
+pub(crate) fn sample(baseline: &ClimateBaseline, day_of_cycle: f32, hour_of_day: f32, anomaly: &ClimateAnomaly) -> ClimateSample {
+    let season = seasonal_curve(day_of_cycle, baseline.southern);
+    let diurnal = diurnal_curve(hour_of_day);
+
+    // Rain falls when moisture runs above the threshold, which drops in the wet season
+    let threshold = baseline.rain_threshold - baseline.wet_season * season * 0.5;
+    let precipitation = ((anomaly.moisture - threshold).max(0.0) * baseline.rain_intensity).min(PRECIPITATION_MAX);
+    let raining = if precipitation > 0.0 { 1.0 } else { 0.0 };
+
+    let temperature = baseline.mean_temperature
+        + baseline.seasonal_amplitude * season
+        + baseline.diurnal_range / 2.0 * diurnal
+        + anomaly.temperature
+        - RAIN_COOLING * raining;
+    let humidity = baseline.humidity
+        + RAIN_HUMIDITY * raining
+        - DIURNAL_HUMIDITY / 2.0 * diurnal
+        + anomaly.humidity;
+    let pressure = SEA_LEVEL_PRESSURE - PRESSURE_PER_SIGMA * anomaly.moisture;
+    let wind_speed = BASE_WIND * (1.0 + GUST_PER_SIGMA * anomaly.moisture.abs())
+        + (anomaly.moisture - STORM_SIGMA).max(0.0) * STORM_WIND_PER_SIGMA;
+
+    ClimateSample {
+        temperature: temperature.clamp(TEMPERATURE_RANGE.0, TEMPERATURE_RANGE.1),
+        humidity: humidity.clamp(HUMIDITY_RANGE.0, HUMIDITY_RANGE.1),
+        precipitation,
+        wind_speed: wind_speed.clamp(0.0, WIND_MAX),
+        pressure: pressure.clamp(PRESSURE_RANGE.0, PRESSURE_RANGE.1),
+    }
+}
+
+/// Expected anomalies `hours` ahead: each fades toward zero at its own rate
+pub(crate) fn expected_anomaly(anomaly: &ClimateAnomaly, hours: f32) -> ClimateAnomaly {
+    ClimateAnomaly {
+        temperature: anomaly.temperature * (-hours / TEMPERATURE_ANOMALY_HOURS).exp(),
+        humidity: anomaly.humidity * (-hours / HUMIDITY_ANOMALY_HOURS).exp(),
+        moisture: anomaly.moisture * (-hours / MOISTURE_ANOMALY_HOURS).exp(),
+    }
+}
+
+/// Share of today's temperature anomaly still expected `hours` ahead; how much the present tells about the future
+pub(crate) fn persistence(hours: f32) -> f32 {
+    (-hours.max(0.0) / TEMPERATURE_ANOMALY_HOURS).exp()
+}
+
+/// Chance of rain when the moisture anomaly is expected at `moisture` but could be off by `spread` sigma
+pub(crate) fn rain_chance(baseline: &ClimateBaseline, day_of_cycle: f32, moisture: f32, spread: f32) -> f32 {
+    let season = seasonal_curve(day_of_cycle, baseline.southern);
+    let threshold = baseline.rain_threshold - baseline.wet_season * season * 0.5;
+    // Logistic approximation of the normal tail
+    1.0 / (1.0 + (-1.7 * (moisture - threshold) / spread.max(0.05)).exp())
+}
+
+/// Spread of the moisture anomaly `hours` ahead, in sigma
+pub(crate) fn moisture_spread(hours: f32) -> f32 {
+    (1.0 - (-2.0 * hours.max(0.0) / MOISTURE_ANOMALY_HOURS).exp()).sqrt()
+}
+
+/// The anomalies a region currently carries
+pub(crate) fn anomaly_of(climate: &ClimateState) -> ClimateAnomaly {
+    ClimateAnomaly {
+        temperature: climate.temperature_anomaly,
+        humidity: climate.humidity_anomaly,
+        moisture: climate.moisture_anomaly,
+    }
+}
+
+/// The pattern that best describes a sample, judged against its baseline
+pub(crate) fn classify(sample: &ClimateSample, baseline: &ClimateBaseline) -> WeatherPattern {
+    let warm_spell = baseline.mean_temperature + baseline.seasonal_amplitude + TEMPERATURE_ANOMALY_SD;
+    let cold_spell = baseline.mean_temperature - baseline.seasonal_amplitude - TEMPERATURE_ANOMALY_SD;
+    if sample.precipitation > 0.0 && sample.wind_speed >= 50.0 {
+        WeatherPattern::Stormy
+    } else if sample.precipitation > 0.0 {
+        WeatherPattern::Rainy
+    } else if sample.wind_speed >= 35.0 {
+        WeatherPattern::Windy
+    } else if sample.humidity >= 95.0 && sample.wind_speed < 10.0 {
+        WeatherPattern::Foggy
+    } else if sample.temperature >= warm_spell {
+        WeatherPattern::Hot
+    } else if sample.temperature <= cold_spell {
+        WeatherPattern::Cold
+    } else if sample.humidity >= 80.0 {
+        WeatherPattern::Cloudy
+    } else {
+        WeatherPattern::Clear
+    }
+}
+
+/// Day of the 360-day cycle (1-360) and hour of day for a simulation hour
+pub(crate) fn calendar(hour: u64) -> (f32, f32) {
+    (((hour / 24) % DAYS_PER_CYCLE as u64 + 1) as f32, (hour % 24) as f32)
+}
+
+/// Bring a region's climate up to `hour`
+pub(crate) fn advance(climate: &mut ClimateState, zone: ClimateZone, hour: u64, rng: &mut impl Rng) {
+    let elapsed = hour.saturating_sub(climate.last_updated_hour) as f32;
+    let anomaly = evolve_anomaly(anomaly_of(climate), elapsed, rng);
+
+    let baseline = baseline(zone, climate.latitude);
+    let (day, hour_of_day) = calendar(hour);
+    let now = sample(&baseline, day, hour_of_day, &anomaly);
+
+    climate.current_temperature = now.temperature;
+    climate.humidity = now.humidity;
+    climate.precipitation = now.precipitation;
+    climate.wind_speed = now.wind_speed;
+    climate.atmospheric_pressure = now.pressure;
+    climate.weather_pattern = classify(&now, &baseline);
+    climate.temperature_anomaly = anomaly.temperature;
+    climate.humidity_anomaly = anomaly.humidity;
+    climate.moisture_anomaly = anomaly.moisture;
+    climate.last_updated_hour = hour;
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    const ZONES: [(ClimateZone, f32); 6] = [
+        (ClimateZone::Arctic, 70.0),
+        (ClimateZone::Temperate, 48.0),
+        (ClimateZone::Temperate, -48.0),
+        (ClimateZone::Tropical, 10.0),
+        (ClimateZone::Arid, 25.0),
+        (ClimateZone::Mediterranean, 38.0),
+    ];
+    const YEARS: u64 = 5;
+
+    fn region(latitude: f32) -> ClimateState {
+        ClimateState {
+            id: 1,
+            world_id: 1,
+            region_id: 1,
+            current_temperature: 0.0,
+            humidity: 50.0,
+            precipitation: 0.0,
+            wind_speed: 0.0,
+            atmospheric_pressure: SEA_LEVEL_PRESSURE,
+            air_quality: 100.0,
+            last_updated_hour: 0,
+            weather_pattern: WeatherPattern::Clear,
+            latitude,
+            temperature_anomaly: 0.0,
+            humidity_anomaly: 0.0,
+            moisture_anomaly: 0.0,
+        }
+    }
+
+    /// Run a region hour by hour for several years, handing each hour's state to `check`
+    fn run(zone: ClimateZone, latitude: f32, seed: u64, mut check: impl FnMut(u64, &ClimateState)) {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut climate = region(latitude);
+        for hour in 1..=YEARS * HOURS_PER_CYCLE {
+            advance(&mut climate, zone, hour, &mut rng);
+            check(hour, &climate);
+        }
+    }
+
+    #[test]
+    fn conditions_stay_within_their_ranges() {
+        for (seed, (zone, latitude)) in ZONES.into_iter().enumerate() {
+            run(zone, latitude, seed as u64, |hour, c| {
+                assert!((TEMPERATURE_RANGE.0..=TEMPERATURE_RANGE.1).contains(&c.current_temperature),
+                    "{zone:?} temperature {} at hour {hour}", c.current_temperature);
+                assert!((HUMIDITY_RANGE.0..=HUMIDITY_RANGE.1).contains(&c.humidity),
+                    "{zone:?} humidity {} at hour {hour}", c.humidity);
+                assert!((0.0..=PRECIPITATION_MAX).contains(&c.precipitation),
+                    "{zone:?} precipitation {} at hour {hour}", c.precipitation);
+                assert!((0.0..=WIND_MAX).contains(&c.wind_speed),
+                    "{zone:?} wind {} at hour {hour}", c.wind_speed);
+                assert!((PRESSURE_RANGE.0..=PRESSURE_RANGE.1).contains(&c.atmospheric_pressure),
+                    "{zone:?} pressure {} at hour {hour}", c.atmospheric_pressure);
+            });
+        }
+    }
+
+    #[test]
+    fn anomalies_stay_within_limit() {
+        for (seed, (zone, latitude)) in ZONES.into_iter().enumerate() {
+            run(zone, latitude, 100 + seed as u64, |hour, c| {
+                assert!(c.temperature_anomaly.abs() <= ANOMALY_LIMIT * TEMPERATURE_ANOMALY_SD,
+                    "{zone:?} temperature anomaly {} at hour {hour}", c.temperature_anomaly);
+                assert!(c.humidity_anomaly.abs() <= ANOMALY_LIMIT * HUMIDITY_ANOMALY_SD,
+                    "{zone:?} humidity anomaly {} at hour {hour}", c.humidity_anomaly);
+                assert!(c.moisture_anomaly.abs() <= ANOMALY_LIMIT,
+                    "{zone:?} moisture anomaly {} at hour {hour}", c.moisture_anomaly);
+            });
+        }
+    }
+
+    #[test]
+    fn yearly_mean_returns_to_baseline_without_drift() {
+        for (seed, (zone, latitude)) in ZONES.into_iter().enumerate() {
+            let expected = baseline(zone, latitude).mean_temperature;
+            let mut yearly_totals = vec![0.0f64; YEARS as usize];
+            let mut anomaly_total = 0.0f64;
+            run(zone, latitude, 200 + seed as u64, |hour, c| {
+                yearly_totals[((hour - 1) / HOURS_PER_CYCLE) as usize] += c.current_temperature as f64;
+                anomaly_total += c.temperature_anomaly as f64;
+            });
+            let yearly_means: Vec<f32> = yearly_totals.iter().map(|t| (t / HOURS_PER_CYCLE as f64) as f32).collect();
+
+            // Seasons and days average out over a year; what remains is anomaly noise and rain cooling
+            for (year, mean) in yearly_means.iter().enumerate() {
+                assert!((mean - expected).abs() < 2.5,
+                    "{zone:?} year {year} mean {mean} far from baseline {expected}");
+            }
+            let drift = yearly_means[YEARS as usize - 1] - yearly_means[0];
+            assert!(drift.abs() < 2.0, "{zone:?} drifted {drift} degrees over {YEARS} years");
+            let mean_anomaly = (anomaly_total / (YEARS * HOURS_PER_CYCLE) as f64) as f32;
+            assert!(mean_anomaly.abs() < 0.5 * TEMPERATURE_ANOMALY_SD,
+                "{zone:?} mean anomaly {mean_anomaly}");
+        }
+    }
+}
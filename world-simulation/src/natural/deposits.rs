@@ -0,0 +1,314 @@
+// Natural resource deposits: ore, coal, stone and timber held by each region, drawn down, regrown and rediscovered
+
+use spacetimedb::{ReducerContext, Table, SpacetimeType};
+use serde::{Serialize, Deserialize};
+use log;
+use rand::Rng;
+use crate::natural::{climate_state, NaturalEventType, record_natural_event};
+use crate::natural::weather::{microclimate, MicroclimateType, get_microclimate_modifiers};
+use crate::economics::{ResourceType, EconomicEventType, TradeRoute, base_price, create_economic_event, market, trade_route};
+use crate::economics::enonomics_integration::enonomics_natural_resources;
+use crate::economics::trade_routes::travel_hours;
+use crate::tables::city::city;
+use crate::tables::building::building;
+use crate::types::JobType;
+use crate::reducers::extraction::is_extraction_site;
+
+const STONE_QUANTITY: (f32, f32) = (200000.0, 500000.0);
+const SEAM_QUANTITY: (f32, f32) = (50000.0, 200000.0);   // Iron and coal
+const TIMBER_PER_COVER: f32 = 2000.0;                    // Standing timber per point of forest vegetation density
+const DIFFICULTY: (f32, f32) = (0.1, 0.5);
+const UNLISTED_SEAM_CHANCE: f32 = 0.3;                   // Regions Enonomics says nothing about may still hold iron or coal
+const LATER_SEAM_DIFFICULTY: f32 = 0.1;                  // Each further seam of a kind lies deeper than the last
+
+const TIMBER_REGROWTH: f32 = 0.002;                      // Share of the felled forest that grows back each day
+
+const RESERVE_PER_RESIDENT: f32 = 50.0;                  // Workable reserves a region needs to feel secure
+const SCARCE: f32 = 0.6;
+const PLENTIFUL: f32 = 0.2;
+const ROUTE_VOLUME_PER_RESIDENT: f32 = 0.05;
+const PROSPECT_CHANCE: f32 = 0.02;                       // Per day for a scarce region with working mines or quarries
+
+// A body of ore, coal, stone or timber in one region
+#[spacetimedb::table(name = resource_deposit)]
+pub struct ResourceDeposit {
+    #[primary_key]
+    pub id: u32,
+    pub world_id: u32,
+    pub region_id: u32,
+    pub kind: DepositKind,
+    pub quantity: f32,
+    pub initial_quantity: f32,
+    pub difficulty: f32,          // 0-1, share of effort lost to depth, hardness or distance
+    pub discovered_hour: u64,
+    pub exhausted: bool,          // Worked out; timber is never exhausted, it grows back
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, SpacetimeType)]
+pub enum DepositKind {
+    Iron,
+    Coal,
+    Stone,
+    Timber,
+}
+
+impl DepositKind {
+    pub fn resource(&self) -> ResourceType {
+        match self {
+            DepositKind::Coal => ResourceType::Energy,
+            DepositKind::Iron | DepositKind::Stone | DepositKind::Timber => ResourceType::RawMaterials,
+        }
+    }
+
+    pub fn worked_by(&self) -> JobType {
+        match self {
+            DepositKind::Iron | DepositKind::Coal => JobType::Mining,
+            DepositKind::Stone => JobType::Quarrying,
+            DepositKind::Timber => JobType::Logging,
+        }
+    }
+}
+
+// Regrow forests, open supply routes and prospect for new seams (daily); scarcity reaches prices through
+// thinning extraction asks and workshops bidding harder for their running-down stockpiles
+pub fn update_resource_deposits(
+    ctx: &ReducerContext,
+    world_id: u32,
+    current_hour: u64,
+) -> Result<(), String> {
+    let regions: Vec<u32> = ctx.db.climate_state()
+        .iter()
+        .filter(|c| c.world_id == world_id)
+        .map(|c| c.region_id)
+        .collect();
+
+    for &region_id in &regions {
+        if !ctx.db.resource_deposit().iter().any(|d| d.world_id == world_id && d.region_id == region_id) {
+            seed_deposits(ctx, world_id, region_id, current_hour);
+        }
+        regrow_forest(ctx, world_id, region_id);
+    }
+
+    let mut rng = rand::thread_rng();
+    for resource_type in [ResourceType::RawMaterials, ResourceType::Energy] {
+        let scarcity: Vec<(u32, f32)> = regions.iter()
+            .map(|&region_id| (region_id, regional_scarcity(ctx, world_id, region_id, resource_type)))
+            .collect();
+
+        for &(region_id, share) in &scarcity {
+            // Mines and quarries short of work send out prospectors
+            let prospecting = ctx.db.building().iter()
+                .any(|b| b.city_id == region_id && !b.destroyed && is_extraction_site(&b));
+            if share >= SCARCE && prospecting && rng.gen::<f32>() < PROSPECT_CHANCE {
+                let kind = match resource_type {
+                    ResourceType::Energy => DepositKind::Coal,
+                    _ if rng.gen::<bool>() => DepositKind::Iron,
+                    _ => DepositKind::Stone,
+                };
+                discover_deposit(ctx, world_id, region_id, Some(kind), current_hour)?;
+            }
+        }
+
+        open_supply_routes(ctx, world_id, resource_type, &scarcity, current_hour)?;
+    }
+
+    Ok(())
+}
+
+// Stone everywhere, timber where there is forest, and iron and coal where Enonomics lists them or luck allows
+fn seed_deposits(ctx: &ReducerContext, world_id: u32, region_id: u32, current_hour: u64) {
+    let mut rng = rand::thread_rng();
+    let listed = enonomics_natural_resources(ctx, region_id);
+    let is_listed = |name: &str| listed.iter().any(|r| r.eq_ignore_ascii_case(name));
+
+    insert_deposit(ctx, world_id, region_id, DepositKind::Stone, rng.gen_range(STONE_QUANTITY.0..STONE_QUANTITY.1), 0.0, current_hour);
+
+    let cover = forest_cover(ctx, world_id, region_id);
+    if cover > 0.0 {
+        insert_deposit(ctx, world_id, region_id, DepositKind::Timber, cover * TIMBER_PER_COVER, 0.0, current_hour);
+    }
+
+    for (kind, name) in [(DepositKind::Iron, "Iron"), (DepositKind::Coal, "Coal")] {
+        if is_listed(name) || (listed.is_empty() && rng.gen::<f32>() < UNLISTED_SEAM_CHANCE) {
+            insert_deposit(ctx, world_id, region_id, kind, rng.gen_range(SEAM_QUANTITY.0..SEAM_QUANTITY.1), 0.0, current_hour);
+        }
+    }
+}
+
+fn insert_deposit(
+    ctx: &ReducerContext,
+    world_id: u32,
+    region_id: u32,
+    kind: DepositKind,
+    quantity: f32,
+    extra_difficulty: f32,
+    current_hour: u64,
+) -> u32 {
+    let id = ctx.db.resource_deposit().iter().count() as u32 + 1;
+    let difficulty = (rand::thread_rng().gen_range(DIFFICULTY.0..DIFFICULTY.1) + extra_difficulty).min(0.9);
+    ctx.db.resource_deposit().insert(ResourceDeposit {
+        id,
+        world_id,
+        region_id,
+        kind,
+        quantity,
+        initial_quantity: quantity,
+        difficulty,
+        discovered_hour: current_hour,
+        exhausted: false,
+    });
+    id
+}
+
+// Find a new deposit in a region and record the discovery; returns the natural event ID
+pub(crate) fn discover_deposit(
+    ctx: &ReducerContext,
+    world_id: u32,
+    region_id: u32,
+    kind: Option<DepositKind>,
+    current_hour: u64,
+) -> Result<u32, String> {
+    let mut rng = rand::thread_rng();
+    let kind = kind.unwrap_or_else(|| match rng.gen_range(0..3) {
+        0 => DepositKind::Iron,
+        1 => DepositKind::Coal,
+        _ => DepositKind::Stone,
+    });
+
+    let earlier = ctx.db.resource_deposit().iter()
+        .filter(|d| d.world_id == world_id && d.region_id == region_id && d.kind == kind)
+        .count();
+    let quantity = match kind {
+        DepositKind::Stone => rng.gen_range(STONE_QUANTITY.0..STONE_QUANTITY.1),
+        _ => rng.gen_range(SEAM_QUANTITY.0..SEAM_QUANTITY.1),
+    };
+    insert_deposit(ctx, world_id, region_id, kind, quantity, earlier as f32 * LATER_SEAM_DIFFICULTY, current_hour);
+
+    let place = ctx.db.city().id().find(&region_id)
+        .map(|c| c.name)
+        .unwrap_or_else(|| format!("region {}", region_id));
+    let description = match kind {
+        DepositKind::Stone => format!("Prospectors near {} have opened up a fresh bed of good building stone.", place),
+        _ => format!("Prospectors near {} have struck a new seam of {:?}.", place, kind),
+    };
+    log::info!("Discovered a {:?} deposit of {:.0} in region {}", kind, quantity, region_id);
+
+    record_natural_event(ctx, world_id, NaturalEventType::ResourceDiscovery, vec![region_id], current_hour, Some(description))
+}
+
+// How short a region is of workable reserves of a resource, 0 (ample) to 1 (none)
+fn regional_scarcity(ctx: &ReducerContext, world_id: u32, region_id: u32, resource_type: ResourceType) -> f32 {
+    let reserves: f32 = ctx.db.resource_deposit().iter()
+        .filter(|d| d.world_id == world_id && d.region_id == region_id && !d.exhausted && d.kind.resource() == resource_type)
+        .map(|d| d.quantity * (1.0 - d.difficulty))
+        .sum();
+    let population = ctx.db.city().id().find(&region_id).map(|c| c.population).unwrap_or(0);
+    let needed = (population as f32 * RESERVE_PER_RESIDENT).max(1.0);
+
+    (1.0 - reserves / needed).clamp(0.0, 1.0)
+}
+
+// A scarce region with no supply of a resource draws a route from the nearest plentiful one
+fn open_supply_routes(
+    ctx: &ReducerContext,
+    world_id: u32,
+    resource_type: ResourceType,
+    scarcity: &[(u32, f32)],
+    current_hour: u64,
+) -> Result<(), String> {
+    for &(region_id, share) in scarcity.iter().filter(|(_, s)| *s >= SCARCE) {
+        let supplied = ctx.db.trade_route().iter()
+            .any(|r| r.world_id == world_id && r.to_city_id == region_id && r.resource_type == resource_type && r.is_active);
+        if supplied {
+            continue;
+        }
+
+        let Some(supplier) = scarcity.iter()
+            .filter(|(id, s)| *id != region_id && *s <= PLENTIFUL)
+            .map(|&(id, _)| id)
+            .min_by_key(|&id| travel_hours(ctx, id, region_id)) else { continue };
+
+        let population = ctx.db.city().id().find(&region_id).map(|c| c.population).unwrap_or(0);
+        let price_at = |city_id: u32| ctx.db.market().iter()
+            .find(|m| m.city_id == city_id && m.resource_type == resource_type)
+            .map(|m| m.price)
+            .unwrap_or_else(|| base_price(resource_type));
+        let (buy, sell) = (price_at(supplier), price_at(region_id));
+
+        let route_id = ctx.db.trade_route().iter().count() as u32 + 1;
+        ctx.db.trade_route().insert(TradeRoute {
+            id: route_id,
+            world_id,
+            from_city_id: supplier,
+            to_city_id: region_id,
+            resource_type,
+            volume: population as f32 * ROUTE_VOLUME_PER_RESIDENT * share,
+            frequency_hours: 24,
+            profitability: ((sell - buy) / buy.max(0.01)).max(0.0),
+            safety: 80.0,
+            merchant_count: 0, // Merchants sign up as the route proves itself
+            last_trade_hour: 0,
+            is_active: true,
+        });
+
+        create_economic_event(
+            ctx,
+            world_id,
+            EconomicEventType::NewTradeRoute,
+            Some(resource_type),
+            vec![supplier, region_id],
+            current_hour,
+            format!("With its own {:?} running short, city {} opens a supply route from city {}", resource_type, region_id, supplier),
+        )?;
+    }
+
+    // Routes whose supplier has run short themselves fall idle
+    let failing: Vec<TradeRoute> = ctx.db.trade_route().iter()
+        .filter(|r| r.world_id == world_id && r.resource_type == resource_type && r.is_active)
+        .filter(|r| scarcity.iter().any(|&(id, s)| id == r.from_city_id && s >= SCARCE))
+        .collect();
+    for mut route in failing {
+        route.is_active = false;
+        ctx.db.trade_route().id().update(route);
+    }
+
+    Ok(())
+}
+
+// Felled timber grows back slowly, and the region's forest thins or thickens with what is standing
+fn regrow_forest(ctx: &ReducerContext, world_id: u32, region_id: u32) {
+    let Some(mut timber) = ctx.db.resource_deposit().iter()
+        .find(|d| d.world_id == world_id && d.region_id == region_id && d.kind == DepositKind::Timber) else { return };
+
+    timber.quantity += (timber.initial_quantity - timber.quantity).max(0.0) * TIMBER_REGROWTH;
+    let standing = (timber.quantity / timber.initial_quantity.max(1.0)).clamp(0.0, 1.0);
+    ctx.db.resource_deposit().id().update(timber);
+
+    // Cleared forest holds less moisture and shade
+    let (temperature, humidity, _, _, _, vegetation, _) = get_microclimate_modifiers(MicroclimateType::Forest);
+    let forests: Vec<_> = ctx.db.microclimate().iter()
+        .filter(|m| m.world_id == world_id && m.region_id == region_id && m.location_type == MicroclimateType::Forest)
+        .collect();
+    for mut forest in forests {
+        forest.vegetation_density = vegetation * standing;
+        forest.humidity_modifier = humidity * standing;
+        forest.temperature_modifier = temperature * standing;
+        ctx.db.microclimate().id().update(forest);
+    }
+}
+
+// Vegetation density of a region's forest, 0 if it has none
+fn forest_cover(ctx: &ReducerContext, world_id: u32, region_id: u32) -> f32 {
+    ctx.db.microclimate().iter()
+        .find(|m| m.world_id == world_id && m.region_id == region_id && m.location_type == MicroclimateType::Forest)
+        .map(|m| m.vegetation_density)
+        .unwrap_or(0.0)
+}
+
+// Share of a region's original forest still standing; land wildlife lives within it
+pub(crate) fn forest_habitat(ctx: &ReducerContext, world_id: u32, region_id: u32) -> f32 {
+    ctx.db.resource_deposit().iter()
+        .find(|d| d.world_id == world_id && d.region_id == region_id && d.kind == DepositKind::Timber)
+        .map(|d| (d.quantity / d.initial_quantity.max(1.0)).clamp(0.0, 1.0))
+        .unwrap_or(1.0)
+}
\ No newline at end of file
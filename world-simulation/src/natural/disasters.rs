@@ -7,6 +7,11 @@ use rand::Rng;
 use crate::world::ClimateZone;
 use crate::natural::{NaturalEventType, EventSeverity};
 use crate::narrative::{create_narrative_event, EventCategory};
+use crate::reducers::health::seed_plague;
+use crate::reducers::damage::{apply_disaster_footprint, dispatch_response};
+use crate::reducers::preparedness::review_warning;
+use crate::tables::damage::disaster_footprint;
+use crate::systems::modifiers::disaster_damage;
 
 // Disaster risk assessment
 #[spacetimedb::table(name = disaster_risk)]
@@ -281,7 +286,6 @@ pub fn assess_disaster_risks(
     let disaster_risks: Vec<DisasterRisk> = ctx.db.disaster_risk()
         .iter()
         .filter(|r| r.world_id == world_id)
-        .cloned()
         .collect();
 
     for risk in disaster_risks {
@@ -558,26 +562,35 @@ pub fn execute_disaster_events(
     current_hour: u64,
 ) -> Result<Vec<u32>, String> {
     let mut disaster_event_ids = Vec::new();
+    let mut rng = rand::thread_rng();
 
     // Find warnings that should trigger now
     let triggering_warnings: Vec<DisasterWarning> = ctx.db.disaster_warning()
         .iter()
         .filter(|w| w.world_id == world_id && w.is_active)
         .filter(|w| current_hour >= w.estimated_impact_hour)
-        .cloned()
         .collect();
 
     for mut warning in triggering_warnings {
-        // Create the actual disaster event
+        warning.is_active = false;
+
+        // Forecasts come true as often as their confidence says; disasters that give no warning always strike
+        let unforecast = warning.estimated_impact_hour == warning.warning_issued_hour;
+        if !unforecast && rng.gen::<f32>() * 100.0 >= warning.confidence {
+            log::info!("{:?} warning {} passed without incident", warning.disaster_type, warning.id);
+            review_warning(ctx, &warning, None, current_hour)?;
+            ctx.db.disaster_warning().id().update(warning);
+            continue;
+        }
+
+        // Create the actual disaster event and weigh the warned response against it
         let disaster_id = create_disaster_event(ctx, &warning, current_hour)?;
         disaster_event_ids.push(disaster_id);
-
-        // Deactivate the warning
-        warning.is_active = false;
-        ctx.db.disaster_warning().id().update(warning.id, warning);
+        review_warning(ctx, &warning, Some(disaster_id), current_hour)?;
 
         // Initiate disaster response
         initiate_disaster_response(ctx, world_id, &warning, disaster_id, current_hour)?;
+        ctx.db.disaster_warning().id().update(warning);
     }
 
     Ok(disaster_event_ids)
@@ -596,9 +609,18 @@ fn create_disaster_event(
         DisasterType::Wildfire => NaturalEventType::Fire,
         DisasterType::Flood => NaturalEventType::Flood,
         DisasterType::Drought => NaturalEventType::Drought,
+        DisasterType::Plague => NaturalEventType::Plague,
         _ => NaturalEventType::WeatherChange, // Generic fallback
     };
 
+    // Plague strikes people rather than structures: seed infections in the affected cities
+    if warning.disaster_type == DisasterType::Plague {
+        let affected_regions: Vec<u32> = serde_json::from_str(&warning.affected_regions)
+            .unwrap_or_default();
+        let infected = seed_plague(ctx, &affected_regions, current_hour);
+        log::info!("Plague seeded {} infections in regions {:?}", infected, affected_regions);
+    }
+
     let (duration, economic_impact, population_impact) = calculate_disaster_impacts(warning.disaster_type, warning.estimated_severity);
 
     let natural_event = crate::natural::NaturalEvent {
@@ -619,6 +641,18 @@ fn create_disaster_event(
 
     ctx.db.natural_event().insert(natural_event);
 
+    // Strike the buildings and people in each affected city; preparedness reduces the damage
+    let affected_regions: Vec<u32> = serde_json::from_str(&warning.affected_regions)
+        .unwrap_or_default();
+    for region_id in affected_regions {
+        let mitigation_level = ctx.db.disaster_risk().iter()
+            .find(|r| r.world_id == warning.world_id && r.region_id == region_id && r.disaster_type == warning.disaster_type)
+            .map(|r| r.mitigation_level)
+            .unwrap_or(0.0);
+        apply_disaster_footprint(ctx, event_id, region_id, warning.disaster_type,
+            warning.estimated_severity, mitigation_level, current_hour);
+    }
+
     // Create high-importance narrative event
     let importance = match warning.estimated_severity {
         EventSeverity::Minor => 4,
@@ -662,6 +696,7 @@ fn calculate_disaster_impacts(disaster_type: DisasterType, severity: EventSeveri
         DisasterType::Volcano => (168, -0.8, -0.5),
         DisasterType::Tsunami => (12, -0.9, -0.7),
         DisasterType::Meteor => (1, -0.95, -0.8),
+        DisasterType::Plague => (168, -0.2, 0.0),    // Casualties come from individual illness
         _ => (24, -0.2, -0.1),
     };
 
@@ -722,41 +757,103 @@ fn initiate_disaster_response(
     disaster_event_id: u32,
     current_hour: u64,
 ) -> Result<u32, String> {
-    let response_id = ctx.db.disaster_response().iter().count() as u32 + 1;
+    let first_response_id = ctx.db.disaster_response().iter().count() as u32 + 1;
 
     // Parse affected regions
     let affected_regions: Vec<u32> = serde_json::from_str(&warning.affected_regions)
         .unwrap_or_else(|_| vec![]);
 
-    if let Some(&region_id) = affected_regions.first() {
-        let response_type = match warning.disaster_type {
-            DisasterType::Wildfire => ResponseType::FireSuppression,
-            DisasterType::Flood => ResponseType::FloodControl,
-            DisasterType::Earthquake => ResponseType::SearchAndRescue,
-            DisasterType::Hurricane => ResponseType::EmergencyServices,
-            _ => ResponseType::Relief,
-        };
+    let primary_response = match warning.disaster_type {
+        DisasterType::Wildfire => ResponseType::FireSuppression,
+        DisasterType::Flood => ResponseType::FloodControl,
+        DisasterType::Earthquake => ResponseType::SearchAndRescue,
+        DisasterType::Hurricane => ResponseType::EmergencyServices,
+        _ => ResponseType::Relief,
+    };
 
-        let disaster_response = DisasterResponse {
-            id: response_id,
-            world_id,
-            region_id,
-            disaster_event_id,
-            response_type,
-            resources_allocated: 100000.0, // Base resource allocation
-            personnel_count: 50,
-            effectiveness: 70.0, // Base effectiveness
-            start_hour: current_hour,
-            duration_hours: 72, // 3 days default response
-            status: ResponseStatus::Active,
-            results: "{}".to_string(),
+    for region_id in affected_regions {
+        let footprint = ctx.db.disaster_footprint().iter()
+            .find(|f| f.natural_event_id == disaster_event_id && f.city_id == region_id);
+
+        // Without structural damage there is nobody to dig out or rebuild for
+        let footprint = match footprint {
+            Some(footprint) => footprint,
+            None => {
+                insert_response(ctx, world_id, region_id, disaster_event_id, primary_response,
+                    0, 0.0, 0.0, current_hour, "{}".to_string());
+                continue;
+            },
         };
 
-        ctx.db.disaster_response().insert(disaster_response);
+        let mut response_types = Vec::new();
+        if warning.evacuation_recommended && footprint.displaced > 0 {
+            response_types.push(ResponseType::Evacuation);
+        }
+        if footprint.injured > 0 && primary_response != ResponseType::SearchAndRescue {
+            response_types.push(ResponseType::SearchAndRescue);
+        }
+        response_types.push(primary_response);
+        if footprint.buildings_damaged > 0 {
+            response_types.push(ResponseType::Reconstruction);
+        }
+
+        for response_type in response_types {
+            let (personnel, resources, results) = dispatch_response(ctx, response_type, footprint.id, current_hour)?;
 
-        log::info!("Initiated {:?} response for disaster {} in region {}",
-            response_type, disaster_event_id, region_id);
+            // Effectiveness is the share of the crews the footprint needed that actually turned up
+            let sites = match response_type {
+                ResponseType::Evacuation => footprint.displaced,
+                ResponseType::SearchAndRescue | ResponseType::MedicalResponse | ResponseType::EmergencyServices => footprint.injured,
+                _ => footprint.buildings_damaged,
+            }.max(1);
+            let needed = sites as f32 * disaster_damage::RESPONDERS_PER_SITE as f32;
+            let effectiveness = (personnel as f32 / needed * 100.0).min(100.0);
+
+            insert_response(ctx, world_id, region_id, disaster_event_id, response_type,
+                personnel, resources, effectiveness, current_hour, results);
+        }
     }
 
-    Ok(response_id)
+    Ok(first_response_id)
+}
+
+fn insert_response(
+    ctx: &ReducerContext,
+    world_id: u32,
+    region_id: u32,
+    disaster_event_id: u32,
+    response_type: ResponseType,
+    personnel_count: u32,
+    resources_allocated: f32,
+    effectiveness: f32,
+    current_hour: u64,
+    results: String,
+) {
+    let response_id = ctx.db.disaster_response().iter().count() as u32 + 1;
+
+    let status = match (response_type, personnel_count) {
+        (ResponseType::Reconstruction, _) => ResponseStatus::Active, // Completed when the last rebuild finishes
+        (_, 0) => ResponseStatus::Failed,
+        _ => ResponseStatus::Completed,
+    };
+
+    let disaster_response = DisasterResponse {
+        id: response_id,
+        world_id,
+        region_id,
+        disaster_event_id,
+        response_type,
+        resources_allocated,
+        personnel_count,
+        effectiveness,
+        start_hour: current_hour,
+        duration_hours: 72, // 3 days default response
+        status,
+        results,
+    };
+
+    ctx.db.disaster_response().insert(disaster_response);
+
+    log::info!("Initiated {:?} response for disaster {} in region {}",
+        response_type, disaster_event_id, region_id);
 }
\ No newline at end of file
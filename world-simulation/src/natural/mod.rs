@@ -6,16 +6,24 @@ use log;
 use rand::Rng;
 use crate::world::{Season, ClimateZone};
 use crate::narrative::{create_narrative_event, EventCategory};
+use crate::reducers::health::seed_plague;
+use crate::tables::city::city;
+use crate::natural::weather::microclimate;
 
 pub mod weather;
 pub mod disasters;
 pub mod seasonal_cycles;
+pub mod climate;
+pub mod wildlife;
+pub mod deposits;
 
 // Natural event types
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, SpacetimeType)]
 pub enum NaturalEventType {
     WeatherChange,
     Storm,
+    Blizzard,
+    Heatwave,
     Flood,
     Drought,
     Earthquake,
@@ -71,6 +79,10 @@ pub struct ClimateState {
     pub air_quality: f32,        // 0-100 index
     pub last_updated_hour: u64,
     pub weather_pattern: WeatherPattern,
+    pub latitude: f32,           // Degrees; negative in the southern hemisphere
+    pub temperature_anomaly: f32, // Degrees from the baseline, fades over days
+    pub humidity_anomaly: f32,
+    pub moisture_anomaly: f32,   // Standard deviations; rain falls when high
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, SpacetimeType)]
@@ -125,31 +137,36 @@ pub fn initialize_natural_systems(
     for city_id in cities {
         let climate_id = ctx.db.climate_state().iter().count() as u32 + 1;
 
-        // Base climate values based on climate zone
-        let (base_temp, base_humidity, base_precipitation) = match climate_zone {
-            ClimateZone::Arctic => (-20.0, 60.0, 0.5),
-            ClimateZone::Temperate => (15.0, 70.0, 1.5),
-            ClimateZone::Tropical => (28.0, 85.0, 3.0),
-            ClimateZone::Arid => (25.0, 30.0, 0.2),
-            ClimateZone::Mediterranean => (20.0, 65.0, 1.0),
-        };
-
-        let climate_state = ClimateState {
+        let latitude = ctx.db.city().id().find(&city_id).map_or(climate::DEFAULT_LATITUDE, |c| c.latitude);
+        let mut climate_state = ClimateState {
             id: climate_id,
             world_id,
             region_id: city_id,
-            current_temperature: base_temp,
-            humidity: base_humidity,
-            precipitation: base_precipitation,
-            wind_speed: 10.0,
-            atmospheric_pressure: 1013.25,
+            current_temperature: 0.0,
+            humidity: 0.0,
+            precipitation: 0.0,
+            wind_speed: 0.0,
+            atmospheric_pressure: 0.0,
             air_quality: 80.0,
             last_updated_hour: world.total_hours,
             weather_pattern: WeatherPattern::Clear,
+            latitude,
+            temperature_anomaly: 0.0,
+            humidity_anomaly: 0.0,
+            moisture_anomaly: 0.0,
         };
+        // Start from the baseline for this moment
+        climate::advance(&mut climate_state, climate_zone, world.total_hours, &mut rand::thread_rng());
 
         ctx.db.climate_state().insert(climate_state);
+
+        // Forest, farmland and coast each region's deposits and wildlife draw on
+        if !ctx.db.microclimate().iter().any(|m| m.world_id == world_id && m.region_id == city_id) {
+            weather::initialize_microclimates(ctx, world_id, city_id)?;
+        }
     }
+    weather::build_region_links(ctx, world_id);
+    disasters::initialize_disaster_risks(ctx, world_id, climate_zone)?;
 
     // Initialize seasonal effects for all seasons
     for season in [Season::Spring, Season::Summer, Season::Autumn, Season::Winter] {
@@ -304,7 +321,7 @@ fn generate_event_probabilities_json(season: Season, climate_zone: ClimateZone)
     probabilities.to_string()
 }
 
-// Update climate conditions based on time and season
+// Update climate conditions from the climate model
 #[spacetimedb::reducer]
 pub fn update_climate_conditions(
     ctx: &ReducerContext,
@@ -316,114 +333,20 @@ pub fn update_climate_conditions(
         .find(&world_id)
         .ok_or("World not found")?;
 
-    let current_season = crate::world::calculate_season_from_hour(current_hour);
-
     let climate_states: Vec<ClimateState> = ctx.db.climate_state()
         .iter()
         .filter(|c| c.world_id == world_id)
-        .cloned()
         .collect();
 
+    let mut rng = rand::thread_rng();
     for mut climate in climate_states {
-        // Apply seasonal changes
-        let seasonal_effect = ctx.db.seasonal_effect()
-            .iter()
-            .find(|e| e.world_id == world_id &&
-                     e.season == current_season &&
-                     e.climate_zone == world.climate_zone);
-
-        if let Some(effect) = seasonal_effect {
-            // Update temperature based on season
-            climate.current_temperature = apply_seasonal_temperature_change(
-                climate.current_temperature,
-                current_season,
-                world.climate_zone,
-            );
-
-            // Update other weather parameters
-            update_weather_parameters(&mut climate, current_season, current_hour);
-        }
-
-        climate.last_updated_hour = current_hour;
-        ctx.db.climate_state().id().update(climate.id, climate);
+        climate::advance(&mut climate, world.climate_zone, current_hour, &mut rng);
+        ctx.db.climate_state().id().update(climate);
     }
 
     Ok(())
 }
 
-// Apply seasonal temperature changes
-fn apply_seasonal_temperature_change(
-    base_temp: f32,
-    season: Season,
-    climate_zone: ClimateZone,
-) -> f32 {
-    let seasonal_adjustment = match (season, climate_zone) {
-        (Season::Summer, ClimateZone::Arctic) => 15.0,
-        (Season::Summer, ClimateZone::Temperate) => 10.0,
-        (Season::Summer, ClimateZone::Tropical) => 2.0,
-        (Season::Summer, ClimateZone::Arid) => 8.0,
-        (Season::Summer, ClimateZone::Mediterranean) => 8.0,
-
-        (Season::Winter, ClimateZone::Arctic) => -25.0,
-        (Season::Winter, ClimateZone::Temperate) => -15.0,
-        (Season::Winter, ClimateZone::Tropical) => -2.0,
-        (Season::Winter, ClimateZone::Arid) => -5.0,
-        (Season::Winter, ClimateZone::Mediterranean) => -8.0,
-
-        (Season::Spring, _) => 0.0,
-        (Season::Autumn, _) => -5.0,
-    };
-
-    base_temp + seasonal_adjustment
-}
-
-// Update weather parameters based on season and time
-fn update_weather_parameters(
-    climate: &mut ClimateState,
-    season: Season,
-    hour: u64,
-) {
-    let mut rng = rand::thread_rng();
-
-    // Daily temperature variation
-    let hour_of_day = hour % 24;
-    let daily_temp_variation = ((hour_of_day as f32 - 12.0) / 24.0 * std::f32::consts::PI).sin() * 5.0;
-    climate.current_temperature += daily_temp_variation;
-
-    // Random weather changes
-    if rng.gen::<f32>() < 0.1 { // 10% chance of weather change
-        climate.weather_pattern = match rng.gen_range(0..8) {
-            0 => WeatherPattern::Clear,
-            1 => WeatherPattern::Cloudy,
-            2 => WeatherPattern::Rainy,
-            3 => WeatherPattern::Stormy,
-            4 => WeatherPattern::Foggy,
-            5 => WeatherPattern::Windy,
-            6 => WeatherPattern::Hot,
-            7 => WeatherPattern::Cold,
-            _ => WeatherPattern::Clear,
-        };
-    }
-
-    // Update based on weather pattern
-    match climate.weather_pattern {
-        WeatherPattern::Rainy => {
-            climate.precipitation = (climate.precipitation + 2.0).min(10.0);
-            climate.humidity = (climate.humidity + 10.0).min(100.0);
-        },
-        WeatherPattern::Stormy => {
-            climate.precipitation = (climate.precipitation + 5.0).min(20.0);
-            climate.wind_speed = (climate.wind_speed + 20.0).min(100.0);
-            climate.humidity = (climate.humidity + 15.0).min(100.0);
-        },
-        WeatherPattern::Clear => {
-            climate.precipitation = (climate.precipitation - 1.0).max(0.0);
-            climate.humidity = (climate.humidity - 5.0).max(20.0);
-        },
-        _ => {} // Other patterns have minimal immediate effects
-    }
-}
-
 // Generate natural events based on season and climate
 #[spacetimedb::reducer]
 pub fn generate_natural_events(
@@ -536,21 +459,61 @@ fn create_natural_event(
     world_id: u32,
     event_type: NaturalEventType,
     hour: u64,
+) -> Result<u32, String> {
+    // Plague, drought, flood and bumper harvests strike one region; plague seeds infections among its residents
+    let affected_regions: Vec<u32> = if matches!(event_type,
+        NaturalEventType::Plague | NaturalEventType::Drought | NaturalEventType::Flood | NaturalEventType::Harvest |
+        NaturalEventType::ResourceDiscovery) {
+        let regions: Vec<u32> = ctx.db.climate_state().iter()
+            .filter(|c| c.world_id == world_id)
+            .map(|c| c.region_id)
+            .collect();
+        if regions.is_empty() {
+            Vec::new()
+        } else {
+            vec![regions[rand::thread_rng().gen_range(0..regions.len())]]
+        }
+    } else {
+        Vec::new()
+    };
+
+    // A discovery is a new deposit in its region
+    if let (NaturalEventType::ResourceDiscovery, Some(&region_id)) = (event_type, affected_regions.first()) {
+        return deposits::discover_deposit(ctx, world_id, region_id, None, hour);
+    }
+
+    record_natural_event(ctx, world_id, event_type, affected_regions, hour, None)
+}
+
+// Record a natural event striking the given regions, optionally telling its own story
+pub(crate) fn record_natural_event(
+    ctx: &ReducerContext,
+    world_id: u32,
+    event_type: NaturalEventType,
+    affected_regions: Vec<u32>,
+    hour: u64,
+    description: Option<String>,
 ) -> Result<u32, String> {
     let event_id = ctx.db.natural_event().iter().count() as u32 + 1;
 
-    let (severity, duration, description, economic_impact, population_impact) =
+    let (severity, duration, default_description, economic_impact, population_impact) =
         generate_event_details(event_type);
+    let description = description.unwrap_or(default_description);
+
+    if event_type == NaturalEventType::Plague {
+        let infected = seed_plague(ctx, &affected_regions, hour);
+        log::info!("Plague outbreak infected {} individuals in regions {:?}", infected, affected_regions);
+    }
 
     let natural_event = NaturalEvent {
         id: event_id,
         world_id,
         event_type,
         severity,
-        affected_region: "[]".to_string(), // TODO: Determine affected regions
+        affected_region: format!("{:?}", affected_regions), // TODO: Determine regions for other event types
         start_hour: hour,
         duration_hours: duration,
-        description,
+        description: description.clone(),
         environmental_effects: generate_environmental_effects_json(event_type, severity),
         economic_impact,
         population_impact,
@@ -593,6 +556,18 @@ fn generate_event_details(event_type: NaturalEventType) -> (EventSeverity, u32,
             let description = "A powerful storm system moves through the region, bringing strong winds and heavy rain.".to_string();
             (severity, duration, description, -0.1, -0.05)
         },
+        NaturalEventType::Blizzard => {
+            let severity = if rng.gen::<f32>() < 0.6 { EventSeverity::Moderate } else { EventSeverity::Major };
+            let duration = rng.gen_range(12..48);
+            let description = "Driving snow and bitter winds bury roads and cut settlements off from one another.".to_string();
+            (severity, duration, description, -0.15, -0.1)
+        },
+        NaturalEventType::Heatwave => {
+            let severity = if rng.gen::<f32>() < 0.6 { EventSeverity::Moderate } else { EventSeverity::Major };
+            let duration = rng.gen_range(72..240);
+            let description = "Relentless heat settles over the region, wilting crops and exhausting the old and the sick.".to_string();
+            (severity, duration, description, -0.1, -0.1)
+        },
         NaturalEventType::Flood => {
             let severity = if rng.gen::<f32>() < 0.6 { EventSeverity::Moderate } else { EventSeverity::Major };
             let duration = rng.gen_range(48..168);
@@ -623,6 +598,12 @@ fn generate_event_details(event_type: NaturalEventType) -> (EventSeverity, u32,
             let description = "New natural resource deposits have been discovered in the region.".to_string();
             (severity, duration, description, 0.2, 0.05)
         },
+        NaturalEventType::EcosystemChange => {
+            let severity = EventSeverity::Moderate;
+            let duration = rng.gen_range(720..2160); // Stocks take seasons to recover
+            let description = "Wildlife in the region has been hunted and fished past the point where it can replenish itself.".to_string();
+            (severity, duration, description, -0.15, -0.05)
+        },
         NaturalEventType::Migration => {
             let severity = EventSeverity::Minor;
             let duration = rng.gen_range(168..720);
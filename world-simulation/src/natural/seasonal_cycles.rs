@@ -5,6 +5,7 @@ use serde::{Serialize, Deserialize};
 use log;
 use crate::world::{Season, ClimateZone};
 use crate::natural::SeasonalEffect;
+use crate::natural::climate::{calendar, DAYS_PER_CYCLE};
 
 // Seasonal transition tracking
 #[spacetimedb::table(name = seasonal_transition)]
@@ -63,7 +64,7 @@ pub struct Phenology {
     pub region_id: u32,
     pub species_type: SpeciesType,
     pub phase: PhenologicalPhase,
-    pub typical_start_day: u32,    // Day of year (1-365)
+    pub typical_start_day: u32,    // Day of year (1-360)
     pub current_year_start: u32,   // Actual start this year
     pub duration_days: u32,
     pub temperature_threshold: f32,
@@ -337,11 +338,11 @@ fn get_phenological_schedule(climate_zone: ClimateZone) -> Vec<(SpeciesType, Phe
             (SpeciesType::Large_Mammals, PhenologicalPhase::Breeding, 110, 60, 8.0),
         ],
         ClimateZone::Tropical => vec![
-            (SpeciesType::Trees, PhenologicalPhase::Flowering, 1, 365, 20.0),
-            (SpeciesType::Trees, PhenologicalPhase::Fruiting, 1, 365, 22.0),
+            (SpeciesType::Trees, PhenologicalPhase::Flowering, 1, 360, 20.0),
+            (SpeciesType::Trees, PhenologicalPhase::Fruiting, 1, 360, 22.0),
             (SpeciesType::Fish, PhenologicalPhase::Migration_Arrival, 180, 60, 25.0),
             (SpeciesType::Fish, PhenologicalPhase::Breeding, 200, 90, 26.0),
-            (SpeciesType::Insects, PhenologicalPhase::Emergence, 1, 365, 24.0),
+            (SpeciesType::Insects, PhenologicalPhase::Emergence, 1, 360, 24.0),
             (SpeciesType::Large_Mammals, PhenologicalPhase::Breeding, 90, 120, 24.0),
         ],
         ClimateZone::Arctic => vec![
@@ -501,12 +502,11 @@ pub fn update_phenological_phases(
 ) -> Result<Vec<u32>, String> {
     let mut updated_phases = Vec::new();
 
-    let current_day_of_year = ((current_hour / 24) % 365) as u32 + 1;
+    let current_day_of_year = calendar(current_hour).0 as u32;
 
     let phenology_data: Vec<Phenology> = ctx.db.phenology()
         .iter()
         .filter(|p| p.world_id == world_id)
-        .cloned()
         .collect();
 
     for mut pheno in phenology_data {
@@ -518,7 +518,6 @@ pub fn update_phenological_phases(
            pheno.current_year_start != start_day {
 
             pheno.current_year_start = start_day;
-            ctx.db.phenology().id().update(pheno.id, pheno);
             updated_phases.push(pheno.id);
 
             log::info!("Phenological phase {:?} started for {:?} in region {}",
@@ -540,12 +539,20 @@ pub fn update_phenological_phases(
                     2, // Low importance for natural cycles
                 )?;
             }
+
+            ctx.db.phenology().id().update(pheno);
         }
     }
 
     Ok(updated_phases)
 }
 
+// Whether a phase is under way at this hour
+pub(crate) fn phase_active(pheno: &Phenology, hour: u64) -> bool {
+    let day_of_year = calendar(hour).0 as u32;
+    day_of_year >= pheno.current_year_start && day_of_year < pheno.current_year_start + pheno.duration_days
+}
+
 // Calculate climate-adjusted phenology start date
 fn calculate_adjusted_phenology_start(
     ctx: &ReducerContext,
@@ -563,7 +570,7 @@ fn calculate_adjusted_phenology_start(
 
     // Ensure reasonable bounds
     let adjusted_start = (pheno.typical_start_day as f32 + temp_adjustment)
-        .clamp(1.0, 365.0) as u32;
+        .clamp(1.0, DAYS_PER_CYCLE) as u32;
 
     Ok(adjusted_start)
 }
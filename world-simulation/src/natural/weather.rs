@@ -4,8 +4,35 @@ use spacetimedb::{ReducerContext, Table, SpacetimeType};
 use serde::{Serialize, Deserialize};
 use log;
 use rand::Rng;
-use crate::world::{Season, ClimateZone};
-use crate::natural::{ClimateState, WeatherPattern};
+use std::collections::HashSet;
+use crate::world::ClimateZone;
+use crate::world::game_world::game_world;
+use crate::natural::{climate_state, natural_event, ClimateState, WeatherPattern, NaturalEventType, record_natural_event};
+use crate::natural::climate::{self, ClimateAnomaly};
+use crate::tables::city::city;
+
+const EARTH_RADIUS_KM: f32 = 6371.0;
+const REGION_NEIGHBOURS: usize = 4;             // Nearest regions each region is linked to
+const MIN_LINK_KM: f32 = 50.0;                  // Regions placed on the same spot still sit a day's ride apart
+
+const FRONT_SPAWN_CHANCE: f32 = 0.05;           // Per hour
+const FRONT_SPEED_KMH: (f32, f32) = (15.0, 45.0);
+const FRONT_SIZE_KM: (f32, f32) = (100.0, 400.0);
+const FRONT_FORCING_HOURS: f32 = 6.0;           // Time for a front to bring a region most of the way to its own weather
+const FRONT_GROWTH_PER_HOUR: f32 = 2.0;         // Intensity gained per hour on full fuel
+const FRONT_FRICTION_PER_HOUR: f32 = 1.0;       // Intensity lost per hour regardless
+const FRONT_DISSIPATE_INTENSITY: f32 = 10.0;
+const FRONT_MAX_AGE_HOURS: u64 = 240;
+const UPWIND_WEIGHT: f32 = 0.02;                // Fronts rarely travel against the prevailing wind
+
+const FORECAST_SKILL_FLOOR: f32 = 0.3;          // Knowing the climate alone still beats a guess
+const FORECAST_FRONT_HOPS: usize = 8;           // How far along a front's track forecasters look
+const FRONT_TIMING_SPREAD: f32 = 0.2;           // Arrival-time uncertainty per hour of travel
+
+const STORM_EVENT_WIND: f32 = 60.0;             // km/h with rain
+const BLIZZARD_EVENT_WIND: f32 = 30.0;          // km/h with snow
+const HEATWAVE_ANOMALY: f32 = 6.0;              // Degrees above the baseline
+const HEATWAVE_TEMPERATURE: f32 = 30.0;
 
 // Weather forecast data
 #[spacetimedb::table(name = weather_forecast)]
@@ -20,6 +47,7 @@ pub struct WeatherForecast {
     pub wind_speed: f32,
     pub weather_pattern: WeatherPattern,
     pub confidence: f32,         // Forecast accuracy 0-100%
+    pub front_id: Option<u32>,   // Front expected over the region by then
     pub created_hour: u64,       // When forecast was made
 }
 
@@ -32,13 +60,15 @@ pub struct WeatherFront {
     pub front_type: FrontType,
     pub origin_region: u32,
     pub current_region: u32,
-    pub target_region: u32,
-    pub movement_speed: f32,     // regions per hour
+    pub target_region: u32,      // Next region along its track
+    pub movement_speed: f32,     // km per hour
+    pub progress_km: f32,        // Distance covered toward the target region
     pub intensity: f32,          // 0-100
-    pub size: f32,              // affected radius
+    pub size: f32,              // Radius of influence in km
+    pub air_temperature: f32,    // Temperature of the air mass it carries
     pub created_hour: u64,
-    pub expected_arrival: u64,
-    pub weather_effects: String, // JSON of effects
+    pub expected_arrival: u64,   // Hour it should reach the target region
+    pub last_moved_hour: u64,
     pub is_active: bool,
 }
 
@@ -52,6 +82,18 @@ pub enum FrontType {
     LowPressure,
 }
 
+// Neighbouring regions; the graph fronts travel over
+#[spacetimedb::table(name = region_link)]
+pub struct RegionLink {
+    #[primary_key]
+    pub id: u32,
+    pub world_id: u32,
+    pub from_region: u32,
+    pub to_region: u32,
+    pub distance_km: f32,
+    pub bearing: f32,            // Degrees clockwise from north
+}
+
 // Microclimate variations within regions
 #[spacetimedb::table(name = microclimate)]
 pub struct Microclimate {
@@ -81,6 +123,16 @@ pub enum MicroclimateType {
     Agricultural,
 }
 
+// A front's expected approach to one region
+struct FrontOutlook {
+    front_id: u32,
+    eta_hours: f32,              // From now
+    dwell_hours: f32,            // How long it should sit over the region
+    probability: f32,            // That its track passes through the region at all
+    timing_spread: f32,          // Hours either side of the eta
+    forcing: ClimateAnomaly,     // Anomalies it brings
+}
+
 // Generate weather forecast for regions
 #[spacetimedb::reducer]
 pub fn generate_weather_forecast(
@@ -89,22 +141,34 @@ pub fn generate_weather_forecast(
     current_hour: u64,
     forecast_hours: u32,
 ) -> Result<Vec<u32>, String> {
-    let mut forecast_ids = Vec::new();
+    let world = ctx.db.game_world()
+        .id()
+        .find(&world_id)
+        .ok_or("World not found")?;
 
     let climate_states: Vec<ClimateState> = ctx.db.climate_state()
         .iter()
         .filter(|c| c.world_id == world_id)
-        .cloned()
         .collect();
+    let fronts: Vec<WeatherFront> = ctx.db.weather_front()
+        .iter()
+        .filter(|f| f.world_id == world_id && f.is_active)
+        .collect();
+    let region_count = climate_states.len();
 
+    let mut forecast_ids = Vec::new();
     for climate in climate_states {
+        let outlooks: Vec<FrontOutlook> = fronts.iter()
+            .filter_map(|f| front_outlook(ctx, f, climate.region_id))
+            .collect();
+
         // Generate forecasts for next N hours
         for hour_offset in 1..=forecast_hours {
             let forecast_hour = current_hour + hour_offset as u64;
             let forecast_id = ctx.db.weather_forecast().iter().count() as u32 + 1;
 
-            let (temp, precip_chance, wind, pattern, confidence) =
-                predict_weather_conditions(&climate, hour_offset, world_id, ctx)?;
+            let (temp, precip_chance, wind, pattern, confidence, front_id) =
+                predict_weather_conditions(&climate, world.climate_zone, &outlooks, hour_offset as f32, forecast_hour);
 
             let forecast = WeatherForecast {
                 id: forecast_id,
@@ -116,6 +180,7 @@ pub fn generate_weather_forecast(
                 wind_speed: wind,
                 weather_pattern: pattern,
                 confidence,
+                front_id,
                 created_hour: current_hour,
             };
 
@@ -125,76 +190,99 @@ pub fn generate_weather_forecast(
     }
 
     log::info!("Generated {} weather forecasts for {} regions",
-        forecast_hours, climate_states.len());
+        forecast_hours, region_count);
     Ok(forecast_ids)
 }
 
-// Predict weather conditions based on current state and patterns
+// Predict conditions `lead` hours ahead from the climate model and any fronts on their way
 fn predict_weather_conditions(
     climate: &ClimateState,
-    hours_ahead: u32,
-    world_id: u32,
-    ctx: &ReducerContext,
-) -> Result<(f32, f32, f32, WeatherPattern, f32), String> {
-    let mut rng = rand::thread_rng();
+    zone: ClimateZone,
+    outlooks: &[FrontOutlook],
+    lead: f32,
+    forecast_hour: u64,
+) -> (f32, f32, f32, WeatherPattern, f32, Option<u32>) {
+    let baseline = climate::baseline(zone, climate.latitude);
+    let (day, hour_of_day) = climate::calendar(forecast_hour);
+    let mut expected = climate::expected_anomaly(&climate::anomaly_of(climate), lead);
+    let mut moisture_spread = climate::moisture_spread(lead);
+
+    // The front most likely to be overhead by then, and how sure we are that it will be
+    let overhead = outlooks.iter()
+        .map(|o| (o, chance_overhead(o, lead)))
+        .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal));
+
+    let (call_confidence, front_id) = match overhead {
+        Some((outlook, chance)) => {
+            // Expect the mixture of front and no front; its spread widens with the doubt
+            let gap = outlook.forcing.moisture - expected.moisture;
+            moisture_spread = (moisture_spread.powi(2) + chance * (1.0 - chance) * gap * gap).sqrt();
+            expected = ClimateAnomaly {
+                temperature: expected.temperature + chance * (outlook.forcing.temperature - expected.temperature),
+                humidity: expected.humidity + chance * (outlook.forcing.humidity - expected.humidity),
+                moisture: expected.moisture + chance * gap,
+            };
+            (chance.max(1.0 - chance), if chance > 0.5 { Some(outlook.front_id) } else { None })
+        },
+        None => (1.0, None),
+    };
 
-    // Base prediction on current conditions
-    let mut predicted_temp = climate.current_temperature;
-    let mut predicted_wind = climate.wind_speed;
-    let mut predicted_pattern = climate.weather_pattern;
+    let predicted = climate::sample(&baseline, day, hour_of_day, &expected);
+    let pattern = climate::classify(&predicted, &baseline);
+    let precip_chance = climate::rain_chance(&baseline, day, expected.moisture, moisture_spread) * 100.0;
 
-    // Account for daily temperature cycles
-    let hour_of_day = (climate.last_updated_hour + hours_ahead as u64) % 24;
-    let daily_temp_variation = ((hour_of_day as f32 - 12.0) / 24.0 * std::f32::consts::PI).sin() * 5.0;
-    predicted_temp += daily_temp_variation;
+    // Confidence falls as today's weather stops telling us about the future, and with doubt over fronts
+    let skill = FORECAST_SKILL_FLOOR + (1.0 - FORECAST_SKILL_FLOOR) * climate::persistence(lead);
+    let confidence = (skill * call_confidence * 100.0).clamp(0.0, 100.0);
 
-    // Check for incoming weather fronts
-    let incoming_fronts: Vec<crate::natural::weather::WeatherFront> = ctx.db.weather_front()
-        .iter()
-        .filter(|f| f.world_id == world_id && f.is_active)
-        .filter(|f| f.target_region == climate.region_id)
-        .filter(|f| f.expected_arrival <= climate.last_updated_hour + hours_ahead as u64)
-        .cloned()
-        .collect();
+    (predicted.temperature, precip_chance, predicted.wind_speed, pattern, confidence, front_id)
+}
 
-    if let Some(front) = incoming_fronts.first() {
-        // Modify predictions based on incoming front
-        match front.front_type {
-            FrontType::ColdFront => {
-                predicted_temp -= 8.0;
-                predicted_wind *= 1.5;
-                predicted_pattern = WeatherPattern::Stormy;
-            },
-            FrontType::WarmFront => {
-                predicted_temp += 5.0;
-                predicted_pattern = WeatherPattern::Cloudy;
-            },
-            FrontType::StormSystem => {
-                predicted_wind *= 2.0;
-                predicted_pattern = WeatherPattern::Stormy;
-            },
-            _ => {}
-        }
-    }
+// Chance a front is over its region `lead` hours from now
+fn chance_overhead(outlook: &FrontOutlook, lead: f32) -> f32 {
+    let arrived = logistic((lead - outlook.eta_hours) / outlook.timing_spread);
+    let departed = logistic((lead - outlook.eta_hours - outlook.dwell_hours) / outlook.timing_spread);
+    outlook.probability * (arrived - departed).max(0.0)
+}
+
+fn logistic(z: f32) -> f32 {
+    // Close to the normal distribution's cumulative curve
+    1.0 / (1.0 + (-1.7 * z).exp())
+}
 
-    // Calculate precipitation chance
-    let precip_chance = match predicted_pattern {
-        WeatherPattern::Clear => 5.0,
-        WeatherPattern::Cloudy => 20.0,
-        WeatherPattern::Rainy => 70.0,
-        WeatherPattern::Stormy => 90.0,
-        WeatherPattern::Foggy => 30.0,
-        _ => 15.0,
+// Follow a front's likeliest track to see whether and when it reaches a region
+fn front_outlook(ctx: &ReducerContext, front: &WeatherFront, region_id: u32) -> Option<FrontOutlook> {
+    let speed = front.movement_speed.max(1.0);
+    let outlook = |eta_hours: f32, probability: f32| FrontOutlook {
+        front_id: front.id,
+        eta_hours,
+        dwell_hours: front.size * 2.0 / speed,
+        probability,
+        timing_spread: 1.0 + FRONT_TIMING_SPREAD * eta_hours,
+        forcing: scaled(front_signature(front.front_type), front.intensity / 100.0),
     };
 
-    // Forecast confidence decreases with time
-    let confidence = (100.0 - (hours_ahead as f32 * 3.0)).max(20.0);
+    if front.current_region == region_id {
+        return Some(outlook(0.0, 1.0));
+    }
 
-    // Add some randomness for weather unpredictability
-    let temp_noise = rng.gen_range(-2.0..2.0);
-    predicted_temp += temp_noise;
+    let first_leg = region_links(ctx, front.world_id, front.current_region).into_iter()
+        .find(|l| l.to_region == front.target_region)?;
+    let mut eta = (first_leg.distance_km - front.progress_km).max(0.0) / speed;
+    let mut probability = 1.0;
+    let (mut previous, mut region) = (front.current_region, front.target_region);
 
-    Ok((predicted_temp, precip_chance, predicted_wind, predicted_pattern, confidence))
+    for _ in 0..FORECAST_FRONT_HOPS {
+        if region == region_id {
+            return Some(outlook(eta, probability));
+        }
+        let (hop, chance) = likeliest_hop(ctx, front.world_id, region, previous)?;
+        eta += hop.distance_km / speed;
+        probability *= chance;
+        previous = region;
+        region = hop.to_region;
+    }
+    None
 }
 
 // Create weather fronts that move between regions
@@ -207,179 +295,400 @@ pub fn generate_weather_fronts(
     let mut front_ids = Vec::new();
     let mut rng = rand::thread_rng();
 
-    // Get all regions in the world
-    let regions: Vec<u32> = ctx.db.climate_state()
+    if !ctx.db.region_link().iter().any(|l| l.world_id == world_id) {
+        build_region_links(ctx, world_id);
+    }
+
+    // Generate fronts with low probability
+    if rng.gen::<f32>() >= FRONT_SPAWN_CHANCE {
+        return Ok(front_ids);
+    }
+
+    let regions: Vec<ClimateState> = ctx.db.climate_state()
         .iter()
         .filter(|c| c.world_id == world_id)
-        .map(|c| c.region_id)
-        .collect::<std::collections::HashSet<u32>>()
-        .into_iter()
         .collect();
-
-    // Generate fronts with low probability
-    if rng.gen::<f32>() < 0.05 { // 5% chance per hour
-        if regions.len() >= 2 {
-            let origin = regions[rng.gen_range(0..regions.len())];
-            let target = regions[rng.gen_range(0..regions.len())];
-
-            if origin != target {
-                let front_id = ctx.db.weather_front().iter().count() as u32 + 1;
-                let front_type = match rng.gen_range(0..6) {
-                    0 => FrontType::ColdFront,
-                    1 => FrontType::WarmFront,
-                    2 => FrontType::StormSystem,
-                    3 => FrontType::HighPressure,
-                    4 => FrontType::LowPressure,
-                    _ => FrontType::OccludedFront,
-                };
-
-                let movement_speed = rng.gen_range(0.1..0.5); // regions per hour
-                let travel_time = (1.0 / movement_speed) as u64;
-
-                let front = WeatherFront {
-                    id: front_id,
-                    world_id,
-                    front_type,
-                    origin_region: origin,
-                    current_region: origin,
-                    target_region: target,
-                    movement_speed,
-                    intensity: rng.gen_range(30.0..90.0),
-                    size: rng.gen_range(1.0..3.0),
-                    created_hour: current_hour,
-                    expected_arrival: current_hour + travel_time,
-                    weather_effects: generate_front_effects_json(front_type),
-                    is_active: true,
-                };
-
-                ctx.db.weather_front().insert(front);
-                front_ids.push(front_id);
-
-                log::info!("Created weather front {} moving from region {} to {}",
-                    front_id, origin, target);
-            }
-        }
+    if regions.is_empty() {
+        return Ok(front_ids);
     }
+    let origin = &regions[rng.gen_range(0..regions.len())];
+
+    let Some(first_leg) = choose_hop(ctx, world_id, origin.region_id, None, &mut rng) else {
+        return Ok(front_ids);
+    };
+
+    let front_id = ctx.db.weather_front().iter().count() as u32 + 1;
+    let front_type = match rng.gen_range(0..6) {
+        0 => FrontType::ColdFront,
+        1 => FrontType::WarmFront,
+        2 => FrontType::StormSystem,
+        3 => FrontType::HighPressure,
+        4 => FrontType::LowPressure,
+        _ => FrontType::OccludedFront,
+    };
+    let movement_speed = rng.gen_range(FRONT_SPEED_KMH.0..FRONT_SPEED_KMH.1);
+    // The air mass is colder or warmer than where it forms
+    let air_temperature = origin.current_temperature + match front_type {
+        FrontType::ColdFront => -8.0,
+        FrontType::WarmFront => 5.0,
+        _ => 0.0,
+    };
+
+    let front = WeatherFront {
+        id: front_id,
+        world_id,
+        front_type,
+        origin_region: origin.region_id,
+        current_region: origin.region_id,
+        target_region: first_leg.to_region,
+        movement_speed,
+        progress_km: 0.0,
+        intensity: rng.gen_range(30.0..90.0),
+        size: rng.gen_range(FRONT_SIZE_KM.0..FRONT_SIZE_KM.1),
+        air_temperature,
+        created_hour: current_hour,
+        expected_arrival: current_hour + (first_leg.distance_km / movement_speed).ceil() as u64,
+        last_moved_hour: current_hour,
+        is_active: true,
+    };
+
+    ctx.db.weather_front().insert(front);
+    front_ids.push(front_id);
+
+    log::info!("Created {:?} {} over region {} heading for region {}",
+        front_type, front_id, origin.region_id, first_leg.to_region);
 
     Ok(front_ids)
 }
 
-// Generate JSON effects for weather fronts
-fn generate_front_effects_json(front_type: FrontType) -> String {
-    let effects = match front_type {
-        FrontType::ColdFront => serde_json::json!({
-            "temperature_change": -8.0,
-            "wind_increase": 1.5,
-            "precipitation_chance": 80.0,
-            "duration_hours": 12
-        }),
-        FrontType::WarmFront => serde_json::json!({
-            "temperature_change": 5.0,
-            "wind_increase": 1.1,
-            "precipitation_chance": 40.0,
-            "duration_hours": 24
-        }),
-        FrontType::StormSystem => serde_json::json!({
-            "temperature_change": -3.0,
-            "wind_increase": 2.5,
-            "precipitation_chance": 95.0,
-            "duration_hours": 8
-        }),
-        FrontType::HighPressure => serde_json::json!({
-            "temperature_change": 2.0,
-            "wind_increase": 0.8,
-            "precipitation_chance": 5.0,
-            "duration_hours": 48
-        }),
-        FrontType::LowPressure => serde_json::json!({
-            "temperature_change": -1.0,
-            "wind_increase": 1.3,
-            "precipitation_chance": 60.0,
-            "duration_hours": 36
-        }),
-        FrontType::OccludedFront => serde_json::json!({
-            "temperature_change": -4.0,
-            "wind_increase": 1.4,
-            "precipitation_chance": 70.0,
-            "duration_hours": 18
-        }),
+// The anomalies a front drives its regions toward at full intensity
+fn front_signature(front_type: FrontType) -> ClimateAnomaly {
+    let (temperature, humidity, moisture) = match front_type {
+        FrontType::ColdFront => (-8.0, 5.0, 2.5),
+        FrontType::WarmFront => (5.0, 10.0, 1.5),
+        FrontType::OccludedFront => (-3.0, 10.0, 2.0),
+        FrontType::StormSystem => (-2.0, 15.0, 3.5),
+        FrontType::HighPressure => (7.0, -15.0, -2.0),
+        FrontType::LowPressure => (-1.0, 8.0, 1.8),
     };
+    ClimateAnomaly { temperature, humidity, moisture }
+}
+
+fn scaled(anomaly: ClimateAnomaly, factor: f32) -> ClimateAnomaly {
+    ClimateAnomaly {
+        temperature: anomaly.temperature * factor,
+        humidity: anomaly.humidity * factor,
+        moisture: anomaly.moisture * factor,
+    }
+}
 
-    effects.to_string()
+// How well the region beneath feeds a front, 0-1
+fn front_fuel(front: &WeatherFront, climate: &ClimateState) -> f32 {
+    let moisture = climate.humidity / 100.0;
+    let contrast = (climate.current_temperature - front.air_temperature) / 15.0;
+    match front.front_type {
+        // Moist, warm air feeds lows and storms
+        FrontType::StormSystem | FrontType::LowPressure | FrontType::OccludedFront =>
+            moisture * ((climate.current_temperature + 10.0) / 35.0).clamp(0.0, 1.0),
+        // Fronts live on the contrast between their air and the air they push into
+        FrontType::ColdFront => contrast.clamp(0.0, 1.0),
+        FrontType::WarmFront => (-contrast).clamp(0.0, 1.0),
+        FrontType::HighPressure => 1.0 - moisture,
+    }
 }
 
-// Update weather front positions and apply effects
+// Move fronts along their tracks, let them grow or fade, and bring their weather to the regions they cross
 #[spacetimedb::reducer]
 pub fn update_weather_fronts(
     ctx: &ReducerContext,
     world_id: u32,
     current_hour: u64,
 ) -> Result<Vec<u32>, String> {
+    let world = ctx.db.game_world()
+        .id()
+        .find(&world_id)
+        .ok_or("World not found")?;
+    let mut rng = rand::thread_rng();
     let mut updated_fronts = Vec::new();
 
     let active_fronts: Vec<WeatherFront> = ctx.db.weather_front()
         .iter()
         .filter(|f| f.world_id == world_id && f.is_active)
-        .cloned()
         .collect();
 
     for mut front in active_fronts {
-        // Check if front has reached its destination
-        if current_hour >= front.expected_arrival {
-            // Apply front effects to target region
-            apply_front_effects(ctx, &front, current_hour)?;
+        let hours = current_hour.saturating_sub(front.last_moved_hour) as f32;
+        if hours <= 0.0 {
+            continue;
+        }
+        front.last_moved_hour = current_hour;
 
-            // Deactivate the front
-            front.is_active = false;
-            ctx.db.weather_front().id().update(front.id, front);
-            updated_fronts.push(front.id);
+        // Grow on good fuel, fade on poor
+        if let Some(beneath) = regional_climate(ctx, world_id, front.current_region) {
+            let fuel = front_fuel(&front, &beneath);
+            front.intensity = (front.intensity
+                + (fuel * FRONT_GROWTH_PER_HOUR - FRONT_FRICTION_PER_HOUR) * hours).clamp(0.0, 100.0);
+        }
 
-            log::info!("Weather front {} reached region {} and dissipated",
-                front.id, front.target_region);
+        // Advance along the track, hopping on to the next region downwind on arrival
+        front.progress_km += front.movement_speed * hours;
+        let mut arrived = false;
+        let mut stalled = false;
+        loop {
+            let Some(leg) = region_links(ctx, world_id, front.current_region).into_iter()
+                .find(|l| l.to_region == front.target_region) else {
+                stalled = true;
+                break;
+            };
+            if front.progress_km < leg.distance_km {
+                front.expected_arrival = current_hour
+                    + ((leg.distance_km - front.progress_km) / front.movement_speed).ceil() as u64;
+                break;
+            }
+            front.progress_km -= leg.distance_km;
+            let previous = front.current_region;
+            front.current_region = front.target_region;
+            arrived = true;
+            match choose_hop(ctx, world_id, front.current_region, Some(previous), &mut rng) {
+                Some(next) => front.target_region = next.to_region,
+                None => {
+                    stalled = true;
+                    break;
+                },
+            }
         }
+
+        apply_front_effects(ctx, &front, world.climate_zone, hours, current_hour);
+        if arrived {
+            if let Err(e) = spawn_front_events(ctx, &front, current_hour) {
+                log::warn!("Failed to record weather from front {}: {}", front.id, e);
+            }
+        }
+
+        let age = current_hour.saturating_sub(front.created_hour);
+        if stalled || front.intensity < FRONT_DISSIPATE_INTENSITY || age > FRONT_MAX_AGE_HOURS {
+            front.is_active = false;
+            log::info!("Weather front {} dissipated over region {}", front.id, front.current_region);
+        }
+
+        updated_fronts.push(front.id);
+        ctx.db.weather_front().id().update(front);
     }
 
     Ok(updated_fronts)
 }
 
-// Apply weather front effects to climate state
+// Pull the weather of every region under a front toward the front's own, strongest at its centre
 fn apply_front_effects(
     ctx: &ReducerContext,
     front: &WeatherFront,
+    zone: ClimateZone,
+    hours: f32,
     current_hour: u64,
-) -> Result<(), String> {
-    if let Some(mut climate) = ctx.db.climate_state()
-        .iter()
-        .find(|c| c.world_id == front.world_id && c.region_id == front.target_region)
-        .cloned() {
+) {
+    let centre = region_position(ctx, front.current_region);
+    let forcing = scaled(front_signature(front.front_type), front.intensity / 100.0);
+    let pull = 1.0 - (-hours / FRONT_FORCING_HOURS).exp();
 
-        let effects: serde_json::Value = serde_json::from_str(&front.weather_effects)
-            .unwrap_or_else(|_| serde_json::json!({}));
+    let regions: Vec<ClimateState> = ctx.db.climate_state()
+        .iter()
+        .filter(|c| c.world_id == front.world_id)
+        .collect();
 
-        // Apply temperature change
-        if let Some(temp_change) = effects.get("temperature_change") {
-            climate.current_temperature += temp_change.as_f64().unwrap_or(0.0) as f32;
+    let mut rng = rand::thread_rng();
+    for mut climate in regions {
+        let reach = if climate.region_id == front.current_region {
+            1.0
+        } else {
+            let (lat, lon) = region_position(ctx, climate.region_id);
+            let (distance, _) = great_circle(centre.0, centre.1, lat, lon);
+            1.0 - distance / front.size
+        };
+        if reach <= 0.0 {
+            continue;
         }
 
-        // Apply wind change
-        if let Some(wind_mult) = effects.get("wind_increase") {
-            climate.wind_speed *= wind_mult.as_f64().unwrap_or(1.0) as f32;
+        let weight = pull * reach;
+        climate.temperature_anomaly += (forcing.temperature - climate.temperature_anomaly) * weight;
+        climate.humidity_anomaly += (forcing.humidity - climate.humidity_anomaly) * weight;
+        climate.moisture_anomaly += (forcing.moisture - climate.moisture_anomaly) * weight;
+        climate::advance(&mut climate, zone, current_hour, &mut rng);
+        ctx.db.climate_state().id().update(climate);
+    }
+}
+
+// Record the storms, blizzards and heatwaves a front brings as it reaches a region
+fn spawn_front_events(ctx: &ReducerContext, front: &WeatherFront, current_hour: u64) -> Result<Option<u32>, String> {
+    let Some(climate) = regional_climate(ctx, front.world_id, front.current_region) else {
+        return Ok(None);
+    };
+
+    let raining = climate.precipitation > 0.0;
+    let event_type = if raining && climate.current_temperature <= 0.0 && climate.wind_speed >= BLIZZARD_EVENT_WIND {
+        NaturalEventType::Blizzard
+    } else if raining && climate.wind_speed >= STORM_EVENT_WIND {
+        NaturalEventType::Storm
+    } else if climate.temperature_anomaly >= HEATWAVE_ANOMALY && climate.current_temperature >= HEATWAVE_TEMPERATURE {
+        NaturalEventType::Heatwave
+    } else {
+        return Ok(None);
+    };
+
+    // A front that already brought this to the region does not bring it again
+    let already = ctx.db.natural_event().iter().any(|e| e.world_id == front.world_id
+        && e.event_type == event_type
+        && !e.resolved
+        && crate::reducers::migration::parse_region_ids(&e.affected_region).contains(&front.current_region));
+    if already {
+        return Ok(None);
+    }
+
+    let event_id = record_natural_event(ctx, front.world_id, event_type, vec![front.current_region], current_hour, None)?;
+    log::info!("Front {} brought a {:?} to region {}", front.id, event_type, front.current_region);
+    Ok(Some(event_id))
+}
+
+fn regional_climate(ctx: &ReducerContext, world_id: u32, region_id: u32) -> Option<ClimateState> {
+    ctx.db.climate_state()
+        .iter()
+        .find(|c| c.world_id == world_id && c.region_id == region_id)
+}
+
+// Where a region lies; regions are cities
+fn region_position(ctx: &ReducerContext, region_id: u32) -> (f32, f32) {
+    ctx.db.city().id().find(&region_id)
+        .map_or((climate::DEFAULT_LATITUDE, 0.0), |c| (c.latitude, c.longitude))
+}
+
+// Distance in km and initial bearing in degrees between two points
+fn great_circle(lat1: f32, lon1: f32, lat2: f32, lon2: f32) -> (f32, f32) {
+    let (phi1, phi2) = (lat1.to_radians(), lat2.to_radians());
+    let d_phi = phi2 - phi1;
+    let d_lambda = (lon2 - lon1).to_radians();
+
+    let a = (d_phi / 2.0).sin().powi(2) + phi1.cos() * phi2.cos() * (d_lambda / 2.0).sin().powi(2);
+    let distance = 2.0 * EARTH_RADIUS_KM * a.sqrt().min(1.0).asin();
+
+    let y = d_lambda.sin() * phi2.cos();
+    let x = phi1.cos() * phi2.sin() - phi1.sin() * phi2.cos() * d_lambda.cos();
+    let bearing = (y.atan2(x).to_degrees() + 360.0) % 360.0;
+
+    (distance, bearing)
+}
+
+// Link each region to its nearest neighbours, both ways
+pub(crate) fn build_region_links(ctx: &ReducerContext, world_id: u32) -> usize {
+    let regions: Vec<(u32, (f32, f32))> = ctx.db.climate_state()
+        .iter()
+        .filter(|c| c.world_id == world_id)
+        .map(|c| (c.region_id, region_position(ctx, c.region_id)))
+        .collect();
+
+    let mut linked: HashSet<(u32, u32)> = ctx.db.region_link()
+        .iter()
+        .filter(|l| l.world_id == world_id)
+        .map(|l| (l.from_region, l.to_region))
+        .collect();
+    let mut created = 0;
+
+    for &(region, (lat, lon)) in &regions {
+        let mut neighbours: Vec<(u32, f32, f32)> = regions.iter()
+            .filter(|(other, _)| *other != region)
+            .map(|&(other, (other_lat, other_lon))| {
+                let (distance, bearing) = great_circle(lat, lon, other_lat, other_lon);
+                (other, distance.max(MIN_LINK_KM), bearing)
+            })
+            .collect();
+        neighbours.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal));
+
+        for (other, distance, bearing) in neighbours.into_iter().take(REGION_NEIGHBOURS) {
+            for (from, to, bearing) in [(region, other, bearing), (other, region, (bearing + 180.0) % 360.0)] {
+                if linked.insert((from, to)) {
+                    let link_id = ctx.db.region_link().iter().count() as u32 + 1;
+                    ctx.db.region_link().insert(RegionLink {
+                        id: link_id,
+                        world_id,
+                        from_region: from,
+                        to_region: to,
+                        distance_km: distance,
+                        bearing,
+                    });
+                    created += 1;
+                }
+            }
         }
+    }
 
-        // Update weather pattern based on front type
-        climate.weather_pattern = match front.front_type {
-            FrontType::StormSystem => WeatherPattern::Stormy,
-            FrontType::ColdFront => WeatherPattern::Rainy,
-            FrontType::WarmFront => WeatherPattern::Cloudy,
-            FrontType::HighPressure => WeatherPattern::Clear,
-            _ => climate.weather_pattern,
-        };
+    log::info!("Linked {} regions of world {} with {} links", regions.len(), world_id, created);
+    created
+}
+
+fn region_links(ctx: &ReducerContext, world_id: u32, region_id: u32) -> Vec<RegionLink> {
+    ctx.db.region_link()
+        .iter()
+        .filter(|l| l.world_id == world_id && l.from_region == region_id)
+        .collect()
+}
 
-        climate.last_updated_hour = current_hour;
-        ctx.db.climate_state().id().update(climate.id, climate);
+// Bearing the prevailing wind blows toward: trade winds, westerlies and polar easterlies
+fn prevailing_wind(latitude: f32) -> f32 {
+    let band = latitude.abs();
+    let northern = latitude >= 0.0;
+    match (band < 30.0 || band >= 60.0, northern) {
+        (true, true) => 225.0,   // From the north-east
+        (true, false) => 315.0,  // From the south-east
+        (false, true) => 45.0,   // From the south-west
+        (false, false) => 135.0, // From the north-west
     }
+}
 
-    Ok(())
+// Links onward from a region with how strongly the wind favours each; doubling back is only a last resort
+fn downwind_links(ctx: &ReducerContext, world_id: u32, region_id: u32, came_from: Option<u32>) -> Vec<(RegionLink, f32)> {
+    let wind = prevailing_wind(region_position(ctx, region_id).0);
+    let links = region_links(ctx, world_id, region_id);
+    let onward: Vec<RegionLink> = if links.iter().any(|l| Some(l.to_region) != came_from) {
+        links.into_iter().filter(|l| Some(l.to_region) != came_from).collect()
+    } else {
+        links
+    };
+
+    onward.into_iter()
+        .map(|l| {
+            let alignment = ((l.bearing - wind).to_radians().cos() + 1.0) / 2.0;
+            let weight = alignment * alignment + UPWIND_WEIGHT;
+            (l, weight)
+        })
+        .collect()
+}
+
+fn choose_hop(
+    ctx: &ReducerContext,
+    world_id: u32,
+    region_id: u32,
+    came_from: Option<u32>,
+    rng: &mut impl Rng,
+) -> Option<RegionLink> {
+    let options = downwind_links(ctx, world_id, region_id, came_from);
+    let total: f32 = options.iter().map(|(_, w)| w).sum();
+    let mut roll = rng.gen::<f32>() * total;
+    let mut chosen = None;
+    for (link, weight) in options {
+        chosen = Some(link);
+        if roll < weight {
+            break;
+        }
+        roll -= weight;
+    }
+    chosen
+}
+
+// The hop a front is most likely to take next, with its chance
+fn likeliest_hop(ctx: &ReducerContext, world_id: u32, region_id: u32, came_from: u32) -> Option<(RegionLink, f32)> {
+    let options = downwind_links(ctx, world_id, region_id, Some(came_from));
+    let total: f32 = options.iter().map(|(_, w)| w).sum();
+    options.into_iter()
+        .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal))
+        .map(|(link, weight)| (link, weight / total))
 }
 
 // Initialize microclimates for a region
@@ -429,7 +738,7 @@ pub fn initialize_microclimates(
 }
 
 // Get modifiers for different microclimate types
-fn get_microclimate_modifiers(
+pub(crate) fn get_microclimate_modifiers(
     microclimate_type: MicroclimateType,
 ) -> (f32, f32, f32, f32, f32, f32, f32) {
     match microclimate_type {
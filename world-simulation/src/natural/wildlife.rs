@@ -0,0 +1,270 @@
+// Wildlife stocks: per-region populations that breed and migrate with phenology and are fished and hunted
+
+use spacetimedb::{ReducerContext, Table};
+use log;
+use rand::Rng;
+use crate::natural::{climate_state, NaturalEventType, record_natural_event};
+use crate::natural::climate::HOURS_PER_CYCLE;
+use crate::natural::deposits::forest_habitat;
+use crate::natural::seasonal_cycles::{phenology, seasonal_activity, phase_active, SpeciesType, PhenologicalPhase, ActivityType};
+use crate::reducers::storage::deposit_stock;
+use crate::tables::city::city;
+use crate::types::ResourceType;
+
+const BREEDING_GROWTH_BOOST: f32 = 3.0;       // Growth multiplier while a breeding phase is under way
+const OFF_SEASON_GROWTH: f32 = 0.2;           // Growth left outside the breeding phase
+const MIGRATION_SHARE_PER_DAY: f32 = 0.1;     // Share of a migrating stock that leaves or returns each day of the phase
+const MAX_DAILY_TAKE: f32 = 0.05;             // Most of a stock that can be taken in one day
+
+const COLLAPSE_SHARE: f32 = 0.2;              // Stock below this share of capacity has collapsed
+const RECOVERY_SHARE: f32 = 0.5;              // Collapsed stocks are reopened once they climb back here
+const THRIVING_SHARE: f32 = 0.9;
+const DISCOVERY_CHANCE: f32 = 0.01;           // Per day for a thriving, harvested stock
+const DISCOVERY_CAPACITY_GAIN: f32 = 0.25;
+const DISCOVERY_COOLDOWN_HOURS: u64 = HOURS_PER_CYCLE;
+
+// A species' population in one region
+#[spacetimedb::table(name = species_stock)]
+pub struct SpeciesStock {
+    #[primary_key]
+    pub id: u32,
+    pub world_id: u32,
+    pub region_id: u32,
+    pub species_type: SpeciesType,
+    pub population: f32,          // Present in the region
+    pub away: f32,                // Migrated out and expected back
+    pub carrying_capacity: f32,
+    pub collapsed: bool,          // Closed to fishing and hunting until it recovers
+    pub harvest_pressure: f32,    // Share of the stock taken on the last harvest day
+    pub last_discovery_hour: Option<u64>,
+    pub last_update_hour: u64,
+}
+
+// One day's catch from one stock
+#[spacetimedb::table(name = wildlife_harvest)]
+pub struct WildlifeHarvest {
+    #[primary_key]
+    pub id: u32,
+    pub world_id: u32,
+    pub region_id: u32,
+    pub species_type: SpeciesType,
+    pub activity: ActivityType,
+    pub hour: u64,
+    pub taken: f32,
+    pub food_yield: f32,          // Meals delivered to the region's granary
+    pub stock_after: f32,
+}
+
+// Capacity, daily growth rate, catch per worker-day per head of stock and meals per head for game species
+fn species_traits(species: SpeciesType) -> Option<(f32, f32, f32, f32)> {
+    match species {
+        SpeciesType::Fish => Some((20000.0, 0.02, 0.000025, 3.0)),
+        SpeciesType::Small_Mammals => Some((8000.0, 0.03, 0.00005, 4.0)),
+        SpeciesType::Migratory_Birds => Some((5000.0, 0.015, 0.00004, 2.0)),
+        SpeciesType::Large_Mammals => Some((1500.0, 0.005, 0.0000067, 200.0)),
+        _ => None,
+    }
+}
+
+// Carrying capacity left once the region's forest has been cleared; fish do not depend on it
+fn habitat_capacity(ctx: &ReducerContext, stock: &SpeciesStock) -> f32 {
+    let habitat = match stock.species_type {
+        SpeciesType::Fish => 1.0,
+        _ => forest_habitat(ctx, stock.world_id, stock.region_id),
+    };
+    (stock.carrying_capacity * habitat).max(1.0)
+}
+
+// Which stocks a seasonal activity draws on
+fn activity_targets(activity: ActivityType) -> &'static [SpeciesType] {
+    match activity {
+        ActivityType::Fishing => &[SpeciesType::Fish],
+        ActivityType::Hunting => &[SpeciesType::Large_Mammals, SpeciesType::Small_Mammals, SpeciesType::Migratory_Birds],
+        _ => &[],
+    }
+}
+
+// Grow, migrate and harvest every wildlife stock in a world (daily)
+pub fn update_wildlife(
+    ctx: &ReducerContext,
+    world_id: u32,
+    current_hour: u64,
+) -> Result<(), String> {
+    seed_wildlife_stocks(ctx, world_id, current_hour);
+
+    let stocks: Vec<SpeciesStock> = ctx.db.species_stock()
+        .iter()
+        .filter(|s| s.world_id == world_id)
+        .collect();
+
+    for mut stock in stocks {
+        let Some((_, growth_rate, _, _)) = species_traits(stock.species_type) else { continue };
+        let days = (current_hour.saturating_sub(stock.last_update_hour) as f32 / 24.0).max(1.0);
+
+        let active: Vec<PhenologicalPhase> = ctx.db.phenology()
+            .iter()
+            .filter(|p| p.world_id == world_id && p.region_id == stock.region_id && p.species_type == stock.species_type)
+            .filter(|p| phase_active(p, current_hour))
+            .map(|p| p.phase)
+            .collect();
+        let has_breeding_season = ctx.db.phenology()
+            .iter()
+            .any(|p| p.world_id == world_id && p.region_id == stock.region_id && p.species_type == stock.species_type
+                && matches!(p.phase, PhenologicalPhase::Breeding | PhenologicalPhase::Emergence));
+
+        // Migration
+        let share = (MIGRATION_SHARE_PER_DAY * days).min(1.0);
+        if active.contains(&PhenologicalPhase::Migration_Departure) {
+            let leaving = stock.population * share;
+            stock.population -= leaving;
+            stock.away += leaving;
+        } else if active.contains(&PhenologicalPhase::Migration_Arrival) {
+            let returning = stock.away * share;
+            stock.away -= returning;
+            stock.population += returning;
+        }
+
+        // Logistic growth, concentrated in the breeding season
+        let seasonal = if active.contains(&PhenologicalPhase::Hibernation) {
+            0.0
+        } else if active.iter().any(|p| matches!(p, PhenologicalPhase::Breeding | PhenologicalPhase::Emergence)) {
+            BREEDING_GROWTH_BOOST
+        } else if has_breeding_season {
+            OFF_SEASON_GROWTH
+        } else {
+            1.0
+        };
+        let k = habitat_capacity(ctx, &stock);
+        let growth = growth_rate * seasonal * stock.population * (1.0 - (stock.population + stock.away) / k);
+        stock.population = (stock.population + growth * days).max(0.0);
+
+        if stock.collapsed && stock.population + stock.away >= k * RECOVERY_SHARE {
+            stock.collapsed = false;
+            log::info!("{:?} stock in region {} has recovered", stock.species_type, stock.region_id);
+        }
+
+        stock.last_update_hour = current_hour;
+        ctx.db.species_stock().id().update(stock);
+    }
+
+    harvest_wildlife(ctx, world_id, current_hour)?;
+
+    Ok(())
+}
+
+// Create the stocks of any region that has none yet
+fn seed_wildlife_stocks(ctx: &ReducerContext, world_id: u32, current_hour: u64) {
+    let regions: Vec<u32> = ctx.db.climate_state()
+        .iter()
+        .filter(|c| c.world_id == world_id)
+        .map(|c| c.region_id)
+        .collect();
+
+    for region_id in regions {
+        if ctx.db.species_stock().iter().any(|s| s.world_id == world_id && s.region_id == region_id) {
+            continue;
+        }
+
+        for species in [SpeciesType::Fish, SpeciesType::Small_Mammals, SpeciesType::Migratory_Birds, SpeciesType::Large_Mammals] {
+            let Some((capacity, _, _, _)) = species_traits(species) else { continue };
+            let id = ctx.db.species_stock().iter().count() as u32 + 1;
+            ctx.db.species_stock().insert(SpeciesStock {
+                id,
+                world_id,
+                region_id,
+                species_type: species,
+                population: capacity * 0.8,
+                away: 0.0,
+                carrying_capacity: capacity,
+                collapsed: false,
+                harvest_pressure: 0.0,
+                last_discovery_hour: None,
+                last_update_hour: current_hour,
+            });
+        }
+    }
+}
+
+// Take the day's catch for every active fishing and hunting season
+fn harvest_wildlife(ctx: &ReducerContext, world_id: u32, current_hour: u64) -> Result<(), String> {
+    let mut rng = rand::thread_rng();
+
+    let activities: Vec<(u32, ActivityType, f32)> = ctx.db.seasonal_activity()
+        .iter()
+        .filter(|a| a.world_id == world_id && a.is_active)
+        .filter(|a| matches!(a.activity_type, ActivityType::Fishing | ActivityType::Hunting))
+        .map(|a| (a.region_id, a.activity_type, a.participation_rate))
+        .collect();
+
+    for (region_id, activity, participation_rate) in activities {
+        let Some(region) = ctx.db.city().id().find(&region_id) else { continue };
+
+        let targets: Vec<SpeciesStock> = ctx.db.species_stock()
+            .iter()
+            .filter(|s| s.world_id == world_id && s.region_id == region_id)
+            .filter(|s| activity_targets(activity).contains(&s.species_type) && s.population >= 1.0)
+            .collect();
+        let open = targets.iter().filter(|s| !s.collapsed).count();
+        if open == 0 {
+            continue;
+        }
+
+        // Effort is shared across the stocks still open to the activity
+        let effort = region.population as f32 * participation_rate.min(100.0) / 100.0 / open as f32;
+
+        for mut stock in targets {
+            if stock.collapsed {
+                stock.harvest_pressure = 0.0;
+                ctx.db.species_stock().id().update(stock);
+                continue;
+            }
+            let Some((_, _, catchability, meals_per_head)) = species_traits(stock.species_type) else { continue };
+
+            let taken = (catchability * effort * stock.population).min(stock.population * MAX_DAILY_TAKE);
+            stock.population -= taken;
+            stock.harvest_pressure = taken / (stock.population + taken);
+
+            let food_yield = deposit_stock(ctx, region_id, ResourceType::Food, taken * meals_per_head, current_hour);
+
+            let harvest_id = ctx.db.wildlife_harvest().iter().count() as u32 + 1;
+            ctx.db.wildlife_harvest().insert(WildlifeHarvest {
+                id: harvest_id,
+                world_id,
+                region_id,
+                species_type: stock.species_type,
+                activity,
+                hour: current_hour,
+                taken,
+                food_yield,
+                stock_after: stock.population,
+            });
+
+            let k = habitat_capacity(ctx, &stock);
+            let share = (stock.population + stock.away) / k;
+
+            if share < COLLAPSE_SHARE {
+                stock.collapsed = true;
+                let description = format!(
+                    "{:?} in {} have been taken faster than they can breed; what remains is left alone to recover.",
+                    stock.species_type, region.name);
+                record_natural_event(ctx, world_id, NaturalEventType::EcosystemChange, vec![region_id], current_hour, Some(description))?;
+                log::info!("{:?} stock in region {} collapsed under {:?}", stock.species_type, region_id, activity);
+            } else if share > THRIVING_SHARE
+                && stock.last_discovery_hour.map_or(true, |h| current_hour >= h + DISCOVERY_COOLDOWN_HOURS)
+                && rng.gen::<f32>() < DISCOVERY_CHANCE {
+                // Plentiful game draws people further out, where they find new grounds
+                stock.carrying_capacity *= 1.0 + DISCOVERY_CAPACITY_GAIN;
+                stock.last_discovery_hour = Some(current_hour);
+                let description = match activity {
+                    ActivityType::Fishing => format!("Fishermen from {} have found rich new fishing grounds.", region.name),
+                    _ => format!("Hunters from {} have found new hunting grounds teeming with {:?}.", region.name, stock.species_type),
+                };
+                record_natural_event(ctx, world_id, NaturalEventType::ResourceDiscovery, vec![region_id], current_hour, Some(description))?;
+            }
+
+            ctx.db.species_stock().id().update(stock);
+        }
+    }
+
+    Ok(())
+}
\ No newline at end of file
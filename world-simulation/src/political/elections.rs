@@ -0,0 +1,502 @@
+// Elections: offices change hands at the end of each term under their ElectionMethod's rules
+
+use spacetimedb::{ReducerContext, Table, SpacetimeType};
+use serde::{Serialize, Deserialize};
+use log;
+use rand::Rng;
+use rand::seq::SliceRandom;
+use std::collections::HashMap;
+use crate::political::{ElectionMethod, FactionType, Ideology, PoliticalOffice, faction, political_office};
+use crate::political::faction_membership::{faction_membership, ideology_affinity};
+use crate::tables::individual::{individual, relationship, Individual, RelationshipType};
+use crate::tables::city::{city, City, PolicyType};
+use crate::types::SpecializedRole;
+use crate::reducers::city::{city_residents, enact_policy};
+use crate::narrative::{create_narrative_event, EventCategory};
+use crate::world::game_world::game_world;
+
+const DEMOCRATIC_CAMPAIGN_HOURS: u64 = 168;
+const RELIGIOUS_CAMPAIGN_HOURS: u64 = 72;
+const VACANCY_RETRY_HOURS: u64 = 168;      // Wait after an election no one stood in, at least a campaign's length
+const BASE_TURNOUT: f32 = 0.3;             // Plus up to COMMUNITY_TURNOUT for the well-connected
+const COMMUNITY_TURNOUT: f32 = 0.6;
+const INDEPENDENT_APPEAL: f32 = 0.4;       // Voter preference for a candidate with no faction
+const MEMBER_LOYALTY: f32 = 1.5;           // Voter preference for their own faction's candidate
+const APPROVAL_TRACKING: f32 = 0.2;        // Share of the gap to city conditions closed per update
+const PLATFORM_INTENSITY: f32 = 0.5;       // Policy strength a new holder enacts from their faction's platform
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, SpacetimeType)]
+pub enum ElectionStatus {
+    Campaigning,
+    Decided,
+    Void,       // No one stood
+}
+
+// One contest for an office
+#[spacetimedb::table(name = election)]
+pub struct Election {
+    #[primary_key]
+    pub id: u32,
+    pub world_id: u32,
+    pub office_id: u32,
+    pub city_id: u32,
+    pub method: ElectionMethod,
+    pub called_hour: u64,
+    pub closes_hour: u64,
+    pub status: ElectionStatus,
+    pub winner_id: Option<u32>,
+    pub ballots_cast: u32,
+}
+
+// A candidate standing in an election, with their tally once it is decided
+#[spacetimedb::table(name = election_candidate)]
+pub struct ElectionCandidate {
+    #[primary_key]
+    pub id: u32,
+    pub election_id: u32,
+    pub individual_id: u32,
+    pub faction_id: Option<u32>,
+    pub votes: f32,
+}
+
+// Create an office for a city; its first holder is chosen by election
+#[spacetimedb::reducer]
+pub fn create_office(
+    ctx: &ReducerContext,
+    world_id: u32,
+    city_id: u32,
+    office_name: String,
+    election_method: ElectionMethod,
+    term_length_hours: u32,
+    power_level: f32,
+) -> Result<(), String> {
+    let world = ctx.db.game_world().id().find(&world_id)
+        .ok_or("World not found")?;
+    ctx.db.city().id().find(&city_id)
+        .ok_or("City not found")?;
+
+    let id = ctx.db.political_office().iter().count() as u32 + 1;
+    let office = ctx.db.political_office().insert(PoliticalOffice {
+        id,
+        world_id,
+        city_id,
+        office_name,
+        holder_id: 0,
+        faction_id: None,
+        power_level: power_level.clamp(0.0, 100.0),
+        term_start_hour: world.total_hours,
+        term_length_hours,
+        election_method,
+        approval_rating: 50.0,
+    });
+
+    call_election(ctx, &office, world.total_hours);
+    Ok(())
+}
+
+// The holder of an office enacts a policy for its city
+#[spacetimedb::reducer]
+pub fn office_enact_policy(
+    ctx: &ReducerContext,
+    office_id: u32,
+    holder_id: u32,
+    policy_type: PolicyType,
+    intensity: f32,
+) -> Result<(), String> {
+    let office = ctx.db.political_office().id().find(&office_id)
+        .ok_or("Office not found")?;
+    if office.holder_id == 0 || office.holder_id != holder_id {
+        return Err(format!("Individual {} does not hold {}", holder_id, office.office_name));
+    }
+    if ctx.db.individual().id().find(&holder_id).map_or(true, |h| h.health <= 0.0) {
+        return Err(format!("{} is vacant", office.office_name));
+    }
+    let hour = ctx.db.game_world().id().find(&office.world_id)
+        .ok_or("World not found")?
+        .total_hours;
+
+    enact_policy(ctx, office.city_id, policy_type.clone(), intensity, hour);
+    log::info!("{} of city {} set {:?} to {:.2}", office.office_name, office.city_id, policy_type, intensity);
+    Ok(())
+}
+
+// Track approval, end terms, fill vacancies and count the votes of closed elections
+#[spacetimedb::reducer]
+pub fn update_offices(ctx: &ReducerContext, world_id: u32, hour: u64) -> Result<(), String> {
+    let offices: Vec<PoliticalOffice> = ctx.db.political_office().iter()
+        .filter(|o| o.world_id == world_id)
+        .collect();
+
+    for mut office in offices {
+        if let Some(city) = ctx.db.city().id().find(&office.city_id) {
+            let target = city_approval(&city);
+            office.approval_rating += (target - office.approval_rating) * APPROVAL_TRACKING;
+        }
+
+        // A dead holder stays on record until replaced, so heirs can be traced from them
+        let vacant = office.holder_id == 0 || ctx.db.individual().id().find(&office.holder_id)
+            .map_or(true, |h| h.health <= 0.0);
+        let term_over = hour >= office.term_start_hour + office.term_length_hours as u64;
+        let office = ctx.db.political_office().id().update(office);
+
+        if (vacant || term_over) && open_election(ctx, office.id).is_none() && !retry_pending(ctx, &office, hour) {
+            call_election(ctx, &office, hour);
+        }
+    }
+
+    let due: Vec<Election> = ctx.db.election().iter()
+        .filter(|e| e.world_id == world_id && e.status == ElectionStatus::Campaigning && hour >= e.closes_hour)
+        .collect();
+    for election in due {
+        decide_election(ctx, election, hour)?;
+    }
+
+    Ok(())
+}
+
+/// A faction calling for elections brings forward the vote for every office in its city
+pub(crate) fn call_snap_elections(ctx: &ReducerContext, city_id: u32, hour: u64) {
+    let offices: Vec<PoliticalOffice> = ctx.db.political_office().iter()
+        .filter(|o| o.city_id == city_id && o.election_method == ElectionMethod::Democratic)
+        .collect();
+    for office in offices {
+        if open_election(ctx, office.id).is_none() {
+            call_election(ctx, &office, hour);
+        }
+    }
+}
+
+/// Approval a holder earns from how the city is doing, 0-100
+fn city_approval(city: &City) -> f32 {
+    ((city.stability + city.safety + city.health + city.average_happiness) / 4.0 - city.unemployment_rate * 0.5)
+        .clamp(0.0, 100.0)
+}
+
+fn open_election(ctx: &ReducerContext, office_id: u32) -> Option<Election> {
+    ctx.db.election().iter()
+        .find(|e| e.office_id == office_id && e.status == ElectionStatus::Campaigning)
+}
+
+fn campaign_hours(method: ElectionMethod) -> u64 {
+    match method {
+        ElectionMethod::Democratic => DEMOCRATIC_CAMPAIGN_HOURS,
+        ElectionMethod::Religious => RELIGIOUS_CAMPAIGN_HOURS,
+        _ => 0,
+    }
+}
+
+/// Whether the office's last election found no one to stand recently enough that calling another is pointless
+fn retry_pending(ctx: &ReducerContext, office: &PoliticalOffice, hour: u64) -> bool {
+    let wait = campaign_hours(office.election_method).max(VACANCY_RETRY_HOURS);
+    ctx.db.election().iter()
+        .filter(|e| e.office_id == office.id)
+        .max_by_key(|e| (e.called_hour, e.id))
+        .map_or(false, |last| last.status == ElectionStatus::Void && hour < last.closes_hour + wait)
+}
+
+/// Order of succession: earliest born first, then eldest, then lowest ID
+fn eldest_first(a: &Individual, b: &Individual) -> std::cmp::Ordering {
+    a.birth_hour.cmp(&b.birth_hour)
+        .then(b.age.cmp(&a.age))
+        .then(a.id.cmp(&b.id))
+}
+
+/// Open an election and register its candidates
+fn call_election(ctx: &ReducerContext, office: &PoliticalOffice, hour: u64) {
+    let campaign = campaign_hours(office.election_method);
+
+    let id = ctx.db.election().iter().count() as u32 + 1;
+    ctx.db.election().insert(Election {
+        id,
+        world_id: office.world_id,
+        office_id: office.id,
+        city_id: office.city_id,
+        method: office.election_method,
+        called_hour: hour,
+        closes_hour: hour + campaign,
+        status: ElectionStatus::Campaigning,
+        winner_id: None,
+        ballots_cast: 0,
+    });
+
+    let candidates = match office.election_method {
+        ElectionMethod::Hereditary => heirs(ctx, office),
+        _ => Vec::new(),
+    };
+    let candidates = if candidates.is_empty() { candidacy(ctx, office) } else { candidates };
+    for (individual_id, faction_id) in candidates {
+        let candidate_id = ctx.db.election_candidate().iter().count() as u32 + 1;
+        ctx.db.election_candidate().insert(ElectionCandidate {
+            id: candidate_id,
+            election_id: id,
+            individual_id,
+            faction_id,
+            votes: 0.0,
+        });
+    }
+
+    log::info!("Election {} called for {} of city {} ({:?})", id, office.office_name, office.city_id, office.election_method);
+}
+
+/// The incumbent, leaders of the city's factions and residents with a Leader's calling
+fn candidacy(ctx: &ReducerContext, office: &PoliticalOffice) -> Vec<(u32, Option<u32>)> {
+    let residents = city_residents(ctx, office.city_id);
+    let memberships = faction_of(ctx);
+    let mut candidates: Vec<(u32, Option<u32>)> = Vec::new();
+
+    if office.holder_id != 0 && residents.iter().any(|r| r.id == office.holder_id && r.health > 0.0) {
+        candidates.push((office.holder_id, memberships.get(&office.holder_id).copied()));
+    }
+
+    let leaders = ctx.db.faction().iter()
+        .filter(|f| f.world_id == office.world_id && f.is_active && f.leader_id != 0)
+        .filter(|f| f.base_city_id == office.city_id || residents.iter().any(|r| r.id == f.leader_id));
+    for faction in leaders {
+        if !candidates.iter().any(|(id, _)| *id == faction.leader_id) {
+            candidates.push((faction.leader_id, Some(faction.id)));
+        }
+    }
+
+    for resident in residents.iter()
+        .filter(|r| r.specialized_role == SpecializedRole::Leader && r.age >= 18 && r.health > 0.0)
+    {
+        if !candidates.iter().any(|(id, _)| *id == resident.id) {
+            candidates.push((resident.id, memberships.get(&resident.id).copied()));
+        }
+    }
+
+    candidates
+}
+
+/// Adult family of the incumbent, eldest first
+fn heirs(ctx: &ReducerContext, office: &PoliticalOffice) -> Vec<(u32, Option<u32>)> {
+    if office.holder_id == 0 {
+        return Vec::new();
+    }
+    let memberships = faction_of(ctx);
+    let mut family: Vec<Individual> = ctx.db.relationship().iter()
+        .filter(|r| r.relationship_type == RelationshipType::Family)
+        .filter_map(|r| {
+            if r.individual1_id == office.holder_id {
+                Some(r.individual2_id)
+            } else if r.individual2_id == office.holder_id {
+                Some(r.individual1_id)
+            } else {
+                None
+            }
+        })
+        .filter_map(|id| ctx.db.individual().id().find(&id))
+        .filter(|i| i.age >= 18 && i.health > 0.0)
+        .collect();
+    family.sort_by(eldest_first);
+    family.into_iter()
+        .map(|i| (i.id, memberships.get(&i.id).copied()))
+        .collect()
+}
+
+/// Individual -> the faction they currently belong to
+fn faction_of(ctx: &ReducerContext) -> HashMap<u32, u32> {
+    ctx.db.faction_membership().iter()
+        .filter(|m| m.is_active)
+        .map(|m| (m.individual_id, m.faction_id))
+        .collect()
+}
+
+/// Count the votes under the office's method and hand the office to the winner
+fn decide_election(ctx: &ReducerContext, mut election: Election, hour: u64) -> Result<(), String> {
+    let Some(mut office) = ctx.db.political_office().id().find(&election.office_id) else {
+        election.status = ElectionStatus::Void;
+        ctx.db.election().id().update(election);
+        return Ok(());
+    };
+    let mut candidates: Vec<ElectionCandidate> = ctx.db.election_candidate().iter()
+        .filter(|c| c.election_id == election.id)
+        .filter(|c| ctx.db.individual().id().find(&c.individual_id).map_or(false, |i| i.health > 0.0))
+        .collect();
+
+    if candidates.is_empty() {
+        election.status = ElectionStatus::Void;
+        ctx.db.election().id().update(election);
+        log::warn!("No one stood for {} of city {}", office.office_name, office.city_id);
+        return Ok(());
+    }
+
+    let mut rng = rand::thread_rng();
+    let ballots = match election.method {
+        ElectionMethod::Democratic => vote(ctx, &office, &mut candidates, None),
+        ElectionMethod::Religious => {
+            // Religious factions' candidates stand if there are any; the devout count for more
+            let religious: Vec<u32> = ctx.db.faction().iter()
+                .filter(|f| f.faction_type == FactionType::Religious)
+                .map(|f| f.id)
+                .collect();
+            if candidates.iter().any(|c| c.faction_id.map_or(false, |f| religious.contains(&f))) {
+                candidates.retain(|c| c.faction_id.map_or(false, |f| religious.contains(&f)));
+            }
+            vote(ctx, &office, &mut candidates, Some(Ideology::Theocratic))
+        },
+        ElectionMethod::Hereditary => {
+            // The eldest heir succeeds; with no heirs, the eldest claimant
+            let eldest = candidates.iter()
+                .filter_map(|c| ctx.db.individual().id().find(&c.individual_id).map(|i| (c.id, i)))
+                .min_by(|(_, a), (_, b)| eldest_first(a, b))
+                .map(|(id, _)| id);
+            if let Some(candidate) = candidates.iter_mut().find(|c| Some(c.id) == eldest) {
+                candidate.votes = 1.0;
+            }
+            1
+        },
+        ElectionMethod::Appointed => {
+            // The most influential faction puts in its own; otherwise the most accomplished
+            for candidate in candidates.iter_mut() {
+                candidate.votes = match candidate.faction_id.and_then(|f| ctx.db.faction().id().find(&f)) {
+                    Some(f) => f.influence,
+                    None => ctx.db.individual().id().find(&candidate.individual_id)
+                        .map_or(0.0, |i| i.achievements / 10.0),
+                };
+            }
+            1
+        },
+        ElectionMethod::MightMakesRight => {
+            // Whoever musters the most strength takes it
+            for candidate in candidates.iter_mut() {
+                let backing = candidate.faction_id
+                    .and_then(|f| ctx.db.faction().id().find(&f))
+                    .map_or(0.0, |f| f.member_count as f32 + f.influence + f.treasury / 1000.0);
+                candidate.votes = backing + rng.gen_range(0.0..20.0);
+            }
+            0
+        },
+        ElectionMethod::Lottery => {
+            let drawn = rng.gen_range(0..candidates.len());
+            candidates[drawn].votes = 1.0;
+            0
+        },
+    };
+
+    for candidate in &candidates {
+        if let Some(mut row) = ctx.db.election_candidate().id().find(&candidate.id) {
+            row.votes = candidate.votes;
+            ctx.db.election_candidate().id().update(row);
+        }
+    }
+
+    let total: f32 = candidates.iter().map(|c| c.votes).sum();
+    let winner = candidates.iter()
+        .max_by(|a, b| a.votes.total_cmp(&b.votes))
+        .map(|c| (c.individual_id, c.faction_id, c.votes))
+        .unwrap_or((candidates[0].individual_id, candidates[0].faction_id, 0.0));
+    let (winner_id, winner_faction, winner_votes) = winner;
+
+    let re_elected = winner_id == office.holder_id;
+    office.holder_id = winner_id;
+    office.faction_id = winner_faction;
+    office.term_start_hour = hour;
+    if !re_elected {
+        office.approval_rating = if election.method == ElectionMethod::Democratic && total > 0.0 {
+            winner_votes / total * 100.0
+        } else {
+            50.0
+        };
+    }
+    let office_name = office.office_name.clone();
+    let city_id = office.city_id;
+    let world_id = office.world_id;
+    ctx.db.political_office().id().update(office);
+
+    election.status = ElectionStatus::Decided;
+    election.winner_id = Some(winner_id);
+    election.ballots_cast = ballots;
+    ctx.db.election().id().update(election);
+
+    if !re_elected {
+        enact_platform(ctx, city_id, winner_faction, hour);
+    }
+
+    let winner_name = ctx.db.individual().id().find(&winner_id)
+        .map(|i| i.name)
+        .unwrap_or_else(|| format!("Individual {}", winner_id));
+    let city_name = ctx.db.city().id().find(&city_id)
+        .map(|c| c.name)
+        .unwrap_or_else(|| format!("City {}", city_id));
+    let description = if re_elected {
+        format!("{} keeps the office of {} in {}", winner_name, office_name, city_name)
+    } else {
+        format!("{} becomes {} of {}", winner_name, office_name, city_name)
+    };
+    log::info!("{}", description);
+    create_narrative_event(ctx, world_id, 1, EventCategory::Political,
+        format!("{} of {} decided", office_name, city_name), description, 4)?;
+    Ok(())
+}
+
+/// Residents who turn out each back the candidate they prefer; returns ballots cast.
+/// With a weighting ideology, each ballot counts by the voter's affinity to it.
+fn vote(ctx: &ReducerContext, office: &PoliticalOffice, candidates: &mut [ElectionCandidate], weighting: Option<Ideology>) -> u32 {
+    let mut rng = rand::thread_rng();
+    let memberships = faction_of(ctx);
+    let ideologies: HashMap<u32, Ideology> = ctx.db.faction().iter()
+        .map(|f| (f.id, f.ideology))
+        .collect();
+    let standing: HashMap<u32, f32> = candidates.iter()
+        .filter_map(|c| ctx.db.individual().id().find(&c.individual_id).map(|i| (c.individual_id, i.achievements)))
+        .collect();
+
+    let mut voters: Vec<Individual> = city_residents(ctx, office.city_id).into_iter()
+        .filter(|i| i.age >= 18 && i.health > 0.0)
+        .collect();
+    voters.shuffle(&mut rng);
+
+    let mut ballots = 0;
+    for voter in voters {
+        let turnout = BASE_TURNOUT + voter.community / 100.0 * COMMUNITY_TURNOUT;
+        if rng.gen::<f32>() >= turnout {
+            continue;
+        }
+
+        let preference = |c: &ElectionCandidate| -> f32 {
+            let mut score = match c.faction_id {
+                Some(f) if memberships.get(&voter.id) == Some(&f) => MEMBER_LOYALTY,
+                Some(f) => ideologies.get(&f).map_or(INDEPENDENT_APPEAL, |i| ideology_affinity(ctx, &voter, *i)),
+                None => INDEPENDENT_APPEAL + standing.get(&c.individual_id).copied().unwrap_or(0.0) / 250.0,
+            };
+            if c.individual_id == office.holder_id {
+                score += (office.approval_rating - 50.0) / 100.0;
+            }
+            score
+        };
+        let choice = candidates.iter()
+            .enumerate()
+            .max_by(|(_, a), (_, b)| preference(a).total_cmp(&preference(b)))
+            .map(|(index, _)| index);
+
+        if let Some(index) = choice {
+            candidates[index].votes += weighting.map_or(1.0, |i| ideology_affinity(ctx, &voter, i));
+            ballots += 1;
+        }
+    }
+
+    ballots
+}
+
+/// A new holder enacts the policy their faction stands for
+fn enact_platform(ctx: &ReducerContext, city_id: u32, faction_id: Option<u32>, hour: u64) {
+    let Some(faction) = faction_id.and_then(|f| ctx.db.faction().id().find(&f)) else {
+        return;
+    };
+    if let Some((policy_type, intensity)) = platform(faction.ideology) {
+        enact_policy(ctx, city_id, policy_type, intensity, hour);
+    }
+}
+
+/// The policy an ideology stands for, and how hard
+pub(crate) fn platform(ideology: Ideology) -> Option<(PolicyType, f32)> {
+    match ideology {
+        Ideology::Authoritarian | Ideology::Militaristic => Some((PolicyType::SecurityFunding, PLATFORM_INTENSITY)),
+        Ideology::Democratic => Some((PolicyType::PublicSpending, PLATFORM_INTENSITY)),
+        Ideology::Theocratic => Some((PolicyType::CulturalInvestment, PLATFORM_INTENSITY)),
+        Ideology::Scholarly => Some((PolicyType::EducationFunding, PLATFORM_INTENSITY)),
+        Ideology::Mercantile => Some((PolicyType::TaxRate, PLATFORM_INTENSITY * 0.6)),
+        Ideology::Anarchist => None,
+    }
+}
\ No newline at end of file
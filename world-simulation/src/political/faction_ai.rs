@@ -0,0 +1,589 @@
+// Faction AI: each faction keeps a set of weighted goals and, every planning cycle, takes the actions that serve them best
+
+use spacetimedb::{ReducerContext, Table, SpacetimeType};
+use serde::{Serialize, Deserialize};
+use log;
+use rand::Rng;
+use crate::political::{Faction, PoliticalEventType, RelationshipType, create_political_event, faction, faction_relationship, political_office};
+use crate::political::faction_membership::{active_members, faction_membership, ideology_affinity, join_faction, JOIN_AFFINITY};
+use crate::political::faction_relationships::{InteractionKind, record_interaction};
+use crate::political::elections::platform;
+use crate::political::warfare::{active_war_of, enlist, field_armies};
+use crate::tables::individual::individual;
+use crate::tables::city::city;
+use crate::tables::events::{city_event, CityEvent, CityEventType};
+use crate::reducers::city::{city_residents, enact_policy};
+use crate::reducers::unrest::{crack_down, incite, unrest_level};
+use crate::systems::modifiers::disorder;
+use crate::narrative::{create_narrative_event, EventCategory};
+
+const PLANNING_PERIOD_HOURS: u64 = 24;
+const ACTIONS_PER_CYCLE: usize = 2;
+const MIN_UTILITY: f32 = 0.1;             // Not worth acting below this
+const COST_AVERSION: f32 = 0.5;           // Utility lost per treasury share spent
+const TREASURY_TARGET: f32 = 5000.0;
+const SUPPORT_TARGET: f32 = 60.0;         // Public support a faction wants in its cities
+const EXPANSION_INFLUENCE: f32 = 50.0;    // Influence before a faction looks beyond its home city
+const RIVAL_MARGIN: f32 = 20.0;           // Influence lead that makes a rival no longer a threat
+
+const RECRUIT_COST: f32 = 200.0;
+const RECRUITS_PER_DRIVE: usize = 5;
+const RECRUIT_AFFINITY_DISCOUNT: f32 = 0.15; // A drive wins over people slightly less convinced
+const FESTIVAL_COST: f32 = 300.0;
+const LOBBY_COST: f32 = 100.0;
+const BRIBE_COST: f32 = 500.0;
+const BRIBE_EXPOSURE_CHANCE: f32 = 0.2;
+const PROPAGANDA_COST: f32 = 150.0;
+const MERCENARY_WAGE: f32 = 100.0;        // Per mercenary hired
+const MERCENARIES_PER_HIRE: usize = 10;
+const DONATION_SHARE: f32 = 0.1;          // Of a member's income above DONATION_FLOOR
+const DONATION_FLOOR: f32 = 50.0;
+const INCITE_COST: f32 = 250.0;
+const CRACKDOWN_COST: f32 = 200.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, SpacetimeType)]
+pub enum GoalKind {
+    ExpandInfluence,     // In target_city_id
+    AccumulateTreasury,
+    UndermineRival,      // target_faction_id
+    WinWar,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, SpacetimeType)]
+pub enum GoalStatus {
+    Active,
+    Achieved,
+    Abandoned,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, SpacetimeType)]
+pub enum FactionActionKind {
+    Recruit,
+    Bribe,
+    ProposePolicy,
+    FundFestival,
+    HireMercenaries,
+    SpreadPropaganda,
+    SolicitDonations,
+    InciteUnrest,
+    SuppressUnrest,
+    Wait,            // Nothing was worth doing; recorded so the faction waits a cycle before replanning
+}
+
+// Something a faction is working toward
+#[spacetimedb::table(name = faction_goal)]
+pub struct FactionGoal {
+    #[primary_key]
+    pub id: u32,
+    pub faction_id: u32,
+    pub kind: GoalKind,
+    pub target_city_id: Option<u32>,
+    pub target_faction_id: Option<u32>,
+    pub priority: f32,         // 0-1
+    pub status: GoalStatus,
+    pub created_hour: u64,
+    pub resolved_hour: Option<u64>,
+}
+
+// An action a faction took, why, and what came of it
+#[spacetimedb::table(name = faction_action)]
+pub struct FactionAction {
+    #[primary_key]
+    pub id: u32,
+    pub faction_id: u32,
+    pub goal_id: u32,          // 0 when the faction had no goal to pursue
+    pub hour: u64,
+    pub action: FactionActionKind,
+    pub target_city_id: Option<u32>,
+    pub target_faction_id: Option<u32>,
+    pub cost: f32,
+    pub utility: f32,
+    pub reasoning: String,
+    pub outcome: String,
+}
+
+// One option the planner weighed
+struct Candidate {
+    goal_id: u32,
+    action: FactionActionKind,
+    city_id: Option<u32>,
+    rival_id: Option<u32>,
+    cost: f32,
+    utility: f32,
+}
+
+// Plan for every faction whose planning cycle has come round
+#[spacetimedb::reducer]
+pub fn plan_faction_actions(ctx: &ReducerContext, world_id: u32, hour: u64) -> Result<(), String> {
+    let factions: Vec<Faction> = ctx.db.faction().iter()
+        .filter(|f| f.world_id == world_id && f.is_active)
+        .collect();
+
+    for faction in factions {
+        let last_planned = ctx.db.faction_action().iter()
+            .filter(|a| a.faction_id == faction.id)
+            .map(|a| a.hour)
+            .max();
+        if last_planned.map_or(false, |h| hour < h + PLANNING_PERIOD_HOURS) {
+            continue;
+        }
+        plan(ctx, faction.id, hour)?;
+    }
+    Ok(())
+}
+
+/// Refresh goals, weigh every action against them and carry out the best few the treasury can cover
+fn plan(ctx: &ReducerContext, faction_id: u32, hour: u64) -> Result<(), String> {
+    let Some(faction) = ctx.db.faction().id().find(&faction_id) else { return Ok(()); };
+    let goals = review_goals(ctx, &faction, hour);
+    if goals.is_empty() {
+        record_action(ctx, faction_id, 0, hour, None, "no goals to pursue".to_string(), String::new());
+        return Ok(());
+    }
+
+    let thrift = goals.iter()
+        .find(|g| g.kind == GoalKind::AccumulateTreasury)
+        .map_or(0.0, |g| g.priority);
+    let mut candidates: Vec<Candidate> = goals.iter()
+        .flat_map(|goal| options(ctx, &faction, goal))
+        .filter(|c| c.cost <= faction.treasury)
+        .map(|mut c| {
+            c.utility -= COST_AVERSION * (1.0 + thrift) * c.cost / faction.treasury.max(1.0);
+            c
+        })
+        .filter(|c| c.utility >= MIN_UTILITY)
+        .collect();
+    candidates.sort_by(|a, b| b.utility.total_cmp(&a.utility));
+
+    let mut budget = faction.treasury;
+    let mut taken: Vec<FactionActionKind> = Vec::new();
+    for (index, candidate) in candidates.iter().enumerate() {
+        if taken.len() >= ACTIONS_PER_CYCLE {
+            break;
+        }
+        // Earlier choices this cycle may have spent what this one needs
+        if taken.contains(&candidate.action) || candidate.cost > budget {
+            continue;
+        }
+        let goal = goals.iter().find(|g| g.id == candidate.goal_id).expect("candidate comes from a goal");
+        let passed_over: Vec<String> = candidates.iter().skip(index + 1)
+            .filter(|c| c.action != candidate.action)
+            .take(2)
+            .map(|c| format!("{:?} ({:.2})", c.action, c.utility))
+            .collect();
+        let reasoning = format!("{} (priority {:.2}); chose {:?} at utility {:.2}{}",
+            describe_goal(ctx, goal), goal.priority, candidate.action, candidate.utility,
+            if passed_over.is_empty() { String::new() } else { format!(" over {}", passed_over.join(", ")) });
+
+        let outcome = act(ctx, faction_id, candidate, hour)?;
+        budget -= candidate.cost;
+        taken.push(candidate.action);
+
+        log::info!("Faction {} {:?}: {} -> {}", faction_id, candidate.action, reasoning, outcome);
+        record_action(ctx, faction_id, candidate.goal_id, hour, Some(candidate), reasoning, outcome);
+    }
+
+    if taken.is_empty() {
+        let goal = goals.iter().max_by(|a, b| a.priority.total_cmp(&b.priority)).expect("goals is not empty");
+        let reasoning = format!("{} (priority {:.2}); nothing worth its cost within a treasury of {:.0}",
+            describe_goal(ctx, goal), goal.priority, faction.treasury);
+        record_action(ctx, faction_id, goal.id, hour, None, reasoning, String::new());
+    }
+    Ok(())
+}
+
+/// Log what a faction did this cycle, or that it waited when `candidate` is None
+fn record_action(
+    ctx: &ReducerContext,
+    faction_id: u32,
+    goal_id: u32,
+    hour: u64,
+    candidate: Option<&Candidate>,
+    reasoning: String,
+    outcome: String,
+) {
+    let id = ctx.db.faction_action().iter().count() as u32 + 1;
+    ctx.db.faction_action().insert(FactionAction {
+        id,
+        faction_id,
+        goal_id,
+        hour,
+        action: candidate.map_or(FactionActionKind::Wait, |c| c.action),
+        target_city_id: candidate.and_then(|c| c.city_id),
+        target_faction_id: candidate.and_then(|c| c.rival_id),
+        cost: candidate.map_or(0.0, |c| c.cost),
+        utility: candidate.map_or(0.0, |c| c.utility),
+        reasoning,
+        outcome,
+    });
+}
+
+/// Open goals the faction's situation calls for, reprioritise existing ones and close those met
+fn review_goals(ctx: &ReducerContext, faction: &Faction, hour: u64) -> Vec<FactionGoal> {
+    let mut wanted: Vec<(GoalKind, Option<u32>, Option<u32>, f32)> = Vec::new();
+
+    if faction.treasury < TREASURY_TARGET {
+        wanted.push((GoalKind::AccumulateTreasury, None, None, 1.0 - faction.treasury / TREASURY_TARGET));
+    }
+    // Weak support at home, or home streets in turmoil, call for shoring up the base city
+    let home_unrest = unrest_level(ctx, faction.base_city_id);
+    if faction.public_support < SUPPORT_TARGET || home_unrest >= disorder::PROTEST_LEVEL {
+        let shortfall = (SUPPORT_TARGET - faction.public_support).max(0.0) / SUPPORT_TARGET;
+        wanted.push((GoalKind::ExpandInfluence, Some(faction.base_city_id), None, shortfall.max(home_unrest / 100.0)));
+    }
+    if faction.influence > EXPANSION_INFLUENCE {
+        // Strong factions look to the most populous city they have no hold on
+        let target = ctx.db.city().iter()
+            .filter(|c| c.id != faction.base_city_id && c.population > 0)
+            .filter(|c| crate::reducers::migration::world_for_city(ctx, c.id) == Some(faction.world_id))
+            .max_by_key(|c| c.population);
+        if let Some(city) = target {
+            wanted.push((GoalKind::ExpandInfluence, Some(city.id), None, (faction.influence - EXPANSION_INFLUENCE) / 100.0 + 0.2));
+        }
+    }
+    if let Some((rival_id, threat)) = chief_rival(ctx, faction) {
+        wanted.push((GoalKind::UndermineRival, None, Some(rival_id), threat));
+    }
+    if active_war_of(ctx, faction.id).is_some() {
+        wanted.push((GoalKind::WinWar, None, None, 1.0));
+    }
+
+    let existing: Vec<FactionGoal> = ctx.db.faction_goal().iter()
+        .filter(|g| g.faction_id == faction.id && g.status == GoalStatus::Active)
+        .collect();
+    for mut goal in existing {
+        match wanted.iter().position(|w| w.0 == goal.kind && w.1 == goal.target_city_id && w.2 == goal.target_faction_id) {
+            Some(index) => {
+                goal.priority = wanted.remove(index).3.clamp(0.0, 1.0);
+            },
+            None => {
+                // No longer called for: met, unless the rival or war simply vanished
+                goal.status = match goal.kind {
+                    GoalKind::UndermineRival | GoalKind::WinWar => GoalStatus::Abandoned,
+                    _ => GoalStatus::Achieved,
+                };
+                goal.resolved_hour = Some(hour);
+            },
+        }
+        ctx.db.faction_goal().id().update(goal);
+    }
+
+    for (kind, target_city_id, target_faction_id, priority) in wanted {
+        let id = ctx.db.faction_goal().iter().count() as u32 + 1;
+        ctx.db.faction_goal().insert(FactionGoal {
+            id,
+            faction_id: faction.id,
+            kind,
+            target_city_id,
+            target_faction_id,
+            priority: priority.clamp(0.0, 1.0),
+            status: GoalStatus::Active,
+            created_hour: hour,
+            resolved_hour: None,
+        });
+    }
+
+    ctx.db.faction_goal().iter()
+        .filter(|g| g.faction_id == faction.id && g.status == GoalStatus::Active)
+        .collect()
+}
+
+/// The hostile or rival faction that most threatens this one, and how much (0-1)
+fn chief_rival(ctx: &ReducerContext, faction: &Faction) -> Option<(u32, f32)> {
+    ctx.db.faction_relationship().iter()
+        .filter(|r| r.faction1_id == faction.id || r.faction2_id == faction.id)
+        .filter(|r| matches!(r.relationship_type, RelationshipType::Rival | RelationshipType::Hostile | RelationshipType::AtWar))
+        .filter_map(|r| {
+            let other = if r.faction1_id == faction.id { r.faction2_id } else { r.faction1_id };
+            let rival = ctx.db.faction().id().find(&other).filter(|f| f.is_active)?;
+            let lead = rival.influence - faction.influence;
+            if lead < -RIVAL_MARGIN {
+                return None;
+            }
+            Some((other, ((lead + RIVAL_MARGIN) / 100.0 - r.relationship / 200.0).clamp(0.0, 1.0)))
+        })
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+}
+
+fn describe_goal(ctx: &ReducerContext, goal: &FactionGoal) -> String {
+    let city_name = |id: Option<u32>| id.and_then(|c| ctx.db.city().id().find(&c))
+        .map_or_else(|| "its city".to_string(), |c| c.name);
+    let faction_name = |id: Option<u32>| id.and_then(|f| ctx.db.faction().id().find(&f))
+        .map_or_else(|| "a rival".to_string(), |f| f.name);
+    match goal.kind {
+        GoalKind::ExpandInfluence => format!("to expand its influence in {}", city_name(goal.target_city_id)),
+        GoalKind::AccumulateTreasury => "to fill its treasury".to_string(),
+        GoalKind::UndermineRival => format!("to undermine {}", faction_name(goal.target_faction_id)),
+        GoalKind::WinWar => "to win its war".to_string(),
+    }
+}
+
+/// The actions that would serve a goal, each with its cost and the good it would do (before cost)
+fn options(ctx: &ReducerContext, faction: &Faction, goal: &FactionGoal) -> Vec<Candidate> {
+    let option = |action, city_id, rival_id, cost, effect: f32| Candidate {
+        goal_id: goal.id,
+        action,
+        city_id,
+        rival_id,
+        cost,
+        utility: goal.priority * effect,
+    };
+    let mut options = Vec::new();
+
+    match goal.kind {
+        GoalKind::ExpandInfluence => {
+            let city_id = goal.target_city_id.unwrap_or(faction.base_city_id);
+            if !recruits_in(ctx, faction, city_id).is_empty() {
+                options.push(option(FactionActionKind::Recruit, Some(city_id), None, RECRUIT_COST, 0.6));
+            }
+            options.push(option(FactionActionKind::FundFestival, Some(city_id), None, FESTIVAL_COST, 0.5));
+            if platform(faction.ideology).is_some() {
+                match office_in(ctx, city_id) {
+                    Some((_, Some(holder_faction))) if holder_faction == faction.id => {
+                        options.push(option(FactionActionKind::ProposePolicy, Some(city_id), None, LOBBY_COST, 0.7));
+                    },
+                    Some(_) => {
+                        options.push(option(FactionActionKind::Bribe, Some(city_id), None, BRIBE_COST, 0.6));
+                    },
+                    None => {},
+                }
+            }
+            let unrest = unrest_level(ctx, city_id);
+            if unrest >= disorder::PROTEST_LEVEL && office_in(ctx, city_id).map_or(false, |(_, f)| f == Some(faction.id)) {
+                options.push(option(FactionActionKind::SuppressUnrest, Some(city_id), None, CRACKDOWN_COST, 0.4 + unrest / 200.0));
+            }
+        },
+        GoalKind::AccumulateTreasury => {
+            if active_members(ctx, faction.id).len() > 1 {
+                options.push(option(FactionActionKind::SolicitDonations, None, None, 0.0, 0.8));
+            }
+        },
+        GoalKind::UndermineRival => {
+            let Some(rival_id) = goal.target_faction_id else { return options; };
+            options.push(option(FactionActionKind::SpreadPropaganda, None, Some(rival_id), PROPAGANDA_COST, 0.7));
+            if let Some(rival) = ctx.db.faction().id().find(&rival_id) {
+                // A restless city ruled by the rival is ripe for agitation
+                let unrest = unrest_level(ctx, rival.base_city_id);
+                if unrest >= disorder::PROTEST_LEVEL && office_in(ctx, rival.base_city_id).map_or(false, |(_, f)| f == Some(rival_id)) {
+                    options.push(option(FactionActionKind::InciteUnrest, Some(rival.base_city_id), Some(rival_id), INCITE_COST, 0.5 + unrest / 200.0));
+                }
+                if platform(faction.ideology).is_some() {
+                    if let Some((_, Some(holder_faction))) = office_in(ctx, rival.base_city_id) {
+                        if holder_faction == rival_id {
+                            options.push(option(FactionActionKind::Bribe, Some(rival.base_city_id), Some(rival_id), BRIBE_COST, 0.4));
+                        }
+                    }
+                }
+            }
+        },
+        GoalKind::WinWar => {
+            if let Some(war) = active_war_of(ctx, faction.id) {
+                if !field_armies(ctx, war.id, faction.id).is_empty() {
+                    let hires = mercenaries_in(ctx, faction).min(MERCENARIES_PER_HIRE);
+                    if hires > 0 {
+                        options.push(option(FactionActionKind::HireMercenaries, Some(faction.base_city_id), None,
+                            hires as f32 * MERCENARY_WAGE, 0.9));
+                    }
+                }
+            }
+            if !recruits_in(ctx, faction, faction.base_city_id).is_empty() {
+                options.push(option(FactionActionKind::Recruit, Some(faction.base_city_id), None, RECRUIT_COST, 0.5));
+            }
+        },
+    }
+    options
+}
+
+/// The most powerful office in a city: its id and the holder's faction
+fn office_in(ctx: &ReducerContext, city_id: u32) -> Option<(u32, Option<u32>)> {
+    ctx.db.political_office().iter()
+        .filter(|o| o.city_id == city_id && o.holder_id != 0)
+        .max_by(|a, b| a.power_level.total_cmp(&b.power_level))
+        .map(|o| (o.id, o.faction_id))
+}
+
+/// Unaffiliated adults in a city a recruitment drive could win over
+fn recruits_in(ctx: &ReducerContext, faction: &Faction, city_id: u32) -> Vec<u32> {
+    let affiliated: Vec<u32> = ctx.db.faction_membership().iter()
+        .filter(|m| m.is_active)
+        .map(|m| m.individual_id)
+        .collect();
+    city_residents(ctx, city_id).into_iter()
+        .filter(|i| i.age >= 18 && i.health > 0.0 && !affiliated.contains(&i.id))
+        .filter(|i| ideology_affinity(ctx, i, faction.ideology) >= JOIN_AFFINITY - RECRUIT_AFFINITY_DISCOUNT)
+        .take(RECRUITS_PER_DRIVE)
+        .map(|i| i.id)
+        .collect()
+}
+
+/// Unemployed, able-bodied adults in the home city who would fight for pay
+fn mercenaries_in(ctx: &ReducerContext, faction: &Faction) -> usize {
+    hireable(ctx, faction).len()
+}
+
+fn hireable(ctx: &ReducerContext, faction: &Faction) -> Vec<u32> {
+    let members: Vec<u32> = active_members(ctx, faction.id).iter().map(|m| m.individual_id).collect();
+    city_residents(ctx, faction.base_city_id).into_iter()
+        .filter(|i| i.age >= 18 && i.health >= 50.0 && i.workplace_id.is_none() && !members.contains(&i.id))
+        .map(|i| i.id)
+        .collect()
+}
+
+/// Carry out an action through the tables it touches; returns what came of it
+fn act(ctx: &ReducerContext, faction_id: u32, candidate: &Candidate, hour: u64) -> Result<String, String> {
+    let Some(mut faction) = ctx.db.faction().id().find(&faction_id) else {
+        return Ok("faction gone".to_string());
+    };
+    if faction.treasury < candidate.cost {
+        return Ok("could not afford it".to_string());
+    }
+    faction.treasury -= candidate.cost;
+    let city_id = candidate.city_id.unwrap_or(faction.base_city_id);
+
+    let outcome = match candidate.action {
+        FactionActionKind::Recruit => {
+            let recruits = recruits_in(ctx, &faction, city_id);
+            for individual_id in &recruits {
+                if let Some(recruit) = ctx.db.individual().id().find(individual_id) {
+                    join_faction(ctx, &faction, &recruit, hour);
+                }
+            }
+            faction.member_count += recruits.len() as u32;
+            format!("{} joined", recruits.len())
+        },
+        FactionActionKind::FundFestival => {
+            let residents = city_residents(ctx, city_id);
+            for mut resident in residents.into_iter().filter(|i| i.age >= 18) {
+                resident.community = (resident.community + 10.0).min(100.0);
+                ctx.db.individual().id().update(resident);
+            }
+            let participants = match ctx.db.city().id().find(&city_id) {
+                Some(mut city) => {
+                    city.stability = (city.stability + 3.0).min(100.0);
+                    let population = city.population;
+                    let name = city.name.clone();
+                    ctx.db.city().id().update(city);
+                    let id = ctx.db.city_event().iter().count() as u32 + 1;
+                    ctx.db.city_event().insert(CityEvent {
+                        id,
+                        city_id,
+                        hour,
+                        event_type: CityEventType::Festival,
+                        description: format!("{} throws a festival in {}", faction.name, name),
+                        participants: population,
+                        impact_stability: 3.0,
+                        impact_culture: 5.0,
+                    });
+                    population
+                },
+                None => 0,
+            };
+            faction.public_support = (faction.public_support + 5.0).min(100.0);
+            format!("festival for {} residents", participants)
+        },
+        FactionActionKind::ProposePolicy => {
+            match platform(faction.ideology) {
+                Some((policy_type, intensity)) => {
+                    enact_policy(ctx, city_id, policy_type.clone(), intensity, hour);
+                    format!("{:?} enacted", policy_type)
+                },
+                None => "nothing to propose".to_string(),
+            }
+        },
+        FactionActionKind::Bribe => {
+            let holder = office_in(ctx, city_id)
+                .and_then(|(office_id, _)| ctx.db.political_office().id().find(&office_id))
+                .and_then(|o| ctx.db.individual().id().find(&o.holder_id));
+            match (holder, platform(faction.ideology)) {
+                (Some(mut holder), Some((policy_type, intensity))) => {
+                    holder.income += candidate.cost;
+                    let holder_name = holder.name.clone();
+                    ctx.db.individual().id().update(holder);
+                    enact_policy(ctx, city_id, policy_type.clone(), intensity, hour);
+                    if rand::thread_rng().gen::<f32>() < BRIBE_EXPOSURE_CHANCE {
+                        faction.influence = (faction.influence - 10.0).max(0.0);
+                        faction.public_support = (faction.public_support - 10.0).max(0.0);
+                        let description = format!("{} is caught paying {} to push {:?}", faction.name, holder_name, policy_type);
+                        create_political_event(ctx, faction.world_id, PoliticalEventType::Scandal, faction.id, candidate.rival_id, hour, description.clone())?;
+                        create_narrative_event(ctx, faction.world_id, 1, EventCategory::Political,
+                            format!("Bribery scandal engulfs {}", faction.name), description, 4)?;
+                        format!("{} bought {:?}, but the bribe was exposed", holder_name, policy_type)
+                    } else {
+                        format!("{} quietly enacted {:?}", holder_name, policy_type)
+                    }
+                },
+                _ => "no one to bribe".to_string(),
+            }
+        },
+        FactionActionKind::SpreadPropaganda => {
+            match candidate.rival_id.and_then(|r| ctx.db.faction().id().find(&r)) {
+                Some(mut rival) => {
+                    rival.public_support = (rival.public_support - 5.0).max(0.0);
+                    rival.stability = (rival.stability - 3.0).max(0.0);
+                    let rival_name = rival.name.clone();
+                    ctx.db.faction().id().update(rival);
+                    faction.public_support = (faction.public_support + 2.0).min(100.0);
+                    record_interaction(ctx, faction.world_id, faction.id, candidate.rival_id.unwrap_or(0), InteractionKind::Propaganda, None, -5.0,
+                        format!("{} spreads propaganda against {}", faction.name, rival_name), hour);
+                    format!("{} smeared", rival_name)
+                },
+                None => "rival gone".to_string(),
+            }
+        },
+        FactionActionKind::HireMercenaries => {
+            let army = active_war_of(ctx, faction.id)
+                .and_then(|war| field_armies(ctx, war.id, faction.id).into_iter().next());
+            match army {
+                Some(army) => {
+                    let hired: Vec<u32> = hireable(ctx, &faction).into_iter()
+                        .take((candidate.cost / MERCENARY_WAGE) as usize)
+                        .collect();
+                    for individual_id in &hired {
+                        if let Some(mut mercenary) = ctx.db.individual().id().find(individual_id) {
+                            mercenary.income += MERCENARY_WAGE;
+                            ctx.db.individual().id().update(mercenary);
+                        }
+                        enlist(ctx, army.id, *individual_id, hour);
+                    }
+                    // Unspent wages go back to the treasury
+                    faction.treasury += candidate.cost - hired.len() as f32 * MERCENARY_WAGE;
+                    format!("{} mercenaries joined the {}", hired.len(), army.name)
+                },
+                None => {
+                    faction.treasury += candidate.cost;
+                    "no army to join".to_string()
+                },
+            }
+        },
+        FactionActionKind::InciteUnrest => {
+            let level = incite(ctx, city_id, faction.id, hour);
+            faction.public_support = (faction.public_support + 3.0).min(100.0);
+            format!("unrest stirred to {:.0}", level)
+        },
+        FactionActionKind::SuppressUnrest => {
+            let relief = crack_down(ctx, city_id, hour);
+            // Crackdowns calm the streets but cost goodwill
+            faction.public_support = (faction.public_support - 2.0).max(0.0);
+            format!("unrest cut by {:.0}", relief)
+        },
+        FactionActionKind::SolicitDonations => {
+            let mut raised = 0.0;
+            for membership in active_members(ctx, faction.id) {
+                if let Some(mut member) = ctx.db.individual().id().find(&membership.individual_id) {
+                    if member.income > DONATION_FLOOR {
+                        let gift = (member.income - DONATION_FLOOR) * DONATION_SHARE;
+                        member.income -= gift;
+                        raised += gift;
+                        ctx.db.individual().id().update(member);
+                    }
+                }
+            }
+            faction.treasury += raised;
+            format!("{:.0} raised", raised)
+        },
+        FactionActionKind::Wait => "waited".to_string(),
+    };
+
+    ctx.db.faction().id().update(faction);
+    Ok(outcome)
+}
\ No newline at end of file
@@ -0,0 +1,344 @@
+// Faction membership: residents join the factions whose ideology fits their lives, pay dues and elect leaders from among themselves
+
+use spacetimedb::{ReducerContext, Table};
+use log;
+use rand::Rng;
+use rand::seq::SliceRandom;
+use crate::political::{Faction, Ideology, PoliticalEventType, faction, create_political_event};
+use crate::tables::individual::{individual, Individual};
+use crate::tables::building::building;
+use crate::types::{BuildingType, JobType, SpecializedRole};
+use crate::reducers::city::city_residents;
+use crate::political::religion::{devotion_of, keeps_faith_of};
+use crate::narrative::{create_narrative_event, EventCategory};
+use crate::world::game_world::game_world;
+
+pub(crate) const JOIN_AFFINITY: f32 = 0.6;         // Affinity needed to sign up
+const LEAVE_AFFINITY: f32 = 0.35;         // Members drift away below this
+const SUPPORT_AFFINITY: f32 = 0.45;       // Poll respondents back the faction above this
+const JOIN_CHANCE: f32 = 0.1;             // Per eligible resident per update
+const DUES_PER_WEEK: f32 = 5.0;           // Paid from income into the treasury
+const DUES_PERIOD_HOURS: u64 = 168;
+const POLL_SAMPLE: usize = 50;            // Residents asked per poll
+const POLL_PERIOD_HOURS: u64 = 168;
+const INSTABILITY_DISSATISFACTION: f32 = 0.2; // Affinity lost at zero faction stability
+
+// An individual's active or past membership of a faction
+#[spacetimedb::table(name = faction_membership)]
+pub struct FactionMembership {
+    #[primary_key]
+    pub id: u32,
+    pub faction_id: u32,
+    pub individual_id: u32,
+    pub joined_hour: u64,
+    pub left_hour: Option<u64>,
+    pub affinity: f32,         // 0-1 fit with the faction's ideology at last check
+    pub dues_paid: f32,        // Lifetime total
+    pub last_dues_hour: u64,
+    pub is_active: bool,
+}
+
+// One round of polling residents of a faction's base city
+#[spacetimedb::table(name = faction_poll)]
+pub struct FactionPoll {
+    #[primary_key]
+    pub id: u32,
+    pub faction_id: u32,
+    pub city_id: u32,
+    pub hour: u64,
+    pub respondents: u32,
+    pub supporters: u32,
+    pub support: f32,          // 0-100 share of respondents
+}
+
+// Remove a faction's leader and hand the faction to a successor
+#[spacetimedb::reducer]
+pub fn remove_faction_leader(ctx: &ReducerContext, faction_id: u32, reason: String) -> Result<(), String> {
+    let mut faction = ctx.db.faction().id().find(&faction_id)
+        .ok_or("Faction not found")?;
+    let hour = current_hour(ctx, faction.world_id)?;
+
+    let outgoing = faction.leader_id;
+    succeed_leader(ctx, &mut faction, Some(outgoing), &reason, hour)?;
+    ctx.db.faction().id().update(faction);
+    Ok(())
+}
+
+// Recruit, collect dues, let the dissatisfied leave, poll residents and check the leadership
+#[spacetimedb::reducer]
+pub fn update_faction_membership(ctx: &ReducerContext, world_id: u32, hour: u64) -> Result<(), String> {
+    let factions: Vec<Faction> = ctx.db.faction().iter()
+        .filter(|f| f.world_id == world_id && f.is_active)
+        .collect();
+
+    for mut faction in factions {
+        review_members(ctx, &mut faction, hour);
+        recruit(ctx, &faction, hour);
+
+        faction.member_count = active_members(ctx, faction.id).len() as u32;
+
+        let last_poll = ctx.db.faction_poll().iter()
+            .filter(|p| p.faction_id == faction.id)
+            .map(|p| p.hour)
+            .max();
+        if last_poll.map_or(true, |h| hour >= h + POLL_PERIOD_HOURS) {
+            faction.public_support = poll_residents(ctx, &faction, hour);
+        }
+
+        let leader = ctx.db.individual().id().find(&faction.leader_id);
+        let leader_is_member = active_members(ctx, faction.id).iter()
+            .any(|m| m.individual_id == faction.leader_id);
+        match leader {
+            Some(l) if l.health > 0.0 && leader_is_member => {},
+            Some(l) if l.health > 0.0 => {
+                succeed_leader(ctx, &mut faction, Some(l.id), "left the faction", hour)?;
+            },
+            Some(l) => {
+                succeed_leader(ctx, &mut faction, Some(l.id), "died", hour)?;
+            },
+            None if faction.leader_id != 0 => {
+                succeed_leader(ctx, &mut faction, None, "is gone", hour)?;
+            },
+            None => {
+                // Leaderless factions keep looking
+                succeed_leader(ctx, &mut faction, None, "was never chosen", hour)?;
+            },
+        }
+
+        ctx.db.faction().id().update(faction);
+    }
+
+    Ok(())
+}
+
+/// Enrol someone as a member, e.g. a founding leader
+pub(crate) fn join_faction(ctx: &ReducerContext, faction: &Faction, individual: &Individual, hour: u64) {
+    if active_members(ctx, faction.id).iter().any(|m| m.individual_id == individual.id) {
+        return;
+    }
+    let id = ctx.db.faction_membership().iter().count() as u32 + 1;
+    ctx.db.faction_membership().insert(FactionMembership {
+        id,
+        faction_id: faction.id,
+        individual_id: individual.id,
+        joined_hour: hour,
+        left_hour: None,
+        affinity: ideology_affinity(ctx, individual, faction.ideology),
+        dues_paid: 0.0,
+        last_dues_hour: hour,
+        is_active: true,
+    });
+}
+
+pub(crate) fn active_members(ctx: &ReducerContext, faction_id: u32) -> Vec<FactionMembership> {
+    ctx.db.faction_membership().iter()
+        .filter(|m| m.faction_id == faction_id && m.is_active)
+        .collect()
+}
+
+/// How well an ideology answers an individual's needs, job and role, 0.0-1.0
+pub(crate) fn ideology_affinity(ctx: &ReducerContext, individual: &Individual, ideology: Ideology) -> f32 {
+    let job = individual.workplace_id
+        .and_then(|id| ctx.db.building().id().find(&id))
+        .and_then(|b| match b.building_type {
+            BuildingType::Workplace(config) => Some(config.job_type),
+            _ => None,
+        });
+    let role = &individual.specialized_role;
+
+    let (needs, calling) = match ideology {
+        // Order for those who feel unsafe
+        Ideology::Authoritarian => (
+            (individual.threat + (100.0 - individual.safety)) / 200.0,
+            matches!(job, Some(JobType::Government) | Some(JobType::Utilities)),
+        ),
+        // A voice for those with a community to speak for
+        Ideology::Democratic => (
+            (individual.community + individual.relationship * 3.0) / 200.0,
+            matches!(job, Some(JobType::Office) | Some(JobType::Education)) || *role == SpecializedRole::Leader,
+        ),
+        // Comfort for the stressed and the ill, and a voice for the devout
+        Ideology::Theocratic => (
+            ((individual.stress + (100.0 - individual.health)) / 200.0).max(devotion_of(ctx, individual.id)),
+            matches!(job, Some(JobType::Healthcare)) || *role == SpecializedRole::Healer,
+        ),
+        // Trade for those doing well from it
+        Ideology::Mercantile => (
+            (individual.income / 200.0).min(1.0),
+            matches!(job, Some(JobType::Retail) | Some(JobType::Office) | Some(JobType::Agriculture)),
+        ),
+        // Strength for the threatened and the restless
+        Ideology::Militaristic => (
+            (individual.threat + individual.stress) / 200.0,
+            matches!(job, Some(JobType::Factory) | Some(JobType::Mining) | Some(JobType::Quarrying) | Some(JobType::Logging)),
+        ),
+        // Learning for those still growing
+        Ideology::Scholarly => (
+            (individual.progression + individual.achievements) / 200.0,
+            matches!(job, Some(JobType::Research) | Some(JobType::Education))
+                || matches!(role, SpecializedRole::Scientist | SpecializedRole::Educator),
+        ),
+        // Nothing to lose
+        Ideology::Anarchist => (
+            ((100.0 - individual.income.min(100.0)) + individual.stress) / 200.0,
+            job.is_none() || *role == SpecializedRole::Artist,
+        ),
+    };
+
+    (needs * 0.7 + if calling { 0.3 } else { 0.0 }).clamp(0.0, 1.0)
+}
+
+fn current_hour(ctx: &ReducerContext, world_id: u32) -> Result<u64, String> {
+    ctx.db.game_world().id().find(&world_id)
+        .map(|w| w.total_hours)
+        .ok_or_else(|| "World not found".to_string())
+}
+
+/// Refresh each member's affinity, collect dues, and let the dissatisfied go
+fn review_members(ctx: &ReducerContext, faction: &mut Faction, hour: u64) {
+    let discontent = (1.0 - faction.stability / 100.0) * INSTABILITY_DISSATISFACTION;
+
+    for mut membership in active_members(ctx, faction.id) {
+        let Some(mut member) = ctx.db.individual().id().find(&membership.individual_id) else {
+            membership.is_active = false;
+            membership.left_hour = Some(hour);
+            ctx.db.faction_membership().id().update(membership);
+            continue;
+        };
+
+        membership.affinity = ideology_affinity(ctx, &member, faction.ideology) - discontent;
+
+        let dues_due = hour >= membership.last_dues_hour + DUES_PERIOD_HOURS;
+        let leaving = member.health <= 0.0
+            || membership.affinity < LEAVE_AFFINITY
+            || (dues_due && member.income < DUES_PER_WEEK);
+
+        if leaving {
+            membership.is_active = false;
+            membership.left_hour = Some(hour);
+            log::debug!("Individual {} left faction {} (affinity {:.2})", member.id, faction.id, membership.affinity);
+        } else if dues_due {
+            member.income -= DUES_PER_WEEK;
+            faction.treasury += DUES_PER_WEEK;
+            membership.dues_paid += DUES_PER_WEEK;
+            membership.last_dues_hour = hour;
+            ctx.db.individual().id().update(member);
+        }
+        ctx.db.faction_membership().id().update(membership);
+    }
+}
+
+/// Residents of the base city who fit the faction well may sign up
+fn recruit(ctx: &ReducerContext, faction: &Faction, hour: u64) {
+    let mut rng = rand::thread_rng();
+    let members: Vec<u32> = ctx.db.faction_membership().iter()
+        .filter(|m| m.is_active)
+        .map(|m| m.individual_id)
+        .collect();
+
+    // One faction at a time: only the unaffiliated are recruited
+    let candidates: Vec<Individual> = city_residents(ctx, faction.base_city_id).into_iter()
+        .filter(|i| i.age >= 18 && i.health > 0.0 && !members.contains(&i.id))
+        .collect();
+
+    let mut joined = 0;
+    for candidate in candidates {
+        if ideology_affinity(ctx, &candidate, faction.ideology) >= JOIN_AFFINITY && rng.gen::<f32>() < JOIN_CHANCE {
+            join_faction(ctx, faction, &candidate, hour);
+            joined += 1;
+        }
+    }
+    if joined > 0 {
+        log::info!("{} recruited {} members in city {}", faction.name, joined, faction.base_city_id);
+    }
+}
+
+/// Ask a sample of base-city residents whether they back the faction; returns support 0-100
+fn poll_residents(ctx: &ReducerContext, faction: &Faction, hour: u64) -> f32 {
+    let mut residents: Vec<Individual> = city_residents(ctx, faction.base_city_id).into_iter()
+        .filter(|i| i.age >= 18)
+        .collect();
+    if residents.is_empty() {
+        return faction.public_support;
+    }
+    residents.shuffle(&mut rand::thread_rng());
+    residents.truncate(POLL_SAMPLE);
+
+    let members: Vec<u32> = active_members(ctx, faction.id).iter()
+        .map(|m| m.individual_id)
+        .collect();
+    let supporters = residents.iter()
+        .filter(|i| members.contains(&i.id) || ideology_affinity(ctx, i, faction.ideology) >= SUPPORT_AFFINITY ||
+            keeps_faith_of(ctx, i.id, faction.id))
+        .count() as u32;
+    let respondents = residents.len() as u32;
+    let support = supporters as f32 / respondents as f32 * 100.0;
+
+    let id = ctx.db.faction_poll().iter().count() as u32 + 1;
+    ctx.db.faction_poll().insert(FactionPoll {
+        id,
+        faction_id: faction.id,
+        city_id: faction.base_city_id,
+        hour,
+        respondents,
+        supporters,
+        support,
+    });
+
+    support
+}
+
+/// Hand leadership to the most suitable remaining member, excluding the outgoing leader
+pub(crate) fn succeed_leader(
+    ctx: &ReducerContext,
+    faction: &mut Faction,
+    outgoing: Option<u32>,
+    reason: &str,
+    hour: u64,
+) -> Result<(), String> {
+    let successor = active_members(ctx, faction.id).into_iter()
+        .filter(|m| Some(m.individual_id) != outgoing)
+        .filter_map(|m| ctx.db.individual().id().find(&m.individual_id).map(|i| (m, i)))
+        .filter(|(_, i)| i.health > 0.0 && i.age >= 18)
+        .max_by(|(ma, a), (mb, b)| {
+            succession_score(ma, a, hour).total_cmp(&succession_score(mb, b, hour))
+        })
+        .map(|(_, i)| i);
+
+    let had_leader = faction.leader_id != 0;
+    let Some(successor) = successor else {
+        if had_leader {
+            faction.leader_id = 0;
+            faction.stability = (faction.stability - 15.0).max(0.0);
+            log::warn!("{} has no one to succeed its leader", faction.name);
+        }
+        return Ok(());
+    };
+
+    let old_name = outgoing
+        .and_then(|id| ctx.db.individual().id().find(&id))
+        .map(|i| i.name)
+        .unwrap_or_else(|| "the old leader".to_string());
+    faction.leader_id = successor.id;
+    if had_leader {
+        faction.stability = (faction.stability - 5.0).max(0.0);
+    }
+
+    let description = if had_leader {
+        format!("{} leads {} after {} {}", successor.name, faction.name, old_name, reason)
+    } else {
+        format!("{} is chosen to lead {}", successor.name, faction.name)
+    };
+    log::info!("{}", description);
+    create_political_event(ctx, faction.world_id, PoliticalEventType::Succession, faction.id, None, hour, description.clone())?;
+    create_narrative_event(ctx, faction.world_id, 1, EventCategory::Political,
+        format!("New leader for {}", faction.name), description, 4)?;
+    Ok(())
+}
+
+/// Members with standing, seniority and a talent for leading come first
+fn succession_score(membership: &FactionMembership, individual: &Individual, hour: u64) -> f32 {
+    let seniority = (hour.saturating_sub(membership.joined_hour) as f32 / DUES_PERIOD_HOURS as f32).min(52.0);
+    let talent = if individual.specialized_role == SpecializedRole::Leader { 30.0 } else { 0.0 };
+    membership.affinity * 50.0 + individual.achievements * 0.3 + seniority + talent
+}
\ No newline at end of file
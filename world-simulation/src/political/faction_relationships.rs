@@ -0,0 +1,573 @@
+// Diplomacy: factions weigh each other up, propose and sign treaties, honour or breach them, and remember what passed between them
+
+use spacetimedb::{ReducerContext, Table, SpacetimeType};
+use serde::{Serialize, Deserialize};
+use log;
+use rand::Rng;
+use crate::political::{Faction, FactionRelationship, Ideology, RelationshipType, TreatyStatus, faction, faction_relationship};
+use crate::political::warfare::{WarOutcome, open_war, war};
+use crate::economics::trade_route;
+use crate::narrative::{create_narrative_event, EventCategory};
+use crate::world::game_world::game_world;
+
+const PROPOSAL_CHANCE: f32 = 0.05;         // Per pair of factions per update
+const RESPONSE_HOURS: u64 = 24;            // Time a faction takes to answer a proposal
+const ACCEPT_THRESHOLD: f32 = 0.4;         // Appeal the receiving faction needs to sign
+const TRADE_VOLUME_SCALE: f32 = 200.0;     // Route volume worth the full trade appeal
+const RELATIONSHIP_DRIFT_PER_DAY: f32 = 1.0; // Toward friendship while a treaty holds
+const BREACH_PENALTY: f32 = 40.0;          // Relationship lost with the wronged party
+const BREACH_INFLUENCE_LOSS: f32 = 10.0;   // Everyone else takes note
+const TRIBUTE_PER_INFLUENCE_GAP: f32 = 10.0; // Weekly tribute per point of influence the protector has over its client
+const TRIBUTE_GAP: f32 = 20.0;             // Influence gap before a protector asks for tribute
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, SpacetimeType)]
+pub enum TreatyState {
+    Proposed,
+    Active,
+    Rejected,
+    Expired,
+    Breached,
+    Superseded,   // Replaced by a deeper treaty between the same factions
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, SpacetimeType)]
+pub enum InteractionKind {
+    TreatyProposed,
+    TreatySigned,
+    TreatyRejected,
+    TreatyExpired,
+    TreatyBreached,
+    TributePaid,
+    WarDeclared,
+    CalledToArms,
+    PeaceSigned,
+    Propaganda,
+}
+
+// A treaty between two factions and its terms
+#[spacetimedb::table(name = treaty)]
+pub struct Treaty {
+    #[primary_key]
+    pub id: u32,
+    pub world_id: u32,
+    pub proposer_id: u32,
+    pub recipient_id: u32,
+    pub treaty_type: TreatyStatus,
+    pub state: TreatyState,
+    pub proposed_hour: u64,
+    pub signed_hour: Option<u64>,
+    pub duration_hours: u64,
+    pub tribute_payer_id: Option<u32>,  // Client paying for protection, if any
+    pub tribute_per_week: f32,
+    pub last_tribute_hour: u64,
+    pub last_review_hour: u64,
+    pub breached_by: Option<u32>,
+    pub ended_hour: Option<u64>,
+}
+
+// Something that passed between two factions
+#[spacetimedb::table(name = diplomatic_interaction)]
+pub struct DiplomaticInteraction {
+    #[primary_key]
+    pub id: u32,
+    pub world_id: u32,
+    pub faction1_id: u32,
+    pub faction2_id: u32,
+    pub hour: u64,
+    pub kind: InteractionKind,
+    pub treaty_id: Option<u32>,
+    pub relationship_change: f32,
+    pub description: String,
+}
+
+// Propose a treaty to another faction; it answers after RESPONSE_HOURS
+#[spacetimedb::reducer]
+pub fn propose_treaty(ctx: &ReducerContext, proposer_id: u32, recipient_id: u32, treaty_type: TreatyStatus) -> Result<(), String> {
+    if treaty_type == TreatyStatus::None {
+        return Err("Choose a kind of treaty to propose".to_string());
+    }
+    let proposer = ctx.db.faction().id().find(&proposer_id).ok_or("Proposing faction not found")?;
+    let recipient = ctx.db.faction().id().find(&recipient_id).ok_or("Receiving faction not found")?;
+    let hour = ctx.db.game_world().id().find(&proposer.world_id)
+        .ok_or("World not found")?
+        .total_hours;
+
+    offer(ctx, &proposer, &recipient, treaty_type, hour).map(|_| ())
+}
+
+// Weigh up proposals, answer pending ones, enforce terms, catch breaches and let treaties lapse
+#[spacetimedb::reducer]
+pub fn update_diplomacy(ctx: &ReducerContext, world_id: u32, hour: u64) -> Result<(), String> {
+    let mut rng = rand::thread_rng();
+    let factions: Vec<Faction> = ctx.db.faction().iter()
+        .filter(|f| f.world_id == world_id && f.is_active)
+        .collect();
+
+    for (i, a) in factions.iter().enumerate() {
+        for b in factions.iter().skip(i + 1) {
+            let mut relationship = relationship_row(ctx, a, b, hour);
+            relationship.trade_volume = trade_volume(ctx, a, b);
+            ctx.db.faction_relationship().id().update(relationship);
+
+            if rng.gen::<f32>() < PROPOSAL_CHANCE {
+                let (proposer, recipient) = if rng.gen_bool(0.5) { (a, b) } else { (b, a) };
+                if let Some(treaty_type) = next_treaty(ctx, proposer, recipient) {
+                    offer(ctx, proposer, recipient, treaty_type, hour)?;
+                }
+            }
+        }
+    }
+
+    let treaties: Vec<Treaty> = ctx.db.treaty().iter()
+        .filter(|t| t.world_id == world_id && matches!(t.state, TreatyState::Proposed | TreatyState::Active))
+        .collect();
+    for treaty in treaties {
+        match treaty.state {
+            TreatyState::Proposed if hour >= treaty.proposed_hour + RESPONSE_HOURS => answer(ctx, treaty, hour)?,
+            TreatyState::Active => uphold(ctx, treaty, hour)?,
+            _ => {},
+        }
+    }
+
+    Ok(())
+}
+
+/// The strongest treaty currently binding two factions
+pub(crate) fn treaty_between(ctx: &ReducerContext, a: u32, b: u32) -> TreatyStatus {
+    ctx.db.treaty().iter()
+        .filter(|t| t.state == TreatyState::Active && is_between(t, a, b))
+        .map(|t| t.treaty_type)
+        .max_by_key(|t| rank(*t))
+        .unwrap_or(TreatyStatus::None)
+}
+
+/// Record a diplomatic interaction and apply its effect on the relationship
+pub(crate) fn record_interaction(
+    ctx: &ReducerContext,
+    world_id: u32,
+    faction1_id: u32,
+    faction2_id: u32,
+    kind: InteractionKind,
+    treaty_id: Option<u32>,
+    relationship_change: f32,
+    description: String,
+    hour: u64,
+) {
+    let id = ctx.db.diplomatic_interaction().iter().count() as u32 + 1;
+    ctx.db.diplomatic_interaction().insert(DiplomaticInteraction {
+        id,
+        world_id,
+        faction1_id,
+        faction2_id,
+        hour,
+        kind,
+        treaty_id,
+        relationship_change,
+        description,
+    });
+
+    if let Some(mut relationship) = ctx.db.faction_relationship().iter()
+        .find(|r| (r.faction1_id == faction1_id && r.faction2_id == faction2_id)
+            || (r.faction1_id == faction2_id && r.faction2_id == faction1_id))
+    {
+        relationship.relationship = (relationship.relationship + relationship_change).clamp(-100.0, 100.0);
+        relationship.last_interaction_hour = hour;
+        refresh_standing(ctx, &mut relationship);
+        ctx.db.faction_relationship().id().update(relationship);
+    }
+}
+
+/// Two friendly factions sit down to talk: the next treaty that would suit them is put forward
+pub(crate) fn open_negotiations(ctx: &ReducerContext, proposer_id: u32, recipient_id: u32, hour: u64) -> Result<(), String> {
+    let (Some(proposer), Some(recipient)) = (ctx.db.faction().id().find(&proposer_id), ctx.db.faction().id().find(&recipient_id)) else {
+        return Ok(());
+    };
+    if let Some(treaty_type) = next_treaty(ctx, &proposer, &recipient) {
+        offer(ctx, &proposer, &recipient, treaty_type, hour)?;
+    }
+    Ok(())
+}
+
+/// A war has broken out: any treaty between the two is breached by the attacker
+pub(crate) fn war_declared(ctx: &ReducerContext, world_id: u32, attacker_id: u32, defender_id: u32, hour: u64) -> Result<(), String> {
+    let binding: Vec<Treaty> = ctx.db.treaty().iter()
+        .filter(|t| t.state == TreatyState::Active && is_between(t, attacker_id, defender_id))
+        .collect();
+    for treaty in binding {
+        breach(ctx, treaty, attacker_id, "went to war", hour)?;
+    }
+    record_interaction(ctx, world_id, attacker_id, defender_id, InteractionKind::WarDeclared, None, -30.0,
+        format!("Faction {} declared war on faction {}", attacker_id, defender_id), hour);
+    Ok(())
+}
+
+/// Mutual defence: allies of the defender join the war against the attacker
+pub(crate) fn call_allies(ctx: &ReducerContext, world_id: u32, attacker_id: u32, defender_id: u32, hour: u64) -> Result<(), String> {
+    let allies: Vec<(u32, u32)> = ctx.db.treaty().iter()
+        .filter(|t| t.state == TreatyState::Active
+            && matches!(t.treaty_type, TreatyStatus::MutualDefense | TreatyStatus::FullAlliance)
+            && (t.proposer_id == defender_id || t.recipient_id == defender_id))
+        .map(|t| (t.id, if t.proposer_id == defender_id { t.recipient_id } else { t.proposer_id }))
+        .filter(|(_, ally)| *ally != attacker_id)
+        .collect();
+    for (treaty_id, ally_id) in allies {
+        let Some(ally) = ctx.db.faction().id().find(&ally_id) else { continue; };
+        if !ally.is_active {
+            continue;
+        }
+        record_interaction(ctx, world_id, defender_id, ally_id, InteractionKind::CalledToArms, Some(treaty_id), 5.0,
+            format!("{} answers the call to defend faction {}", ally.name, defender_id), hour);
+        open_war(ctx, world_id, ally_id, attacker_id, hour)?;
+    }
+    Ok(())
+}
+
+/// A war has ended: the two sign a non-aggression pact
+pub(crate) fn on_peace(ctx: &ReducerContext, world_id: u32, attacker_id: u32, defender_id: u32, hour: u64) -> Result<(), String> {
+    let (Some(attacker), Some(defender)) = (ctx.db.faction().id().find(&attacker_id), ctx.db.faction().id().find(&defender_id)) else {
+        return Ok(());
+    };
+    let treaty_id = insert_treaty(ctx, &attacker, &defender, TreatyStatus::NonAggressionPact, TreatyState::Active, hour);
+    if let Some(mut relationship) = ctx.db.faction_relationship().iter().find(|r| is_pair(r, attacker_id, defender_id)) {
+        relationship.relationship = relationship.relationship.max(-50.0);
+        ctx.db.faction_relationship().id().update(relationship);
+    }
+    record_interaction(ctx, world_id, attacker_id, defender_id, InteractionKind::PeaceSigned, Some(treaty_id), 10.0,
+        format!("{} and {} sign a peace", attacker.name, defender.name), hour);
+    Ok(())
+}
+
+fn is_between(treaty: &Treaty, a: u32, b: u32) -> bool {
+    (treaty.proposer_id == a && treaty.recipient_id == b) || (treaty.proposer_id == b && treaty.recipient_id == a)
+}
+
+fn is_pair(relationship: &FactionRelationship, a: u32, b: u32) -> bool {
+    (relationship.faction1_id == a && relationship.faction2_id == b) || (relationship.faction1_id == b && relationship.faction2_id == a)
+}
+
+fn rank(treaty_type: TreatyStatus) -> u8 {
+    match treaty_type {
+        TreatyStatus::None => 0,
+        TreatyStatus::TradeAgreement => 1,
+        TreatyStatus::NonAggressionPact => 2,
+        TreatyStatus::MutualDefense => 3,
+        TreatyStatus::FullAlliance => 4,
+    }
+}
+
+fn duration(treaty_type: TreatyStatus) -> u64 {
+    match treaty_type {
+        TreatyStatus::TradeAgreement => 2160,      // A season
+        TreatyStatus::NonAggressionPact => 4320,
+        TreatyStatus::MutualDefense => 4320,
+        TreatyStatus::FullAlliance => 8640,        // A year
+        TreatyStatus::None => 0,
+    }
+}
+
+/// The two factions' relationship, opened from their ideologies the first time they deal with each other
+pub(crate) fn relationship_row(ctx: &ReducerContext, a: &Faction, b: &Faction, hour: u64) -> FactionRelationship {
+    if let Some(existing) = ctx.db.faction_relationship().iter().find(|r| is_pair(r, a.id, b.id)) {
+        return existing;
+    }
+    let id = ctx.db.faction_relationship().iter().count() as u32 + 1;
+    let mut relationship = FactionRelationship {
+        id,
+        world_id: a.world_id,
+        faction1_id: a.id,
+        faction2_id: b.id,
+        relationship: compatibility(a.ideology, b.ideology) * 20.0,
+        relationship_type: RelationshipType::Neutral,
+        trade_volume: 0.0,
+        last_interaction_hour: hour,
+        treaty_status: TreatyStatus::None,
+    };
+    refresh_standing(ctx, &mut relationship);
+    ctx.db.faction_relationship().insert(relationship)
+}
+
+/// Keep the relationship's type and treaty status in step with its value, wars and treaties
+fn refresh_standing(ctx: &ReducerContext, relationship: &mut FactionRelationship) {
+    let (a, b) = (relationship.faction1_id, relationship.faction2_id);
+    relationship.treaty_status = treaty_between(ctx, a, b);
+    let at_war = ctx.db.war().iter().any(|w| w.outcome == WarOutcome::Ongoing
+        && ((w.attacker_id == a && w.defender_id == b) || (w.attacker_id == b && w.defender_id == a)));
+
+    relationship.relationship_type = if at_war {
+        RelationshipType::AtWar
+    } else if relationship.treaty_status == TreatyStatus::FullAlliance || relationship.relationship > 60.0 {
+        RelationshipType::Allied
+    } else if relationship.relationship > 20.0 {
+        RelationshipType::Friendly
+    } else if relationship.relationship > -20.0 {
+        RelationshipType::Neutral
+    } else if relationship.relationship > -60.0 {
+        RelationshipType::Rival
+    } else {
+        RelationshipType::Hostile
+    };
+}
+
+/// How well two ideologies sit together, -1.0 to 1.0
+fn compatibility(a: Ideology, b: Ideology) -> f32 {
+    use Ideology::*;
+    if a == b {
+        return 1.0;
+    }
+    let pair = |x: Ideology, y: Ideology| (a == x && b == y) || (a == y && b == x);
+    if pair(Authoritarian, Militaristic) || pair(Authoritarian, Theocratic) || pair(Democratic, Mercantile)
+        || pair(Democratic, Scholarly) || pair(Mercantile, Scholarly) {
+        0.5
+    } else if pair(Authoritarian, Democratic) || pair(Anarchist, Authoritarian) || pair(Theocratic, Scholarly)
+        || pair(Anarchist, Theocratic) || pair(Anarchist, Militaristic) {
+        -1.0
+    } else if a == Anarchist || b == Anarchist {
+        -0.5
+    } else {
+        0.0
+    }
+}
+
+/// Goods moving along trade routes between the two factions' home cities
+fn trade_volume(ctx: &ReducerContext, a: &Faction, b: &Faction) -> f32 {
+    ctx.db.trade_route().iter()
+        .filter(|r| r.is_active
+            && ((r.from_city_id == a.base_city_id && r.to_city_id == b.base_city_id)
+                || (r.from_city_id == b.base_city_id && r.to_city_id == a.base_city_id)))
+        .map(|r| r.volume)
+        .sum()
+}
+
+/// Factions both are fighting or hostile to
+fn shared_enemies(ctx: &ReducerContext, a: u32, b: u32) -> usize {
+    let enemies_of = |faction_id: u32| -> Vec<u32> {
+        let mut enemies: Vec<u32> = ctx.db.faction_relationship().iter()
+            .filter(|r| (r.faction1_id == faction_id || r.faction2_id == faction_id)
+                && matches!(r.relationship_type, RelationshipType::Hostile | RelationshipType::AtWar))
+            .map(|r| if r.faction1_id == faction_id { r.faction2_id } else { r.faction1_id })
+            .collect();
+        enemies.extend(ctx.db.war().iter()
+            .filter(|w| w.outcome == WarOutcome::Ongoing && (w.attacker_id == faction_id || w.defender_id == faction_id))
+            .map(|w| if w.attacker_id == faction_id { w.defender_id } else { w.attacker_id }));
+        enemies
+    };
+    let theirs = enemies_of(b);
+    let mut shared: Vec<u32> = enemies_of(a).into_iter().filter(|e| theirs.contains(e)).collect();
+    shared.sort();
+    shared.dedup();
+    shared.len()
+}
+
+/// How much `of` wants closer ties with `with`, roughly -1.0 to 2.0
+fn appeal(ctx: &ReducerContext, of: &Faction, with: &Faction) -> f32 {
+    let relationship = ctx.db.faction_relationship().iter()
+        .find(|r| is_pair(r, of.id, with.id))
+        .map_or(0.0, |r| r.relationship);
+    let at_war = ctx.db.war().iter().any(|w| w.outcome == WarOutcome::Ongoing && (w.attacker_id == of.id || w.defender_id == of.id));
+
+    relationship / 100.0
+        + shared_enemies(ctx, of.id, with.id) as f32 * 0.3
+        + (trade_volume(ctx, of, with) / TRADE_VOLUME_SCALE).min(0.5)
+        + compatibility(of.ideology, with.ideology) * 0.3
+        + if at_war { 0.2 } else { 0.0 }
+}
+
+/// The next treaty worth proposing, one step beyond what binds them now
+fn next_treaty(ctx: &ReducerContext, proposer: &Faction, recipient: &Faction) -> Option<TreatyStatus> {
+    let pending = ctx.db.treaty().iter()
+        .any(|t| t.state == TreatyState::Proposed && is_between(&t, proposer.id, recipient.id));
+    let at_war = ctx.db.war().iter().any(|w| w.outcome == WarOutcome::Ongoing
+        && ((w.attacker_id == proposer.id && w.defender_id == recipient.id)
+            || (w.attacker_id == recipient.id && w.defender_id == proposer.id)));
+    if pending || at_war {
+        return None;
+    }
+
+    let score = appeal(ctx, proposer, recipient);
+    let candidate = match treaty_between(ctx, proposer.id, recipient.id) {
+        TreatyStatus::None if trade_volume(ctx, proposer, recipient) > 0.0 => TreatyStatus::TradeAgreement,
+        TreatyStatus::None | TreatyStatus::TradeAgreement => TreatyStatus::NonAggressionPact,
+        TreatyStatus::NonAggressionPact if shared_enemies(ctx, proposer.id, recipient.id) > 0 => TreatyStatus::MutualDefense,
+        TreatyStatus::MutualDefense => TreatyStatus::FullAlliance,
+        _ => return None,
+    };
+    let needed = match candidate {
+        TreatyStatus::TradeAgreement => 0.2,
+        TreatyStatus::NonAggressionPact => 0.3,
+        TreatyStatus::MutualDefense => 0.6,
+        _ => 0.9,
+    };
+    (score >= needed).then_some(candidate)
+}
+
+fn insert_treaty(ctx: &ReducerContext, proposer: &Faction, recipient: &Faction, treaty_type: TreatyStatus, state: TreatyState, hour: u64) -> u32 {
+    // A protector much stronger than its client asks tribute for defence
+    let (tribute_payer_id, tribute_per_week) = match treaty_type {
+        TreatyStatus::MutualDefense | TreatyStatus::FullAlliance => {
+            let gap = proposer.influence - recipient.influence;
+            if gap > TRIBUTE_GAP {
+                (Some(recipient.id), gap * TRIBUTE_PER_INFLUENCE_GAP)
+            } else if -gap > TRIBUTE_GAP {
+                (Some(proposer.id), -gap * TRIBUTE_PER_INFLUENCE_GAP)
+            } else {
+                (None, 0.0)
+            }
+        },
+        _ => (None, 0.0),
+    };
+
+    let id = ctx.db.treaty().iter().count() as u32 + 1;
+    ctx.db.treaty().insert(Treaty {
+        id,
+        world_id: proposer.world_id,
+        proposer_id: proposer.id,
+        recipient_id: recipient.id,
+        treaty_type,
+        state,
+        proposed_hour: hour,
+        signed_hour: (state == TreatyState::Active).then_some(hour),
+        duration_hours: duration(treaty_type),
+        tribute_payer_id,
+        tribute_per_week,
+        last_tribute_hour: hour,
+        last_review_hour: hour,
+        breached_by: None,
+        ended_hour: None,
+    });
+    id
+}
+
+fn offer(ctx: &ReducerContext, proposer: &Faction, recipient: &Faction, treaty_type: TreatyStatus, hour: u64) -> Result<u32, String> {
+    if proposer.id == recipient.id {
+        return Err("A faction cannot make a treaty with itself".to_string());
+    }
+    relationship_row(ctx, proposer, recipient, hour);
+    let treaty_id = insert_treaty(ctx, proposer, recipient, treaty_type, TreatyState::Proposed, hour);
+    record_interaction(ctx, proposer.world_id, proposer.id, recipient.id, InteractionKind::TreatyProposed, Some(treaty_id), 2.0,
+        format!("{} proposes a {:?} to {}", proposer.name, treaty_type, recipient.name), hour);
+    log::info!("{} proposed {:?} to {}", proposer.name, treaty_type, recipient.name);
+    Ok(treaty_id)
+}
+
+/// The recipient signs if the treaty appeals enough, tribute counting against it if it would pay
+fn answer(ctx: &ReducerContext, mut treaty: Treaty, hour: u64) -> Result<(), String> {
+    let (Some(proposer), Some(recipient)) = (ctx.db.faction().id().find(&treaty.proposer_id), ctx.db.faction().id().find(&treaty.recipient_id)) else {
+        treaty.state = TreatyState::Rejected;
+        treaty.ended_hour = Some(hour);
+        ctx.db.treaty().id().update(treaty);
+        return Ok(());
+    };
+
+    let burden = if treaty.tribute_payer_id == Some(recipient.id) {
+        treaty.tribute_per_week / recipient.treasury.max(1.0)
+    } else {
+        0.0
+    };
+    let score = appeal(ctx, &recipient, &proposer) - burden + rand::thread_rng().gen_range(-0.1..0.1);
+
+    if score >= ACCEPT_THRESHOLD {
+        // A deeper treaty replaces the shallower ones it covers
+        let covered: Vec<Treaty> = ctx.db.treaty().iter()
+            .filter(|t| t.state == TreatyState::Active && is_between(t, proposer.id, recipient.id)
+                && rank(t.treaty_type) < rank(treaty.treaty_type) && t.treaty_type != TreatyStatus::TradeAgreement)
+            .collect();
+        for mut old in covered {
+            old.state = TreatyState::Superseded;
+            old.ended_hour = Some(hour);
+            ctx.db.treaty().id().update(old);
+        }
+
+        treaty.state = TreatyState::Active;
+        treaty.signed_hour = Some(hour);
+        treaty.last_tribute_hour = hour;
+        treaty.last_review_hour = hour;
+        let (treaty_id, treaty_type) = (treaty.id, treaty.treaty_type);
+        ctx.db.treaty().id().update(treaty);
+        record_interaction(ctx, proposer.world_id, proposer.id, recipient.id, InteractionKind::TreatySigned, Some(treaty_id), 15.0,
+            format!("{} and {} sign a {:?}", proposer.name, recipient.name, treaty_type), hour);
+        create_narrative_event(ctx, proposer.world_id, 1, EventCategory::Political,
+            format!("{:?} between {} and {}", treaty_type, proposer.name, recipient.name),
+            format!("{} has accepted the {:?} offered by {}", recipient.name, treaty_type, proposer.name),
+            if rank(treaty_type) >= 3 { 4 } else { 3 })?;
+    } else {
+        treaty.state = TreatyState::Rejected;
+        treaty.ended_hour = Some(hour);
+        let (treaty_id, treaty_type) = (treaty.id, treaty.treaty_type);
+        ctx.db.treaty().id().update(treaty);
+        record_interaction(ctx, proposer.world_id, recipient.id, proposer.id, InteractionKind::TreatyRejected, Some(treaty_id), -5.0,
+            format!("{} turns down the {:?} offered by {}", recipient.name, treaty_type, proposer.name), hour);
+    }
+    Ok(())
+}
+
+/// Enforce an active treaty's terms: tribute, goodwill, and expiry
+fn uphold(ctx: &ReducerContext, mut treaty: Treaty, hour: u64) -> Result<(), String> {
+    if let Some(signed) = treaty.signed_hour {
+        if hour >= signed + treaty.duration_hours {
+            treaty.state = TreatyState::Expired;
+            treaty.ended_hour = Some(hour);
+            let (world_id, a, b, id, treaty_type) = (treaty.world_id, treaty.proposer_id, treaty.recipient_id, treaty.id, treaty.treaty_type);
+            ctx.db.treaty().id().update(treaty);
+            record_interaction(ctx, world_id, a, b, InteractionKind::TreatyExpired, Some(id), 0.0,
+                format!("The {:?} between factions {} and {} has lapsed", treaty_type, a, b), hour);
+            return Ok(());
+        }
+    }
+
+    // Tribute falls due weekly; failing to pay it breaks the treaty
+    if let Some(payer_id) = treaty.tribute_payer_id {
+        if hour >= treaty.last_tribute_hour + 168 {
+            let receiver_id = if payer_id == treaty.proposer_id { treaty.recipient_id } else { treaty.proposer_id };
+            match (ctx.db.faction().id().find(&payer_id), ctx.db.faction().id().find(&receiver_id)) {
+                (Some(mut payer), Some(mut receiver)) if payer.treasury >= treaty.tribute_per_week => {
+                    payer.treasury -= treaty.tribute_per_week;
+                    receiver.treasury += treaty.tribute_per_week;
+                    ctx.db.faction().id().update(payer);
+                    ctx.db.faction().id().update(receiver);
+                    treaty.last_tribute_hour = hour;
+                    record_interaction(ctx, treaty.world_id, payer_id, receiver_id, InteractionKind::TributePaid, Some(treaty.id), 0.0,
+                        format!("Faction {} pays {:.0} in tribute to faction {}", payer_id, treaty.tribute_per_week, receiver_id), hour);
+                },
+                _ => return breach(ctx, treaty, payer_id, "could not pay its tribute", hour),
+            }
+        }
+    }
+
+    // Goodwill grows while the treaty holds
+    let days = hour.saturating_sub(treaty.last_review_hour) as f32 / 24.0;
+    treaty.last_review_hour = hour;
+    if let Some(mut relationship) = ctx.db.faction_relationship().iter().find(|r| is_pair(r, treaty.proposer_id, treaty.recipient_id)) {
+        relationship.relationship = (relationship.relationship + RELATIONSHIP_DRIFT_PER_DAY * days).min(100.0);
+        refresh_standing(ctx, &mut relationship);
+        ctx.db.faction_relationship().id().update(relationship);
+    }
+    ctx.db.treaty().id().update(treaty);
+    Ok(())
+}
+
+/// A party broke the treaty: it ends, the wronged party's trust collapses and the breaker's name suffers
+fn breach(ctx: &ReducerContext, mut treaty: Treaty, breaker_id: u32, how: &str, hour: u64) -> Result<(), String> {
+    let wronged_id = if breaker_id == treaty.proposer_id { treaty.recipient_id } else { treaty.proposer_id };
+    treaty.state = TreatyState::Breached;
+    treaty.breached_by = Some(breaker_id);
+    treaty.ended_hour = Some(hour);
+    let (world_id, treaty_id, treaty_type) = (treaty.world_id, treaty.id, treaty.treaty_type);
+    ctx.db.treaty().id().update(treaty);
+
+    let breaker_name = match ctx.db.faction().id().find(&breaker_id) {
+        Some(mut breaker) => {
+            breaker.influence = (breaker.influence - BREACH_INFLUENCE_LOSS).max(0.0);
+            let name = breaker.name.clone();
+            ctx.db.faction().id().update(breaker);
+            name
+        },
+        None => format!("Faction {}", breaker_id),
+    };
+    let description = format!("{} {} and broke its {:?} with faction {}", breaker_name, how, treaty_type, wronged_id);
+    log::info!("{}", description);
+    record_interaction(ctx, world_id, breaker_id, wronged_id, InteractionKind::TreatyBreached, Some(treaty_id), -BREACH_PENALTY,
+        description.clone(), hour);
+    create_narrative_event(ctx, world_id, 1, EventCategory::Political,
+        format!("{} breaks its word", breaker_name), description, 4)?;
+    Ok(())
+}
\ No newline at end of file
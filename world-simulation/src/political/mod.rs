@@ -4,9 +4,15 @@ use spacetimedb::{ReducerContext, Table, SpacetimeType};
 use serde::{Serialize, Deserialize};
 use log;
 use rand::Rng;
+use crate::tables::individual::individual;
 
 pub mod faction_relationships;
 pub mod political_events;
+pub mod faction_membership;
+pub mod elections;
+pub mod warfare;
+pub mod faction_ai;
+pub mod religion;
 
 // Faction types and ideologies
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, SpacetimeType)]
@@ -48,8 +54,6 @@ pub struct Faction {
     pub public_support: f32,   // Popular backing
     pub member_count: u32,
     pub founding_hour: u64,
-    pub goals: String,         // JSON array of faction objectives
-    pub recent_actions: String, // JSON array of recent events
     pub is_active: bool,
 }
 
@@ -64,7 +68,6 @@ pub struct FactionRelationship {
     pub relationship: f32,     // -100 (war) to +100 (alliance)
     pub relationship_type: RelationshipType,
     pub trade_volume: f32,
-    pub recent_interactions: String, // JSON array of events
     pub last_interaction_hour: u64,
     pub treaty_status: TreatyStatus,
 }
@@ -178,14 +181,19 @@ pub fn create_faction(
         treasury: 10000.0,
         stability: 75.0,
         public_support: 50.0,
-        member_count: 10,
+        member_count: 0,
         founding_hour: world.total_hours,
-        goals: "[]".to_string(),
-        recent_actions: "[]".to_string(),
         is_active: true,
     };
 
-    ctx.db.faction().insert(faction);
+    let mut faction = ctx.db.faction().insert(faction);
+
+    // The founding leader is the first member
+    if let Some(leader) = ctx.db.individual().id().find(&leader_id) {
+        faction_membership::join_faction(ctx, &faction, &leader, world.total_hours);
+        faction.member_count = 1;
+        ctx.db.faction().id().update(faction);
+    }
 
     log::info!("Created new faction '{}' (ID: {}) of type {:?} in world {}",
         name, faction_id, faction_type, world_id);
@@ -210,7 +218,7 @@ pub fn update_faction_status(
         // Natural decay/growth of influence
         let base_change = match faction.faction_type {
             FactionType::Political => faction.public_support * 0.001,
-            FactionType::Religious => faction.stability * 0.0005,
+            FactionType::Religious => 0.0, // Follows its congregation, see religion::update_religion
             FactionType::Economic => faction.treasury * 0.00001,
             FactionType::Military => faction.member_count as f32 * 0.01,
             FactionType::Cultural => faction.influence * 0.0008,
@@ -223,7 +231,7 @@ pub fn update_faction_status(
         let treasury_change = match faction.faction_type {
             FactionType::Economic => faction.influence * 100.0,
             FactionType::Political => faction.public_support * 50.0,
-            FactionType::Religious => faction.member_count as f32 * 10.0,
+            FactionType::Religious => 0.0, // Tithed at worship
             _ => faction.influence * 25.0,
         };
 
@@ -279,6 +287,7 @@ pub fn generate_political_events(
                 format!("{} calls for new elections!", faction.name),
             )?;
             event_ids.push(event_id);
+            elections::call_snap_elections(ctx, faction.base_city_id, hour);
         }
 
         // Coup attempts for low stability factions
@@ -348,6 +357,7 @@ pub fn generate_political_events(
                 "War declared between rival factions!".to_string(),
             )?;
             event_ids.push(event_id);
+            warfare::start_war(ctx, world_id, relationship.faction1_id, relationship.faction2_id, hour)?;
         }
 
         // Treaty negotiations for improving relationships
@@ -364,6 +374,7 @@ pub fn generate_political_events(
                 "Diplomatic negotiations begin between allies!".to_string(),
             )?;
             event_ids.push(event_id);
+            faction_relationships::open_negotiations(ctx, relationship.faction1_id, relationship.faction2_id, hour)?;
         }
     }
 
@@ -375,7 +386,7 @@ pub fn generate_political_events(
 }
 
 // Helper function to create political events
-fn create_political_event(
+pub(crate) fn create_political_event(
     ctx: &ReducerContext,
     world_id: u32,
     event_type: PoliticalEventType,
@@ -475,7 +486,9 @@ fn resolve_political_event(
         PoliticalEventType::Coup => {
             if let Some(mut faction) = ctx.db.faction().id().find(&event.primary_faction_id) {
                 if success {
-                    // Successful coup - gain power but lose stability
+                    // Successful coup - the leader is ousted, power grows but stability suffers
+                    let ousted = faction.leader_id;
+                    faction_membership::succeed_leader(ctx, &mut faction, Some(ousted), "was ousted in a coup", event.start_hour + event.duration_hours as u64)?;
                     faction.influence = (faction.influence + 30.0).min(100.0);
                     faction.stability = (faction.stability - 40.0).max(0.0);
                     faction.public_support = (faction.public_support - 25.0).max(0.0);
@@ -489,26 +502,9 @@ fn resolve_political_event(
             }
         },
         PoliticalEventType::War => {
-            // War outcomes affect both factions
-            if let (Some(mut faction1), Some(mut faction2)) = (
-                ctx.db.faction().id().find(&event.primary_faction_id),
-                event.secondary_faction_id.and_then(|id| ctx.db.faction().id().find(&id))
-            ) {
-                if success {
-                    // Faction 1 wins
-                    faction1.influence = (faction1.influence + 25.0).min(100.0);
-                    faction1.treasury = (faction1.treasury - 5000.0).max(0.0);
-                    faction2.influence = (faction2.influence - 30.0).max(0.0);
-                    faction2.treasury = (faction2.treasury - 10000.0).max(0.0);
-                } else {
-                    // Faction 2 wins
-                    faction2.influence = (faction2.influence + 25.0).min(100.0);
-                    faction2.treasury = (faction2.treasury - 5000.0).max(0.0);
-                    faction1.influence = (faction1.influence - 30.0).max(0.0);
-                    faction1.treasury = (faction1.treasury - 10000.0).max(0.0);
-                }
-                ctx.db.faction().id().update(faction1.id, faction1);
-                ctx.db.faction().id().update(faction2.id, faction2);
+            // Armies decide wars; the event records whether the attacker was ahead when it ran its course
+            if let Some(defender_id) = event.secondary_faction_id {
+                return Ok(warfare::attacker_ahead(ctx, event.primary_faction_id, defender_id));
             }
         },
         _ => {
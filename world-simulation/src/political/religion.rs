@@ -0,0 +1,542 @@
+// Religion: faiths founded at temple holy sites, personal devotion, conversion between neighbours and seasonal worship
+
+use spacetimedb::{ReducerContext, Table, SpacetimeType};
+use serde::{Serialize, Deserialize};
+use log;
+use rand::Rng;
+use rand::seq::SliceRandom;
+use std::collections::HashMap;
+use crate::political::{Faction, FactionType, Ideology, faction};
+use crate::political::faction_membership::ideology_affinity;
+use crate::natural::climate_state;
+use crate::natural::seasonal_cycles::{ActivityType, seasonal_activity};
+use crate::tables::individual::{individual, Individual};
+use crate::tables::city::city;
+use crate::tables::events::{city_event, CityEvent, CityEventType};
+use crate::reducers::city::city_residents;
+use crate::types::BuildingType;
+use crate::narrative::{create_narrative_event, EventCategory};
+use crate::world::Season;
+use crate::world::game_world::game_world;
+
+const UPDATE_PERIOD_HOURS: u64 = 24;
+const SERVICE_PERIOD_HOURS: u64 = 168;     // One holy day a week
+const FESTIVAL_PERIOD_HOURS: u64 = 2160;   // At most one holy festival a season (90 days)
+const FOUNDING_DEVOTION: f32 = 0.5;        // Theocratic affinity that makes a founding believer
+const CONVERT_DEVOTION: f32 = 0.3;         // Devotion of the newly converted
+const PREACH_DEVOTION: f32 = 0.5;          // Believers this devout talk about their faith
+const CONVERSION_CHANCE: f32 = 0.05;       // Per conversation, scaled by devotion and receptiveness
+const LAPSE_DEVOTION: f32 = 0.05;          // Belief is abandoned below this
+const DEVOTION_DECAY_PER_DAY: f32 = 0.005; // Without worship
+const SERVICE_DEVOTION: f32 = 0.05;
+const FESTIVAL_DEVOTION: f32 = 0.1;
+const SERVICE_STRESS_RELIEF: f32 = 10.0;   // At full devotion
+const SERVICE_COMMUNITY: f32 = 15.0;       // At full devotion
+const DAILY_SOLACE: f32 = 1.0;             // Stress eased each day by faith alone, at full devotion
+const TITHE_RATE: f32 = 0.05;              // Of income above TITHE_FLOOR, at each service
+const TITHE_FLOOR: f32 = 20.0;
+const INFLUENCE_PULL: f32 = 0.1;           // Share of the gap to congregation size closed per day
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, SpacetimeType)]
+pub enum Doctrine {
+    Evangelical,   // Seeks converts
+    Ascetic,       // Solace through discipline, small tithes
+    Communal,      // Fellowship first
+    Charitable,    // Tithes go to the faithful poor
+    Mystical,      // Deep devotion, slow to spread
+    Martial,       // Stern and demanding
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, SpacetimeType)]
+pub enum WorshipKind {
+    Service,
+    HolyFestival,
+}
+
+// A temple imported from Eno data, the seat of a faith once one is founded there
+#[spacetimedb::table(name = holy_site)]
+pub struct HolySite {
+    #[primary_key]
+    pub id: u32,
+    pub city_id: u32,
+    pub building_id: u32,
+    pub name: String,
+    pub faith_id: Option<u32>,
+    pub sanctity: f32,         // 0-100, grows with festivals held there
+    pub consecrated_hour: u64,
+}
+
+#[spacetimedb::table(name = faith)]
+pub struct Faith {
+    #[primary_key]
+    pub id: u32,
+    pub world_id: u32,
+    pub name: String,
+    pub primary_doctrine: Doctrine,
+    pub secondary_doctrine: Doctrine,
+    pub holy_site_id: Option<u32>,
+    pub seat_city_id: u32,
+    pub faction_id: Option<u32>,  // The Religious faction that speaks for it
+    pub holy_season: Season,
+    pub adherents: u32,
+    pub treasury: f32,            // Tithes, when no faction collects them
+    pub founded_hour: u64,
+    pub last_update_hour: u64,
+    pub last_service_hour: u64,
+    pub last_festival_hour: u64,
+}
+
+// An individual's faith and how strongly they hold it
+#[spacetimedb::table(name = belief)]
+pub struct Belief {
+    #[primary_key]
+    pub id: u32,
+    pub individual_id: u32,
+    pub faith_id: u32,
+    pub devotion: f32,         // 0-1
+    pub since_hour: u64,
+    pub last_worship_hour: u64,
+    pub is_active: bool,
+}
+
+#[spacetimedb::table(name = worship_event)]
+pub struct WorshipEvent {
+    #[primary_key]
+    pub id: u32,
+    pub faith_id: u32,
+    pub city_id: u32,
+    pub hour: u64,
+    pub kind: WorshipKind,
+    pub attendees: u32,
+    pub tithes: f32,
+}
+
+// Found faiths, spread and tend belief, and hold worship for each faith in a world
+#[spacetimedb::reducer]
+pub fn update_religion(ctx: &ReducerContext, world_id: u32, hour: u64) -> Result<(), String> {
+    found_faiths(ctx, world_id, hour)?;
+
+    let faiths: Vec<Faith> = ctx.db.faith().iter()
+        .filter(|f| f.world_id == world_id && hour >= f.last_update_hour + UPDATE_PERIOD_HOURS)
+        .collect();
+    if faiths.is_empty() {
+        return Ok(());
+    }
+
+    let cities: Vec<u32> = ctx.db.climate_state().iter()
+        .filter(|c| c.world_id == world_id)
+        .map(|c| c.region_id)
+        .collect();
+    let season = ctx.db.game_world().id().find(&world_id).map(|w| w.season);
+
+    for city_id in &cities {
+        spread_faith(ctx, *city_id, hour);
+    }
+
+    for mut faith in faiths {
+        let days = ((hour - faith.last_update_hour) / 24).max(1) as f32;
+        tend_belief(ctx, &faith, days, hour);
+
+        if hour >= faith.last_service_hour + SERVICE_PERIOD_HOURS {
+            worship(ctx, &mut faith, &cities, WorshipKind::Service, hour)?;
+        }
+        if hour >= faith.last_festival_hour + FESTIVAL_PERIOD_HOURS && festival_due(ctx, &faith, season) {
+            worship(ctx, &mut faith, &cities, WorshipKind::HolyFestival, hour)?;
+        }
+
+        faith.adherents = believers(ctx, faith.id).len() as u32;
+        faith.last_update_hour = hour;
+        empower_faction(ctx, &faith, &cities);
+        ctx.db.faith().id().update(faith);
+    }
+    Ok(())
+}
+
+/// Record a temple from Eno data as a holy site, building it in the city
+pub(crate) fn consecrate_temple(ctx: &ReducerContext, city_id: u32, city_name: &str, x: f32, y: f32, hour: u64) -> Result<u32, String> {
+    use crate::reducers::building::insert_building;
+
+    let building_id = insert_building(ctx, format!("Temple of {}", city_name), city_id, BuildingType::CultureCenter, x, y)?;
+
+    let id = ctx.db.holy_site().iter().count() as u32 + 1;
+    ctx.db.holy_site().insert(HolySite {
+        id,
+        city_id,
+        building_id,
+        name: format!("Temple of {}", city_name),
+        faith_id: None,
+        sanctity: 50.0,
+        consecrated_hour: hour,
+    });
+    Ok(id)
+}
+
+/// How devout an individual is, 0 if they hold no faith
+pub(crate) fn devotion_of(ctx: &ReducerContext, individual_id: u32) -> f32 {
+    ctx.db.belief().iter()
+        .find(|b| b.individual_id == individual_id && b.is_active)
+        .map_or(0.0, |b| b.devotion)
+}
+
+pub(crate) fn believers(ctx: &ReducerContext, faith_id: u32) -> Vec<Belief> {
+    ctx.db.belief().iter()
+        .filter(|b| b.faith_id == faith_id && b.is_active)
+        .collect()
+}
+
+/// (proselytism, solace, fellowship, tithe) multipliers a doctrine brings
+fn doctrine_weights(doctrine: Doctrine) -> (f32, f32, f32, f32) {
+    match doctrine {
+        Doctrine::Evangelical => (2.0, 1.0, 1.0, 1.0),
+        Doctrine::Ascetic => (0.8, 1.5, 0.8, 0.5),
+        Doctrine::Communal => (1.0, 1.0, 1.5, 1.0),
+        Doctrine::Charitable => (1.2, 1.0, 1.2, 1.0),
+        Doctrine::Mystical => (0.5, 1.3, 1.0, 1.0),
+        Doctrine::Martial => (1.0, 0.8, 1.0, 1.5),
+    }
+}
+
+/// A faith's weights: the primary doctrine in full, the secondary at half strength
+fn faith_weights(faith: &Faith) -> (f32, f32, f32, f32) {
+    let (p1, s1, f1, t1) = doctrine_weights(faith.primary_doctrine);
+    let (p2, s2, f2, t2) = doctrine_weights(faith.secondary_doctrine);
+    let blend = |a: f32, b: f32| a * (1.0 + (b - 1.0) * 0.5);
+    (blend(p1, p2), blend(s1, s2), blend(f1, f2), blend(t1, t2))
+}
+
+/// Found a faith at every unclaimed holy site, and one for every Religious faction without one
+fn found_faiths(ctx: &ReducerContext, world_id: u32, hour: u64) -> Result<(), String> {
+    let world_cities: Vec<u32> = ctx.db.climate_state().iter()
+        .filter(|c| c.world_id == world_id)
+        .map(|c| c.region_id)
+        .collect();
+
+    let unclaimed: Vec<HolySite> = ctx.db.holy_site().iter()
+        .filter(|s| s.faith_id.is_none() && world_cities.contains(&s.city_id))
+        .collect();
+    for site in unclaimed {
+        let faction = ctx.db.faction().iter()
+            .find(|f| f.world_id == world_id && f.is_active && f.faction_type == FactionType::Religious
+                && f.base_city_id == site.city_id && faith_of_faction(ctx, f.id).is_none());
+        found_faith(ctx, world_id, site.city_id, Some(site), faction, hour)?;
+    }
+
+    let faithless: Vec<Faction> = ctx.db.faction().iter()
+        .filter(|f| f.world_id == world_id && f.is_active && f.faction_type == FactionType::Religious)
+        .filter(|f| faith_of_faction(ctx, f.id).is_none())
+        .collect();
+    for faction in faithless {
+        found_faith(ctx, world_id, faction.base_city_id, None, Some(faction), hour)?;
+    }
+    Ok(())
+}
+
+pub(crate) fn faith_of_faction(ctx: &ReducerContext, faction_id: u32) -> Option<Faith> {
+    ctx.db.faith().iter().find(|f| f.faction_id == Some(faction_id))
+}
+
+fn found_faith(
+    ctx: &ReducerContext,
+    world_id: u32,
+    city_id: u32,
+    site: Option<HolySite>,
+    faction: Option<Faction>,
+    hour: u64,
+) -> Result<u32, String> {
+    let mut rng = rand::thread_rng();
+    let doctrines = [Doctrine::Evangelical, Doctrine::Ascetic, Doctrine::Communal,
+        Doctrine::Charitable, Doctrine::Mystical, Doctrine::Martial];
+    let chosen: Vec<Doctrine> = doctrines.choose_multiple(&mut rng, 2).copied().collect();
+    let holy_season = *[Season::Spring, Season::Summer, Season::Autumn, Season::Winter].choose(&mut rng).expect("seasons");
+
+    let city_name = ctx.db.city().id().find(&city_id).map_or_else(|| "the City".to_string(), |c| c.name);
+    let name = match chosen[0] {
+        Doctrine::Evangelical => format!("Heralds of {}", city_name),
+        Doctrine::Ascetic => format!("The Austere Path of {}", city_name),
+        Doctrine::Communal => format!("The Fellowship of {}", city_name),
+        Doctrine::Charitable => format!("The Open Hand of {}", city_name),
+        Doctrine::Mystical => format!("Mysteries of {}", city_name),
+        Doctrine::Martial => format!("The Sworn of {}", city_name),
+    };
+
+    let id = ctx.db.faith().iter().count() as u32 + 1;
+    ctx.db.faith().insert(Faith {
+        id,
+        world_id,
+        name: name.clone(),
+        primary_doctrine: chosen[0],
+        secondary_doctrine: chosen[1],
+        holy_site_id: site.as_ref().map(|s| s.id),
+        seat_city_id: city_id,
+        faction_id: faction.as_ref().map(|f| f.id),
+        holy_season,
+        adherents: 0,
+        treasury: 0.0,
+        founded_hour: hour,
+        last_update_hour: hour,
+        last_service_hour: hour,
+        last_festival_hour: 0,
+    });
+    if let Some(mut site) = site {
+        site.faith_id = Some(id);
+        ctx.db.holy_site().id().update(site);
+    }
+
+    // Those already inclined to faith become its first believers
+    let mut founders = 0;
+    for resident in city_residents(ctx, city_id) {
+        if resident.health <= 0.0 || devotion_of(ctx, resident.id) > 0.0 {
+            continue;
+        }
+        let affinity = ideology_affinity(ctx, &resident, Ideology::Theocratic);
+        if affinity >= FOUNDING_DEVOTION {
+            believe(ctx, resident.id, id, affinity, hour);
+            founders += 1;
+        }
+    }
+
+    log::info!("Faith '{}' ({:?}/{:?}) founded in city {} with {} believers", name, chosen[0], chosen[1], city_id, founders);
+    create_narrative_event(ctx, world_id, 1, EventCategory::Cultural,
+        format!("{} is founded", name),
+        format!("A {:?} faith with {} believers takes root in {}{}", chosen[0], founders, city_name,
+            faction.map_or(String::new(), |f| format!(", led by {}", f.name))),
+        4)?;
+    Ok(id)
+}
+
+/// Give someone a faith, replacing any they held
+fn believe(ctx: &ReducerContext, individual_id: u32, faith_id: u32, devotion: f32, hour: u64) {
+    let held: Vec<Belief> = ctx.db.belief().iter()
+        .filter(|b| b.individual_id == individual_id && b.is_active)
+        .collect();
+    for mut old in held {
+        old.is_active = false;
+        ctx.db.belief().id().update(old);
+    }
+    let id = ctx.db.belief().iter().count() as u32 + 1;
+    ctx.db.belief().insert(Belief {
+        id,
+        individual_id,
+        faith_id,
+        devotion: devotion.clamp(0.0, 1.0),
+        since_hour: hour,
+        last_worship_hour: hour,
+        is_active: true,
+    });
+}
+
+/// Devout believers talk about their faith with whoever shares a building with them
+fn spread_faith(ctx: &ReducerContext, city_id: u32, hour: u64) {
+    let mut rng = rand::thread_rng();
+    let mut by_location: HashMap<u32, Vec<Individual>> = HashMap::new();
+    for resident in city_residents(ctx, city_id).into_iter().filter(|i| i.health > 0.0) {
+        by_location.entry(resident.current_location_id).or_default().push(resident);
+    }
+
+    let beliefs: HashMap<u32, Belief> = ctx.db.belief().iter()
+        .filter(|b| b.is_active)
+        .map(|b| (b.individual_id, b))
+        .collect();
+    let mut converts: HashMap<u32, u32> = HashMap::new();
+
+    for people in by_location.values().filter(|p| p.len() > 1) {
+        for preacher in people {
+            let Some(belief) = beliefs.get(&preacher.id).filter(|b| b.devotion >= PREACH_DEVOTION) else { continue; };
+            let Some(faith) = ctx.db.faith().id().find(&belief.faith_id) else { continue; };
+            let Some(listener) = people.iter().filter(|p| p.id != preacher.id).collect::<Vec<_>>().choose(&mut rng).copied() else { continue; };
+
+            let held = beliefs.get(&listener.id);
+            if held.map_or(false, |b| b.faith_id == faith.id) {
+                continue;
+            }
+            let (proselytism, _, _, _) = faith_weights(&faith);
+            let receptiveness = ideology_affinity(ctx, listener, Ideology::Theocratic);
+            // Someone else's believer is harder to sway than someone with no faith
+            let resistance = held.map_or(1.0, |b| (1.0 - b.devotion) * 0.5);
+            let chance = CONVERSION_CHANCE * belief.devotion * proselytism * (0.5 + receptiveness) * resistance;
+            if rng.gen::<f32>() < chance {
+                believe(ctx, listener.id, faith.id, CONVERT_DEVOTION, hour);
+                *converts.entry(faith.id).or_default() += 1;
+            }
+        }
+    }
+
+    for (faith_id, count) in converts {
+        log::debug!("Faith {} gained {} converts in city {}", faith_id, count, city_id);
+    }
+}
+
+/// Devotion fades without worship; faith eases the stress of the devout
+fn tend_belief(ctx: &ReducerContext, faith: &Faith, days: f32, hour: u64) {
+    let (_, solace, _, _) = faith_weights(faith);
+    for mut belief in believers(ctx, faith.id) {
+        let Some(mut believer) = ctx.db.individual().id().find(&belief.individual_id) else { continue; };
+        if believer.health <= 0.0 {
+            belief.is_active = false;
+            ctx.db.belief().id().update(belief);
+            continue;
+        }
+
+        if hour > belief.last_worship_hour + SERVICE_PERIOD_HOURS {
+            belief.devotion -= DEVOTION_DECAY_PER_DAY * days;
+        }
+        believer.stress = (believer.stress - DAILY_SOLACE * solace * belief.devotion * days).max(0.0);
+        ctx.db.individual().id().update(believer);
+
+        if belief.devotion < LAPSE_DEVOTION {
+            belief.is_active = false;
+        }
+        ctx.db.belief().id().update(belief);
+    }
+}
+
+/// The faith's holy season has come, or its seat's calendar calls for worship
+fn festival_due(ctx: &ReducerContext, faith: &Faith, season: Option<Season>) -> bool {
+    season == Some(faith.holy_season)
+        || ctx.db.seasonal_activity().iter().any(|a| a.world_id == faith.world_id && a.region_id == faith.seat_city_id
+            && a.activity_type == ActivityType::Worship && a.is_active)
+}
+
+/// Gather the faithful of each city: devotion and community rise, stress falls, tithes are taken
+fn worship(ctx: &ReducerContext, faith: &mut Faith, cities: &[u32], kind: WorshipKind, hour: u64) -> Result<(), String> {
+    let (_, solace, fellowship, tithe_weight) = faith_weights(faith);
+    let charitable = faith.primary_doctrine == Doctrine::Charitable || faith.secondary_doctrine == Doctrine::Charitable;
+    let (devotion_gain, intensity) = match kind {
+        WorshipKind::Service => (SERVICE_DEVOTION, 1.0),
+        WorshipKind::HolyFestival => (FESTIVAL_DEVOTION, 2.0),
+    };
+
+    let mut faithful: HashMap<u32, Belief> = believers(ctx, faith.id).into_iter()
+        .map(|b| (b.individual_id, b))
+        .collect();
+    let mut by_city: Vec<(u32, Vec<(Belief, Individual)>)> = Vec::new();
+    for city_id in cities {
+        let congregation: Vec<(Belief, Individual)> = city_residents(ctx, *city_id).into_iter()
+            .filter_map(|r| faithful.remove(&r.id).map(|b| (b, r)))
+            .collect();
+        by_city.push((*city_id, congregation));
+    }
+
+    let mut total_tithes = 0.0;
+    for (city_id, congregation) in by_city {
+        let mut attendees = 0;
+        let mut tithes = 0.0;
+        let mut poor: Vec<u32> = Vec::new();
+        for (mut belief, mut believer) in congregation {
+            // The devout attend; the lukewarm only for the great festivals
+            if rand::thread_rng().gen::<f32>() > belief.devotion * intensity {
+                continue;
+            }
+            attendees += 1;
+            believer.stress = (believer.stress - SERVICE_STRESS_RELIEF * solace * belief.devotion * intensity).max(0.0);
+            believer.community = (believer.community + SERVICE_COMMUNITY * fellowship * belief.devotion * intensity).min(100.0);
+            if believer.income > TITHE_FLOOR {
+                let tithe = (believer.income - TITHE_FLOOR) * TITHE_RATE * tithe_weight;
+                believer.income -= tithe;
+                tithes += tithe;
+            } else {
+                poor.push(believer.id);
+            }
+            belief.devotion = (belief.devotion + devotion_gain).min(1.0);
+            belief.last_worship_hour = hour;
+            ctx.db.individual().id().update(believer);
+            ctx.db.belief().id().update(belief);
+        }
+        if attendees == 0 {
+            continue;
+        }
+
+        // Charitable faiths give half the collection to the faithful poor
+        if charitable && !poor.is_empty() {
+            let alms = tithes * 0.5 / poor.len() as f32;
+            for id in &poor {
+                if let Some(mut recipient) = ctx.db.individual().id().find(id) {
+                    recipient.income += alms;
+                    ctx.db.individual().id().update(recipient);
+                }
+            }
+            tithes *= 0.5;
+        }
+        total_tithes += tithes;
+
+        let id = ctx.db.worship_event().iter().count() as u32 + 1;
+        ctx.db.worship_event().insert(WorshipEvent {
+            id,
+            faith_id: faith.id,
+            city_id,
+            hour,
+            kind,
+            attendees,
+            tithes,
+        });
+
+        if kind == WorshipKind::HolyFestival {
+            if let Some(mut city) = ctx.db.city().id().find(&city_id) {
+                city.culture = (city.culture + 2.0).min(100.0);
+                city.stability = (city.stability + 1.0).min(100.0);
+                let description = format!("The faithful of {} celebrate their holy festival in {}", faith.name, city.name);
+                ctx.db.city().id().update(city);
+                let event_id = ctx.db.city_event().iter().count() as u32 + 1;
+                ctx.db.city_event().insert(CityEvent {
+                    id: event_id,
+                    city_id,
+                    hour,
+                    event_type: CityEventType::Festival,
+                    description,
+                    participants: attendees,
+                    impact_stability: 1.0,
+                    impact_culture: 2.0,
+                });
+            }
+        }
+    }
+
+    match faith.faction_id.and_then(|id| ctx.db.faction().id().find(&id)) {
+        Some(mut faction) => {
+            faction.treasury += total_tithes;
+            ctx.db.faction().id().update(faction);
+        },
+        None => faith.treasury += total_tithes,
+    }
+
+    match kind {
+        WorshipKind::Service => faith.last_service_hour = hour,
+        WorshipKind::HolyFestival => {
+            faith.last_festival_hour = hour;
+            if let Some(mut site) = faith.holy_site_id.and_then(|id| ctx.db.holy_site().id().find(&id)) {
+                site.sanctity = (site.sanctity + 5.0).min(100.0);
+                ctx.db.holy_site().id().update(site);
+            }
+            create_narrative_event(ctx, faith.world_id, 1, EventCategory::Cultural,
+                format!("Holy festival of {}", faith.name),
+                format!("The faithful gather for the {:?} festival, bringing {:.0} in offerings", faith.holy_season, total_tithes),
+                2)?;
+        },
+    }
+    Ok(())
+}
+
+/// A Religious faction's influence follows the size of its congregation; its public support comes
+/// from the faction poll, which counts the faithful as supporters
+fn empower_faction(ctx: &ReducerContext, faith: &Faith, cities: &[u32]) {
+    let Some(mut faction) = faith.faction_id.and_then(|id| ctx.db.faction().id().find(&id)) else { return; };
+    let population: u32 = cities.iter()
+        .filter_map(|id| ctx.db.city().id().find(id))
+        .map(|c| c.population)
+        .sum();
+    if population == 0 {
+        return;
+    }
+
+    let share = faith.adherents as f32 / population as f32;
+    faction.influence += (share * 100.0 - faction.influence) * INFLUENCE_PULL;
+    faction.influence = faction.influence.clamp(0.0, 100.0);
+    ctx.db.faction().id().update(faction);
+}
+
+/// Whether an individual holds the faith a faction speaks for
+pub(crate) fn keeps_faith_of(ctx: &ReducerContext, individual_id: u32, faction_id: u32) -> bool {
+    ctx.db.belief().iter()
+        .filter(|b| b.individual_id == individual_id && b.is_active)
+        .any(|b| ctx.db.faith().id().find(&b.faith_id).map_or(false, |f| f.faction_id == Some(faction_id)))
+}
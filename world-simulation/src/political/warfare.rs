@@ -0,0 +1,813 @@
+// Warfare: factions levy armies from their members, march them along trade roads, besiege and occupy cities, and make peace
+
+use spacetimedb::{ReducerContext, Table, SpacetimeType};
+use serde::{Serialize, Deserialize};
+use log;
+use rand::Rng;
+use rand::seq::SliceRandom;
+use std::collections::{HashMap, HashSet, VecDeque};
+use crate::political::{Faction, FactionType, PoliticalEventType, create_political_event, faction};
+use crate::political::faction_relationships::{relationship_row, war_declared, call_allies, on_peace};
+use crate::political::faction_membership::active_members;
+use crate::economics::trade_route;
+use crate::economics::trade_routes::travel_hours;
+use crate::natural::weather::{MicroclimateType, microclimate};
+use crate::tables::individual::individual;
+use crate::tables::city::city;
+use crate::reducers::public_works::wall_safety_bonus;
+use crate::narrative::{create_narrative_event, EventCategory};
+use crate::world::game_world::game_world;
+
+const LEVY_SHARE: f32 = 0.5;               // Share of members called up when a faction raises an army
+const RECRUIT_COST: f32 = 50.0;            // Treasury paid per soldier raised
+const UPKEEP_PER_SOLDIER_DAY: f32 = 2.0;
+const MIN_RECRUIT_HEALTH: f32 = 50.0;
+const MARCH_PACE: f32 = 1.5;               // Armies take this much longer than merchants between cities
+const SUPPLY_RECOVERY_PER_DAY: f32 = 10.0;
+const SUPPLY_LOSS_PER_DAY: f32 = 20.0;     // When the treasury cannot pay upkeep
+const FORAGING_LOSS_PER_DAY: f32 = 5.0;    // Besiegers far from home
+const MORALE_RECOVERY_PER_DAY: f32 = 5.0;  // In garrison
+const ROUT_MORALE: f32 = 20.0;
+const SIEGE_PROGRESS_PER_DAY: f32 = 10.0;  // At equal strength to the city's defence
+const DEFENCE_PER_SAFETY_POINT: f32 = 0.5; // Soldier-equivalents a city's safety is worth
+const MILITARY_DRILL: f32 = 1.2;           // Strength bonus for Military factions
+const KILLED_SHARE: f32 = 0.35;            // Of casualties; the rest are wounded
+const WOUND_DAMAGE: f32 = 40.0;
+const OCCUPATION_STABILITY_LOSS: f32 = 20.0;
+const EXHAUSTION_PEACE: f32 = 0.5;         // Casualty share that brings a side to the table
+const MAX_WAR_HOURS: u64 = 2160;           // 90 days before a negotiated peace
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, SpacetimeType)]
+pub enum ArmyStatus {
+    Garrisoned,
+    Marching,
+    Besieging,
+    Routed,
+    Disbanded,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, SpacetimeType)]
+pub enum SoldierStatus {
+    Serving,
+    Wounded,
+    Killed,
+    Discharged,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, SpacetimeType)]
+pub enum WarOutcome {
+    Ongoing,
+    AttackerVictory,
+    DefenderVictory,
+    Negotiated,
+}
+
+// A war between two factions
+#[spacetimedb::table(name = war)]
+pub struct War {
+    #[primary_key]
+    pub id: u32,
+    pub world_id: u32,
+    pub attacker_id: u32,
+    pub defender_id: u32,
+    pub declared_hour: u64,
+    pub ended_hour: Option<u64>,
+    pub outcome: WarOutcome,
+    pub attacker_casualties: u32,
+    pub defender_casualties: u32,
+}
+
+// A faction's field army or garrison
+#[spacetimedb::table(name = army)]
+pub struct Army {
+    #[primary_key]
+    pub id: u32,
+    pub world_id: u32,
+    pub faction_id: u32,
+    pub war_id: u32,
+    pub name: String,
+    pub location_city_id: u32,
+    pub destination_city_id: Option<u32>,  // Next city on the march
+    pub arrival_hour: u64,
+    pub status: ArmyStatus,
+    pub morale: f32,           // 0-100
+    pub supply: f32,           // 0-100
+    pub siege_progress: f32,   // 0-100 against the city it is besieging
+    pub raised_hour: u64,
+    pub last_update_hour: u64,
+}
+
+// An individual serving in an army
+#[spacetimedb::table(name = soldier)]
+pub struct Soldier {
+    #[primary_key]
+    pub id: u32,
+    pub army_id: u32,
+    pub individual_id: u32,
+    pub enlisted_hour: u64,
+    pub status: SoldierStatus,
+    pub status_hour: u64,
+}
+
+// A fought battle and its toll
+#[spacetimedb::table(name = battle)]
+pub struct Battle {
+    #[primary_key]
+    pub id: u32,
+    pub war_id: u32,
+    pub city_id: u32,
+    pub hour: u64,
+    pub attacker_army_id: u32,
+    pub defender_army_id: u32,
+    pub attacker_strength: f32,
+    pub defender_strength: f32,
+    pub attacker_killed: u32,
+    pub attacker_wounded: u32,
+    pub defender_killed: u32,
+    pub defender_wounded: u32,
+    pub victor_faction_id: u32,
+}
+
+// A city held by a faction's army
+#[spacetimedb::table(name = occupation)]
+pub struct Occupation {
+    #[primary_key]
+    pub id: u32,
+    pub world_id: u32,
+    pub war_id: u32,
+    pub city_id: u32,
+    pub occupier_faction_id: u32,
+    pub started_hour: u64,
+    pub ended_hour: Option<u64>,
+}
+
+// Declare war on another faction
+#[spacetimedb::reducer]
+pub fn declare_war(ctx: &ReducerContext, attacker_id: u32, defender_id: u32) -> Result<(), String> {
+    let attacker = ctx.db.faction().id().find(&attacker_id)
+        .ok_or("Attacking faction not found")?;
+    let hour = ctx.db.game_world().id().find(&attacker.world_id)
+        .ok_or("World not found")?
+        .total_hours;
+
+    start_war(ctx, attacker.world_id, attacker_id, defender_id, hour).map(|_| ())
+}
+
+// Levy an army of up to `size` members for a faction at war
+#[spacetimedb::reducer]
+pub fn raise_army(ctx: &ReducerContext, faction_id: u32, size: u32) -> Result<(), String> {
+    let mut faction = ctx.db.faction().id().find(&faction_id)
+        .ok_or("Faction not found")?;
+    let hour = ctx.db.game_world().id().find(&faction.world_id)
+        .ok_or("World not found")?
+        .total_hours;
+    let war = active_war_of(ctx, faction_id)
+        .ok_or_else(|| format!("{} is not at war", faction.name))?;
+
+    let raised = levy(ctx, &mut faction, war.id, size, hour);
+    ctx.db.faction().id().update(faction);
+    if raised == 0 {
+        return Err("No members could be raised".to_string());
+    }
+    Ok(())
+}
+
+// March, supply, besiege, fight and make peace for every war in the world
+#[spacetimedb::reducer]
+pub fn update_warfare(ctx: &ReducerContext, world_id: u32, hour: u64) -> Result<(), String> {
+    let wars: Vec<War> = ctx.db.war().iter()
+        .filter(|w| w.world_id == world_id && w.outcome == WarOutcome::Ongoing)
+        .collect();
+
+    for war in wars {
+        // Each side keeps an army in the field while it can pay for one
+        for faction_id in [war.attacker_id, war.defender_id] {
+            if field_armies(ctx, war.id, faction_id).is_empty() {
+                if let Some(mut faction) = ctx.db.faction().id().find(&faction_id) {
+                    let size = (active_members(ctx, faction_id).len() as f32 * LEVY_SHARE).ceil() as u32;
+                    levy(ctx, &mut faction, war.id, size, hour);
+                    ctx.db.faction().id().update(faction);
+                }
+            }
+        }
+
+        let armies: Vec<Army> = ctx.db.army().iter()
+            .filter(|a| a.war_id == war.id && a.status != ArmyStatus::Disbanded)
+            .collect();
+        for army in armies {
+            // Earlier battles this tick may have routed or destroyed it; one battle per army per tick
+            let fought = ctx.db.battle().iter()
+                .any(|b| b.hour == hour && (b.attacker_army_id == army.id || b.defender_army_id == army.id));
+            if let Some(army) = ctx.db.army().id().find(&army.id) {
+                if army.status != ArmyStatus::Disbanded && !fought {
+                    campaign(ctx, &war, army, hour)?;
+                }
+            }
+        }
+
+        if let Some(war) = ctx.db.war().id().find(&war.id) {
+            check_for_peace(ctx, war, hour)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Declare war between two factions, calling in the defender's allies; returns the war's id
+pub(crate) fn start_war(ctx: &ReducerContext, world_id: u32, attacker_id: u32, defender_id: u32, hour: u64) -> Result<u32, String> {
+    if let Some(existing) = ongoing_war(ctx, attacker_id, defender_id) {
+        return Ok(existing.id);
+    }
+    let id = open_war(ctx, world_id, attacker_id, defender_id, hour)?;
+    call_allies(ctx, world_id, attacker_id, defender_id, hour)?;
+    Ok(id)
+}
+
+/// Open a war between two factions without summoning anyone else; returns the war's id
+pub(crate) fn open_war(ctx: &ReducerContext, world_id: u32, attacker_id: u32, defender_id: u32, hour: u64) -> Result<u32, String> {
+    if attacker_id == defender_id {
+        return Err("A faction cannot make war on itself".to_string());
+    }
+    let attacker = ctx.db.faction().id().find(&attacker_id).ok_or("Attacking faction not found")?;
+    let defender = ctx.db.faction().id().find(&defender_id).ok_or("Defending faction not found")?;
+    if let Some(existing) = ongoing_war(ctx, attacker_id, defender_id) {
+        return Ok(existing.id);
+    }
+
+    let id = ctx.db.war().iter().count() as u32 + 1;
+    ctx.db.war().insert(War {
+        id,
+        world_id,
+        attacker_id,
+        defender_id,
+        declared_hour: hour,
+        ended_hour: None,
+        outcome: WarOutcome::Ongoing,
+        attacker_casualties: 0,
+        defender_casualties: 0,
+    });
+
+    relationship_row(ctx, &attacker, &defender, hour);
+    war_declared(ctx, world_id, attacker_id, defender_id, hour)?;
+
+    log::info!("{} declared war on {}", attacker.name, defender.name);
+    create_narrative_event(ctx, world_id, 1, EventCategory::Military,
+        format!("{} marches on {}", attacker.name, defender.name),
+        format!("{} has declared war on {}; both are calling their members to arms", attacker.name, defender.name),
+        5)?;
+    Ok(id)
+}
+
+/// Whether the attacker is ahead in the war between two factions
+pub(crate) fn attacker_ahead(ctx: &ReducerContext, attacker_id: u32, defender_id: u32) -> bool {
+    let war = ctx.db.war().iter()
+        .filter(|w| w.attacker_id == attacker_id && w.defender_id == defender_id)
+        .max_by_key(|w| w.id);
+    match war {
+        Some(w) if w.outcome == WarOutcome::AttackerVictory => true,
+        Some(w) if w.outcome == WarOutcome::Ongoing => war_score(ctx, &w) > 0.0,
+        _ => false,
+    }
+}
+
+pub(crate) fn active_war_of(ctx: &ReducerContext, faction_id: u32) -> Option<War> {
+    ctx.db.war().iter()
+        .find(|w| w.outcome == WarOutcome::Ongoing && (w.attacker_id == faction_id || w.defender_id == faction_id))
+}
+
+fn ongoing_war(ctx: &ReducerContext, a: u32, b: u32) -> Option<War> {
+    ctx.db.war().iter()
+        .find(|w| w.outcome == WarOutcome::Ongoing
+            && ((w.attacker_id == a && w.defender_id == b) || (w.attacker_id == b && w.defender_id == a)))
+}
+
+pub(crate) fn field_armies(ctx: &ReducerContext, war_id: u32, faction_id: u32) -> Vec<Army> {
+    ctx.db.army().iter()
+        .filter(|a| a.war_id == war_id && a.faction_id == faction_id)
+        .filter(|a| !matches!(a.status, ArmyStatus::Disbanded | ArmyStatus::Routed))
+        .collect()
+}
+
+fn serving(ctx: &ReducerContext, army_id: u32) -> Vec<Soldier> {
+    ctx.db.soldier().iter()
+        .filter(|s| s.army_id == army_id && s.status == SoldierStatus::Serving)
+        .collect()
+}
+
+/// Call up to `size` healthy members not already under arms, as far as the treasury stretches; returns how many
+fn levy(ctx: &ReducerContext, faction: &mut Faction, war_id: u32, size: u32, hour: u64) -> u32 {
+    let affordable = (faction.treasury / RECRUIT_COST) as usize;
+    let under_arms: HashSet<u32> = ctx.db.soldier().iter()
+        .filter(|s| s.status == SoldierStatus::Serving)
+        .map(|s| s.individual_id)
+        .collect();
+    let recruits: Vec<u32> = active_members(ctx, faction.id).into_iter()
+        .filter(|m| !under_arms.contains(&m.individual_id))
+        .filter(|m| ctx.db.individual().id().find(&m.individual_id)
+            .map_or(false, |i| i.age >= 18 && i.health >= MIN_RECRUIT_HEALTH))
+        .map(|m| m.individual_id)
+        .take((size as usize).min(affordable))
+        .collect();
+    if recruits.is_empty() {
+        return 0;
+    }
+
+    let army_id = ctx.db.army().iter().count() as u32 + 1;
+    let number = ctx.db.army().iter().filter(|a| a.faction_id == faction.id).count() + 1;
+    ctx.db.army().insert(Army {
+        id: army_id,
+        world_id: faction.world_id,
+        faction_id: faction.id,
+        war_id,
+        name: format!("{} {}", faction.name, ordinal(number)),
+        location_city_id: faction.base_city_id,
+        destination_city_id: None,
+        arrival_hour: hour,
+        status: ArmyStatus::Garrisoned,
+        morale: (50.0 + faction.stability / 2.0).min(100.0),
+        supply: 100.0,
+        siege_progress: 0.0,
+        raised_hour: hour,
+        last_update_hour: hour,
+    });
+
+    for individual_id in &recruits {
+        enlist(ctx, army_id, *individual_id, hour);
+    }
+    faction.treasury -= recruits.len() as f32 * RECRUIT_COST;
+
+    log::info!("{} raised {} soldiers (army {})", faction.name, recruits.len(), army_id);
+    recruits.len() as u32
+}
+
+/// Put an individual under arms in an army
+pub(crate) fn enlist(ctx: &ReducerContext, army_id: u32, individual_id: u32, hour: u64) {
+    let id = ctx.db.soldier().iter().count() as u32 + 1;
+    ctx.db.soldier().insert(Soldier {
+        id,
+        army_id,
+        individual_id,
+        enlisted_hour: hour,
+        status: SoldierStatus::Serving,
+        status_hour: hour,
+    });
+}
+
+fn ordinal(n: usize) -> String {
+    let suffix = match (n % 10, n % 100) {
+        (1, 11) | (2, 12) | (3, 13) => "th",
+        (1, _) => "st",
+        (2, _) => "nd",
+        (3, _) => "rd",
+        _ => "th",
+    };
+    format!("{}{} Host", n, suffix)
+}
+
+/// One army's turn: upkeep, then marching, fighting or besieging toward its objective
+fn campaign(ctx: &ReducerContext, war: &War, mut army: Army, hour: u64) -> Result<(), String> {
+    let days = hour.saturating_sub(army.last_update_hour) as f32 / 24.0;
+    army.last_update_hour = hour;
+
+    let soldiers = serving(ctx, army.id);
+    if soldiers.is_empty() {
+        army.status = ArmyStatus::Disbanded;
+        ctx.db.army().id().update(army);
+        return Ok(());
+    }
+
+    // Upkeep comes out of the treasury; unpaid armies go hungry
+    if let Some(mut faction) = ctx.db.faction().id().find(&army.faction_id) {
+        let upkeep = soldiers.len() as f32 * UPKEEP_PER_SOLDIER_DAY * days;
+        if faction.treasury >= upkeep {
+            faction.treasury -= upkeep;
+            army.supply = (army.supply + SUPPLY_RECOVERY_PER_DAY * days).min(100.0);
+        } else {
+            faction.treasury = 0.0;
+            army.supply = (army.supply - SUPPLY_LOSS_PER_DAY * days).max(0.0);
+            army.morale = (army.morale - SUPPLY_LOSS_PER_DAY / 2.0 * days).max(0.0);
+        }
+        ctx.db.faction().id().update(faction);
+    }
+
+    let enemy_id = if army.faction_id == war.attacker_id { war.defender_id } else { war.attacker_id };
+    let home = ctx.db.faction().id().find(&army.faction_id).map_or(army.location_city_id, |f| f.base_city_id);
+
+    // Arrive at the next city on the road
+    if army.status == ArmyStatus::Marching || army.status == ArmyStatus::Routed {
+        if hour < army.arrival_hour {
+            ctx.db.army().id().update(army);
+            return Ok(());
+        }
+        if let Some(next) = army.destination_city_id.take() {
+            army.location_city_id = next;
+        }
+        if army.status == ArmyStatus::Routed {
+            if army.location_city_id == home {
+                army.status = ArmyStatus::Garrisoned;
+            } else {
+                march_toward(ctx, &mut army, home, hour);
+            }
+            ctx.db.army().id().update(army);
+            return Ok(());
+        }
+        army.status = ArmyStatus::Garrisoned;
+    }
+
+    let objective = objective(ctx, war, &army, enemy_id, home);
+    if army.location_city_id != objective {
+        army.siege_progress = 0.0;
+        march_toward(ctx, &mut army, objective, hour);
+        ctx.db.army().id().update(army);
+        return Ok(());
+    }
+
+    // An enemy army in the same city means battle
+    let defenders = ctx.db.army().iter()
+        .find(|a| a.war_id == war.id && a.faction_id == enemy_id && a.location_city_id == army.location_city_id
+            && matches!(a.status, ArmyStatus::Garrisoned | ArmyStatus::Besieging));
+    if let Some(defenders) = defenders {
+        return fight(ctx, war, army, defenders, hour);
+    }
+
+    let held = occupier_of(ctx, army.location_city_id);
+    if held == Some(army.faction_id) || (army.location_city_id == home && held.is_none()) {
+        // Holding what it came for
+        army.status = ArmyStatus::Garrisoned;
+        army.morale = (army.morale + MORALE_RECOVERY_PER_DAY * days).min(100.0);
+        ctx.db.army().id().update(army);
+        return Ok(());
+    }
+
+    // No one to fight: lay siege until the city falls
+    if army.status != ArmyStatus::Besieging {
+        army.status = ArmyStatus::Besieging;
+        army.siege_progress = 0.0;
+        announce(ctx, war.world_id, &army, army.location_city_id, "siege", 4)?;
+    }
+    let defence = city_defence(ctx, army.location_city_id);
+    army.supply = (army.supply - FORAGING_LOSS_PER_DAY * days).max(0.0);
+    army.siege_progress += SIEGE_PROGRESS_PER_DAY * days * army_strength(ctx, &army) / defence.max(1.0);
+    if army.siege_progress >= 100.0 {
+        army.siege_progress = 0.0;
+        army.status = ArmyStatus::Garrisoned;
+        occupy(ctx, war, &army, army.location_city_id, hour)?;
+    }
+    ctx.db.army().id().update(army);
+    Ok(())
+}
+
+/// Attackers go for the enemy's home city; defenders retake their own cities, then stand at home
+fn objective(ctx: &ReducerContext, war: &War, army: &Army, enemy_id: u32, home: u32) -> u32 {
+    if army.faction_id == war.attacker_id {
+        let target = ctx.db.faction().id().find(&enemy_id).map_or(home, |f| f.base_city_id);
+        return target;
+    }
+    ctx.db.occupation().iter()
+        .filter(|o| o.war_id == war.id && o.ended_hour.is_none() && o.occupier_faction_id == enemy_id)
+        .map(|o| o.city_id)
+        .min()
+        .unwrap_or(home)
+}
+
+/// Set off toward `target` by the next city on the trade road network, or overland if no road leads there
+fn march_toward(ctx: &ReducerContext, army: &mut Army, target: u32, hour: u64) {
+    let next = next_hop(ctx, army.world_id, army.location_city_id, target).unwrap_or(target);
+    let hours = (travel_hours(ctx, army.location_city_id, next) as f32 * MARCH_PACE) as u64;
+    army.destination_city_id = Some(next);
+    army.arrival_hour = hour + hours.max(1);
+    if army.status != ArmyStatus::Routed {
+        army.status = ArmyStatus::Marching;
+    }
+}
+
+/// First city on the shortest route from `from` to `to` over active trade routes
+fn next_hop(ctx: &ReducerContext, world_id: u32, from: u32, to: u32) -> Option<u32> {
+    let mut roads: HashMap<u32, Vec<u32>> = HashMap::new();
+    for route in ctx.db.trade_route().iter().filter(|r| r.world_id == world_id && r.is_active) {
+        roads.entry(route.from_city_id).or_default().push(route.to_city_id);
+        roads.entry(route.to_city_id).or_default().push(route.from_city_id);
+    }
+
+    let mut came_from: HashMap<u32, u32> = HashMap::new();
+    let mut queue = VecDeque::from([from]);
+    while let Some(city_id) = queue.pop_front() {
+        if city_id == to {
+            let mut step = to;
+            while let Some(&previous) = came_from.get(&step) {
+                if previous == from {
+                    return Some(step);
+                }
+                step = previous;
+            }
+            return None;
+        }
+        for &neighbour in roads.get(&city_id).into_iter().flatten() {
+            if neighbour != from && !came_from.contains_key(&neighbour) {
+                came_from.insert(neighbour, city_id);
+                queue.push_back(neighbour);
+            }
+        }
+    }
+    None
+}
+
+/// Fighting power from numbers, health, morale and supply
+fn army_strength(ctx: &ReducerContext, army: &Army) -> f32 {
+    let fitness: f32 = serving(ctx, army.id).iter()
+        .filter_map(|s| ctx.db.individual().id().find(&s.individual_id))
+        .map(|i| i.health / 100.0)
+        .sum();
+    let drill = match ctx.db.faction().id().find(&army.faction_id) {
+        Some(f) if f.faction_type == FactionType::Military => MILITARY_DRILL,
+        _ => 1.0,
+    };
+    fitness * drill * (army.morale / 100.0) * (0.5 + army.supply / 200.0)
+}
+
+/// How much the ground favours whoever holds the city
+fn terrain_advantage(ctx: &ReducerContext, city_id: u32) -> f32 {
+    let ground = ctx.db.microclimate().iter()
+        .find(|m| m.region_id == city_id)
+        .map(|m| m.location_type);
+    let terrain = match ground {
+        Some(MicroclimateType::Mountain) => 1.3,
+        Some(MicroclimateType::Forest) => 1.15,
+        Some(MicroclimateType::Urban) => 1.1,
+        _ => 1.0,
+    };
+    let walls = if wall_safety_bonus(ctx, city_id) > 0.0 { 1.25 } else { 1.0 };
+    terrain * walls
+}
+
+fn city_defence(ctx: &ReducerContext, city_id: u32) -> f32 {
+    let safety = ctx.db.city().id().find(&city_id).map_or(50.0, |c| c.safety);
+    safety * DEFENCE_PER_SAFETY_POINT * terrain_advantage(ctx, city_id)
+}
+
+fn occupier_of(ctx: &ReducerContext, city_id: u32) -> Option<u32> {
+    ctx.db.occupation().iter()
+        .find(|o| o.city_id == city_id && o.ended_hour.is_none())
+        .map(|o| o.occupier_faction_id)
+}
+
+/// Resolve a battle between an arriving army and the one holding the city
+fn fight(ctx: &ReducerContext, war: &War, attackers: Army, defenders: Army, hour: u64) -> Result<(), String> {
+    let mut rng = rand::thread_rng();
+    let city_id = defenders.location_city_id;
+    let attack = army_strength(ctx, &attackers) * rng.gen_range(0.8..1.2);
+    let defence = army_strength(ctx, &defenders) * terrain_advantage(ctx, city_id) * rng.gen_range(0.8..1.2);
+    let attackers_won = attack > defence;
+
+    let (loss_attackers, loss_defenders) = if attackers_won {
+        (rng.gen_range(0.05..0.1), rng.gen_range(0.1..0.25))
+    } else {
+        (rng.gen_range(0.1..0.25), rng.gen_range(0.05..0.1))
+    };
+    let (attacker_killed, attacker_wounded, fallen_attacker) = take_casualties(ctx, &attackers, loss_attackers, hour);
+    let (defender_killed, defender_wounded, fallen_defender) = take_casualties(ctx, &defenders, loss_defenders, hour);
+
+    let (mut winner, mut loser) = if attackers_won { (attackers, defenders) } else { (defenders, attackers) };
+    winner.morale = (winner.morale + 10.0).min(100.0);
+    loser.morale = (loser.morale - 20.0).max(0.0);
+    if loser.morale < ROUT_MORALE || serving(ctx, loser.id).is_empty() {
+        loser.status = ArmyStatus::Routed;
+        loser.siege_progress = 0.0;
+        let home = ctx.db.faction().id().find(&loser.faction_id).map_or(loser.location_city_id, |f| f.base_city_id);
+        march_toward(ctx, &mut loser, home, hour);
+    }
+    let victor_faction_id = winner.faction_id;
+    let (attacker_army_id, defender_army_id) = if attackers_won { (winner.id, loser.id) } else { (loser.id, winner.id) };
+    let (attacker_faction, defender_faction) = if attackers_won {
+        (winner.faction_id, loser.faction_id)
+    } else {
+        (loser.faction_id, winner.faction_id)
+    };
+
+    let id = ctx.db.battle().iter().count() as u32 + 1;
+    ctx.db.battle().insert(Battle {
+        id,
+        war_id: war.id,
+        city_id,
+        hour,
+        attacker_army_id,
+        defender_army_id,
+        attacker_strength: attack,
+        defender_strength: defence,
+        attacker_killed,
+        attacker_wounded,
+        defender_killed,
+        defender_wounded,
+        victor_faction_id,
+    });
+
+    if let Some(mut war) = ctx.db.war().id().find(&war.id) {
+        let (at, de) = (attacker_killed + attacker_wounded, defender_killed + defender_wounded);
+        if attacker_faction == war.attacker_id {
+            war.attacker_casualties += at;
+            war.defender_casualties += de;
+        } else {
+            war.attacker_casualties += de;
+            war.defender_casualties += at;
+        }
+        ctx.db.war().id().update(war);
+    }
+
+    let victor_name = ctx.db.faction().id().find(&victor_faction_id).map_or_else(|| "Unknown".to_string(), |f| f.name);
+    let city_name = ctx.db.city().id().find(&city_id).map_or_else(|| format!("City {}", city_id), |c| c.name);
+    let mut description = format!("{} carried the day at {}: {} dead and {} wounded on one side, {} dead and {} wounded on the other",
+        victor_name, city_name, attacker_killed, attacker_wounded, defender_killed, defender_wounded);
+    let fallen: Vec<String> = fallen_attacker.into_iter().chain(fallen_defender).collect();
+    if !fallen.is_empty() {
+        description.push_str(&format!(". Among the fallen: {}", fallen.join(", ")));
+    }
+    log::info!("Battle of {}: attackers {} ({:.1}) vs defenders {} ({:.1})",
+        city_name, attacker_faction, attack, defender_faction, defence);
+
+    ctx.db.army().id().update(winner);
+    ctx.db.army().id().update(loser);
+
+    create_narrative_event(ctx, war.world_id, 1, EventCategory::Military,
+        format!("Battle of {}", city_name), description, 5)?;
+    Ok(())
+}
+
+/// Strike a share of an army's soldiers: some killed, the rest wounded and sent home.
+/// Returns (killed, wounded, up to three names of the dead).
+fn take_casualties(ctx: &ReducerContext, army: &Army, share: f32, hour: u64) -> (u32, u32, Vec<String>) {
+    let mut soldiers = serving(ctx, army.id);
+    soldiers.shuffle(&mut rand::thread_rng());
+    let count = ((soldiers.len() as f32 * share).ceil() as usize).min(soldiers.len());
+    let killed_count = (count as f32 * KILLED_SHARE).round() as usize;
+
+    let mut killed = 0;
+    let mut wounded = 0;
+    let mut names = Vec::new();
+    for (index, mut soldier) in soldiers.into_iter().take(count).enumerate() {
+        let Some(mut person) = ctx.db.individual().id().find(&soldier.individual_id) else { continue; };
+        if index < killed_count {
+            person.health = 0.0;
+            soldier.status = SoldierStatus::Killed;
+            killed += 1;
+            if names.len() < 3 {
+                names.push(person.name.clone());
+            }
+        } else {
+            person.health = (person.health - WOUND_DAMAGE).max(1.0);
+            person.threat = (person.threat + 20.0).min(100.0);
+            soldier.status = SoldierStatus::Wounded;
+            wounded += 1;
+        }
+        person.stress = (person.stress + 30.0).min(100.0);
+        soldier.status_hour = hour;
+        ctx.db.individual().id().update(person);
+        ctx.db.soldier().id().update(soldier);
+    }
+    (killed, wounded, names)
+}
+
+/// The besieged city falls to the army's faction
+fn occupy(ctx: &ReducerContext, war: &War, army: &Army, city_id: u32, hour: u64) -> Result<(), String> {
+    // Liberating a city ends its occupation rather than starting a new one
+    let home = ctx.db.faction().id().find(&army.faction_id).map_or(0, |f| f.base_city_id);
+    let current: Vec<Occupation> = ctx.db.occupation().iter()
+        .filter(|o| o.city_id == city_id && o.ended_hour.is_none())
+        .collect();
+    for mut occupation in current {
+        occupation.ended_hour = Some(hour);
+        ctx.db.occupation().id().update(occupation);
+    }
+
+    let liberated = city_id == home;
+    if !liberated {
+        let id = ctx.db.occupation().iter().count() as u32 + 1;
+        ctx.db.occupation().insert(Occupation {
+            id,
+            world_id: war.world_id,
+            war_id: war.id,
+            city_id,
+            occupier_faction_id: army.faction_id,
+            started_hour: hour,
+            ended_hour: None,
+        });
+        if let Some(mut city) = ctx.db.city().id().find(&city_id) {
+            city.stability = (city.stability - OCCUPATION_STABILITY_LOSS).max(0.0);
+            ctx.db.city().id().update(city);
+        }
+    }
+
+    announce(ctx, war.world_id, army, city_id, if liberated { "liberation" } else { "occupation" }, 5)
+}
+
+fn announce(ctx: &ReducerContext, world_id: u32, army: &Army, city_id: u32, what: &str, importance: u8) -> Result<(), String> {
+    let city_name = ctx.db.city().id().find(&city_id).map_or_else(|| format!("City {}", city_id), |c| c.name);
+    let (title, description) = match what {
+        "siege" => (
+            format!("Siege of {}", city_name),
+            format!("The {} has surrounded {} and cut its roads", army.name, city_name),
+        ),
+        "liberation" => (
+            format!("{} liberated", city_name),
+            format!("The {} has driven the occupiers from {}", army.name, city_name),
+        ),
+        _ => (
+            format!("{} falls", city_name),
+            format!("{} has opened its gates to the {}", city_name, army.name),
+        ),
+    };
+    create_narrative_event(ctx, world_id, 1, EventCategory::Military, title, description, importance)?;
+    Ok(())
+}
+
+/// Positive when the attacker is ahead: cities held and casualties inflicted
+fn war_score(ctx: &ReducerContext, war: &War) -> f32 {
+    let held = |faction_id: u32| ctx.db.occupation().iter()
+        .filter(|o| o.war_id == war.id && o.ended_hour.is_none() && o.occupier_faction_id == faction_id)
+        .count() as f32;
+    (held(war.attacker_id) - held(war.defender_id)) * 10.0
+        + war.defender_casualties as f32 - war.attacker_casualties as f32
+}
+
+/// Share of a side's soldiers lost in this war
+fn exhaustion(ctx: &ReducerContext, war: &War, faction_id: u32) -> f32 {
+    let armies: HashSet<u32> = ctx.db.army().iter()
+        .filter(|a| a.war_id == war.id && a.faction_id == faction_id)
+        .map(|a| a.id)
+        .collect();
+    let enlisted = ctx.db.soldier().iter().filter(|s| armies.contains(&s.army_id)).count() as f32;
+    let lost = ctx.db.soldier().iter()
+        .filter(|s| armies.contains(&s.army_id) && matches!(s.status, SoldierStatus::Killed | SoldierStatus::Wounded))
+        .count() as f32;
+    if enlisted > 0.0 { lost / enlisted } else { 0.0 }
+}
+
+/// End the war by treaty once a side is spent, beaten or the war drags on too long
+fn check_for_peace(ctx: &ReducerContext, mut war: War, hour: u64) -> Result<(), String> {
+    let beaten = |faction_id: u32| field_armies(ctx, war.id, faction_id).is_empty()
+        && ctx.db.faction().id().find(&faction_id).map_or(true, |f| f.treasury < RECRUIT_COST);
+    let attacker_spent = beaten(war.attacker_id) || exhaustion(ctx, &war, war.attacker_id) > EXHAUSTION_PEACE;
+    let defender_spent = beaten(war.defender_id) || exhaustion(ctx, &war, war.defender_id) > EXHAUSTION_PEACE;
+    let too_long = hour >= war.declared_hour + MAX_WAR_HOURS;
+
+    war.outcome = match (attacker_spent, defender_spent) {
+        (false, true) => WarOutcome::AttackerVictory,
+        (true, false) => WarOutcome::DefenderVictory,
+        (true, true) => WarOutcome::Negotiated,
+        (false, false) if too_long => WarOutcome::Negotiated,
+        _ => return Ok(()),
+    };
+    war.ended_hour = Some(hour);
+    let war = ctx.db.war().id().update(war);
+
+    // The victor keeps the cities it holds; anything else is handed back
+    let victor = match war.outcome {
+        WarOutcome::AttackerVictory => Some(war.attacker_id),
+        WarOutcome::DefenderVictory => Some(war.defender_id),
+        _ => None,
+    };
+    let held: Vec<Occupation> = ctx.db.occupation().iter()
+        .filter(|o| o.war_id == war.id && o.ended_hour.is_none() && Some(o.occupier_faction_id) != victor)
+        .collect();
+    for mut occupation in held {
+        occupation.ended_hour = Some(hour);
+        ctx.db.occupation().id().update(occupation);
+    }
+
+    // Stand the armies down and send the soldiers home
+    let armies: Vec<Army> = ctx.db.army().iter()
+        .filter(|a| a.war_id == war.id && a.status != ArmyStatus::Disbanded)
+        .collect();
+    for mut army in armies {
+        for mut soldier in serving(ctx, army.id) {
+            soldier.status = SoldierStatus::Discharged;
+            soldier.status_hour = hour;
+            ctx.db.soldier().id().update(soldier);
+        }
+        army.status = ArmyStatus::Disbanded;
+        ctx.db.army().id().update(army);
+    }
+
+    on_peace(ctx, war.world_id, war.attacker_id, war.defender_id, hour)?;
+    if let Some(victor_id) = victor {
+        let loser_id = if victor_id == war.attacker_id { war.defender_id } else { war.attacker_id };
+        for (faction_id, change) in [(victor_id, 15.0), (loser_id, -15.0)] {
+            if let Some(mut faction) = ctx.db.faction().id().find(&faction_id) {
+                faction.influence = (faction.influence + change).clamp(0.0, 100.0);
+                ctx.db.faction().id().update(faction);
+            }
+        }
+    }
+
+    let name = |id: u32| ctx.db.faction().id().find(&id).map_or_else(|| format!("Faction {}", id), |f| f.name);
+    let description = match victor {
+        Some(v) => format!("{} dictates peace after {} casualties on both sides",
+            name(v), war.attacker_casualties + war.defender_casualties),
+        None => format!("{} and {} sign a peace neither can call a victory",
+            name(war.attacker_id), name(war.defender_id)),
+    };
+    log::info!("War {} ended: {:?}", war.id, war.outcome);
+    create_political_event(ctx, war.world_id, PoliticalEventType::Treaty, war.attacker_id, Some(war.defender_id), hour, description.clone())?;
+    create_narrative_event(ctx, war.world_id, 1, EventCategory::Military,
+        format!("Peace between {} and {}", name(war.attacker_id), name(war.defender_id)), description, 5)?;
+    Ok(())
+}
\ No newline at end of file
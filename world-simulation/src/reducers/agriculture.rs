@@ -0,0 +1,317 @@
+use spacetimedb::{ReducerContext, Table};
+use log;
+use crate::tables::*;
+use crate::types::*;
+use crate::systems::*;
+use crate::tables::building::{building, workplace_data};
+use crate::tables::city::city;
+use crate::tables::individual::employment;
+use crate::tables::agriculture::{farm_plot, food_security};
+use crate::tables::events::simulation_time;
+use crate::world::{Season, calculate_season_from_hour};
+use crate::world::game_world::game_world;
+use crate::natural::{natural_event, seasonal_effect, NaturalEventType};
+use crate::natural::climate::calendar;
+use crate::natural::seasonal_cycles::{seasonal_activity, phenology, ActivityType, SpeciesType, PhenologicalPhase};
+use crate::economics::market;
+use crate::narrative::{create_narrative_event, EventCategory};
+use crate::economics::markets::{place_order, OrderSide, Trader};
+use crate::reducers::storage::{stock_level, deposit_stock};
+use crate::reducers::migration::{world_for_city, parse_region_ids};
+
+/// Daily pass: farms work through the crop calendar and the city manages its food reserve
+#[spacetimedb::reducer]
+pub fn process_city_agriculture(ctx: &ReducerContext, city_id: u32) -> Result<(), String> {
+    let current_hour = ctx.db.simulation_time().id().find(&1)
+        .ok_or("Simulation not initialized")?
+        .current_hour;
+
+    let world_id = world_for_city(ctx, city_id);
+    let season = world_id
+        .and_then(|id| ctx.db.game_world().id().find(&id))
+        .map(|w| w.season)
+        .unwrap_or_else(|| calculate_season_from_hour(current_hour));
+
+    let farms: Vec<Building> = ctx.db.building().iter()
+        .filter(|b| b.city_id == city_id && !b.destroyed && is_farm(b))
+        .collect();
+
+    for farm in &farms {
+        tend_farm(ctx, farm, world_id, season, current_hour);
+    }
+
+    update_food_security(ctx, city_id, world_id, season, current_hour)?;
+
+    Ok(())
+}
+
+pub(crate) fn is_farm(building: &Building) -> bool {
+    matches!(&building.building_type, BuildingType::Workplace(config) if config.job_type == JobType::Agriculture)
+}
+
+/// Start a fallow plot for a newly built farm
+pub(crate) fn create_farm_plot(ctx: &ReducerContext, building_id: u32) {
+    ctx.db.farm_plot().insert(FarmPlot {
+        building_id,
+        stage: CropStage::Fallow,
+        planted_hour: 0,
+        growth: 0.0,
+        crop_health: 1.0,
+        last_harvest_hour: 0,
+        last_yield: 0.0,
+    });
+}
+
+fn tend_farm(ctx: &ReducerContext, farm: &Building, world_id: Option<u32>, season: Season, hour: u64) {
+    let mut plot = match ctx.db.farm_plot().building_id().find(&farm.id) {
+        Some(plot) => plot,
+        None => {
+            create_farm_plot(ctx, farm.id);
+            return;
+        }
+    };
+
+    let workers = ctx.db.employment().iter()
+        .filter(|e| e.building_id == farm.id && e.is_active)
+        .count() as u32;
+
+    match plot.stage {
+        CropStage::Fallow => {
+            if workers > 0 && in_calendar(ctx, world_id, farm.city_id, season, ActivityType::Planting, Season::Spring) {
+                plot.stage = CropStage::Growing;
+                plot.planted_hour = hour;
+                plot.growth = 0.0;
+                plot.crop_health = 1.0;
+                log::info!("Farm {} planted its fields", farm.id);
+            }
+        },
+        CropStage::Growing => {
+            if season == Season::Winter {
+                // Frost takes whatever has not ripened
+                plot.stage = CropStage::Fallow;
+                plot.growth = 0.0;
+                log::info!("Farm {} lost its unripe crop to winter", farm.id);
+            } else {
+                let labour = (workers as f32 / farm.max_capacity.max(1) as f32).min(1.0);
+                plot.growth += agriculture_modifier(ctx, world_id, season) * labour / farming::GROWING_DAYS;
+                if !crops_fruiting(ctx, world_id, farm.city_id, hour) {
+                    plot.growth = plot.growth.min(farming::UNRIPE_GROWTH_CAP);
+                }
+
+                plot.crop_health = (plot.crop_health - crop_damage(ctx, farm.city_id, hour)).max(0.0);
+
+                if plot.growth >= 1.0 {
+                    plot.stage = CropStage::Ripe;
+                }
+            }
+        },
+        CropStage::Ripe => {
+            if workers > 0 && in_calendar(ctx, world_id, farm.city_id, season, ActivityType::Harvest, Season::Autumn) {
+                let crop = farming::YIELD_PER_WORKER * workers as f32 * plot.crop_health
+                    * bumper_harvest(ctx, farm.city_id, hour);
+                // Whatever the granary cannot hold is lost
+                let harvest = deposit_stock(ctx, farm.city_id, ResourceType::Food, crop, hour);
+
+                if let Some(mut workplace) = ctx.db.workplace_data().building_id().find(&farm.id) {
+                    workplace.inventory = harvest.min(workplace.max_inventory);
+                    ctx.db.workplace_data().building_id().update(workplace);
+                }
+
+                plot.stage = CropStage::Fallow;
+                plot.growth = 0.0;
+                plot.last_harvest_hour = hour;
+                plot.last_yield = harvest;
+                log::info!("Farm {} harvested {:.0} meals", farm.id, harvest);
+            } else if season == Season::Winter {
+                plot.stage = CropStage::Fallow;
+                plot.growth = 0.0;
+                log::info!("Farm {} left its crop to rot in the fields", farm.id);
+            } else {
+                plot.crop_health = (plot.crop_health - crop_damage(ctx, farm.city_id, hour)).max(0.0);
+            }
+        },
+    }
+
+    ctx.db.farm_plot().building_id().update(plot);
+}
+
+/// Whether the region's seasonal activities schedule this work now, falling back to a default season
+fn in_calendar(
+    ctx: &ReducerContext,
+    world_id: Option<u32>,
+    region_id: u32,
+    season: Season,
+    activity_type: ActivityType,
+    default_season: Season,
+) -> bool {
+    let world_id = match world_id {
+        Some(id) => id,
+        None => return season == default_season,
+    };
+
+    let scheduled: Vec<Season> = ctx.db.seasonal_activity().iter()
+        .filter(|a| a.world_id == world_id && a.region_id == region_id && a.activity_type == activity_type)
+        .map(|a| a.season)
+        .collect();
+
+    if scheduled.is_empty() {
+        season == default_season
+    } else {
+        scheduled.contains(&season)
+    }
+}
+
+fn agriculture_modifier(ctx: &ReducerContext, world_id: Option<u32>, season: Season) -> f32 {
+    let world = match world_id.and_then(|id| ctx.db.game_world().id().find(&id)) {
+        Some(world) => world,
+        None => return 1.0,
+    };
+
+    ctx.db.seasonal_effect().iter()
+        .find(|e| e.world_id == world.id && e.season == season && e.climate_zone == world.climate_zone)
+        .map(|e| e.agriculture_modifier)
+        .unwrap_or(1.0)
+}
+
+/// Crops can only finish ripening once their fruiting phase has begun this year
+fn crops_fruiting(ctx: &ReducerContext, world_id: Option<u32>, region_id: u32, hour: u64) -> bool {
+    let world_id = match world_id {
+        Some(id) => id,
+        None => return true,
+    };
+
+    let day_of_year = calendar(hour).0 as u32;
+    ctx.db.phenology().iter()
+        .find(|p| p.world_id == world_id && p.region_id == region_id &&
+            p.species_type == SpeciesType::Crops && p.phase == PhenologicalPhase::Fruiting)
+        .map(|p| day_of_year >= p.current_year_start)
+        .unwrap_or(true)
+}
+
+fn active_events(ctx: &ReducerContext, region_id: u32, hour: u64) -> Vec<crate::natural::NaturalEvent> {
+    ctx.db.natural_event().iter()
+        .filter(|e| !e.resolved && e.start_hour <= hour && hour < e.start_hour + e.duration_hours as u64)
+        .filter(|e| parse_region_ids(&e.affected_region).contains(&region_id))
+        .collect()
+}
+
+/// Daily crop health lost to drought and flood in the region
+fn crop_damage(ctx: &ReducerContext, region_id: u32, hour: u64) -> f32 {
+    active_events(ctx, region_id, hour).iter()
+        .map(|e| match e.event_type {
+            NaturalEventType::Drought => farming::DROUGHT_CROP_DAMAGE,
+            NaturalEventType::Flood => farming::FLOOD_CROP_DAMAGE,
+            _ => 0.0,
+        })
+        .sum()
+}
+
+/// Yield multiplier from an abundant-harvest event in the region
+fn bumper_harvest(ctx: &ReducerContext, region_id: u32, hour: u64) -> f32 {
+    active_events(ctx, region_id, hour).iter()
+        .filter(|e| e.event_type == NaturalEventType::Harvest)
+        .map(|e| 1.0 + e.economic_impact)
+        .fold(1.0, f32::max)
+}
+
+/// Stockpile for winter, sell surplus to the market, and declare or lift famine
+fn update_food_security(
+    ctx: &ReducerContext,
+    city_id: u32,
+    world_id: Option<u32>,
+    season: Season,
+    hour: u64,
+) -> Result<(), String> {
+    let mut city = ctx.db.city().id().find(&city_id)
+        .ok_or("City not found")?;
+
+    // Cities without farms or a food store are left to the abstract economy
+    let mut stock = match stock_level(ctx, city_id, &ResourceType::Food) {
+        Some(stock) => stock,
+        None => return Ok(()),
+    };
+
+    let daily_consumption = city.population as f32 * farming::MEALS_PER_PERSON_PER_DAY;
+    let winter_target = daily_consumption * farming::WINTER_DAYS;
+
+    let food_market = world_id.and_then(|world_id| ctx.db.market().iter()
+        .find(|m| m.world_id == world_id && m.city_id == city_id &&
+            m.resource_type == crate::economics::ResourceType::Food));
+
+    // The granary trades through the city's food market for a day at a time
+    if let Some(market) = food_market {
+        let meal_price = market.price / farming::MEALS_PER_MARKET_UNIT;
+
+        if season == Season::Autumn && stock < winter_target && meal_price > 0.0 {
+            // Bid for what the harvest did not cover before winter
+            let affordable = city.tax_reserve.max(0.0) * farming::STOCKPILE_BUDGET_SHARE / meal_price;
+            let wanted = (winter_target - stock).min(affordable);
+            if wanted > 0.0 {
+                place_order(ctx, market.world_id, city_id, market.resource_type, OrderSide::Bid, Trader::City(city_id),
+                    wanted / farming::MEALS_PER_MARKET_UNIT, market.price, hour, hour + 24);
+                log::info!("City {} bid for {:.0} meals for the winter", city_id, wanted);
+            }
+        } else if season != Season::Winter && stock > winter_target * farming::SURPLUS_SHARE {
+            let surplus = stock - winter_target * farming::SURPLUS_SHARE;
+            place_order(ctx, market.world_id, city_id, market.resource_type, OrderSide::Ask, Trader::City(city_id),
+                surplus / farming::MEALS_PER_MARKET_UNIT, market.price, hour, hour + 24);
+            stock = stock_level(ctx, city_id, &ResourceType::Food).unwrap_or(0.0);
+            log::info!("City {} offered {:.0} surplus meals", city_id, surplus);
+        }
+    }
+
+    let days_of_supply = if daily_consumption > 0.0 { stock / daily_consumption } else { 0.0 };
+
+    let existing = ctx.db.food_security().city_id().find(&city_id);
+    let is_new = existing.is_none();
+    let mut security = existing
+        .unwrap_or(FoodSecurity {
+            city_id,
+            daily_consumption,
+            days_of_supply,
+            winter_target,
+            famine: false,
+            famine_since_hour: None,
+            last_update_hour: hour,
+        });
+
+    if !security.famine && daily_consumption > 0.0 && stock <= 0.0 {
+        security.famine = true;
+        security.famine_since_hour = Some(hour);
+        log::info!("Famine declared in city {}", city_id);
+
+        if let Some(world_id) = world_id {
+            create_narrative_event(
+                ctx,
+                world_id,
+                1, // Default game ID
+                EventCategory::Economic,
+                format!("Famine in {}", city.name),
+                format!("The granaries of {} are empty and its {} people go hungry", city.name, city.population),
+                6,
+            )?;
+        }
+    } else if security.famine && days_of_supply >= farming::FAMINE_RECOVERY_DAYS {
+        security.famine = false;
+        security.famine_since_hour = None;
+        log::info!("Famine in city {} has ended", city_id);
+    }
+
+    if security.famine {
+        city.stability = (city.stability + farming::FAMINE_STABILITY_LOSS).max(0.0);
+    }
+
+    security.daily_consumption = daily_consumption;
+    security.days_of_supply = days_of_supply;
+    security.winter_target = winter_target;
+    security.last_update_hour = hour;
+
+    if is_new {
+        ctx.db.food_security().insert(security);
+    } else {
+        ctx.db.food_security().city_id().update(security);
+    }
+    ctx.db.city().id().update(city);
+
+    Ok(())
+}
\ No newline at end of file
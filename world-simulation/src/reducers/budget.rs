@@ -0,0 +1,213 @@
+use spacetimedb::{ReducerContext, Table};
+use log;
+use crate::tables::*;
+use crate::types::*;
+use crate::systems::modifiers::*;
+use crate::tables::budget::{budget_ledger, budget_report};
+use crate::tables::city::{city, city_service, city_policy};
+use crate::tables::building::{building, home_data};
+use crate::tables::individual::individual;
+use crate::tables::events::simulation_time;
+use crate::reducers::city::enact_policy;
+use crate::reducers::public_works::fund_projects;
+
+/// A city's tax rates, set by its TaxRate policy
+pub(crate) struct TaxRates {
+    pub income: f32,
+    pub property: f32,
+    pub sales: f32,
+    pub tariff: f32,
+}
+
+/// Set the city's tax level (0-1); zero abolishes taxes until raised again
+#[spacetimedb::reducer]
+pub fn set_tax_rate(ctx: &ReducerContext, city_id: u32, level: f32) -> Result<(), String> {
+    let current_hour = ctx.db.simulation_time().id().find(&1)
+        .ok_or("Simulation not initialized")?
+        .current_hour;
+    
+    ctx.db.city().id().find(&city_id)
+        .ok_or("City not found")?;
+    
+    enact_policy(ctx, city_id, PolicyType::TaxRate, level, current_hour);
+    
+    let rates = tax_rates(ctx, city_id);
+    log::info!("City {} set taxes: income {:.0}%, property {:.0}%, sales {:.0}%, tariffs {:.0}%",
+        city_id, rates.income * 100.0, rates.property * 100.0, rates.sales * 100.0, rates.tariff * 100.0);
+    Ok(())
+}
+
+/// Current tax rates for a city; cities that never set a TaxRate policy tax at the default level
+pub(crate) fn tax_rates(ctx: &ReducerContext, city_id: u32) -> TaxRates {
+    let level = ctx.db.city_policy().iter()
+        .find(|p| p.city_id == city_id && p.policy_type == PolicyType::TaxRate)
+        .map(|p| p.intensity)
+        .unwrap_or(taxation::DEFAULT_TAX_LEVEL);
+    
+    TaxRates {
+        income: level * taxation::MAX_INCOME_TAX,
+        property: level * taxation::MAX_PROPERTY_TAX,
+        sales: level * taxation::MAX_SALES_TAX,
+        tariff: level * taxation::MAX_TARIFF,
+    }
+}
+
+/// Record revenue collected during the week; it reaches the reserve when the budget settles
+pub(crate) fn accrue_revenue(ctx: &ReducerContext, city_id: u32, line: RevenueLine, amount: f32, hour: u64) {
+    if amount <= 0.0 {
+        return;
+    }
+    
+    let existing = ctx.db.budget_ledger().city_id().find(&city_id);
+    let is_new = existing.is_none();
+    let mut ledger = existing.unwrap_or(BudgetLedger {
+        city_id,
+        week_start_hour: hour,
+        income_tax: 0.0,
+        property_tax: 0.0,
+        sales_tax: 0.0,
+        tariffs: 0.0,
+    });
+    
+    match line {
+        RevenueLine::IncomeTax => ledger.income_tax += amount,
+        RevenueLine::PropertyTax => ledger.property_tax += amount,
+        RevenueLine::SalesTax => ledger.sales_tax += amount,
+        RevenueLine::Tariff => ledger.tariffs += amount,
+    }
+    
+    if is_new {
+        ctx.db.budget_ledger().insert(ledger);
+    } else {
+        ctx.db.budget_ledger().city_id().update(ledger);
+    }
+}
+
+/// Weekly budget: collect property tax, pay for services, public works and policies,
+/// charge interest on debt and file the week's report
+pub(crate) fn settle_budget(ctx: &ReducerContext, city: &mut City, hour: u64) -> Result<(), String> {
+    let rates = tax_rates(ctx, city.id);
+    collect_property_tax(ctx, city.id, rates.property, hour);
+    
+    let ledger = ctx.db.budget_ledger().city_id().find(&city.id);
+    let week_start_hour = ledger.as_ref()
+        .map(|l| l.week_start_hour)
+        .unwrap_or(city.last_update_hour);
+    let (income_tax, property_tax, sales_tax, tariffs) = ledger.as_ref()
+        .map(|l| (l.income_tax, l.property_tax, l.sales_tax, l.tariffs))
+        .unwrap_or((0.0, 0.0, 0.0, 0.0));
+    let total_revenue = income_tax + property_tax + sales_tax + tariffs;
+    
+    let services: f32 = ctx.db.city_service().iter()
+        .filter(|s| s.city_id == city.id)
+        .map(|s| s.cost_per_hour * taxation::SERVICE_HOURS_PER_WEEK)
+        .sum();
+    
+    // Public works repair what has decayed, within their share of the budget
+    let policies: Vec<CityPolicy> = ctx.db.city_policy().iter()
+        .filter(|p| p.city_id == city.id)
+        .collect();
+    let public_spending = policies.iter()
+        .find(|p| p.policy_type == PolicyType::PublicSpending)
+        .map(|p| p.intensity)
+        .unwrap_or(0.0);
+    let works_budget = total_revenue.max(0.0) * taxation::PUBLIC_WORKS_BUDGET_SHARE * (1.0 + public_spending);
+    let repairs_needed = (100.0 - city.public_works).max(0.0) * taxation::PUBLIC_WORKS_COST_PER_POINT;
+    let repairs = works_budget.min(repairs_needed);
+    city.public_works = (city.public_works + repairs / taxation::PUBLIC_WORKS_COST_PER_POINT).min(100.0);
+    
+    let policy_costs: f32 = policies.iter()
+        .filter(|p| p.policy_type != PolicyType::TaxRate)
+        .map(|p| p.intensity * p.cost_multiplier * city.population as f32 / 100.0 * taxation::POLICY_COST_PER_100)
+        .sum();
+    
+    let debt_interest = (-city.tax_reserve).max(0.0) * taxation::DEBT_INTEREST_RATE;
+    
+    // Projects draw on a share of whatever the city has in hand
+    let in_hand = city.tax_reserve + total_revenue - (services + repairs + policy_costs + debt_interest);
+    let projects = fund_projects(ctx, city.id, in_hand.max(0.0) * construction::RESERVE_FUNDING_SHARE, hour)?;
+    let public_works = repairs + projects;
+    
+    let total_spending = services + public_works + policy_costs + debt_interest;
+    let balance = total_revenue - total_spending;
+    city.tax_reserve += balance;
+    if rates.income > 0.0 {
+        city.tax_base = income_tax / rates.income;
+    }
+    
+    let id = ctx.db.budget_report().iter().count() as u32 + 1;
+    ctx.db.budget_report().insert(BudgetReport {
+        id,
+        city_id: city.id,
+        week_start_hour,
+        hour,
+        income_tax,
+        property_tax,
+        sales_tax,
+        tariffs,
+        total_revenue,
+        services,
+        public_works,
+        policies: policy_costs,
+        debt_interest,
+        total_spending,
+        balance,
+        reserve: city.tax_reserve,
+        debt: (-city.tax_reserve).max(0.0),
+    });
+    
+    // Start the new week's ledger
+    let fresh = BudgetLedger {
+        city_id: city.id,
+        week_start_hour: hour,
+        income_tax: 0.0,
+        property_tax: 0.0,
+        sales_tax: 0.0,
+        tariffs: 0.0,
+    };
+    if ledger.is_some() {
+        ctx.db.budget_ledger().city_id().update(fresh);
+    } else {
+        ctx.db.budget_ledger().insert(fresh);
+    }
+    
+    log::info!("City {} budget: revenue {:.0}, spending {:.0}, reserve {:.0}",
+        city.id, total_revenue, total_spending, city.tax_reserve);
+    Ok(())
+}
+
+/// Residents pay tax on their home's weekly rent; owners pay for empty homes
+fn collect_property_tax(ctx: &ReducerContext, city_id: u32, rate: f32, hour: u64) {
+    let homes: Vec<Building> = ctx.db.building().iter()
+        .filter(|b| b.city_id == city_id && !b.destroyed && matches!(b.building_type, BuildingType::Home(_)))
+        .collect();
+    
+    let mut collected = 0.0;
+    for mut home in homes {
+        let rent = match ctx.db.home_data().building_id().find(&home.id) {
+            Some(data) => data.rent_amount,
+            None => continue,
+        };
+        let owed = rent * 7.0 * rate;
+        
+        let residents: Vec<Individual> = ctx.db.individual().iter()
+            .filter(|i| i.home_id == Some(home.id))
+            .collect();
+        if residents.is_empty() {
+            home.revenue -= owed;
+            ctx.db.building().id().update(home);
+            collected += owed;
+            continue;
+        }
+        
+        let share = owed / residents.len() as f32;
+        for mut resident in residents {
+            let paid = share.min(resident.income.max(0.0));
+            resident.income -= paid;
+            collected += paid;
+            ctx.db.individual().id().update(resident);
+        }
+    }
+    
+    accrue_revenue(ctx, city_id, RevenueLine::PropertyTax, collected, hour);
+}
\ No newline at end of file
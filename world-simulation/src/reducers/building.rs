@@ -1,10 +1,15 @@
 use spacetimedb::{ReducerContext, Table};
 use log;
 use crate::tables::*;
-use crate::types::{BuildingType, ResourceType};
+use crate::types::{BuildingType, ResourceType, JobType};
 use crate::systems::modifiers::*;
-use crate::tables::building::{building, home_data, workplace_data, location_capability};
+use crate::tables::building::{building, home_data, workplace_data, location_capability, building_upgrade};
+use crate::tables::events::simulation_time;
 use crate::tables::city::city;
+use crate::reducers::city::local_weather;
+use crate::reducers::agriculture::create_farm_plot;
+use crate::reducers::extraction::{create_extraction_site, is_extraction_site};
+use crate::reducers::unrest::on_strike;
 
 /// Create a new building
 #[spacetimedb::reducer]
@@ -16,7 +21,20 @@ pub fn create_building(
     x: f32,
     y: f32,
 ) -> Result<(), String> {
-    let id = (ctx.db.building().iter().count() + 1) as u32;
+    insert_building(ctx, name, city_id, building_type, x, y)?;
+    Ok(())
+}
+
+/// Create a building with its capabilities and type-specific data; returns its ID
+pub(crate) fn insert_building(
+    ctx: &ReducerContext,
+    name: String,
+    city_id: u32,
+    building_type: BuildingType,
+    x: f32,
+    y: f32,
+) -> Result<u32, String> {
+    let id = ctx.db.building().iter().map(|b| b.id).max().unwrap_or(0) + 1;
     
     let (max_capacity, base_cost) = match &building_type {
         BuildingType::Home(home_data) => (home_data.capacity, 100.0),
@@ -35,6 +53,7 @@ pub fn create_building(
         cleanliness: 100.0,
         efficiency_level: 1,
         prestige_level: 1,
+        destroyed: false,
         current_occupants: 0,
         max_capacity,
         operating_cost: base_cost,
@@ -58,23 +77,36 @@ pub fn create_building(
             });
         },
         BuildingType::Workplace(workplace_data) => {
+            // Farms grow food on the seasonal calendar and extractive sites work deposits rather than a stockpile
+            let (resource_type, consumption_rate, production_rate) = match workplace_data.job_type {
+                JobType::Agriculture => (ResourceType::Food, 0.0, farming::YIELD_PER_WORKER),
+                JobType::Mining => (ResourceType::RawMaterials, 0.0, extractive::MINING_PER_WORKER),
+                JobType::Quarrying => (ResourceType::RawMaterials, 0.0, extractive::QUARRYING_PER_WORKER),
+                JobType::Logging => (ResourceType::RawMaterials, 0.0, extractive::LOGGING_PER_WORKER),
+                _ => (ResourceType::Goods, building_depletion::RESOURCE_CONSUMPTION_BASE, building_depletion::RESOURCE_PRODUCTION_BASE),
+            };
             ctx.db.workplace_data().insert(WorkplaceData {
                 building_id: id,
-                resource_type: ResourceType::Goods,
-                consumption_rate: building_depletion::RESOURCE_CONSUMPTION_BASE,
-                production_rate: building_depletion::RESOURCE_PRODUCTION_BASE,
+                resource_type,
+                consumption_rate,
+                production_rate,
                 inventory: 0.0,
                 stockpile: 100.0,
                 max_inventory: 1000.0,
                 max_stockpile: 1000.0,
                 base_wage: 5.0,
             });
+            match workplace_data.job_type {
+                JobType::Agriculture => create_farm_plot(ctx, id),
+                JobType::Mining | JobType::Quarrying | JobType::Logging => create_extraction_site(ctx, id, city_id),
+                _ => {},
+            }
         },
         _ => {},
     }
     
     log::info!("Created building {} with ID {}", name, id);
-    Ok(())
+    Ok(id)
 }
 
 /// Daily building update
@@ -95,7 +127,17 @@ pub fn update_building_daily(ctx: &ReducerContext, building_id: u32) -> Result<(
         building.maintenance += building_depletion::MAINTENANCE_POOR_INFRASTRUCTURE;
     }
     
-    building.maintenance = (building.maintenance + maintenance_decay).clamp(0.0, 100.0);
+    // Storms and heavy rain weather the fabric faster
+    let weather = local_weather(ctx, building.city_id, true);
+    let weather_decay = if weather.is_storm() {
+        weather_effects::STORM_MAINTENANCE_DECAY
+    } else if weather.precipitation >= weather_effects::HEAVY_RAIN {
+        weather_effects::RAIN_MAINTENANCE_DECAY
+    } else {
+        0.0
+    };
+    
+    building.maintenance = (building.maintenance + maintenance_decay + weather_decay).clamp(0.0, 100.0);
     
     // Update cleanliness
     let cleanliness_decay = building_depletion::CLEANLINESS_BASE + 
@@ -115,6 +157,116 @@ pub fn update_building_daily(ctx: &ReducerContext, building_id: u32) -> Result<(
     Ok(())
 }
 
+/// Start an upgrade (or repair/reconstruction) that workers complete through maintenance shifts
+#[spacetimedb::reducer]
+pub fn start_building_upgrade(ctx: &ReducerContext, building_id: u32, upgrade_type: UpgradeType) -> Result<(), String> {
+    let current_hour = ctx.db.simulation_time().id().find(&1)
+        .ok_or("Simulation not initialized")?
+        .current_hour;
+    
+    begin_upgrade(ctx, building_id, upgrade_type, current_hour)?;
+    Ok(())
+}
+
+/// Create an upgrade for a building unless one of the same type is already in progress; returns its ID.
+/// Reconstruction takes priority: it cancels other upgrades, covers repairs and blocks improvements until done.
+pub(crate) fn begin_upgrade(
+    ctx: &ReducerContext,
+    building_id: u32,
+    upgrade_type: UpgradeType,
+    current_hour: u64,
+) -> Result<u32, String> {
+    let building = ctx.db.building().id().find(&building_id)
+        .ok_or("Building not found")?;
+    
+    let pending: Vec<BuildingUpgrade> = ctx.db.building_upgrade().iter()
+        .filter(|u| u.building_id == building_id)
+        .collect();
+    if let Some(existing) = pending.iter().find(|u| u.upgrade_type == upgrade_type) {
+        return Ok(existing.id);
+    }
+    if let Some(rebuild) = pending.iter().find(|u| u.upgrade_type == UpgradeType::Reconstruction) {
+        // Rebuilding restores maintenance anyway; improvements wait for a standing building
+        return match upgrade_type {
+            UpgradeType::Maintenance => Ok(rebuild.id),
+            _ => Err("Building must be rebuilt first".to_string()),
+        };
+    }
+    if upgrade_type == UpgradeType::Reconstruction {
+        for cancelled in pending {
+            log::info!("Cancelled {:?} upgrade on building {} for reconstruction", cancelled.upgrade_type, building_id);
+            ctx.db.building_upgrade().id().delete(&cancelled.id);
+        }
+    } else if building.destroyed {
+        return Err("Building must be rebuilt first".to_string());
+    }
+    
+    let work_hours_needed = match upgrade_type {
+        UpgradeType::Efficiency => upgrades::UPGRADE_WORK_HOURS_EFFICIENCY,
+        UpgradeType::Prestige => upgrades::UPGRADE_WORK_HOURS_PRESTIGE,
+        UpgradeType::Capacity => upgrades::UPGRADE_WORK_HOURS_CAPACITY,
+        UpgradeType::Maintenance => (100.0 - building.maintenance) * upgrades::REPAIR_WORK_HOURS_PER_POINT,
+        UpgradeType::Reconstruction => upgrades::RECONSTRUCTION_WORK_HOURS,
+    };
+    
+    // Finished upgrades are deleted, so counting rows would reuse a live ID
+    let id = ctx.db.building_upgrade().iter().map(|u| u.id).max().unwrap_or(0) + 1;
+    ctx.db.building_upgrade().insert(BuildingUpgrade {
+        id,
+        building_id,
+        upgrade_type: upgrade_type.clone(),
+        work_hours_needed,
+        work_hours_completed: 0.0,
+        started_hour: current_hour,
+    });
+    
+    log::info!("Started {:?} upgrade on building {}", upgrade_type, building_id);
+    Ok(id)
+}
+
+/// Apply finished work hours to a building's most urgent upgrade; returns true if this completed it
+pub(crate) fn contribute_upgrade_work(ctx: &ReducerContext, building_id: u32, hours: f32) -> bool {
+    // Rebuilding first, then repairs, then improvements in the order they were started
+    let urgency = |u: &BuildingUpgrade| match u.upgrade_type {
+        UpgradeType::Reconstruction => 0,
+        UpgradeType::Maintenance => 1,
+        _ => 2,
+    };
+    let mut upgrade = match ctx.db.building_upgrade().iter()
+        .filter(|u| u.building_id == building_id)
+        .min_by_key(|u| (urgency(u), u.started_hour, u.id))
+    {
+        Some(upgrade) => upgrade,
+        None => return false,
+    };
+    
+    upgrade.work_hours_completed += hours;
+    if upgrade.work_hours_completed < upgrade.work_hours_needed {
+        ctx.db.building_upgrade().id().update(upgrade);
+        return false;
+    }
+    
+    if let Some(mut building) = ctx.db.building().id().find(&building_id) {
+        match upgrade.upgrade_type {
+            UpgradeType::Efficiency => building.efficiency_level = (building.efficiency_level + 1).min(5),
+            UpgradeType::Prestige => building.prestige_level = (building.prestige_level + 1).min(5),
+            UpgradeType::Capacity => {
+                building.max_capacity += ((building.max_capacity as f32 * upgrades::CAPACITY_UPGRADE_BONUS) as u32).max(1);
+            },
+            UpgradeType::Maintenance => building.maintenance = 100.0,
+            UpgradeType::Reconstruction => {
+                building.destroyed = false;
+                building.maintenance = 100.0;
+            },
+        }
+        log::info!("Completed {:?} upgrade on building {}", upgrade.upgrade_type, building_id);
+        ctx.db.building().id().update(building);
+    }
+    
+    ctx.db.building_upgrade().id().delete(&upgrade.id);
+    true
+}
+
 fn update_home_daily(ctx: &ReducerContext, building: &mut Building) -> Result<(), String> {
     if let Some(mut home_data) = ctx.db.home_data().building_id().find(&building.id) {
         // Deplete rent
@@ -133,6 +285,11 @@ fn update_home_daily(ctx: &ReducerContext, building: &mut Building) -> Result<()
 
 fn update_workplace_daily(ctx: &ReducerContext, building: &mut Building) -> Result<(), String> {
     if let Some(mut workplace) = ctx.db.workplace_data().building_id().find(&building.id) {
+        // Farm output follows the crop calendar and extractive output the deposits instead; struck workplaces produce nothing
+        if workplace.resource_type == ResourceType::Food || is_extraction_site(building) || on_strike(ctx, building.id) {
+            return Ok(());
+        }
+        
         // Calculate efficiency
         let efficiency_factor = 1.0 + (building.efficiency_level as f32 * upgrades::EFFICIENCY_PRODUCTION_BONUS);
         let consumption_reduction = 1.0 - (building.efficiency_level as f32 * upgrades::EFFICIENCY_CONSUMPTION_REDUCTION);
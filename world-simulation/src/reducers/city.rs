@@ -3,10 +3,19 @@ use log;
 use crate::tables::*;
 use crate::types::*;
 use crate::systems::modifiers::*;
-use crate::tables::city::{city, city_service};
+use crate::systems::exposure::LocalWeather;
+use crate::tables::city::{city, city_service, city_policy};
 use crate::tables::individual::individual;
 use crate::tables::building::building;
 use crate::tables::events::simulation_time;
+use crate::natural::climate_state;
+use crate::natural::weather::{microclimate, MicroclimateType};
+use crate::reducers::crime::{calculate_crime_rate, check_zero_crime_achievement};
+use crate::reducers::health::calculate_city_health;
+use crate::reducers::education::{enroll_school_age_children, check_education_excellence};
+use crate::reducers::budget::settle_budget;
+use crate::reducers::public_works::{propose_public_works, wall_safety_bonus};
+use crate::natural::climate::DEFAULT_LATITUDE;
 
 /// Create a new city
 #[spacetimedb::reducer]
@@ -22,6 +31,8 @@ pub fn create_city(ctx: &ReducerContext, name: String) -> Result<(), String> {
         name: name.clone(),
         founded_hour: current_hour,
         population: 0,
+        latitude: DEFAULT_LATITUDE,
+        longitude: 0.0,
         
         // Infrastructure & Economy
         public_works: 100.0,
@@ -59,27 +70,23 @@ pub fn create_city(ctx: &ReducerContext, name: String) -> Result<(), String> {
 /// Weekly city update
 #[spacetimedb::reducer]
 pub fn update_city_weekly(ctx: &ReducerContext, city_id: u32) -> Result<(), String> {
+    let current_hour = ctx.db.simulation_time().id().find(&1)
+        .ok_or("Simulation not initialized")?
+        .current_hour;
+    
     let mut city = ctx.db.city().id().find(&city_id)
         .ok_or("City not found")?;
     
     // Update population count
-    city.population = ctx.db.individual().iter()
-        .filter(|i| {
-            if let Some(home_id) = i.home_id {
-                ctx.db.building().id().find(&home_id)
-                    .map(|b| b.city_id == city_id)
-                    .unwrap_or(false)
-            } else {
-                false
-            }
-        })
-        .count() as u32;
+    city.population = city_residents(ctx, city_id).len() as u32;
     
     // Update infrastructure
     update_infrastructure(&mut city)?;
     
     // Update economy
     update_economy(&ctx, &mut city)?;
+    propose_public_works(ctx, &city, current_hour)?;
+    settle_budget(ctx, &mut city, current_hour)?;
     
     // Update social metrics
     update_social_metrics(&ctx, &mut city)?;
@@ -87,47 +94,105 @@ pub fn update_city_weekly(ctx: &ReducerContext, city_id: u32) -> Result<(), Stri
     // Update culture and development
     update_culture_development(&ctx, &mut city)?;
     
+    // Update crime and public safety
+    city.crime_rate = calculate_crime_rate(ctx, city_id, city.population, current_hour);
+    check_zero_crime_achievement(ctx, &mut city, current_hour);
+    
+    // Update schooling
+    enroll_school_age_children(ctx, city_id, current_hour);
+    check_education_excellence(ctx, &mut city, current_hour);
+    
+    city.last_update_hour = current_hour;
     ctx.db.city().id().update(city);
     
     Ok(())
 }
 
+/// Individuals whose home is in the given city
+pub(crate) fn city_residents(ctx: &ReducerContext, city_id: u32) -> Vec<Individual> {
+    ctx.db.individual().iter()
+        .filter(|i| {
+            if let Some(home_id) = i.home_id {
+                ctx.db.building().id().find(&home_id)
+                    .map(|b| b.city_id == city_id)
+                    .unwrap_or(false)
+            } else {
+                false
+            }
+        })
+        .collect()
+}
+
+/// Set a city policy's intensity, enacting it if new; zero intensity suspends it but keeps the row,
+/// so a policy deliberately set to nothing stays distinct from one never enacted
+pub(crate) fn enact_policy(ctx: &ReducerContext, city_id: u32, policy_type: PolicyType, intensity: f32, hour: u64) {
+    let intensity = intensity.clamp(0.0, 1.0);
+    let existing = ctx.db.city_policy().iter()
+        .find(|p| p.city_id == city_id && p.policy_type == policy_type);
+    
+    match existing {
+        Some(mut policy) => {
+            if policy.intensity <= 0.0 && intensity > 0.0 {
+                policy.enacted_hour = hour;
+            }
+            policy.intensity = intensity;
+            ctx.db.city_policy().id().update(policy);
+        },
+        None => {
+            let id = ctx.db.city_policy().iter().map(|p| p.id).max().unwrap_or(0) + 1;
+            ctx.db.city_policy().insert(CityPolicy {
+                id,
+                city_id,
+                policy_type,
+                intensity,
+                cost_multiplier: 1.0,
+                enacted_hour: hour,
+            });
+        },
+    }
+}
+
+/// Weather in a city as felt by someone there, from the region's climate plus the urban microclimate
+pub(crate) fn local_weather(ctx: &ReducerContext, city_id: u32, outdoors: bool) -> LocalWeather {
+    let climate = match ctx.db.climate_state().iter().find(|c| c.region_id == city_id) {
+        Some(climate) => climate,
+        None => return LocalWeather::mild(outdoors),
+    };
+    
+    let urban_warming = ctx.db.microclimate().iter()
+        .find(|m| m.region_id == city_id && m.location_type == MicroclimateType::Urban)
+        .map(|m| m.temperature_modifier + m.urban_heat_island)
+        .unwrap_or(0.0);
+    
+    LocalWeather {
+        temperature: climate.current_temperature + urban_warming,
+        precipitation: climate.precipitation,
+        wind_speed: climate.wind_speed,
+        air_quality: climate.air_quality,
+        pattern: climate.weather_pattern,
+        outdoors,
+    }
+}
+
 fn update_infrastructure(city: &mut City) -> Result<(), String> {
     // Public works decay based on population
     let decay = city.population as f32 * city_depletion::PUBLIC_WORKS_PER_CITIZEN;
     city.public_works = (city.public_works + decay).clamp(0.0, 100.0);
     
-    // Tax collection and spending
-    let service_cost = (city.population as f32 / 100.0) * city_depletion::PUBLIC_SERVICE_COST_PER_100;
-    city.tax_reserve -= service_cost;
-    
-    // Calculate imports/exports based on production/consumption balance
-    // Simplified for now
-    if city.tax_reserve < 0.0 {
-        city.import_rate += 10.0;
-        city.tax_reserve -= city_depletion::IMPORT_COST * city.import_rate;
-    }
-    
     Ok(())
 }
 
 fn update_economy(ctx: &ReducerContext, city: &mut City) -> Result<(), String> {
-    // Calculate tax base from working individuals
-    let total_income: f32 = ctx.db.individual().iter()
-        .filter(|i| i.workplace_id.is_some())
-        .map(|i| i.income * 0.2) // 20% tax rate
-        .sum();
-    
-    city.tax_base = total_income;
-    city.tax_reserve += total_income;
+    // Taxes are collected as they fall due and settled by the weekly budget
     
-    // Calculate unemployment
-    let workforce = ctx.db.individual().iter()
+    // Calculate unemployment among this city's residents
+    let residents = city_residents(ctx, city.id);
+    let workforce = residents.iter()
         .filter(|i| i.age >= 18 && i.age <= 65)
         .count();
     
-    let employed = ctx.db.individual().iter()
-        .filter(|i| i.workplace_id.is_some())
+    let employed = residents.iter()
+        .filter(|i| i.age >= 18 && i.age <= 65 && i.workplace_id.is_some())
         .count();
     
     city.unemployment_rate = if workforce > 0 {
@@ -153,16 +218,8 @@ fn update_social_metrics(ctx: &ReducerContext, city: &mut City) -> Result<(), St
         city.stability = (city.stability + 10.0).min(100.0);
     }
     
-    // Calculate average health
-    let total_environment: f32 = ctx.db.individual().iter()
-        .map(|i| i.environment)
-        .sum();
-    
-    city.health = if city.population > 0 {
-        total_environment / city.population as f32
-    } else {
-        100.0
-    };
+    // Calculate average health of residents
+    city.health = calculate_city_health(&city_residents(ctx, city.id));
     
     // Calculate safety (inverse of average threat)
     let total_threat: f32 = ctx.db.individual().iter()
@@ -174,6 +231,7 @@ fn update_social_metrics(ctx: &ReducerContext, city: &mut City) -> Result<(), St
     } else {
         100.0
     };
+    city.safety = (city.safety + wall_safety_bonus(ctx, city.id)).min(100.0);
     
     // Calculate average happiness
     let total_happiness: f32 = ctx.db.individual().iter()
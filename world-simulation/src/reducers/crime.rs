@@ -0,0 +1,288 @@
+use spacetimedb::{ReducerContext, Table};
+use log;
+use rand::Rng;
+use crate::tables::*;
+use crate::types::*;
+use crate::systems::modifiers::*;
+use crate::tables::individual::individual;
+use crate::tables::building::building;
+use crate::tables::city::{city, city_service, city_achievement};
+use crate::tables::crime::crime_event;
+use crate::tables::events::simulation_time;
+use crate::political::{faction, FactionType};
+use crate::reducers::city::city_residents;
+
+/// Hourly crime pass for a city: residents may offend, police may arrest
+#[spacetimedb::reducer]
+pub fn process_city_crime(ctx: &ReducerContext, city_id: u32) -> Result<(), String> {
+    let current_hour = ctx.db.simulation_time().id().find(&1)
+        .ok_or("Simulation not initialized")?
+        .current_hour;
+
+    let mut city = ctx.db.city().id().find(&city_id)
+        .ok_or("City not found")?;
+
+    let mut residents = city_residents(ctx, city_id);
+    if residents.len() < 2 {
+        return Ok(());
+    }
+
+    let officers = police_officers(ctx, city_id);
+    let coverage = calculate_police_coverage(ctx, city_id, officers.len(), residents.len());
+
+    let mut rng = rand::thread_rng();
+    let mut changed = vec![false; residents.len()];
+    let mut offences = 0;
+    let mut arrests = 0;
+
+    for offender_idx in 0..residents.len() {
+        let offender = &residents[offender_idx];
+        if offender.age < 18 || matches!(offender.status, IndividualStatus::Sleeping(_)) {
+            continue;
+        }
+
+        if rng.gen::<f32>() >= calculate_offence_chance(offender, coverage) {
+            continue;
+        }
+
+        let crime_type = choose_crime_type(offender);
+        let victim_idx = match choose_victim(&residents, offender_idx, &crime_type, &mut rng) {
+            Some(idx) => idx,
+            None => continue,
+        };
+
+        // Apply the offence to the victim and offender
+        let (amount_taken, harm) = apply_offence(&mut residents, offender_idx, victim_idx, &crime_type);
+        changed[offender_idx] = true;
+        changed[victim_idx] = true;
+        offences += 1;
+
+        // Criminal factions recruit offenders and take a cut of the proceeds
+        let faction_id = pay_criminal_faction(ctx, city_id, amount_taken, &mut rng);
+        if faction_id.is_some() {
+            residents[offender_idx].income = (residents[offender_idx].income
+                - amount_taken * public_safety::FACTION_CUT).max(0.0);
+        }
+
+        // Police staff may make an arrest
+        let arresting_officer_id = if !officers.is_empty() &&
+            rng.gen::<f32>() < public_safety::ARREST_BASE_CHANCE + coverage * public_safety::ARREST_COVERAGE_FACTOR {
+            Some(officers[rng.gen_range(0..officers.len())])
+        } else {
+            None
+        };
+
+        if arresting_officer_id.is_some() {
+            apply_arrest(&mut residents, offender_idx, victim_idx, amount_taken, &mut city);
+            arrests += 1;
+        }
+
+        let id = ctx.db.crime_event().iter().count() as u32 + 1;
+        ctx.db.crime_event().insert(CrimeEvent {
+            id,
+            city_id,
+            offender_id: residents[offender_idx].id,
+            victim_id: Some(residents[victim_idx].id),
+            location_id: residents[offender_idx].current_location_id,
+            hour: current_hour,
+            crime_type,
+            amount_taken,
+            harm,
+            faction_id,
+            arrested: arresting_officer_id.is_some(),
+            arresting_officer_id,
+        });
+    }
+
+    for (resident, was_changed) in residents.into_iter().zip(changed) {
+        if was_changed {
+            ctx.db.individual().id().update(resident);
+        }
+    }
+
+    if offences > 0 {
+        ctx.db.city().id().update(city);
+        log::info!("City {}: {} offences, {} arrests at hour {}", city_id, offences, arrests, current_hour);
+    }
+
+    Ok(())
+}
+
+/// Offences per 1000 residents over the last week
+pub(crate) fn calculate_crime_rate(ctx: &ReducerContext, city_id: u32, population: u32, current_hour: u64) -> f32 {
+    if population == 0 {
+        return 0.0;
+    }
+
+    let window_start = current_hour.saturating_sub(public_safety::CRIME_RATE_WINDOW_HOURS);
+    let recent_crimes = ctx.db.crime_event().iter()
+        .filter(|c| c.city_id == city_id && c.hour > window_start)
+        .count() as f32;
+
+    recent_crimes / population as f32 * 1000.0
+}
+
+/// Award the ZeroCrime achievement the first time a populated city goes a week without offences
+pub(crate) fn check_zero_crime_achievement(ctx: &ReducerContext, city: &mut City, current_hour: u64) {
+    if city.population == 0 || city.crime_rate > 0.0 {
+        return;
+    }
+
+    let already_achieved = ctx.db.city_achievement().iter()
+        .any(|a| a.city_id == city.id && a.achievement_type == CityAchievementType::ZeroCrime);
+    if already_achieved {
+        return;
+    }
+
+    let prestige_bonus = 50.0;
+    let id = ctx.db.city_achievement().iter().count() as u32 + 1;
+    ctx.db.city_achievement().insert(CityAchievement {
+        id,
+        city_id: city.id,
+        achievement_type: CityAchievementType::ZeroCrime,
+        achieved_hour: current_hour,
+        description: format!("{} went a full week without a single crime", city.name),
+        prestige_bonus,
+    });
+    city.prestige += prestige_bonus;
+
+    log::info!("City {} earned the ZeroCrime achievement", city.id);
+}
+
+/// Individuals employed at a police station in the city
+pub(crate) fn police_officers(ctx: &ReducerContext, city_id: u32) -> Vec<u32> {
+    ctx.db.individual().iter()
+        .filter(|i| {
+            i.workplace_id
+                .and_then(|w| ctx.db.building().id().find(&w))
+                .map(|b| b.city_id == city_id && b.building_type == BuildingType::PoliceStation)
+                .unwrap_or(false)
+        })
+        .map(|i| i.id)
+        .collect()
+}
+
+/// Police coverage 0.0-1.0 from staffing and the city's police service
+pub(crate) fn calculate_police_coverage(ctx: &ReducerContext, city_id: u32, officer_count: usize, population: usize) -> f32 {
+    let officers_needed = (population as f32 / 100.0 * public_safety::POLICE_OFFICERS_PER_100).max(1.0);
+    let staffing = (officer_count as f32 / officers_needed).min(1.0);
+
+    let service_coverage = ctx.db.city_service().iter()
+        .find(|s| s.city_id == city_id && s.service_type == ServiceType::Police)
+        .map(|s| s.coverage / 100.0)
+        .unwrap_or(0.0);
+
+    ((staffing + service_coverage) / 2.0).clamp(0.0, 1.0)
+}
+
+/// Hourly chance that an individual offends
+fn calculate_offence_chance(individual: &Individual, police_coverage: f32) -> f32 {
+    let stress = individual.stress / thresholds::NEED_MAX;
+    let deprivation = 1.0 - (individual.income / thresholds::NEED_MAX).clamp(0.0, 1.0);
+
+    let chance = public_safety::OFFENCE_BASE_CHANCE
+        + stress * public_safety::OFFENCE_STRESS_FACTOR
+        + deprivation * public_safety::OFFENCE_DEPRIVATION_FACTOR;
+
+    chance * (1.0 - police_coverage * public_safety::POLICE_DETERRENCE)
+}
+
+fn choose_crime_type(offender: &Individual) -> CrimeType {
+    if offender.income < thresholds::INCOME_CRITICAL {
+        CrimeType::Theft
+    } else if offender.stress > thresholds::STRESS_CRITICAL {
+        CrimeType::Assault
+    } else if offender.workplace_id.is_some() {
+        CrimeType::Fraud
+    } else {
+        CrimeType::Theft
+    }
+}
+
+/// Theft and assault need someone at the same location; fraud can target anyone in the city
+fn choose_victim(
+    residents: &[Individual],
+    offender_idx: usize,
+    crime_type: &CrimeType,
+    rng: &mut impl Rng,
+) -> Option<usize> {
+    let location_id = residents[offender_idx].current_location_id;
+    let candidates: Vec<usize> = residents.iter()
+        .enumerate()
+        .filter(|(idx, i)| {
+            *idx != offender_idx &&
+            (*crime_type == CrimeType::Fraud || i.current_location_id == location_id)
+        })
+        .map(|(idx, _)| idx)
+        .collect();
+
+    if candidates.is_empty() {
+        None
+    } else {
+        Some(candidates[rng.gen_range(0..candidates.len())])
+    }
+}
+
+/// Returns (amount taken, safety harm) for the crime event record
+fn apply_offence(
+    residents: &mut [Individual],
+    offender_idx: usize,
+    victim_idx: usize,
+    crime_type: &CrimeType,
+) -> (f32, f32) {
+    let (amount_taken, harm) = match crime_type {
+        CrimeType::Theft => (residents[victim_idx].income * public_safety::THEFT_SHARE, 0.0),
+        CrimeType::Fraud => (residents[victim_idx].income * public_safety::FRAUD_SHARE, 0.0),
+        CrimeType::Assault => (0.0, -public_safety::ASSAULT_SAFETY_LOSS),
+    };
+
+    let victim = &mut residents[victim_idx];
+    victim.income = (victim.income - amount_taken).max(0.0);
+    victim.safety = (victim.safety - harm).clamp(0.0, thresholds::NEED_MAX);
+    victim.stress = (victim.stress + public_safety::VICTIM_STRESS_GAIN).clamp(0.0, thresholds::NEED_MAX);
+    if *crime_type == CrimeType::Assault {
+        victim.threat = (victim.threat + public_safety::ASSAULT_THREAT_GAIN).clamp(0.0, thresholds::NEED_MAX);
+    }
+
+    let offender = &mut residents[offender_idx];
+    offender.income = (offender.income + amount_taken).clamp(0.0, thresholds::INCOME_MAX);
+
+    (amount_taken, harm)
+}
+
+/// Returns the criminal faction that profited, if one operates in the city
+fn pay_criminal_faction(ctx: &ReducerContext, city_id: u32, amount_taken: f32, rng: &mut impl Rng) -> Option<u32> {
+    let mut faction = ctx.db.faction().iter()
+        .filter(|f| f.is_active && f.faction_type == FactionType::Criminal && f.base_city_id == city_id)
+        .max_by(|a, b| a.influence.partial_cmp(&b.influence).unwrap())?;
+
+    if rng.gen::<f32>() < public_safety::FACTION_RECRUIT_CHANCE {
+        faction.member_count += 1;
+    }
+    faction.treasury += amount_taken * public_safety::FACTION_CUT * public_safety::FACTION_TREASURY_SCALE;
+
+    let faction_id = faction.id;
+    ctx.db.faction().id().update(faction);
+    Some(faction_id)
+}
+
+/// Arrested offenders return what they took, pay a fine to the city and take a stress hit
+fn apply_arrest(
+    residents: &mut [Individual],
+    offender_idx: usize,
+    victim_idx: usize,
+    amount_taken: f32,
+    city: &mut City,
+) {
+    let offender = &mut residents[offender_idx];
+    let restitution = amount_taken.min(offender.income);
+    offender.income -= restitution;
+
+    let fine = public_safety::ARREST_FINE.min(offender.income);
+    offender.income -= fine;
+    offender.stress = (offender.stress + public_safety::ARREST_STRESS_GAIN).clamp(0.0, thresholds::NEED_MAX);
+    city.tax_reserve += fine;
+
+    let victim = &mut residents[victim_idx];
+    victim.income = (victim.income + restitution).clamp(0.0, thresholds::INCOME_MAX);
+}
\ No newline at end of file
@@ -0,0 +1,476 @@
+use spacetimedb::{ReducerContext, Table};
+use log;
+use rand::Rng;
+use std::collections::HashMap;
+use crate::tables::*;
+use crate::types::*;
+use crate::systems::*;
+use crate::tables::individual::individual;
+use crate::tables::building::{building, building_upgrade};
+use crate::tables::city::city;
+use crate::tables::damage::{disaster_footprint, building_damage, disaster_casualty};
+use crate::tables::events::simulation_time;
+use crate::natural::EventSeverity;
+use crate::natural::disasters::{DisasterType, ResponseType, ResponseStatus, disaster_response};
+use crate::reducers::building::begin_upgrade;
+use crate::reducers::city::local_weather;
+use crate::reducers::preparedness::is_shelter;
+
+/// Strike a city with a disaster: damage buildings inside the footprint, injure people there
+/// and displace residents of destroyed homes. Returns the footprint ID, or None for disasters
+/// that do no structural damage.
+pub(crate) fn apply_disaster_footprint(
+    ctx: &ReducerContext,
+    natural_event_id: u32,
+    city_id: u32,
+    disaster_type: DisasterType,
+    severity: EventSeverity,
+    mitigation_level: f32,
+    hour: u64,
+) -> Option<u32> {
+    let (footprint_share, damage_factor) = structural_profile(disaster_type)?;
+
+    let buildings: Vec<Building> = ctx.db.building().iter()
+        .filter(|b| b.city_id == city_id && !b.destroyed)
+        .collect();
+    if buildings.is_empty() {
+        return None;
+    }
+
+    let mut rng = rand::thread_rng();
+
+    // Epicentre at a random building; radius scales with the city's extent
+    let epicentre = &buildings[rng.gen_range(0..buildings.len())];
+    let (center_x, center_y) = (epicentre.location_x, epicentre.location_y);
+    let extent = buildings.iter()
+        .map(|b| calculate_distance(center_x, center_y, b.location_x, b.location_y))
+        .fold(0.0, f32::max);
+    let radius = extent * footprint_share;
+
+    let mitigation = (mitigation_level / 100.0).clamp(0.0, 1.0);
+    let intensity = severity_intensity(severity) * (1.0 - mitigation * disaster_damage::MITIGATION_EFFECT);
+
+    let footprint_id = ctx.db.disaster_footprint().iter().count() as u32 + 1;
+    let mut footprint = DisasterFootprint {
+        id: footprint_id,
+        natural_event_id,
+        city_id,
+        center_x,
+        center_y,
+        radius,
+        intensity,
+        mitigation_level,
+        hour,
+        buildings_damaged: 0,
+        buildings_destroyed: 0,
+        injured: 0,
+        displaced: 0,
+    };
+
+    // Damage falls off with distance from the epicentre
+    let mut falloff_by_building: HashMap<u32, f32> = HashMap::new();
+    let mut destroyed_homes: Vec<u32> = Vec::new();
+
+    for mut building in buildings {
+        let distance = calculate_distance(center_x, center_y, building.location_x, building.location_y);
+        if distance > radius {
+            continue;
+        }
+
+        let falloff = if radius > 0.0 { 1.0 - distance / radius } else { 1.0 };
+        let damage = disaster_damage::MAX_BUILDING_DAMAGE * intensity * falloff * damage_factor;
+        if damage <= 0.0 {
+            continue;
+        }
+
+        falloff_by_building.insert(building.id, falloff);
+        building.maintenance = (building.maintenance - damage).max(0.0);
+        let destroyed = damage >= disaster_damage::DESTROY_THRESHOLD || building.maintenance <= 0.0;
+
+        if destroyed {
+            building.destroyed = true;
+            building.maintenance = 0.0;
+            footprint.buildings_destroyed += 1;
+            if matches!(building.building_type, BuildingType::Home(_)) {
+                destroyed_homes.push(building.id);
+            }
+        }
+        footprint.buildings_damaged += 1;
+
+        let damage_id = ctx.db.building_damage().iter().count() as u32 + 1;
+        ctx.db.building_damage().insert(BuildingDamage {
+            id: damage_id,
+            footprint_id,
+            building_id: building.id,
+            maintenance_lost: damage,
+            destroyed,
+            repair: RepairStatus::Pending,
+        });
+
+        ctx.db.building().id().update(building);
+    }
+
+    // People inside the footprint may be injured; residents of destroyed homes are displaced
+    let affected: Vec<Individual> = ctx.db.individual().iter()
+        .filter(|i| {
+            falloff_by_building.contains_key(&i.current_location_id) ||
+                i.home_id.map_or(false, |h| destroyed_homes.contains(&h))
+        })
+        .collect();
+
+    for mut person in affected {
+        if let Some(falloff) = falloff_by_building.get(&person.current_location_id) {
+            // Designated shelters are built to take a blow
+            let shelter = if is_shelter(ctx, person.current_location_id) { 1.0 - early_warning::SHELTER_PROTECTION } else { 1.0 };
+            if rng.gen::<f32>() < disaster_damage::INJURY_CHANCE * intensity * falloff * shelter {
+                person.health = (person.health + disaster_damage::INJURY_HEALTH_LOSS).max(0.0);
+                person.safety = (person.safety + disaster_damage::INJURY_SAFETY_LOSS).max(0.0);
+                person.threat = (person.threat + disaster_damage::INJURY_THREAT_GAIN).min(thresholds::NEED_MAX);
+                footprint.injured += 1;
+
+                let casualty_id = ctx.db.disaster_casualty().iter().count() as u32 + 1;
+                ctx.db.disaster_casualty().insert(DisasterCasualty {
+                    id: casualty_id,
+                    footprint_id,
+                    individual_id: person.id,
+                    rescued: false,
+                });
+            }
+        }
+
+        if person.home_id.map_or(false, |h| destroyed_homes.contains(&h)) {
+            person.stress = (person.stress + disaster_damage::DISPLACED_STRESS_GAIN).min(thresholds::NEED_MAX);
+            footprint.displaced += 1;
+        }
+
+        ctx.db.individual().id().update(person);
+    }
+
+    log::info!("{:?} struck city {}: {} buildings damaged ({} destroyed), {} injured, {} displaced",
+        disaster_type, city_id, footprint.buildings_damaged, footprint.buildings_destroyed,
+        footprint.injured, footprint.displaced);
+
+    ctx.db.disaster_footprint().insert(footprint);
+    Some(footprint_id)
+}
+
+/// Send real responders from the city to a disaster footprint.
+/// Returns (personnel dispatched, funds spent, JSON summary of results).
+pub(crate) fn dispatch_response(
+    ctx: &ReducerContext,
+    response_type: ResponseType,
+    footprint_id: u32,
+    hour: u64,
+) -> Result<(u32, f32, String), String> {
+    let footprint = ctx.db.disaster_footprint().id().find(&footprint_id)
+        .ok_or("Disaster footprint not found")?;
+    let mut city = ctx.db.city().id().find(&footprint.city_id)
+        .ok_or("City not found")?;
+
+    let damaged: Vec<BuildingDamage> = ctx.db.building_damage().iter()
+        .filter(|d| d.footprint_id == footprint_id)
+        .collect();
+    let struck: Vec<u32> = damaged.iter().map(|d| d.building_id).collect();
+
+    let sites = match response_type {
+        ResponseType::Evacuation => footprint.displaced.max(1) as usize,
+        ResponseType::SearchAndRescue | ResponseType::MedicalResponse | ResponseType::EmergencyServices => {
+            footprint.injured.max(1) as usize
+        },
+        _ => damaged.len(),
+    };
+
+    let affordable = (city.tax_reserve.max(0.0) / disaster_damage::RESPONDER_COST) as usize;
+    let wanted = (sites * disaster_damage::RESPONDERS_PER_SITE).min(affordable);
+    let responders = available_responders(ctx, footprint.city_id, &struck, wanted);
+    let personnel = responders.len() as u32;
+
+    let results = match response_type {
+        ResponseType::Evacuation => evacuate(ctx, &footprint, &struck, responders, hour),
+        ResponseType::SearchAndRescue | ResponseType::MedicalResponse | ResponseType::EmergencyServices => {
+            rescue(ctx, &footprint, &struck, responders, hour)
+        },
+        _ => begin_repairs(ctx, damaged, responders, hour)?,
+    };
+
+    let cost = personnel as f32 * disaster_damage::RESPONDER_COST;
+    city.tax_reserve -= cost;
+    ctx.db.city().id().update(city);
+
+    log::info!("{:?} dispatched {} responders to footprint {}", response_type, personnel, footprint_id);
+    Ok((personnel, cost, results))
+}
+
+/// Hourly rebuilding pass: keep crews on damaged buildings until their repairs complete
+#[spacetimedb::reducer]
+pub fn process_reconstruction(ctx: &ReducerContext, city_id: u32) -> Result<(), String> {
+    let current_hour = ctx.db.simulation_time().id().find(&1)
+        .ok_or("Simulation not initialized")?
+        .current_hour;
+
+    let footprints: Vec<DisasterFootprint> = ctx.db.disaster_footprint().iter()
+        .filter(|f| f.city_id == city_id)
+        .collect();
+
+    for footprint in footprints {
+        let open: Vec<BuildingDamage> = ctx.db.building_damage().iter()
+            .filter(|d| d.footprint_id == footprint.id && d.repair == RepairStatus::InProgress)
+            .collect();
+
+        let mut still_open = Vec::new();
+        for mut damage in open {
+            if repair_complete(ctx, &damage) {
+                damage.repair = RepairStatus::Repaired;
+                ctx.db.building_damage().id().update(damage);
+            } else {
+                still_open.push(damage.building_id);
+            }
+        }
+
+        // Top up crews on sites that are short-handed
+        for building_id in &still_open {
+            let on_site = ctx.db.individual().iter()
+                .filter(|i| matches!(&i.status, IndividualStatus::Maintaining(data) if data.target_building == Some(*building_id)))
+                .count();
+            let needed = disaster_damage::RESPONDERS_PER_SITE.saturating_sub(on_site);
+            for mut worker in available_responders(ctx, city_id, &[], needed) {
+                assign_shift(&mut worker, *building_id, current_hour);
+                ctx.db.individual().id().update(worker);
+            }
+        }
+
+        let nothing_pending = still_open.is_empty() && !ctx.db.building_damage().iter()
+            .any(|d| d.footprint_id == footprint.id && d.repair == RepairStatus::Pending);
+        if nothing_pending {
+            complete_reconstruction_response(ctx, &footprint, current_hour);
+        }
+    }
+
+    Ok(())
+}
+
+/// Whether the work a damage record called for is finished: a destroyed building must be standing again,
+/// a damaged one must have no repair or rebuild left pending
+fn repair_complete(ctx: &ReducerContext, damage: &BuildingDamage) -> bool {
+    let Some(building) = ctx.db.building().id().find(&damage.building_id) else {
+        return true;
+    };
+    if damage.destroyed {
+        return !building.destroyed;
+    }
+    !ctx.db.building_upgrade().iter().any(|u| u.building_id == damage.building_id &&
+        matches!(u.upgrade_type, UpgradeType::Maintenance | UpgradeType::Reconstruction))
+}
+
+/// (Share of the city's extent covered, structural damage factor); None for disasters that
+/// leave buildings standing
+fn structural_profile(disaster_type: DisasterType) -> Option<(f32, f32)> {
+    match disaster_type {
+        DisasterType::Earthquake => Some((1.0, 1.0)),
+        DisasterType::Volcano => Some((0.8, 1.0)),
+        DisasterType::Tsunami => Some((0.6, 1.0)),
+        DisasterType::Hurricane => Some((1.0, 0.7)),
+        DisasterType::Tornado => Some((0.25, 1.0)),
+        DisasterType::Wildfire => Some((0.5, 0.9)),
+        DisasterType::Flood => Some((0.6, 0.6)),
+        DisasterType::Landslide => Some((0.3, 1.0)),
+        DisasterType::Blizzard => Some((1.0, 0.3)),
+        DisasterType::Meteor => Some((0.3, 1.0)),
+        DisasterType::Heatwave | DisasterType::Drought | DisasterType::Plague => None,
+    }
+}
+
+fn severity_intensity(severity: EventSeverity) -> f32 {
+    match severity {
+        EventSeverity::Minor => 0.2,
+        EventSeverity::Moderate => 0.4,
+        EventSeverity::Major => 0.7,
+        EventSeverity::Catastrophic => 1.0,
+    }
+}
+
+/// Able-bodied adults outside the struck buildings, emergency workers first, then the unemployed
+fn available_responders(ctx: &ReducerContext, city_id: u32, struck: &[u32], wanted: usize) -> Vec<Individual> {
+    if wanted == 0 {
+        return Vec::new();
+    }
+
+    let mut candidates: Vec<(u8, Individual)> = ctx.db.individual().iter()
+        .filter(|i| i.age >= 18 && i.health >= thresholds::HEALTH_CRITICAL && !struck.contains(&i.current_location_id))
+        .filter(|i| matches!(i.status, IndividualStatus::Idle | IndividualStatus::Working(_) | IndividualStatus::Socializing(_)))
+        .filter_map(|i| {
+            let home = i.home_id.and_then(|h| ctx.db.building().id().find(&h))?;
+            if home.city_id != city_id {
+                return None;
+            }
+            let workplace_type = i.workplace_id
+                .and_then(|w| ctx.db.building().id().find(&w))
+                .map(|b| b.building_type);
+            let rank = match workplace_type {
+                Some(BuildingType::PoliceStation) | Some(BuildingType::Hospital) | Some(BuildingType::CityHall) => 0,
+                None => 1,
+                Some(_) => 2,
+            };
+            Some((rank, i))
+        })
+        .collect();
+
+    candidates.sort_by_key(|(rank, _)| *rank);
+    candidates.into_iter().take(wanted).map(|(_, i)| i).collect()
+}
+
+fn assign_shift(worker: &mut Individual, building_id: u32, hour: u64) {
+    worker.status = IndividualStatus::Maintaining(StatusData {
+        until_hour: hour + actions::MAINTAIN_DURATION,
+        target_location: None,
+        target_building: Some(building_id),
+    });
+}
+
+/// Move people out of the footprint to the nearest standing shelter outside it
+fn evacuate(
+    ctx: &ReducerContext,
+    footprint: &DisasterFootprint,
+    struck: &[u32],
+    responders: Vec<Individual>,
+    hour: u64,
+) -> String {
+    if responders.is_empty() {
+        return "{\"evacuated\": 0}".to_string();
+    }
+
+    let shelters: Vec<Building> = ctx.db.building().iter()
+        .filter(|b| b.city_id == footprint.city_id && !b.destroyed && !struck.contains(&b.id))
+        .filter(|b| matches!(b.building_type, BuildingType::School | BuildingType::CityHall | BuildingType::Hospital | BuildingType::Park))
+        .collect();
+    let shelter = shelters.iter().min_by(|a, b| {
+        let da = calculate_distance(footprint.center_x, footprint.center_y, a.location_x, a.location_y);
+        let db = calculate_distance(footprint.center_x, footprint.center_y, b.location_x, b.location_y);
+        da.partial_cmp(&db).unwrap()
+    });
+
+    let shelter = match shelter {
+        Some(shelter) => shelter,
+        None => return "{\"evacuated\": 0, \"shelter\": null}".to_string(),
+    };
+
+    let evacuees: Vec<Individual> = ctx.db.individual().iter()
+        .filter(|i| struck.contains(&i.current_location_id))
+        .collect();
+    let evacuated = evacuees.len();
+    let weather = local_weather(ctx, footprint.city_id, true);
+
+    for mut person in evacuees {
+        let from = ctx.db.building().id().find(&person.current_location_id);
+        let travel_time = from
+            .map(|b| calculate_travel_time(calculate_distance(b.location_x, b.location_y, shelter.location_x, shelter.location_y), &weather))
+            .unwrap_or(actions::MOVE_DURATION);
+        person.status = IndividualStatus::InTransit(StatusData {
+            until_hour: hour + travel_time,
+            target_location: Some(shelter.id),
+            target_building: None,
+        });
+        person.safety = (person.safety + disaster_damage::EVACUATION_SAFETY_GAIN).min(thresholds::NEED_MAX);
+        ctx.db.individual().id().update(person);
+    }
+
+    for mut responder in responders {
+        assign_shift(&mut responder, shelter.id, hour);
+        ctx.db.individual().id().update(responder);
+    }
+
+    format!("{{\"evacuated\": {}, \"shelter\": {}}}", evacuated, shelter.id)
+}
+
+/// Find the injured in the footprint, stabilise them and take them to hospital
+fn rescue(
+    ctx: &ReducerContext,
+    footprint: &DisasterFootprint,
+    struck: &[u32],
+    responders: Vec<Individual>,
+    hour: u64,
+) -> String {
+    let hospital = ctx.db.building().iter()
+        .find(|b| b.city_id == footprint.city_id && !b.destroyed && b.building_type == BuildingType::Hospital);
+
+    // Those the disaster injured, wherever they have since got to
+    let casualties: Vec<DisasterCasualty> = ctx.db.disaster_casualty().iter()
+        .filter(|c| c.footprint_id == footprint.id && !c.rescued)
+        .take(responders.len() * 2)
+        .collect();
+    let rescued = casualties.len();
+
+    for mut casualty in casualties {
+        casualty.rescued = true;
+        let person = ctx.db.individual().id().find(&casualty.individual_id);
+        ctx.db.disaster_casualty().id().update(casualty);
+        let Some(mut person) = person else { continue };
+
+        person.health = (person.health + disaster_damage::RESCUE_HEALTH_GAIN).min(thresholds::NEED_MAX);
+        if let Some(hospital) = &hospital {
+            person.status = IndividualStatus::InTransit(StatusData {
+                until_hour: hour + actions::MOVE_DURATION,
+                target_location: Some(hospital.id),
+                target_building: None,
+            });
+        }
+        ctx.db.individual().id().update(person);
+    }
+
+    if !struck.is_empty() {
+        for (i, mut responder) in responders.into_iter().enumerate() {
+            assign_shift(&mut responder, struck[i % struck.len()], hour);
+            ctx.db.individual().id().update(responder);
+        }
+    }
+
+    format!("{{\"rescued\": {}, \"hospital\": {}}}", rescued,
+        hospital.map(|h| h.id.to_string()).unwrap_or_else(|| "null".to_string()))
+}
+
+/// Start repair or reconstruction on every damaged building and put crews on them
+fn begin_repairs(
+    ctx: &ReducerContext,
+    damaged: Vec<BuildingDamage>,
+    responders: Vec<Individual>,
+    hour: u64,
+) -> Result<String, String> {
+    let mut sites = Vec::new();
+
+    for mut damage in damaged.into_iter().filter(|d| d.repair == RepairStatus::Pending) {
+        // A building still in ruins from an earlier disaster needs rebuilding, not just repair
+        let in_ruins = ctx.db.building().id().find(&damage.building_id).is_some_and(|b| b.destroyed);
+        let upgrade_type = if damage.destroyed || in_ruins { UpgradeType::Reconstruction } else { UpgradeType::Maintenance };
+        begin_upgrade(ctx, damage.building_id, upgrade_type, hour)?;
+        sites.push(damage.building_id);
+
+        damage.repair = RepairStatus::InProgress;
+        ctx.db.building_damage().id().update(damage);
+    }
+
+    let crews = responders.len();
+    if !sites.is_empty() {
+        for (i, mut responder) in responders.into_iter().enumerate() {
+            assign_shift(&mut responder, sites[i % sites.len()], hour);
+            ctx.db.individual().id().update(responder);
+        }
+    }
+
+    Ok(format!("{{\"sites\": {}, \"crews\": {}}}", sites.len(), crews))
+}
+
+fn complete_reconstruction_response(ctx: &ReducerContext, footprint: &DisasterFootprint, hour: u64) {
+    let responses: Vec<_> = ctx.db.disaster_response().iter()
+        .filter(|r| r.disaster_event_id == footprint.natural_event_id && r.region_id == footprint.city_id)
+        .filter(|r| r.response_type == ResponseType::Reconstruction && r.status == ResponseStatus::Active)
+        .collect();
+
+    for mut response in responses {
+        response.status = ResponseStatus::Completed;
+        response.duration_hours = hour.saturating_sub(response.start_hour) as u32;
+        response.results = format!("{{\"rebuilt\": {}, \"destroyed\": {}}}",
+            footprint.buildings_damaged, footprint.buildings_destroyed);
+        ctx.db.disaster_response().id().update(response);
+        log::info!("Reconstruction of city {} after event {} complete", footprint.city_id, footprint.natural_event_id);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,434 @@
+use spacetimedb::{ReducerContext, Table};
+use log;
+use crate::tables::*;
+use crate::types::*;
+use crate::systems::*;
+use crate::tables::individual::{individual, employment};
+use crate::tables::building::{building, workplace_data};
+use crate::tables::city::{city_policy, city_achievement};
+use crate::tables::education::{skill, enrollment};
+use crate::tables::events::simulation_time;
+use crate::reducers::city::{city_residents, local_weather};
+
+const ALL_JOB_TYPES: [JobType; 13] = [
+    JobType::Factory,
+    JobType::Office,
+    JobType::Retail,
+    JobType::Healthcare,
+    JobType::Education,
+    JobType::Research,
+    JobType::Culture,
+    JobType::Utilities,
+    JobType::Government,
+    JobType::Agriculture,
+    JobType::Mining,
+    JobType::Quarrying,
+    JobType::Logging,
+];
+
+/// Enroll a school-age child at a school
+#[spacetimedb::reducer]
+pub fn enroll_in_school(ctx: &ReducerContext, individual_id: u32, school_id: u32) -> Result<(), String> {
+    let current_hour = ctx.db.simulation_time().id().find(&1)
+        .ok_or("Simulation not initialized")?
+        .current_hour;
+
+    let student = ctx.db.individual().id().find(&individual_id)
+        .ok_or("Individual not found")?;
+    if student.age < schooling::SCHOOL_AGE_MIN || student.age > schooling::SCHOOL_AGE_MAX {
+        return Err("Individual is not of school age".to_string());
+    }
+
+    check_school(ctx, school_id)?;
+    end_enrollments(ctx, individual_id);
+    insert_enrollment(ctx, individual_id, school_id, EnrollmentKind::Schooling, current_hour);
+
+    log::info!("Individual {} enrolled at school {}", individual_id, school_id);
+    Ok(())
+}
+
+/// Enroll an adult in evening retraining classes for a job type
+#[spacetimedb::reducer]
+pub fn enroll_in_retraining(
+    ctx: &ReducerContext,
+    individual_id: u32,
+    school_id: u32,
+    job_type: JobType,
+) -> Result<(), String> {
+    let current_hour = ctx.db.simulation_time().id().find(&1)
+        .ok_or("Simulation not initialized")?
+        .current_hour;
+
+    let mut student = ctx.db.individual().id().find(&individual_id)
+        .ok_or("Individual not found")?;
+    if student.age <= schooling::SCHOOL_AGE_MAX {
+        return Err("Retraining is only open to adults".to_string());
+    }
+    if student.income < schooling::RETRAINING_TUITION {
+        return Err("Cannot afford tuition".to_string());
+    }
+
+    check_school(ctx, school_id)?;
+    end_enrollments(ctx, individual_id);
+    insert_enrollment(ctx, individual_id, school_id, EnrollmentKind::Retraining(job_type.clone()), current_hour);
+
+    student.income -= schooling::RETRAINING_TUITION;
+    ctx.db.individual().id().update(student);
+
+    log::info!("Individual {} retraining for {:?} at school {}", individual_id, job_type, school_id);
+    Ok(())
+}
+
+/// Apply for a position at a workplace; requires the job's minimum skill
+#[spacetimedb::reducer]
+pub fn apply_for_job(ctx: &ReducerContext, individual_id: u32, building_id: u32) -> Result<(), String> {
+    let current_hour = ctx.db.simulation_time().id().find(&1)
+        .ok_or("Simulation not initialized")?
+        .current_hour;
+
+    let mut applicant = ctx.db.individual().id().find(&individual_id)
+        .ok_or("Individual not found")?;
+    let workplace = ctx.db.building().id().find(&building_id)
+        .ok_or("Building not found")?;
+
+    let config = match workplace.building_type {
+        BuildingType::Workplace(config) => config,
+        _ => return Err("Building is not a workplace".to_string()),
+    };
+
+    let filled = ctx.db.employment().iter()
+        .filter(|e| e.building_id == building_id && e.is_active)
+        .count() as u32;
+    if filled >= config.positions {
+        return Err("No open positions".to_string());
+    }
+
+    let level = skill_level(ctx, individual_id, &config.job_type);
+    if !is_qualified_for_job(level, &config.job_type) {
+        return Err(format!("Skill {:.0} below the {:.0} required for {:?}",
+            level, required_skill_for_job(&config.job_type), config.job_type));
+    }
+
+    // Leave any current job
+    let current_jobs: Vec<Employment> = ctx.db.employment().iter()
+        .filter(|e| e.individual_id == individual_id && e.is_active)
+        .collect();
+    for mut job in current_jobs {
+        job.is_active = false;
+        job.ended_hour = Some(current_hour);
+        ctx.db.employment().id().update(job);
+    }
+
+    let base_wage = ctx.db.workplace_data().building_id().find(&building_id)
+        .map(|w| w.base_wage)
+        .unwrap_or(5.0);
+    let wage = base_wage * (1.0 + level / schooling::SKILL_MAX * schooling::WAGE_SKILL_BONUS);
+
+    let id = ctx.db.employment().iter().count() as u32 + 1;
+    ctx.db.employment().insert(Employment {
+        id,
+        individual_id,
+        building_id,
+        job_type: config.job_type.clone(),
+        wage,
+        started_hour: current_hour,
+        ended_hour: None,
+        is_active: true,
+    });
+
+    applicant.workplace_id = Some(building_id);
+    ctx.db.individual().id().update(applicant);
+
+    log::info!("Individual {} hired as {:?} at building {}", individual_id, config.job_type, building_id);
+    Ok(())
+}
+
+/// Hourly school pass: students in class build skills according to school quality
+#[spacetimedb::reducer]
+pub fn process_school_hour(ctx: &ReducerContext, school_id: u32) -> Result<(), String> {
+    let current_hour = ctx.db.simulation_time().id().find(&1)
+        .ok_or("Simulation not initialized")?
+        .current_hour;
+
+    let school = ctx.db.building().id().find(&school_id)
+        .ok_or("School not found")?;
+    let quality = calculate_education_quality(ctx, &school);
+
+    let in_class: Vec<u32> = ctx.db.individual().iter()
+        .filter(|i| matches!(&i.status, IndividualStatus::Studying(data) if data.target_building == Some(school_id)))
+        .map(|i| i.id)
+        .collect();
+
+    for individual_id in in_class {
+        let mut place = match active_enrollment(ctx, individual_id) {
+            Some(place) if place.school_id == school_id => place,
+            _ => continue,
+        };
+
+        match &place.kind {
+            EnrollmentKind::Schooling => {
+                for job_type in ALL_JOB_TYPES.iter() {
+                    train_skill(ctx, individual_id, job_type, schooling::SCHOOLING_SKILL_GAIN * quality, current_hour);
+                }
+            },
+            EnrollmentKind::Retraining(job_type) => {
+                train_skill(ctx, individual_id, job_type, schooling::RETRAINING_SKILL_GAIN * quality, current_hour);
+            },
+        }
+
+        place.hours_attended += 1.0;
+        ctx.db.enrollment().id().update(place);
+    }
+
+    Ok(())
+}
+
+/// Send an idle student to class if it is their class time; returns whether they went
+pub(crate) fn attend_class(ctx: &ReducerContext, individual: &mut Individual, current_hour: u64) -> Result<bool, String> {
+    // Hungry, exhausted or sick students stay home
+    if individual.food_water < thresholds::NEED_CRITICAL_LOW ||
+        individual.rest < thresholds::NEED_CRITICAL_LOW ||
+        individual.health < thresholds::HEALTH_CRITICAL {
+        return Ok(false);
+    }
+
+    let place = match active_enrollment(ctx, individual.id) {
+        Some(place) => place,
+        None => return Ok(false),
+    };
+
+    let end_hour = match class_end_hour(&place.kind, current_hour) {
+        Some(end_hour) => end_hour,
+        None => return Ok(false),
+    };
+
+    if individual.current_location_id == place.school_id {
+        individual.status = IndividualStatus::Studying(StatusData {
+            until_hour: end_hour,
+            target_location: None,
+            target_building: Some(place.school_id),
+        });
+        return Ok(true);
+    }
+
+    let from = ctx.db.building().id().find(&individual.current_location_id)
+        .ok_or("Current building not found")?;
+    let school = ctx.db.building().id().find(&place.school_id)
+        .ok_or("School not found")?;
+
+    let distance = calculate_distance(from.location_x, from.location_y, school.location_x, school.location_y);
+    let travel_time = calculate_travel_time(distance, &local_weather(ctx, from.city_id, true));
+
+    individual.status = IndividualStatus::InTransit(StatusData {
+        until_hour: current_hour + travel_time,
+        target_location: Some(place.school_id),
+        target_building: None,
+    });
+    individual.rest += actions::MOVE_REST_COST * travel_time as f32;
+
+    Ok(true)
+}
+
+/// Enroll school-age residents without a place at the nearest school with room, and
+/// release those who have aged out
+pub(crate) fn enroll_school_age_children(ctx: &ReducerContext, city_id: u32, current_hour: u64) {
+    let schools: Vec<Building> = ctx.db.building().iter()
+        .filter(|b| b.city_id == city_id && b.building_type == BuildingType::School)
+        .collect();
+
+    for resident in city_residents(ctx, city_id) {
+        let place = active_enrollment(ctx, resident.id);
+        let school_age = resident.age >= schooling::SCHOOL_AGE_MIN && resident.age <= schooling::SCHOOL_AGE_MAX;
+
+        match place {
+            Some(place) if !school_age && place.kind == EnrollmentKind::Schooling => {
+                end_enrollments(ctx, resident.id);
+                log::info!("Individual {} finished school after {:.0} hours", resident.id, place.hours_attended);
+            },
+            None if school_age => {
+                let home = match resident.home_id.and_then(|h| ctx.db.building().id().find(&h)) {
+                    Some(home) => home,
+                    None => continue,
+                };
+
+                let nearest = schools.iter()
+                    .filter(|s| enrolled_count(ctx, s.id) < s.max_capacity)
+                    .min_by(|a, b| {
+                        let da = calculate_distance(home.location_x, home.location_y, a.location_x, a.location_y);
+                        let db = calculate_distance(home.location_x, home.location_y, b.location_x, b.location_y);
+                        da.partial_cmp(&db).unwrap()
+                    });
+
+                if let Some(school) = nearest {
+                    insert_enrollment(ctx, resident.id, school.id, EnrollmentKind::Schooling, current_hour);
+                }
+            },
+            _ => {},
+        }
+    }
+}
+
+/// Current skill level for a job type (0 if never trained)
+pub(crate) fn skill_level(ctx: &ReducerContext, individual_id: u32, job_type: &JobType) -> f32 {
+    ctx.db.skill().iter()
+        .find(|s| s.individual_id == individual_id && s.job_type == *job_type)
+        .map(|s| s.level)
+        .unwrap_or(0.0)
+}
+
+/// Raise a skill, creating it on first training
+pub(crate) fn train_skill(ctx: &ReducerContext, individual_id: u32, job_type: &JobType, gain: f32, hour: u64) {
+    match ctx.db.skill().iter().find(|s| s.individual_id == individual_id && s.job_type == *job_type) {
+        Some(mut skill) => {
+            skill.level = (skill.level + gain).min(schooling::SKILL_MAX);
+            skill.last_trained_hour = hour;
+            ctx.db.skill().id().update(skill);
+        },
+        None => {
+            let id = ctx.db.skill().iter().count() as u32 + 1;
+            ctx.db.skill().insert(Skill {
+                id,
+                individual_id,
+                job_type: job_type.clone(),
+                level: gain.min(schooling::SKILL_MAX),
+                last_trained_hour: hour,
+            });
+        },
+    }
+}
+
+/// Education quality 0.0-1.0 from Educator staffing and the city's EducationFunding policy
+pub(crate) fn calculate_education_quality(ctx: &ReducerContext, school: &Building) -> f32 {
+    let staff: Vec<Individual> = ctx.db.individual().iter()
+        .filter(|i| i.workplace_id == Some(school.id))
+        .collect();
+    let teachers = staff.len() as f32 + staff.iter()
+        .filter(|i| i.specialized_role == SpecializedRole::Educator)
+        .count() as f32 * schooling::EDUCATOR_ROLE_BONUS;
+
+    let students = enrolled_count(ctx, school.id).max(1) as f32;
+    let staffing = (teachers * schooling::STUDENTS_PER_EDUCATOR / students).min(1.0);
+
+    let funding = ctx.db.city_policy().iter()
+        .find(|p| p.city_id == school.city_id && p.policy_type == PolicyType::EducationFunding)
+        .map(|p| p.intensity.clamp(0.0, 1.0))
+        .unwrap_or(0.0);
+    let funding_factor = schooling::UNFUNDED_QUALITY + (1.0 - schooling::UNFUNDED_QUALITY) * funding;
+
+    (staffing * funding_factor).max(schooling::MIN_QUALITY)
+}
+
+/// Award EducationExcellence once when nearly every child is enrolled at well-run schools
+pub(crate) fn check_education_excellence(ctx: &ReducerContext, city: &mut City, current_hour: u64) {
+    let already_achieved = ctx.db.city_achievement().iter()
+        .any(|a| a.city_id == city.id && a.achievement_type == CityAchievementType::EducationExcellence);
+    if already_achieved {
+        return;
+    }
+
+    let children: Vec<u32> = city_residents(ctx, city.id).into_iter()
+        .filter(|i| i.age >= schooling::SCHOOL_AGE_MIN && i.age <= schooling::SCHOOL_AGE_MAX)
+        .map(|i| i.id)
+        .collect();
+    if children.is_empty() {
+        return;
+    }
+
+    let enrolled = children.iter()
+        .filter(|id| active_enrollment(ctx, **id).is_some())
+        .count() as f32;
+    let enrollment_rate = enrolled / children.len() as f32;
+
+    let schools: Vec<Building> = ctx.db.building().iter()
+        .filter(|b| b.city_id == city.id && b.building_type == BuildingType::School)
+        .collect();
+    let average_quality = schools.iter()
+        .map(|s| calculate_education_quality(ctx, s))
+        .sum::<f32>() / schools.len().max(1) as f32;
+
+    if enrollment_rate < schooling::EXCELLENCE_ENROLLMENT || average_quality < schooling::EXCELLENCE_QUALITY {
+        return;
+    }
+
+    let prestige_bonus = 50.0;
+    let id = ctx.db.city_achievement().iter().count() as u32 + 1;
+    ctx.db.city_achievement().insert(CityAchievement {
+        id,
+        city_id: city.id,
+        achievement_type: CityAchievementType::EducationExcellence,
+        achieved_hour: current_hour,
+        description: format!("{} schools nearly every child with well-staffed teachers", city.name),
+        prestige_bonus,
+    });
+    city.prestige += prestige_bonus;
+
+    log::info!("City {} earned the EducationExcellence achievement", city.id);
+}
+
+/// End of the current class session, if one is running
+fn class_end_hour(kind: &EnrollmentKind, current_hour: u64) -> Option<u64> {
+    let hour_of_day = current_hour % 24;
+    let day_of_week = (current_hour / 24) % 7;
+
+    let (start, end) = match kind {
+        EnrollmentKind::Schooling => {
+            if day_of_week >= schooling::SCHOOL_DAYS_PER_WEEK {
+                return None;
+            }
+            (schooling::SCHOOL_START_HOUR, schooling::SCHOOL_END_HOUR)
+        },
+        EnrollmentKind::Retraining(_) => (schooling::RETRAINING_START_HOUR, schooling::RETRAINING_END_HOUR),
+    };
+
+    if hour_of_day >= start && hour_of_day < end {
+        Some(current_hour - hour_of_day + end)
+    } else {
+        None
+    }
+}
+
+fn check_school(ctx: &ReducerContext, school_id: u32) -> Result<(), String> {
+    let school = ctx.db.building().id().find(&school_id)
+        .ok_or("School not found")?;
+    if school.building_type != BuildingType::School {
+        return Err("Building is not a school".to_string());
+    }
+    if enrolled_count(ctx, school_id) >= school.max_capacity {
+        return Err("School is full".to_string());
+    }
+    Ok(())
+}
+
+fn active_enrollment(ctx: &ReducerContext, individual_id: u32) -> Option<Enrollment> {
+    ctx.db.enrollment().iter()
+        .find(|e| e.individual_id == individual_id && e.is_active)
+}
+
+fn enrolled_count(ctx: &ReducerContext, school_id: u32) -> u32 {
+    ctx.db.enrollment().iter()
+        .filter(|e| e.school_id == school_id && e.is_active)
+        .count() as u32
+}
+
+fn insert_enrollment(ctx: &ReducerContext, individual_id: u32, school_id: u32, kind: EnrollmentKind, hour: u64) {
+    let id = ctx.db.enrollment().iter().count() as u32 + 1;
+    ctx.db.enrollment().insert(Enrollment {
+        id,
+        individual_id,
+        school_id,
+        kind,
+        enrolled_hour: hour,
+        hours_attended: 0.0,
+        is_active: true,
+    });
+}
+
+pub(crate) fn end_enrollments(ctx: &ReducerContext, individual_id: u32) {
+    let current: Vec<Enrollment> = ctx.db.enrollment().iter()
+        .filter(|e| e.individual_id == individual_id && e.is_active)
+        .collect();
+    for mut place in current {
+        place.is_active = false;
+        ctx.db.enrollment().id().update(place);
+    }
+}
\ No newline at end of file
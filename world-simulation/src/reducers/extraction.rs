@@ -0,0 +1,137 @@
+use spacetimedb::{ReducerContext, Table};
+use log;
+use crate::tables::*;
+use crate::types::*;
+use crate::systems::modifiers::*;
+use crate::tables::extraction::extraction_site;
+use crate::tables::building::{building, workplace_data};
+use crate::tables::city::city;
+use crate::tables::individual::employment;
+use crate::tables::events::simulation_time;
+use crate::natural::deposits::{resource_deposit, ResourceDeposit, DepositKind};
+use crate::narrative::{create_narrative_event, EventCategory};
+use crate::reducers::migration::world_for_city;
+use crate::reducers::unrest::on_strike;
+
+/// Daily pass: mines, quarries and logging camps draw down the region's deposits
+#[spacetimedb::reducer]
+pub fn process_city_extraction(ctx: &ReducerContext, city_id: u32) -> Result<(), String> {
+    let current_hour = ctx.db.simulation_time().id().find(&1)
+        .ok_or("Simulation not initialized")?
+        .current_hour;
+
+    let sites: Vec<Building> = ctx.db.building().iter()
+        .filter(|b| b.city_id == city_id && !b.destroyed && is_extraction_site(b))
+        .collect();
+
+    for site in &sites {
+        work_site(ctx, site, current_hour)?;
+    }
+
+    Ok(())
+}
+
+pub(crate) fn is_extraction_site(building: &Building) -> bool {
+    matches!(&building.building_type, BuildingType::Workplace(config)
+        if matches!(config.job_type, JobType::Mining | JobType::Quarrying | JobType::Logging))
+}
+
+/// Start an idle site for a newly built mine, quarry or logging camp
+pub(crate) fn create_extraction_site(ctx: &ReducerContext, building_id: u32, city_id: u32) {
+    ctx.db.extraction_site().insert(ExtractionSite {
+        building_id,
+        city_id,
+        deposit_id: None,
+        extracted_total: 0.0,
+        last_output: 0.0,
+        last_update_hour: 0,
+    });
+}
+
+fn work_site(ctx: &ReducerContext, building: &Building, hour: u64) -> Result<(), String> {
+    let BuildingType::Workplace(config) = &building.building_type else { return Ok(()) };
+    let mut site = match ctx.db.extraction_site().building_id().find(&building.id) {
+        Some(site) => site,
+        None => {
+            create_extraction_site(ctx, building.id, building.city_id);
+            return Ok(());
+        }
+    };
+    let Some(mut output) = ctx.db.workplace_data().building_id().find(&building.id) else { return Ok(()) };
+
+    // Keep working the current deposit, or move on to the easiest one left
+    let current = site.deposit_id
+        .and_then(|id| ctx.db.resource_deposit().id().find(&id))
+        .filter(|d| !d.exhausted && d.quantity >= 1.0);
+    let deposit = current.or_else(|| {
+        ctx.db.resource_deposit().iter()
+            .filter(|d| d.region_id == building.city_id && !d.exhausted && d.quantity >= 1.0)
+            .filter(|d| d.kind.worked_by() == config.job_type)
+            .min_by(|a, b| a.difficulty.partial_cmp(&b.difficulty).unwrap())
+    });
+    site.deposit_id = deposit.as_ref().map(|d| d.id);
+    site.last_output = 0.0;
+    site.last_update_hour = hour;
+
+    let workers = ctx.db.employment().iter()
+        .filter(|e| e.building_id == building.id && e.is_active)
+        .count() as u32;
+
+    if let Some(mut deposit) = deposit {
+        if workers > 0 && !on_strike(ctx, building.id) {
+            let per_worker = match config.job_type {
+                JobType::Quarrying => extractive::QUARRYING_PER_WORKER,
+                JobType::Logging => extractive::LOGGING_PER_WORKER,
+                _ => extractive::MINING_PER_WORKER,
+            };
+            // Seams get deeper and forests thinner as they are worked
+            let depletion = 1.0 - (deposit.quantity / deposit.initial_quantity.max(1.0)).clamp(0.0, 1.0);
+            let ease = (1.0 - deposit.difficulty) * (1.0 - extractive::DEPLETION_SLOWDOWN * depletion);
+            let efficiency = 1.0 + building.efficiency_level as f32 * upgrades::EFFICIENCY_PRODUCTION_BONUS;
+
+            let extracted = (workers as f32 * per_worker * ease * efficiency)
+                .min(deposit.quantity)
+                .min((output.max_inventory - output.inventory).max(0.0));
+
+            deposit.quantity -= extracted;
+            output.inventory += extracted;
+            output.resource_type = match deposit.kind.resource() {
+                crate::economics::ResourceType::Energy => ResourceType::Energy,
+                _ => ResourceType::RawMaterials,
+            };
+            site.last_output = extracted;
+            site.extracted_total += extracted;
+
+            if deposit.quantity < 1.0 && deposit.kind != DepositKind::Timber {
+                deposit.exhausted = true;
+                report_exhausted(ctx, &deposit, building, hour)?;
+            }
+            ctx.db.resource_deposit().id().update(deposit);
+        }
+    }
+
+    ctx.db.workplace_data().building_id().update(output);
+    ctx.db.extraction_site().building_id().update(site);
+    Ok(())
+}
+
+fn report_exhausted(ctx: &ReducerContext, deposit: &ResourceDeposit, building: &Building, hour: u64) -> Result<(), String> {
+    log::info!("{:?} deposit {} worked out by building {} at hour {}", deposit.kind, deposit.id, building.id, hour);
+
+    let Some(world_id) = world_for_city(ctx, building.city_id) else { return Ok(()) };
+    let city_name = ctx.db.city().id().find(&building.city_id)
+        .map(|c| c.name)
+        .unwrap_or_default();
+
+    create_narrative_event(
+        ctx,
+        world_id,
+        1, // Default game ID
+        EventCategory::Economic,
+        format!("{:?} Worked Out", deposit.kind),
+        format!("The last of the {:?} near {} has been brought out; {} must look further afield.",
+            deposit.kind, city_name, building.name),
+        3,
+    )?;
+    Ok(())
+}
\ No newline at end of file
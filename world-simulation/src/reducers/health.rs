@@ -0,0 +1,399 @@
+use spacetimedb::{ReducerContext, Table};
+use log;
+use rand::Rng;
+use std::collections::{HashMap, HashSet};
+use crate::tables::*;
+use crate::types::*;
+use crate::systems::modifiers::*;
+use crate::tables::individual::{individual, employment};
+use crate::tables::building::building;
+use crate::tables::health::{illness, epidemic_snapshot};
+use crate::tables::events::simulation_time;
+use crate::reducers::city::city_residents;
+use crate::reducers::public_works::sanitation_level;
+
+// How each disease behaves
+struct DiseaseProfile {
+    incubation_hours: u64,
+    transmission_chance: f32,   // Per contagious contact per hour
+    base_severity: f32,
+    recovery_hours: f32,
+    contagious_while_incubating: bool,
+    sporadic_chance: f32,       // Per person per hour, without contact
+}
+
+fn disease_profile(disease: &DiseaseType) -> DiseaseProfile {
+    match disease {
+        DiseaseType::Cold => DiseaseProfile {
+            incubation_hours: 24,
+            transmission_chance: 0.05,
+            base_severity: 10.0,
+            recovery_hours: 72.0,
+            contagious_while_incubating: false,
+            sporadic_chance: 0.0002,
+        },
+        DiseaseType::Fever => DiseaseProfile {
+            incubation_hours: 48,
+            transmission_chance: 0.04,
+            base_severity: 30.0,
+            recovery_hours: 120.0,
+            contagious_while_incubating: false,
+            sporadic_chance: 0.00005,
+        },
+        DiseaseType::Dysentery => DiseaseProfile {
+            incubation_hours: 24,
+            transmission_chance: 0.02,
+            base_severity: 40.0,
+            recovery_hours: 96.0,
+            contagious_while_incubating: false,
+            sporadic_chance: 0.0,   // Only from poor sanitation, see below
+        },
+        DiseaseType::Plague => DiseaseProfile {
+            incubation_hours: 72,
+            transmission_chance: 0.08,
+            base_severity: 70.0,
+            recovery_hours: 240.0,
+            contagious_while_incubating: true,
+            sporadic_chance: 0.0,   // Only seeded by plague events
+        },
+    }
+}
+
+const ALL_DISEASES: [DiseaseType; 4] = [
+    DiseaseType::Cold,
+    DiseaseType::Fever,
+    DiseaseType::Dysentery,
+    DiseaseType::Plague,
+];
+
+/// Hourly health pass for a city: incubation, spread, symptoms, treatment and recovery
+#[spacetimedb::reducer]
+pub fn process_city_health(ctx: &ReducerContext, city_id: u32) -> Result<(), String> {
+    let current_hour = ctx.db.simulation_time().id().find(&1)
+        .ok_or("Simulation not initialized")?
+        .current_hour;
+
+    let residents = city_residents(ctx, city_id);
+    if residents.is_empty() {
+        return Ok(());
+    }
+
+    let mut rng = rand::thread_rng();
+    let sanitation = sanitation_level(ctx, city_id);
+
+    // Advance existing cases
+    let active: Vec<Illness> = ctx.db.illness().iter()
+        .filter(|i| i.city_id == city_id && i.stage != IllnessStage::Recovered)
+        .collect();
+
+    let mut sick: HashSet<u32> = HashSet::new();
+    let mut contagious: HashMap<(u32, DiseaseType), u32> = HashMap::new();
+    let mut symptom_severity: HashMap<u32, f32> = HashMap::new();
+
+    for mut case in active {
+        let person = match residents.iter().find(|r| r.id == case.individual_id) {
+            Some(person) => person,
+            None => continue, // Not housed here; migration hands cases to the new city on arrival
+        };
+        let profile = disease_profile(&case.disease);
+
+        if case.stage == IllnessStage::Incubating && current_hour >= case.symptoms_hour {
+            case.stage = IllnessStage::Symptomatic;
+        }
+
+        if case.stage == IllnessStage::Symptomatic {
+            // Treatment in a hospital speeds recovery and lowers severity
+            let recovery_rate = match hospital_at(ctx, person.current_location_id, city_id) {
+                Some(hospital_id) => {
+                    case.treated_at = Some(hospital_id);
+                    case.severity = (case.severity + disease::TREATMENT_SEVERITY_REDUCTION).max(0.0);
+                    disease::TREATMENT_RECOVERY_MULTIPLIER * (1.0 + hospital_staff_bonus(ctx, hospital_id))
+                },
+                None => 1.0,
+            };
+
+            case.recovery_progress += recovery_rate;
+            if case.recovery_progress >= profile.recovery_hours {
+                case.stage = IllnessStage::Recovered;
+                log::info!("Individual {} recovered from {:?}", case.individual_id, case.disease);
+            } else {
+                symptom_severity.insert(case.individual_id, case.severity);
+            }
+        }
+
+        if case.stage != IllnessStage::Recovered {
+            sick.insert(case.individual_id);
+            let is_contagious = case.stage == IllnessStage::Symptomatic || profile.contagious_while_incubating;
+            if is_contagious {
+                *contagious.entry((person.current_location_id, case.disease.clone())).or_insert(0) += 1;
+            }
+        }
+
+        ctx.db.illness().id().update(case);
+    }
+
+    // Spread between co-located people and sporadic infections
+    let mut new_cases = 0;
+    for person in &residents {
+        if sick.contains(&person.id) {
+            continue;
+        }
+
+        for disease in ALL_DISEASES.iter() {
+            if is_immune(ctx, person.id, disease) {
+                continue;
+            }
+
+            let profile = disease_profile(disease);
+            let contacts = contagious.get(&(person.current_location_id, disease.clone())).copied().unwrap_or(0);
+            let contact_chance = 1.0 - (1.0 - profile.transmission_chance).powi(contacts as i32);
+
+            let sporadic_chance = match disease {
+                DiseaseType::Dysentery if person.waste > thresholds::WASTE_CRITICAL => 0.001,
+                _ => profile.sporadic_chance,
+            };
+            // Clean water and drains keep dysentery out
+            let sporadic_chance = if *disease == DiseaseType::Dysentery {
+                sporadic_chance * (1.0 - sanitation * disease::SANITATION_PROTECTION)
+            } else {
+                sporadic_chance
+            };
+
+            if rng.gen::<f32>() < contact_chance + sporadic_chance {
+                let source = if contacts > 0 {
+                    find_contagious_contact(ctx, &residents, person.current_location_id, disease)
+                } else {
+                    None
+                };
+                infect(ctx, person.id, city_id, disease.clone(), source, current_hour, &mut rng);
+                sick.insert(person.id);
+                new_cases += 1;
+                break;
+            }
+        }
+    }
+
+    // Health follows symptoms
+    for mut person in residents {
+        let before = person.health;
+        match symptom_severity.get(&person.id) {
+            Some(severity) => {
+                person.health += severity * disease::SYMPTOM_HEALTH_LOSS;
+                person.rest = (person.rest + disease::SYMPTOM_REST_LOSS).clamp(0.0, thresholds::NEED_MAX);
+                person.stress = (person.stress + disease::SYMPTOM_STRESS_GAIN).clamp(0.0, thresholds::NEED_MAX);
+            },
+            None => person.health += disease::HEALTH_RECOVERY,
+        }
+        person.health = person.health.clamp(0.0, thresholds::NEED_MAX);
+
+        if person.health != before || symptom_severity.contains_key(&person.id) {
+            ctx.db.individual().id().update(person);
+        }
+    }
+
+    if new_cases > 0 {
+        log::info!("City {}: {} new infections at hour {}", city_id, new_cases, current_hour);
+    }
+
+    Ok(())
+}
+
+/// Manually seed an outbreak in a city
+#[spacetimedb::reducer]
+pub fn start_outbreak(ctx: &ReducerContext, city_id: u32, disease: DiseaseType, cases: u32) -> Result<(), String> {
+    let current_hour = ctx.db.simulation_time().id().find(&1)
+        .ok_or("Simulation not initialized")?
+        .current_hour;
+
+    let infected = seed_outbreak(ctx, city_id, disease, cases, current_hour);
+    log::info!("Seeded {} cases in city {}", infected, city_id);
+    Ok(())
+}
+
+/// Infect up to `cases` healthy residents of a city; returns how many were infected
+pub(crate) fn seed_outbreak(ctx: &ReducerContext, city_id: u32, disease: DiseaseType, cases: u32, hour: u64) -> u32 {
+    let mut rng = rand::thread_rng();
+    let mut candidates: Vec<u32> = city_residents(ctx, city_id).into_iter()
+        .map(|i| i.id)
+        .filter(|id| !is_sick(ctx, *id) && !is_immune(ctx, *id, &disease))
+        .collect();
+
+    let mut infected = 0;
+    while infected < cases && !candidates.is_empty() {
+        let idx = rng.gen_range(0..candidates.len());
+        let individual_id = candidates.swap_remove(idx);
+        infect(ctx, individual_id, city_id, disease.clone(), None, hour, &mut rng);
+        infected += 1;
+    }
+
+    infected
+}
+
+/// Seed plague cases in each affected city, scaled by its population; returns total infected
+pub(crate) fn seed_plague(ctx: &ReducerContext, city_ids: &[u32], hour: u64) -> u32 {
+    city_ids.iter()
+        .map(|&city_id| {
+            let population = city_residents(ctx, city_id).len() as f32;
+            let cases = ((population * disease::PLAGUE_SEED_SHARE) as u32).max(disease::PLAGUE_MIN_SEED);
+            seed_outbreak(ctx, city_id, DiseaseType::Plague, cases, hour)
+        })
+        .sum()
+}
+
+/// Daily epidemic curve snapshot for every disease present in the city
+#[spacetimedb::reducer]
+pub fn record_epidemic_curve(ctx: &ReducerContext, city_id: u32) -> Result<(), String> {
+    let current_hour = ctx.db.simulation_time().id().find(&1)
+        .ok_or("Simulation not initialized")?
+        .current_hour;
+
+    let population = city_residents(ctx, city_id).len() as u32;
+
+    for disease in ALL_DISEASES.iter() {
+        let cases: Vec<Illness> = ctx.db.illness().iter()
+            .filter(|i| i.city_id == city_id && i.disease == *disease)
+            .collect();
+        if cases.is_empty() {
+            continue;
+        }
+
+        let last_snapshot_hour = ctx.db.epidemic_snapshot().iter()
+            .filter(|s| s.city_id == city_id && s.disease == *disease)
+            .map(|s| s.hour)
+            .max();
+
+        let count_stage = |stage: IllnessStage| cases.iter().filter(|c| c.stage == stage).count() as u32;
+        let incubating = count_stage(IllnessStage::Incubating);
+        let symptomatic = count_stage(IllnessStage::Symptomatic);
+        let recovered = count_stage(IllnessStage::Recovered);
+        let new_cases = cases.iter()
+            .filter(|c| last_snapshot_hour.map_or(true, |h| c.infected_hour > h))
+            .count() as u32;
+
+        let id = ctx.db.epidemic_snapshot().iter().count() as u32 + 1;
+        ctx.db.epidemic_snapshot().insert(EpidemicSnapshot {
+            id,
+            city_id,
+            disease: disease.clone(),
+            hour: current_hour,
+            susceptible: population.saturating_sub(incubating + symptomatic + recovered),
+            incubating,
+            symptomatic,
+            recovered,
+            new_cases,
+        });
+    }
+
+    Ok(())
+}
+
+/// Log the epidemic curve for a disease in a city
+#[spacetimedb::reducer]
+pub fn get_epidemic_curve(ctx: &ReducerContext, city_id: u32, disease: DiseaseType) -> Result<(), String> {
+    let mut snapshots: Vec<EpidemicSnapshot> = ctx.db.epidemic_snapshot().iter()
+        .filter(|s| s.city_id == city_id && s.disease == disease)
+        .collect();
+    snapshots.sort_by_key(|s| s.hour);
+
+    let mut report = format!("{:?} epidemic curve for city {}:\n", disease, city_id);
+    for s in &snapshots {
+        report.push_str(&format!("Day {}: {} new, {} incubating, {} sick, {} recovered, {} susceptible\n",
+            s.hour / 24, s.new_cases, s.incubating, s.symptomatic, s.recovered, s.susceptible));
+    }
+
+    log::info!("{}", report);
+    Ok(())
+}
+
+/// Average health of a city's residents
+pub(crate) fn calculate_city_health(residents: &[Individual]) -> f32 {
+    if residents.is_empty() {
+        return 100.0;
+    }
+    residents.iter().map(|i| i.health).sum::<f32>() / residents.len() as f32
+}
+
+/// Move an individual's cases to the city they now live in, so they progress, recover and spread there
+pub(crate) fn relocate_cases(ctx: &ReducerContext, individual_id: u32, city_id: u32) {
+    let cases: Vec<Illness> = ctx.db.illness().iter()
+        .filter(|i| i.individual_id == individual_id && i.city_id != city_id)
+        .collect();
+    for mut case in cases {
+        case.city_id = city_id;
+        case.treated_at = None;
+        ctx.db.illness().id().update(case);
+    }
+}
+
+fn infect(
+    ctx: &ReducerContext,
+    individual_id: u32,
+    city_id: u32,
+    disease: DiseaseType,
+    source_individual_id: Option<u32>,
+    hour: u64,
+    rng: &mut impl Rng,
+) {
+    let profile = disease_profile(&disease);
+    let severity = (profile.base_severity * rng.gen_range(0.7..1.3)).clamp(0.0, 100.0);
+
+    let id = ctx.db.illness().iter().count() as u32 + 1;
+    ctx.db.illness().insert(Illness {
+        id,
+        individual_id,
+        city_id,
+        disease,
+        stage: IllnessStage::Incubating,
+        severity,
+        infected_hour: hour,
+        symptoms_hour: hour + profile.incubation_hours,
+        recovery_progress: 0.0,
+        source_individual_id,
+        treated_at: None,
+    });
+}
+
+fn is_sick(ctx: &ReducerContext, individual_id: u32) -> bool {
+    ctx.db.illness().iter()
+        .any(|i| i.individual_id == individual_id && i.stage != IllnessStage::Recovered)
+}
+
+/// Recovering from a disease grants immunity to it (colds excepted)
+fn is_immune(ctx: &ReducerContext, individual_id: u32, disease: &DiseaseType) -> bool {
+    *disease != DiseaseType::Cold && ctx.db.illness().iter()
+        .any(|i| i.individual_id == individual_id && i.disease == *disease && i.stage == IllnessStage::Recovered)
+}
+
+fn find_contagious_contact(
+    ctx: &ReducerContext,
+    residents: &[Individual],
+    location_id: u32,
+    disease: &DiseaseType,
+) -> Option<u32> {
+    residents.iter()
+        .filter(|r| r.current_location_id == location_id)
+        .find(|r| ctx.db.illness().iter().any(|i| {
+            i.individual_id == r.id && i.disease == *disease && i.stage != IllnessStage::Recovered
+        }))
+        .map(|r| r.id)
+}
+
+fn hospital_at(ctx: &ReducerContext, building_id: u32, city_id: u32) -> Option<u32> {
+    ctx.db.building().id().find(&building_id)
+        .filter(|b| b.city_id == city_id && b.building_type == BuildingType::Hospital)
+        .map(|b| b.id)
+}
+
+/// Recovery bonus from Healers and healthcare workers employed at the hospital
+fn hospital_staff_bonus(ctx: &ReducerContext, hospital_id: u32) -> f32 {
+    let healers = ctx.db.individual().iter()
+        .filter(|i| i.workplace_id == Some(hospital_id) && i.specialized_role == SpecializedRole::Healer)
+        .count() as f32;
+
+    let healthcare_workers = ctx.db.employment().iter()
+        .filter(|e| e.building_id == hospital_id && e.is_active && e.job_type == JobType::Healthcare)
+        .count() as f32;
+
+    (healers * disease::HEALER_BONUS + healthcare_workers * disease::HEALTHCARE_STAFF_BONUS)
+        .min(disease::MAX_STAFF_BONUS)
+}
@@ -7,6 +7,13 @@ use crate::tables::individual::individual;
 use crate::tables::building::building;
 use crate::tables::events::{simulation_time, movement_event, need_fulfillment_event, work_event};
 use crate::tables::individual::employment;
+use crate::reducers::education::{attend_class, skill_level, train_skill};
+use crate::reducers::building::contribute_upgrade_work;
+use crate::reducers::city::local_weather;
+use crate::reducers::storage::serve_meal;
+use crate::reducers::budget::{tax_rates, accrue_revenue};
+use crate::reducers::unrest::on_strike;
+use crate::reducers::preparedness::heed_warning;
 
 /// Create a new individual
 #[spacetimedb::reducer]
@@ -23,6 +30,13 @@ pub fn create_individual(
     // Generate ID (in production, use proper ID generation)
     let id = (ctx.db.individual().iter().count() + 1) as u32;
     
+    // Staff take the job their building does; skill requirements apply to later hiring
+    let job = workplace_id.and_then(|building_id| {
+        let building = ctx.db.building().id().find(&building_id)?;
+        staff_job_type(&building.building_type).map(|job_type| (building_id, job_type))
+    });
+    let workplace_id = job.as_ref().map(|(building_id, _)| *building_id);
+    
     // Get starting location
     let location_id = home_id.unwrap_or(1); // Default to location 1 if no home
     
@@ -40,6 +54,7 @@ pub fn create_individual(
         intimacy: 50.0,
         rest: 80.0,
         waste: 20.0,
+        health: 100.0,
         
         // Level 2 needs
         threat: 20.0,
@@ -65,13 +80,13 @@ pub fn create_individual(
     ctx.db.individual().insert(individual);
     
     // If has workplace, create employment record
-    if let Some(workplace_id) = workplace_id {
+    if let Some((workplace_id, job_type)) = job {
         let employment_id = (ctx.db.employment().iter().count() + 1) as u32;
         ctx.db.employment().insert(Employment {
             id: employment_id,
             individual_id: id,
             building_id: workplace_id,
-            job_type: JobType::Office,
+            job_type,
             wage: 5.0,
             started_hour: current_hour,
             ended_hour: None,
@@ -101,17 +116,33 @@ pub fn update_individual_needs(ctx: &ReducerContext, individual_id: u32) -> Resu
     // Get current location capabilities
     let location = get_location_for_building(ctx, individual.current_location_id)?;
     
-    // Update needs based on time and location
-    individual.update_needs(hours_passed, &location);
+    // Read the weather of the individual's own region; travellers and park-goers are out in it
+    let current_building = ctx.db.building().id().find(&individual.current_location_id)
+        .ok_or("Current building not found")?;
+    let outdoors = matches!(individual.status, IndividualStatus::InTransit(_)) ||
+        matches!(current_building.building_type, BuildingType::Park);
+    let weather = local_weather(ctx, current_building.city_id, outdoors);
+    
+    // Update needs based on time, location and weather
+    individual.update_needs(hours_passed, &location, &weather);
     
     // Check if any status has expired
     match &individual.status {
+        IndividualStatus::Maintaining(status_data) => {
+            if status_data.until_hour <= current_hour {
+                // Finished shifts go towards any upgrade or rebuild at the building
+                if let Some(building_id) = status_data.target_building {
+                    contribute_upgrade_work(ctx, building_id, actions::MAINTAIN_DURATION as f32);
+                }
+                individual.status = IndividualStatus::Idle;
+            }
+        },
         IndividualStatus::Working(status_data) |
         IndividualStatus::Sleeping(status_data) |
         IndividualStatus::Eating(status_data) |
         IndividualStatus::Socializing(status_data) |
-        IndividualStatus::Maintaining(status_data) |
-        IndividualStatus::UsingFacilities(status_data) => {
+        IndividualStatus::UsingFacilities(status_data) |
+        IndividualStatus::Studying(status_data) => {
             if status_data.until_hour <= current_hour {
                 individual.status = IndividualStatus::Idle;
             }
@@ -130,8 +161,10 @@ pub fn update_individual_needs(ctx: &ReducerContext, individual_id: u32) -> Resu
         _ => {},
     }
     
-    // If idle, check for pressing needs
-    if matches!(individual.status, IndividualStatus::Idle) {
+    // If idle, act on any disaster warning, then go to class when scheduled, otherwise check for pressing needs
+    if matches!(individual.status, IndividualStatus::Idle) &&
+        !heed_warning(ctx, &mut individual, current_hour)? &&
+        !attend_class(ctx, &mut individual, current_hour)? {
         if let Some((need, _priority)) = individual.get_most_pressing_need() {
             handle_pressing_need(ctx, &mut individual, need, current_hour)?;
         }
@@ -159,6 +192,8 @@ fn handle_pressing_need(
         .find(|b| b.id == individual.current_location_id)
         .ok_or("Current building not found")?;
     
+    let weather = local_weather(ctx, current_building.city_id, false);
+    
     // Find best location for need
     if let Some((target_building_id, _score)) = find_best_location_for_need(
         individual,
@@ -167,6 +202,7 @@ fn handle_pressing_need(
         &locations,
         current_building.location_x,
         current_building.location_y,
+        &weather,
     ) {
         // If at target location, perform action
         if target_building_id == individual.current_location_id {
@@ -186,7 +222,7 @@ fn handle_pressing_need(
                 target_building.location_y,
             );
             
-            let travel_time = calculate_travel_time(distance);
+            let travel_time = calculate_travel_time(distance, &weather);
             
             individual.status = IndividualStatus::InTransit(StatusData {
                 until_hour: current_hour + travel_time,
@@ -210,6 +246,15 @@ fn perform_action(
     current_hour: u64,
 ) -> Result<(), String> {
     match action {
+        IndividualAction::Work if individual.workplace_id.map_or(false, |id| on_strike(ctx, id)) => {
+            // Strikers picket the workplace instead: no wages, no output
+            individual.status = IndividualStatus::Socializing(StatusData {
+                until_hour: current_hour + actions::WORK_DURATION,
+                target_location: None,
+                target_building: individual.workplace_id,
+            });
+            individual.community += disorder::PICKET_COMMUNITY_GAIN;
+        },
         IndividualAction::Work => {
             individual.status = IndividualStatus::Working(StatusData {
                 until_hour: current_hour + actions::WORK_DURATION,
@@ -218,11 +263,32 @@ fn perform_action(
             });
             individual.rest += actions::WORK_REST_COST;
             individual.stress += actions::WORK_STRESS_GAIN;
-            individual.income += actions::WORK_INCOME_GAIN;
             
-            // Log work event
+            // Income tax is withheld by the city the workplace is in
+            let income_tax = individual.workplace_id
+                .and_then(|id| ctx.db.building().id().find(&id))
+                .map(|workplace| {
+                    let tax = actions::WORK_INCOME_GAIN * tax_rates(ctx, workplace.city_id).income;
+                    accrue_revenue(ctx, workplace.city_id, RevenueLine::IncomeTax, tax, current_hour);
+                    tax
+                })
+                .unwrap_or(0.0);
+            individual.income += actions::WORK_INCOME_GAIN - income_tax;
+            
+            // Log work event; skill sets productivity and grows with practice
             if let Some(workplace_id) = individual.workplace_id {
-                log_work_event(ctx, individual.id, workplace_id, current_hour, actions::WORK_DURATION as f32);
+                let job_type = ctx.db.employment().iter()
+                    .find(|e| e.individual_id == individual.id && e.building_id == workplace_id && e.is_active)
+                    .map(|e| e.job_type);
+                let productivity = match &job_type {
+                    Some(job_type) => calculate_productivity(individual, skill_level(ctx, individual.id, job_type)),
+                    None => calculate_productivity(individual, 0.0),
+                };
+                if let Some(job_type) = &job_type {
+                    train_skill(ctx, individual.id, job_type,
+                        schooling::WORK_SKILL_GAIN * actions::WORK_DURATION as f32, current_hour);
+                }
+                log_work_event(ctx, individual.id, workplace_id, current_hour, actions::WORK_DURATION as f32, productivity);
             }
         },
         IndividualAction::Sleep => {
@@ -239,8 +305,17 @@ fn perform_action(
                 target_location: None,
                 target_building: None,
             });
-            individual.food_water += actions::EAT_FOOD_GAIN;
-            individual.income -= 5.0; // Cost of meal
+            // Meals come out of the city's food warehouse; empty stores mean scraps, rationing may mean a queue
+            let portion = match ctx.db.building().id().find(&individual.current_location_id) {
+                Some(location) => serve_meal(ctx, location.city_id, current_hour),
+                None => 1.0,
+            };
+            if portion > 0.0 {
+                individual.food_water += actions::EAT_FOOD_GAIN * portion;
+                individual.income -= 5.0; // Cost of meal
+            } else {
+                individual.stress += warehousing::RATION_QUEUE_STRESS;
+            }
         },
         IndividualAction::Socialize => {
             individual.status = IndividualStatus::Socializing(StatusData {
@@ -333,7 +408,7 @@ fn log_need_fulfillment(ctx: &ReducerContext, individual_id: u32, location_id: u
     });
 }
 
-fn log_work_event(ctx: &ReducerContext, individual_id: u32, building_id: u32, hour: u64, hours: f32) {
+fn log_work_event(ctx: &ReducerContext, individual_id: u32, building_id: u32, hour: u64, hours: f32, productivity: f32) {
     let id = (ctx.db.work_event().iter().count() + 1) as u32;
     ctx.db.work_event().insert(WorkEvent {
         id,
@@ -342,9 +417,9 @@ fn log_work_event(ctx: &ReducerContext, individual_id: u32, building_id: u32, ho
         hour,
         hours_worked: hours,
         wage_earned: hours * 5.0,
-        productivity: 1.0,
+        productivity,
         resources_consumed: 5.0,
-        resources_produced: 10.0,
+        resources_produced: 10.0 * productivity,
     });
 }
 
@@ -0,0 +1,406 @@
+use spacetimedb::{ReducerContext, Table};
+use log;
+use std::collections::HashSet;
+use crate::tables::*;
+use crate::types::*;
+use crate::systems::*;
+use crate::tables::individual::{individual, employment, relationship};
+use crate::tables::building::building;
+use crate::tables::city::city;
+use crate::tables::migration::{migration_event, migration_pressure};
+use crate::tables::events::simulation_time;
+use crate::world::game_world::{game_world, world_stats};
+use crate::natural::{climate_state, natural_event, seasonal_effect};
+use crate::narrative::{create_narrative_event, EventCategory};
+use crate::reducers::city::city_residents;
+use crate::reducers::education::end_enrollments;
+use crate::reducers::health::relocate_cases;
+
+/// Weekly migration pass for a city: residents build pressure to leave and, once it is
+/// high enough, move their household to a better city
+#[spacetimedb::reducer]
+pub fn evaluate_migration(ctx: &ReducerContext, city_id: u32) -> Result<(), String> {
+    let current_hour = ctx.db.simulation_time().id().find(&1)
+        .ok_or("Simulation not initialized")?
+        .current_hour;
+
+    let home_city = ctx.db.city().id().find(&city_id)
+        .ok_or("City not found")?;
+
+    let world_id = world_for_city(ctx, city_id);
+    let tendency = world_id.map(|w| seasonal_migration_tendency(ctx, w)).unwrap_or(1.0);
+    let disaster_exposed = recent_disaster(ctx, city_id, current_hour);
+
+    let home_score = city_attractiveness(ctx, &home_city);
+    let home_centre = city_centre(ctx, city_id);
+    let destinations: Vec<(u32, f32, (f32, f32))> = ctx.db.city().iter()
+        .filter(|c| c.id != city_id)
+        .map(|c| (c.id, city_attractiveness(ctx, &c), city_centre(ctx, c.id)))
+        .collect();
+
+    let residents = city_residents(ctx, city_id);
+    let mut moved: HashSet<u32> = in_transit(ctx);
+    let mut disaster_departures = 0;
+
+    for resident in residents.iter().filter(|r| r.age >= 18) {
+        if moved.contains(&resident.id) {
+            continue;
+        }
+
+        // Build or relieve pressure to leave
+        let (push, reason) = push_factors(resident, disaster_exposed);
+        let existing = ctx.db.migration_pressure().individual_id().find(&resident.id);
+        let is_new = existing.is_none();
+        let mut pressure = existing.unwrap_or(MigrationPressure {
+            individual_id: resident.id,
+            pressure: 0.0,
+            main_reason: MigrationReason::UnmetNeeds,
+            last_evaluated_hour: current_hour,
+        });
+
+        if push > 0.0 {
+            pressure.pressure += push * tendency;
+            pressure.main_reason = reason;
+        } else {
+            pressure.pressure = (pressure.pressure - relocation::PRESSURE_DECAY).max(0.0);
+        }
+        pressure.last_evaluated_hour = current_hour;
+
+        if pressure.pressure < relocation::PRESSURE_THRESHOLD {
+            save_pressure(ctx, pressure, is_new);
+            continue;
+        }
+
+        // Pick the best reachable city that is clearly better than home and has room
+        let household = household_members(ctx, resident, &residents, &moved);
+        let best = destinations.iter()
+            .map(|(id, score, centre)| {
+                let distance = calculate_distance(home_centre.0, home_centre.1, centre.0, centre.1);
+                (*id, score - distance * relocation::DISTANCE_PENALTY, distance)
+            })
+            .filter(|(_, score, _)| *score > home_score + relocation::MIN_IMPROVEMENT)
+            .filter_map(|(id, score, distance)| {
+                find_vacant_home(ctx, id, household.len() as u32).map(|home| (id, score, distance, home))
+            })
+            .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
+
+        let (to_city_id, _, distance, to_home_id) = match best {
+            Some(best) => best,
+            None => {
+                save_pressure(ctx, pressure, is_new);
+                continue;
+            },
+        };
+
+        let travel_hours = ((distance * relocation::TRAVEL_HOURS_PER_UNIT) as u64).max(relocation::MIN_TRAVEL_HOURS);
+        let reason = pressure.main_reason.clone();
+
+        for member_id in &household {
+            depart(ctx, *member_id, resident.id, city_id, to_city_id, to_home_id, reason.clone(),
+                current_hour, current_hour + travel_hours);
+            moved.insert(*member_id);
+        }
+
+        if reason == MigrationReason::Disaster {
+            disaster_departures += household.len() as u32;
+        }
+
+        pressure.pressure = 0.0;
+        save_pressure(ctx, pressure, is_new);
+
+        log::info!("Household of {} ({} people) leaving city {} for city {} ({:?})",
+            resident.id, household.len(), city_id, to_city_id, reason);
+    }
+
+    // A wave of departures after a disaster becomes part of the story
+    if disaster_departures >= relocation::EXODUS_NARRATIVE_THRESHOLD {
+        if let Some(world_id) = world_id {
+            create_narrative_event(
+                ctx,
+                world_id,
+                1, // Default game ID
+                EventCategory::Social,
+                format!("Exodus from {}", home_city.name),
+                format!("{} people have fled {} in the wake of disaster, seeking safety elsewhere",
+                    disaster_departures, home_city.name),
+                5,
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Hourly pass: travellers who reach their destination settle into their new homes
+#[spacetimedb::reducer]
+pub fn process_migration_arrivals(ctx: &ReducerContext) -> Result<(), String> {
+    let current_hour = ctx.db.simulation_time().id().find(&1)
+        .ok_or("Simulation not initialized")?
+        .current_hour;
+
+    let arriving: Vec<MigrationEvent> = ctx.db.migration_event().iter()
+        .filter(|m| !m.arrived && m.arrival_hour <= current_hour)
+        .collect();
+    if arriving.is_empty() {
+        return Ok(());
+    }
+
+    let mut affected_cities: HashSet<u32> = HashSet::new();
+    let mut arrivals = Vec::new();
+
+    for mut event in arriving {
+        if let Some(mut migrant) = ctx.db.individual().id().find(&event.individual_id) {
+            migrant.home_id = Some(event.to_home_id);
+            ctx.db.individual().id().update(migrant);
+            relocate_cases(ctx, event.individual_id, event.to_city_id);
+        }
+
+        affected_cities.insert(event.from_city_id);
+        affected_cities.insert(event.to_city_id);
+        arrivals.push((event.to_city_id, event.reason.clone()));
+
+        event.arrived = true;
+        ctx.db.migration_event().id().update(event);
+    }
+
+    // Population moves with the settlers
+    for city_id in &affected_cities {
+        if let Some(mut city) = ctx.db.city().id().find(city_id) {
+            city.population = city_residents(ctx, *city_id).len() as u32;
+            ctx.db.city().id().update(city);
+        }
+    }
+
+    for (to_city_id, reason) in arrivals {
+        if let Some(world_id) = world_for_city(ctx, to_city_id) {
+            record_world_migration(ctx, world_id, reason == MigrationReason::Disaster);
+        }
+    }
+
+    Ok(())
+}
+
+/// Weekly push to leave, with the strongest reason
+fn push_factors(individual: &Individual, disaster_exposed: bool) -> (f32, MigrationReason) {
+    let mut factors = Vec::new();
+
+    if disaster_exposed {
+        factors.push((relocation::DISASTER_PRESSURE, MigrationReason::Disaster));
+    }
+    if individual.workplace_id.is_none() {
+        factors.push((relocation::UNEMPLOYMENT_PRESSURE, MigrationReason::Unemployment));
+    }
+    if individual.food_water < thresholds::NEED_CRITICAL_LOW ||
+        individual.safety < thresholds::NEED_CRITICAL_LOW ||
+        individual.health < thresholds::HEALTH_CRITICAL {
+        factors.push((relocation::UNMET_NEEDS_PRESSURE, MigrationReason::UnmetNeeds));
+    }
+
+    let total = factors.iter().map(|(push, _)| push).sum();
+    let reason = factors.into_iter()
+        .max_by(|a, b| a.0.partial_cmp(&b.0).unwrap())
+        .map(|(_, reason)| reason)
+        .unwrap_or(MigrationReason::UnmetNeeds);
+
+    (total, reason)
+}
+
+/// How appealing a city is to settle in: jobs, housing, safety and health
+fn city_attractiveness(ctx: &ReducerContext, city: &City) -> f32 {
+    let mut open_positions = 0;
+    let mut vacant_places = 0;
+
+    for building in ctx.db.building().iter().filter(|b| b.city_id == city.id) {
+        match &building.building_type {
+            BuildingType::Workplace(config) => {
+                let filled = ctx.db.employment().iter()
+                    .filter(|e| e.building_id == building.id && e.is_active)
+                    .count() as u32;
+                open_positions += config.positions.saturating_sub(filled);
+            },
+            BuildingType::Home(config) => {
+                vacant_places += config.capacity.saturating_sub(home_occupancy(ctx, building.id));
+            },
+            _ => {},
+        }
+    }
+
+    let job_score = (open_positions as f32 * relocation::JOB_OPENING_WEIGHT).min(relocation::MAX_JOB_SCORE);
+    let housing_score = (vacant_places as f32 * relocation::HOUSING_WEIGHT).min(relocation::MAX_HOUSING_SCORE);
+
+    job_score + housing_score + city.safety * relocation::SAFETY_WEIGHT + city.health * relocation::HEALTH_WEIGHT
+}
+
+/// Average building position, used as the city's location on the map
+pub(crate) fn city_centre(ctx: &ReducerContext, city_id: u32) -> (f32, f32) {
+    let positions: Vec<(f32, f32)> = ctx.db.building().iter()
+        .filter(|b| b.city_id == city_id)
+        .map(|b| (b.location_x, b.location_y))
+        .collect();
+
+    if positions.is_empty() {
+        return (0.0, 0.0);
+    }
+
+    let count = positions.len() as f32;
+    (
+        positions.iter().map(|p| p.0).sum::<f32>() / count,
+        positions.iter().map(|p| p.1).sum::<f32>() / count,
+    )
+}
+
+/// Residents plus reserved places for migrants already on their way
+fn home_occupancy(ctx: &ReducerContext, home_id: u32) -> u32 {
+    let residents = ctx.db.individual().iter()
+        .filter(|i| i.home_id == Some(home_id))
+        .count() as u32;
+    let incoming = ctx.db.migration_event().iter()
+        .filter(|m| !m.arrived && m.to_home_id == home_id)
+        .count() as u32;
+    residents + incoming
+}
+
+fn find_vacant_home(ctx: &ReducerContext, city_id: u32, household_size: u32) -> Option<u32> {
+    ctx.db.building().iter()
+        .filter(|b| b.city_id == city_id)
+        .find(|b| match &b.building_type {
+            BuildingType::Home(config) => home_occupancy(ctx, b.id) + household_size <= config.capacity,
+            _ => false,
+        })
+        .map(|b| b.id)
+}
+
+/// The individual plus partners and family sharing their home
+fn household_members(
+    ctx: &ReducerContext,
+    head: &Individual,
+    residents: &[Individual],
+    moved: &HashSet<u32>,
+) -> Vec<u32> {
+    let mut members = vec![head.id];
+
+    for other in residents.iter().filter(|r| r.id != head.id && r.home_id == head.home_id && !moved.contains(&r.id)) {
+        let related = ctx.db.relationship().iter().any(|r| {
+            ((r.individual1_id == head.id && r.individual2_id == other.id) ||
+                (r.individual1_id == other.id && r.individual2_id == head.id)) &&
+            matches!(r.relationship_type, RelationshipType::Partner | RelationshipType::Family)
+        });
+        if related {
+            members.push(other.id);
+        }
+    }
+
+    members
+}
+
+/// Leave job and school and set off for the new home
+fn depart(
+    ctx: &ReducerContext,
+    individual_id: u32,
+    household_head_id: u32,
+    from_city_id: u32,
+    to_city_id: u32,
+    to_home_id: u32,
+    reason: MigrationReason,
+    departed_hour: u64,
+    arrival_hour: u64,
+) {
+    let mut migrant = match ctx.db.individual().id().find(&individual_id) {
+        Some(migrant) => migrant,
+        None => return,
+    };
+
+    let jobs: Vec<Employment> = ctx.db.employment().iter()
+        .filter(|e| e.individual_id == individual_id && e.is_active)
+        .collect();
+    for mut job in jobs {
+        job.is_active = false;
+        job.ended_hour = Some(departed_hour);
+        ctx.db.employment().id().update(job);
+    }
+    end_enrollments(ctx, individual_id);
+
+    let id = ctx.db.migration_event().iter().count() as u32 + 1;
+    ctx.db.migration_event().insert(MigrationEvent {
+        id,
+        individual_id,
+        household_head_id,
+        from_city_id,
+        to_city_id,
+        from_home_id: migrant.home_id,
+        to_home_id,
+        reason,
+        departed_hour,
+        arrival_hour,
+        arrived: false,
+    });
+
+    migrant.workplace_id = None;
+    migrant.status = IndividualStatus::InTransit(StatusData {
+        until_hour: arrival_hour,
+        target_location: Some(to_home_id),
+        target_building: None,
+    });
+    ctx.db.individual().id().update(migrant);
+}
+
+fn save_pressure(ctx: &ReducerContext, pressure: MigrationPressure, is_new: bool) {
+    if is_new {
+        ctx.db.migration_pressure().insert(pressure);
+    } else {
+        ctx.db.migration_pressure().individual_id().update(pressure);
+    }
+}
+
+/// Individuals currently travelling between cities
+fn in_transit(ctx: &ReducerContext) -> HashSet<u32> {
+    ctx.db.migration_event().iter()
+        .filter(|m| !m.arrived)
+        .map(|m| m.individual_id)
+        .collect()
+}
+
+/// Whether a harmful natural event has struck the city recently
+fn recent_disaster(ctx: &ReducerContext, city_id: u32, current_hour: u64) -> bool {
+    ctx.db.natural_event().iter()
+        .filter(|e| e.population_impact < 0.0)
+        .filter(|e| e.start_hour + e.duration_hours as u64 + relocation::DISASTER_EXPOSURE_HOURS >= current_hour)
+        .any(|e| parse_region_ids(&e.affected_region).contains(&city_id))
+}
+
+/// Parse a JSON array of region ids such as "[1, 2]"
+pub(crate) fn parse_region_ids(regions: &str) -> Vec<u32> {
+    regions.trim_matches(|c| c == '[' || c == ']')
+        .split(',')
+        .filter_map(|id| id.trim().parse().ok())
+        .collect()
+}
+
+/// Regions in the natural systems are cities, so the climate record ties a city to its world
+pub(crate) fn world_for_city(ctx: &ReducerContext, city_id: u32) -> Option<u32> {
+    ctx.db.climate_state().iter()
+        .find(|c| c.region_id == city_id)
+        .map(|c| c.world_id)
+}
+
+fn seasonal_migration_tendency(ctx: &ReducerContext, world_id: u32) -> f32 {
+    let season = match ctx.db.game_world().id().find(&world_id) {
+        Some(world) => world.season,
+        None => return 1.0,
+    };
+
+    ctx.db.seasonal_effect().iter()
+        .find(|e| e.world_id == world_id && e.season == season)
+        .map(|e| e.migration_tendency)
+        .unwrap_or(1.0)
+}
+
+fn record_world_migration(ctx: &ReducerContext, world_id: u32, displaced_by_disaster: bool) {
+    if let Some(mut stats) = ctx.db.world_stats().iter().find(|s| s.world_id == world_id) {
+        stats.total_migrations += 1;
+        if displaced_by_disaster {
+            stats.disaster_migrations += 1;
+        }
+        ctx.db.world_stats().id().update(stats);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,446 @@
+use spacetimedb::{ReducerContext, Table};
+use log;
+use rand::Rng;
+use std::collections::HashSet;
+use crate::tables::*;
+use crate::types::*;
+use crate::systems::*;
+use crate::tables::preparedness::{warning_trust, warning_response, shelter, warning_preparation, warning_review};
+use crate::tables::city::city;
+use crate::tables::building::building;
+use crate::tables::damage::disaster_footprint;
+use crate::tables::events::simulation_time;
+use crate::natural::disasters::{disaster_warning, disaster_risk, DisasterWarning, WarningLevel};
+use crate::narrative::{create_narrative_event, EventCategory};
+use crate::reducers::city::{city_residents, local_weather};
+use crate::reducers::migration::parse_region_ids;
+use crate::reducers::storage::serve_meal;
+
+/// Get the city ready for every warning hanging over it: spend on mitigation and open shelters
+#[spacetimedb::reducer]
+pub fn process_disaster_preparedness(ctx: &ReducerContext, city_id: u32) -> Result<(), String> {
+    let current_hour = ctx.db.simulation_time().id().find(&1)
+        .ok_or("Simulation not initialized")?
+        .current_hour;
+
+    let warnings: Vec<DisasterWarning> = ctx.db.disaster_warning().iter()
+        .filter(|w| w.is_active && parse_region_ids(&w.affected_regions).contains(&city_id))
+        .collect();
+    for warning in warnings {
+        prepare_city(ctx, city_id, &warning, current_hour);
+    }
+
+    Ok(())
+}
+
+/// Act on any warning over the individual's city; returns whether it kept them busy
+pub(crate) fn heed_warning(ctx: &ReducerContext, individual: &mut Individual, current_hour: u64) -> Result<bool, String> {
+    let here = ctx.db.building().id().find(&individual.current_location_id)
+        .ok_or("Current building not found")?;
+    let warning = match active_warning(ctx, here.city_id) {
+        Some(warning) => warning,
+        None => return Ok(false),
+    };
+    prepare_city(ctx, here.city_id, &warning, current_hour);
+
+    let mut response = match ctx.db.warning_response().iter()
+        .find(|r| r.warning_id == warning.id && r.individual_id == individual.id) {
+        Some(response) => response,
+        None => respond(ctx, individual, &warning, here.city_id, current_hour),
+    };
+
+    // The hungry eat from what they laid in before anything drives them out
+    if !response.released && individual.food_water < thresholds::NEED_ADEQUATE && response.meals_stored > 0.0 {
+        let portion = response.meals_stored.min(1.0);
+        response.meals_stored -= portion;
+        individual.food_water = (individual.food_water + actions::EAT_FOOD_GAIN * portion).min(thresholds::NEED_MAX);
+        response = ctx.db.warning_response().id().update(response);
+    }
+
+    // Hunger still drives people out to eat once their stores run out
+    if response.released || individual.food_water < thresholds::NEED_CRITICAL_LOW {
+        return Ok(false);
+    }
+
+    let refuge = match response.action {
+        PreparednessAction::StayHome => individual.home_id,
+        PreparednessAction::Evacuate => response.shelter_id,
+        PreparednessAction::Ignore | PreparednessAction::StockFood => None,
+    };
+    let refuge = match refuge {
+        Some(refuge) => refuge,
+        None => return Ok(false),
+    };
+
+    if individual.current_location_id == refuge {
+        let hold = StatusData {
+            until_hour: current_hour + early_warning::HOLD_HOURS,
+            target_location: None,
+            target_building: Some(refuge),
+        };
+        individual.status = match response.action {
+            PreparednessAction::Evacuate => IndividualStatus::Socializing(hold),
+            _ => IndividualStatus::Sleeping(hold),
+        };
+        return Ok(true);
+    }
+
+    let destination = ctx.db.building().id().find(&refuge)
+        .ok_or("Refuge not found")?;
+    let distance = calculate_distance(here.location_x, here.location_y, destination.location_x, destination.location_y);
+    let travel_time = calculate_travel_time(distance, &local_weather(ctx, here.city_id, true));
+
+    individual.status = IndividualStatus::InTransit(StatusData {
+        until_hour: current_hour + travel_time,
+        target_location: Some(refuge),
+        target_building: None,
+    });
+    individual.rest += actions::MOVE_REST_COST * travel_time as f32;
+    Ok(true)
+}
+
+/// Whether a building is a designated shelter
+pub(crate) fn is_shelter(ctx: &ReducerContext, building_id: u32) -> bool {
+    ctx.db.shelter().building_id().find(&building_id).is_some()
+}
+
+/// Compare what a city's residents did about a warning with what came of it, and let trust follow.
+/// `natural_event_id` is None for a false alarm.
+pub(crate) fn review_warning(
+    ctx: &ReducerContext,
+    warning: &DisasterWarning,
+    natural_event_id: Option<u32>,
+    hour: u64,
+) -> Result<(), String> {
+    for city_id in parse_region_ids(&warning.affected_regions) {
+        let city = match ctx.db.city().id().find(&city_id) {
+            Some(city) => city,
+            None => continue,
+        };
+        let mut trust = trust_record(ctx, city_id, hour);
+        let trust_before = trust.trust;
+
+        let responses: Vec<WarningResponse> = ctx.db.warning_response().iter()
+            .filter(|r| r.warning_id == warning.id && r.city_id == city_id)
+            .collect();
+        let heeded: HashSet<u32> = responses.iter()
+            .filter(|r| r.action != PreparednessAction::Ignore)
+            .map(|r| r.individual_id)
+            .collect();
+        let count = |action: PreparednessAction| responses.iter().filter(|r| r.action == action).count() as u32;
+
+        // Who was inside the struck area when it hit
+        let footprint = natural_event_id.and_then(|event_id| ctx.db.disaster_footprint().iter()
+            .find(|f| f.natural_event_id == event_id && f.city_id == city_id));
+        let (mut exposed_heeded, mut exposed_ignored) = (0, 0);
+        let residents = city_residents(ctx, city_id);
+        let (injured, displaced) = match &footprint {
+            Some(footprint) => {
+                let struck: HashSet<u32> = ctx.db.building().iter()
+                    .filter(|b| b.city_id == city_id &&
+                        calculate_distance(footprint.center_x, footprint.center_y, b.location_x, b.location_y) <= footprint.radius)
+                    .map(|b| b.id)
+                    .collect();
+                for person in residents.iter().filter(|i| struck.contains(&i.current_location_id)) {
+                    if heeded.contains(&person.id) {
+                        exposed_heeded += 1;
+                    } else {
+                        exposed_ignored += 1;
+                    }
+                }
+                (footprint.injured, footprint.displaced)
+            },
+            None => (0, 0),
+        };
+
+        let false_alarm = natural_event_id.is_none();
+        let weight = heed_weight(warning.warning_level);
+        if false_alarm {
+            trust.trust -= early_warning::FALSE_ALARM_TRUST_LOSS * weight / early_warning::HEED_EMERGENCY;
+            trust.false_alarms += 1;
+        } else {
+            trust.trust += early_warning::CONFIRMED_TRUST_GAIN;
+        }
+        trust.trust = trust.trust.clamp(0.0, 100.0);
+        trust.warnings_reviewed += 1;
+        trust.last_review_hour = hour;
+
+        let preparation_spent: f32 = ctx.db.warning_preparation().iter()
+            .filter(|p| p.warning_id == warning.id && p.city_id == city_id)
+            .map(|p| p.spent)
+            .sum();
+        let mitigation_level = ctx.db.disaster_risk().iter()
+            .find(|r| r.region_id == city_id && r.disaster_type == warning.disaster_type)
+            .map_or(0.0, |r| r.mitigation_level);
+
+        let heeded_count = heeded.len() as u32;
+        let ignored_count = (residents.len() as u32).saturating_sub(heeded_count);
+        let finding = if false_alarm {
+            format!("False alarm: the {:?} never came; {} of {} residents acted on the {:?}",
+                warning.disaster_type, heeded_count, residents.len(), warning.warning_level)
+        } else {
+            format!("{:?} struck: {} injured, {} displaced; {:.0}% of residents who acted were in harm's way against {:.0}% of those who did not",
+                warning.disaster_type, injured, displaced,
+                share(exposed_heeded, heeded_count), share(exposed_ignored, ignored_count))
+        };
+
+        let review_id = ctx.db.warning_review().iter().count() as u32 + 1;
+        ctx.db.warning_review().insert(WarningReview {
+            id: review_id,
+            warning_id: warning.id,
+            city_id,
+            natural_event_id,
+            false_alarm,
+            residents: residents.len() as u32,
+            heeded: heeded_count,
+            evacuated: count(PreparednessAction::Evacuate),
+            stayed_home: count(PreparednessAction::StayHome),
+            stocked_food: responses.iter().filter(|r| r.stocked_food).count() as u32,
+            exposed_heeded,
+            exposed_ignored,
+            injured,
+            displaced,
+            preparation_spent,
+            mitigation_level,
+            trust_before,
+            trust_after: trust.trust,
+            hour,
+            finding: finding.clone(),
+        });
+
+        // The warning has passed; everyone goes about their business
+        for mut response in responses.into_iter().filter(|r| !r.released) {
+            response.released = true;
+            ctx.db.warning_response().id().update(response);
+        }
+
+        if false_alarm && heeded_count > 0 {
+            create_narrative_event(
+                ctx,
+                warning.world_id,
+                1, // Default game ID
+                EventCategory::Natural,
+                format!("False Alarm in {}", city.name),
+                format!("{} Trust in the authorities' warnings falls to {:.0}.", finding, trust.trust),
+                3,
+            )?;
+        }
+
+        log::info!("City {} review of warning {}: {} (trust {:.0} -> {:.0})",
+            city_id, warning.id, finding, trust_before, trust.trust);
+        ctx.db.warning_trust().city_id().update(trust);
+    }
+
+    Ok(())
+}
+
+/// The most serious active warning covering a city
+fn active_warning(ctx: &ReducerContext, city_id: u32) -> Option<DisasterWarning> {
+    ctx.db.disaster_warning().iter()
+        .filter(|w| w.is_active && parse_region_ids(&w.affected_regions).contains(&city_id))
+        .max_by(|a, b| heed_weight(a.warning_level).partial_cmp(&heed_weight(b.warning_level)).unwrap())
+}
+
+/// Chance a fully trusting resident acts on a warning of this level
+fn heed_weight(level: WarningLevel) -> f32 {
+    match level {
+        WarningLevel::Watch => early_warning::HEED_WATCH,
+        WarningLevel::Advisory => early_warning::HEED_ADVISORY,
+        WarningLevel::Warning => early_warning::HEED_WARNING,
+        WarningLevel::Emergency => early_warning::HEED_EMERGENCY,
+    }
+}
+
+/// Decide and record what a resident does about a warning: trust and the warning's level set whether
+/// they act at all, their circumstances what they do
+fn respond(
+    ctx: &ReducerContext,
+    individual: &mut Individual,
+    warning: &DisasterWarning,
+    city_id: u32,
+    hour: u64,
+) -> WarningResponse {
+    let mut rng = rand::thread_rng();
+    let trust = trust_record(ctx, city_id, hour).trust;
+
+    let vulnerable = individual.age >= early_warning::VULNERABLE_AGE || individual.health < thresholds::HEALTH_CRITICAL;
+    let heed_chance = trust / 100.0 * heed_weight(warning.warning_level)
+        + if vulnerable { early_warning::VULNERABLE_HEED_BONUS } else { 0.0 };
+
+    let mut action = if rng.gen::<f32>() >= heed_chance {
+        PreparednessAction::Ignore
+    } else {
+        match warning.warning_level {
+            WarningLevel::Watch => PreparednessAction::StockFood,
+            WarningLevel::Advisory => PreparednessAction::StayHome,
+            WarningLevel::Warning | WarningLevel::Emergency if warning.evacuation_recommended => {
+                // Hard-up workers fear for their wages more than the storm, unless they are the ones at risk
+                let hard_up = individual.workplace_id.is_some() && individual.income < early_warning::HARD_UP_INCOME;
+                if hard_up && !vulnerable && rng.gen::<f32>() < early_warning::WAGE_WORRY {
+                    PreparednessAction::StayHome
+                } else {
+                    PreparednessAction::Evacuate
+                }
+            },
+            WarningLevel::Warning | WarningLevel::Emergency => PreparednessAction::StayHome,
+        }
+    };
+
+    let shelter_id = if action == PreparednessAction::Evacuate {
+        let shelter = nearest_shelter_with_room(ctx, city_id, warning.id, individual);
+        if shelter.is_none() {
+            action = PreparednessAction::StayHome;
+        }
+        shelter
+    } else {
+        None
+    };
+    if action == PreparednessAction::StayHome && individual.home_id.is_none() {
+        action = PreparednessAction::StockFood;
+    }
+
+    // Anyone acting lays in food if they can afford it
+    let meals_stored = if action != PreparednessAction::Ignore { stock_food(ctx, individual, city_id, hour) } else { 0.0 };
+
+    let id = ctx.db.warning_response().iter().count() as u32 + 1;
+    ctx.db.warning_response().insert(WarningResponse {
+        id,
+        warning_id: warning.id,
+        individual_id: individual.id,
+        city_id,
+        action,
+        shelter_id,
+        stocked_food: meals_stored > 0.0,
+        meals_stored,
+        hour,
+        released: false,
+    })
+}
+
+/// Buy a few meals in from the city's stores to eat while the warning lasts; returns the portions bought
+fn stock_food(ctx: &ReducerContext, individual: &mut Individual, city_id: u32, hour: u64) -> f32 {
+    let mut stocked = 0.0;
+    for _ in 0..early_warning::STOCK_MEALS {
+        if individual.income < early_warning::STOCK_MEAL_COST {
+            break;
+        }
+        let portion = serve_meal(ctx, city_id, hour);
+        if portion <= 0.0 {
+            break;
+        }
+        individual.income -= early_warning::STOCK_MEAL_COST * portion;
+        stocked += portion;
+    }
+    stocked
+}
+
+fn nearest_shelter_with_room(ctx: &ReducerContext, city_id: u32, warning_id: u32, individual: &Individual) -> Option<u32> {
+    let from = ctx.db.building().id().find(&individual.home_id.unwrap_or(individual.current_location_id))?;
+    ctx.db.shelter().iter()
+        .filter(|s| s.city_id == city_id)
+        .filter(|s| {
+            let sheltering = ctx.db.warning_response().iter()
+                .filter(|r| r.warning_id == warning_id && r.shelter_id == Some(s.building_id))
+                .count() as u32;
+            sheltering < s.capacity
+        })
+        .filter_map(|s| ctx.db.building().id().find(&s.building_id))
+        .filter(|b| !b.destroyed)
+        .min_by(|a, b| {
+            let da = calculate_distance(from.location_x, from.location_y, a.location_x, a.location_y);
+            let db = calculate_distance(from.location_x, from.location_y, b.location_x, b.location_y);
+            da.partial_cmp(&db).unwrap()
+        })
+        .map(|b| b.id)
+}
+
+/// Once per warning: spend on raising the city's mitigation against it and open shelters if evacuation is advised
+fn prepare_city(ctx: &ReducerContext, city_id: u32, warning: &DisasterWarning, hour: u64) {
+    if ctx.db.warning_preparation().iter().any(|p| p.warning_id == warning.id && p.city_id == city_id) {
+        return;
+    }
+    let mut city = match ctx.db.city().id().find(&city_id) {
+        Some(city) => city,
+        None => return,
+    };
+
+    let shelters = if warning.evacuation_recommended { designate_shelters(ctx, city_id, hour) } else { 0 };
+
+    let residents = city.population.max(1) as f32;
+    let per_resident = match warning.warning_level {
+        WarningLevel::Watch => early_warning::PREPARATION_PER_RESIDENT_WATCH,
+        WarningLevel::Advisory => early_warning::PREPARATION_PER_RESIDENT_ADVISORY,
+        WarningLevel::Warning => early_warning::PREPARATION_PER_RESIDENT_WARNING,
+        WarningLevel::Emergency => early_warning::PREPARATION_PER_RESIDENT_EMERGENCY,
+    };
+    let spent = (per_resident * residents).min(city.tax_reserve.max(0.0));
+
+    let risk = ctx.db.disaster_risk().iter()
+        .find(|r| r.region_id == city_id && r.disaster_type == warning.disaster_type);
+    let mitigation_before = risk.as_ref().map_or(0.0, |r| r.mitigation_level);
+    let mut mitigation_after = mitigation_before;
+    if let Some(mut risk) = risk {
+        // Each point costs more the better prepared the city already is
+        let points = spent / (early_warning::MITIGATION_COST_PER_RESIDENT * residents) * (1.0 - risk.mitigation_level / 100.0);
+        risk.mitigation_level = (risk.mitigation_level + points).min(100.0);
+        mitigation_after = risk.mitigation_level;
+        ctx.db.disaster_risk().id().update(risk);
+    }
+
+    city.tax_reserve -= spent;
+    let id = ctx.db.warning_preparation().iter().count() as u32 + 1;
+    ctx.db.warning_preparation().insert(WarningPreparation {
+        id,
+        warning_id: warning.id,
+        city_id,
+        spent,
+        mitigation_before,
+        mitigation_after,
+        shelters,
+        hour,
+    });
+
+    log::info!("{} spent {:.0} preparing for {:?} (mitigation {:.0} -> {:.0}, {} shelters)",
+        city.name, spent, warning.disaster_type, mitigation_before, mitigation_after, shelters);
+    ctx.db.city().id().update(city);
+}
+
+/// Set aside the city's sturdy public buildings as shelters; returns how many the city has
+fn designate_shelters(ctx: &ReducerContext, city_id: u32, hour: u64) -> u32 {
+    let candidates: Vec<Building> = ctx.db.building().iter()
+        .filter(|b| b.city_id == city_id && !b.destroyed)
+        .filter(|b| matches!(b.building_type,
+            BuildingType::School | BuildingType::CityHall | BuildingType::Hospital | BuildingType::CultureCenter))
+        .collect();
+
+    for building in candidates {
+        if is_shelter(ctx, building.id) {
+            continue;
+        }
+        ctx.db.shelter().insert(Shelter {
+            building_id: building.id,
+            city_id,
+            capacity: (building.max_capacity as f32 * early_warning::SHELTER_CAPACITY_SHARE) as u32,
+            designated_hour: hour,
+        });
+    }
+
+    ctx.db.shelter().iter().filter(|s| s.city_id == city_id).count() as u32
+}
+
+fn trust_record(ctx: &ReducerContext, city_id: u32, hour: u64) -> WarningTrust {
+    match ctx.db.warning_trust().city_id().find(&city_id) {
+        Some(trust) => trust,
+        None => ctx.db.warning_trust().insert(WarningTrust {
+            city_id,
+            trust: early_warning::INITIAL_TRUST,
+            warnings_reviewed: 0,
+            false_alarms: 0,
+            last_review_hour: hour,
+        }),
+    }
+}
+
+fn share(part: u32, whole: u32) -> f32 {
+    if whole == 0 { 0.0 } else { part as f32 / whole as f32 * 100.0 }
+}
\ No newline at end of file
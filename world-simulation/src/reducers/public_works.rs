@@ -0,0 +1,401 @@
+use spacetimedb::{ReducerContext, Table};
+use log;
+use std::collections::HashSet;
+use crate::tables::*;
+use crate::types::*;
+use crate::systems::modifiers::*;
+use crate::tables::public_works::{public_works_project, public_works_crew};
+use crate::tables::city::{city, city_service};
+use crate::tables::individual::individual;
+use crate::tables::events::{simulation_time, city_event};
+use crate::economics::trade_route;
+use crate::narrative::{create_narrative_event, EventCategory};
+use crate::reducers::city::city_residents;
+use crate::reducers::migration::world_for_city;
+use crate::reducers::budget::{tax_rates, accrue_revenue};
+
+/// Propose an infrastructure project; building starts once the weekly budget funds it
+#[spacetimedb::reducer]
+pub fn propose_project(ctx: &ReducerContext, city_id: u32, project_type: ProjectType) -> Result<(), String> {
+    let current_hour = ctx.db.simulation_time().id().find(&1)
+        .ok_or("Simulation not initialized")?
+        .current_hour;
+
+    let city = ctx.db.city().id().find(&city_id)
+        .ok_or("City not found")?;
+
+    if open_projects(ctx, city_id).iter().any(|p| p.project_type == project_type) {
+        return Err(format!("{} already has {:?} under way", city.name, project_type));
+    }
+
+    open_project(ctx, &city, project_type, current_hour)
+}
+
+/// Daily construction pass: crews work a shift on each funded project, paid from its funds
+#[spacetimedb::reducer]
+pub fn process_public_works(ctx: &ReducerContext, city_id: u32) -> Result<(), String> {
+    let current_hour = ctx.db.simulation_time().id().find(&1)
+        .ok_or("Simulation not initialized")?
+        .current_hour;
+
+    let mut city = ctx.db.city().id().find(&city_id)
+        .ok_or("City not found")?;
+
+    let projects: Vec<PublicWorksProject> = open_projects(ctx, city_id).into_iter()
+        .filter(|p| p.status != ProjectStatus::Proposed)
+        .collect();
+
+    for mut project in projects {
+        let shift_cost = actions::WORK_INCOME_GAIN + construction::CREW_SHIFT_HOURS * construction::MATERIALS_PER_HOUR;
+        if project.funds < shift_cost {
+            if project.status == ProjectStatus::UnderConstruction {
+                project.status = ProjectStatus::Stalled;
+                release_crew(ctx, project.id);
+                announce(ctx, &city, &project, current_hour, 0,
+                    format!("{:?} works halted in {}", project.project_type, city.name),
+                    format!("Money for the {:?} in {} has run out with the work {:.0}% done",
+                        project.project_type, city.name, progress(&project) * 100.0),
+                    -1.0)?;
+                ctx.db.public_works_project().id().update(project);
+            }
+            continue;
+        }
+        project.status = ProjectStatus::UnderConstruction;
+
+        let income_tax = tax_rates(ctx, city_id).income;
+        let affordable = (project.funds / shift_cost) as usize;
+        let crew = staff_crew(ctx, &project, current_hour);
+        let mut shifts = 0;
+        for mut worker in crew.into_iter().take(affordable) {
+            let tax = actions::WORK_INCOME_GAIN * income_tax;
+            accrue_revenue(ctx, city_id, RevenueLine::IncomeTax, tax, current_hour);
+            worker.income += actions::WORK_INCOME_GAIN - tax;
+            worker.rest += actions::WORK_REST_COST;
+            worker.stress += actions::WORK_STRESS_GAIN;
+            worker.status = IndividualStatus::Working(StatusData {
+                until_hour: current_hour + construction::CREW_SHIFT_HOURS as u64,
+                target_location: None,
+                target_building: None,
+            });
+            ctx.db.individual().id().update(worker);
+            shifts += 1;
+        }
+
+        let cost = shifts as f32 * shift_cost;
+        project.funds -= cost;
+        project.spent += cost;
+        project.work_hours_completed += shifts as f32 * construction::CREW_SHIFT_HOURS;
+
+        // Announce each quarter of the work
+        let milestone = ((progress(&project) * 4.0).floor() as u8 * 25).min(100);
+        if milestone >= 100 {
+            complete_project(ctx, &mut city, &mut project, current_hour)?;
+        } else if milestone > project.last_milestone {
+            project.last_milestone = milestone;
+            announce(ctx, &city, &project, current_hour, shifts,
+                format!("{:?} in {} {}% complete", project.project_type, city.name, milestone),
+                format!("Crews in {} have finished {}% of the {:?}", city.name, milestone, project.project_type),
+                0.0)?;
+        }
+
+        ctx.db.public_works_project().id().update(project);
+    }
+
+    ctx.db.city().id().update(city);
+    Ok(())
+}
+
+/// Weekly: put forward projects for the city's most pressing needs
+pub(crate) fn propose_public_works(ctx: &ReducerContext, city: &City, hour: u64) -> Result<(), String> {
+    let open = open_projects(ctx, city.id);
+    let mut wanted = Vec::new();
+
+    if city.public_works < construction::ROADS_PUBLIC_WORKS_THRESHOLD {
+        wanted.push(ProjectType::Roads);
+    }
+    if city.health < construction::SEWERS_HEALTH_THRESHOLD {
+        wanted.push(ProjectType::Sewers);
+    }
+    if city.safety < construction::WALLS_SAFETY_THRESHOLD && !has_completed(ctx, city.id, ProjectType::Walls) {
+        wanted.push(ProjectType::Walls);
+    }
+    if city.population >= construction::AQUEDUCT_POPULATION && !has_completed(ctx, city.id, ProjectType::Aqueduct) {
+        wanted.push(ProjectType::Aqueduct);
+    }
+
+    let mut open_count = open.len();
+    for project_type in wanted {
+        if open_count >= construction::MAX_OPEN_PROJECTS {
+            break;
+        }
+        if open.iter().any(|p| p.project_type == project_type) {
+            continue;
+        }
+        open_project(ctx, city, project_type, hour)?;
+        open_count += 1;
+    }
+
+    Ok(())
+}
+
+/// Weekly: allocate up to `available` to open projects, oldest first; returns what was allocated
+pub(crate) fn fund_projects(ctx: &ReducerContext, city_id: u32, available: f32, hour: u64) -> Result<f32, String> {
+    let mut remaining = available.max(0.0);
+    let mut allocated = 0.0;
+
+    let mut projects = open_projects(ctx, city_id);
+    projects.sort_by_key(|p| p.id);
+
+    for mut project in projects {
+        let shortfall = (project.cost - project.spent - project.funds).max(0.0);
+        let grant = shortfall.min(remaining);
+        if grant <= 0.0 {
+            continue;
+        }
+        remaining -= grant;
+        allocated += grant;
+        project.funds += grant;
+
+        if project.status == ProjectStatus::Proposed {
+            project.status = ProjectStatus::UnderConstruction;
+            if let Some(city) = ctx.db.city().id().find(&city_id) {
+                announce(ctx, &city, &project, hour, 0,
+                    format!("Ground broken on {:?} in {}", project.project_type, city.name),
+                    format!("{} has funded the {:?} and is hiring {} workers",
+                        city.name, project.project_type, project.workers_needed),
+                    1.0)?;
+            }
+        } else if project.status == ProjectStatus::Stalled {
+            project.status = ProjectStatus::UnderConstruction;
+        }
+        ctx.db.public_works_project().id().update(project);
+    }
+
+    Ok(allocated)
+}
+
+/// Sanitation 0.0-1.0 from the city's service coverage and quality
+pub(crate) fn sanitation_level(ctx: &ReducerContext, city_id: u32) -> f32 {
+    ctx.db.city_service().iter()
+        .find(|s| s.city_id == city_id && s.service_type == ServiceType::Sanitation)
+        .map(|s| (s.coverage / 100.0) * (s.quality / 100.0))
+        .unwrap_or(0.0)
+        .clamp(0.0, 1.0)
+}
+
+/// Safety the city's walls add on top of what its residents feel
+pub(crate) fn wall_safety_bonus(ctx: &ReducerContext, city_id: u32) -> f32 {
+    if has_completed(ctx, city_id, ProjectType::Walls) {
+        construction::WALL_SAFETY_BONUS
+    } else {
+        0.0
+    }
+}
+
+/// (Cost, work hours, crew size) for each kind of project
+fn project_profile(project_type: ProjectType) -> (f32, f32, u32) {
+    match project_type {
+        ProjectType::Roads => (2000.0, 400.0, 5),
+        ProjectType::Sewers => (4000.0, 800.0, 8),
+        ProjectType::Walls => (6000.0, 1200.0, 10),
+        ProjectType::Aqueduct => (8000.0, 1600.0, 10),
+    }
+}
+
+fn open_project(ctx: &ReducerContext, city: &City, project_type: ProjectType, hour: u64) -> Result<(), String> {
+    let (cost, work_hours_needed, workers_needed) = project_profile(project_type);
+    let id = ctx.db.public_works_project().iter().count() as u32 + 1;
+    let project = ctx.db.public_works_project().insert(PublicWorksProject {
+        id,
+        city_id: city.id,
+        project_type,
+        status: ProjectStatus::Proposed,
+        cost,
+        funds: 0.0,
+        spent: 0.0,
+        work_hours_needed,
+        work_hours_completed: 0.0,
+        workers_needed,
+        proposed_hour: hour,
+        completed_hour: None,
+        last_milestone: 0,
+    });
+
+    log::info!("City {} proposed {:?} (cost {:.0})", city.id, project_type, cost);
+    announce(ctx, city, &project, hour, 0,
+        format!("{} proposes {:?}", city.name, project_type),
+        format!("The council of {} has put forward {:?} at a cost of {:.0}", city.name, project_type, cost),
+        0.0)
+}
+
+fn open_projects(ctx: &ReducerContext, city_id: u32) -> Vec<PublicWorksProject> {
+    ctx.db.public_works_project().iter()
+        .filter(|p| p.city_id == city_id && p.status != ProjectStatus::Completed)
+        .collect()
+}
+
+fn has_completed(ctx: &ReducerContext, city_id: u32, project_type: ProjectType) -> bool {
+    ctx.db.public_works_project().iter()
+        .any(|p| p.city_id == city_id && p.project_type == project_type && p.status == ProjectStatus::Completed)
+}
+
+fn progress(project: &PublicWorksProject) -> f32 {
+    if project.work_hours_needed <= 0.0 {
+        return 1.0;
+    }
+    (project.work_hours_completed / project.work_hours_needed).min(1.0)
+}
+
+/// Current crew for a project, hiring unemployed residents to fill it
+fn staff_crew(ctx: &ReducerContext, project: &PublicWorksProject, hour: u64) -> Vec<Individual> {
+    let mut crew = Vec::new();
+    let rows: Vec<PublicWorksCrew> = ctx.db.public_works_crew().iter()
+        .filter(|c| c.project_id == project.id && c.is_active)
+        .collect();
+
+    // Crew who left the city or found other work drop off the roll
+    let mut residents = city_residents(ctx, project.city_id);
+    for mut row in rows {
+        let still_here = residents.iter().position(|r| r.id == row.individual_id
+            && r.workplace_id.is_none()
+            && !matches!(r.status, IndividualStatus::InTransit(_)));
+        match still_here {
+            Some(index) => crew.push(residents.swap_remove(index)),
+            None => {
+                row.is_active = false;
+                ctx.db.public_works_crew().id().update(row);
+            },
+        }
+    }
+
+    let needed = (project.workers_needed as usize).saturating_sub(crew.len());
+    if needed == 0 {
+        return crew;
+    }
+
+    let on_crews: HashSet<u32> = ctx.db.public_works_crew().iter()
+        .filter(|c| c.is_active)
+        .map(|c| c.individual_id)
+        .collect();
+    let hires: Vec<Individual> = residents.into_iter()
+        .filter(|i| i.age >= 18 && i.workplace_id.is_none() && i.health >= thresholds::HEALTH_CRITICAL)
+        .filter(|i| matches!(i.status, IndividualStatus::Idle | IndividualStatus::Socializing(_)))
+        .filter(|i| !on_crews.contains(&i.id))
+        .take(needed)
+        .collect();
+
+    for worker in hires {
+        let id = ctx.db.public_works_crew().iter().count() as u32 + 1;
+        ctx.db.public_works_crew().insert(PublicWorksCrew {
+            id,
+            project_id: project.id,
+            individual_id: worker.id,
+            joined_hour: hour,
+            is_active: true,
+        });
+        crew.push(worker);
+    }
+
+    crew
+}
+
+fn release_crew(ctx: &ReducerContext, project_id: u32) {
+    let rows: Vec<PublicWorksCrew> = ctx.db.public_works_crew().iter()
+        .filter(|c| c.project_id == project_id && c.is_active)
+        .collect();
+    for mut row in rows {
+        row.is_active = false;
+        ctx.db.public_works_crew().id().update(row);
+    }
+}
+
+fn complete_project(ctx: &ReducerContext, city: &mut City, project: &mut PublicWorksProject, hour: u64) -> Result<(), String> {
+    // Every finished project restores the city's public works, then adds its own benefit
+    city.public_works = (city.public_works + construction::PUBLIC_WORKS_RESTORED).min(100.0);
+    match project.project_type {
+        ProjectType::Roads => {
+            let routes: Vec<_> = ctx.db.trade_route().iter()
+                .filter(|r| r.from_city_id == city.id || r.to_city_id == city.id)
+                .collect();
+            for mut route in routes {
+                route.safety = (route.safety + construction::ROUTE_SAFETY_GAIN).min(100.0);
+                ctx.db.trade_route().id().update(route);
+            }
+        },
+        ProjectType::Sewers => {
+            improve_sanitation(ctx, city.id, 0.0, construction::SANITATION_QUALITY_GAIN);
+        },
+        // Walls add their safety bonus whenever the city's safety is next measured
+        ProjectType::Walls => {},
+        ProjectType::Aqueduct => {
+            improve_sanitation(ctx, city.id, construction::SANITATION_COVERAGE_GAIN, 0.0);
+        },
+    }
+
+    project.status = ProjectStatus::Completed;
+    project.completed_hour = Some(hour);
+    project.last_milestone = 100;
+    release_crew(ctx, project.id);
+
+    log::info!("City {} completed {:?} for {:.0}", city.id, project.project_type, project.spent);
+    announce(ctx, city, project, hour, 0,
+        format!("{:?} completed in {}", project.project_type, city.name),
+        format!("{} celebrates its finished {:?}, built over {} days for {:.0}",
+            city.name, project.project_type, hour.saturating_sub(project.proposed_hour) / 24, project.spent),
+        2.0)
+}
+
+fn improve_sanitation(ctx: &ReducerContext, city_id: u32, coverage_gain: f32, quality_gain: f32) {
+    match ctx.db.city_service().iter()
+        .find(|s| s.city_id == city_id && s.service_type == ServiceType::Sanitation)
+    {
+        Some(mut service) => {
+            service.coverage = (service.coverage + coverage_gain).min(100.0);
+            service.quality = (service.quality + quality_gain).min(100.0);
+            ctx.db.city_service().id().update(service);
+        },
+        None => {
+            let id = (ctx.db.city_service().iter().count() + 1) as u32;
+            ctx.db.city_service().insert(CityService {
+                id,
+                city_id,
+                service_type: ServiceType::Sanitation,
+                coverage: coverage_gain.min(100.0),
+                quality: (50.0 + quality_gain).min(100.0),
+                cost_per_hour: 10.0,
+                workers_needed: 5,
+                workers_assigned: 0,
+            });
+        },
+    }
+}
+
+/// Record a project milestone as a city event and a story beat
+fn announce(
+    ctx: &ReducerContext,
+    city: &City,
+    project: &PublicWorksProject,
+    hour: u64,
+    participants: u32,
+    title: String,
+    description: String,
+    impact_stability: f32,
+) -> Result<(), String> {
+    let id = ctx.db.city_event().iter().count() as u32 + 1;
+    ctx.db.city_event().insert(CityEvent {
+        id,
+        city_id: city.id,
+        hour,
+        event_type: CityEventType::InfrastructureProject,
+        description: description.clone(),
+        participants,
+        impact_stability,
+        impact_culture: 0.0,
+    });
+
+    if let Some(world_id) = world_for_city(ctx, city.id) {
+        let importance = if project.status == ProjectStatus::Completed { 5 } else { 3 };
+        create_narrative_event(ctx, world_id, 1, EventCategory::Economic, title, description, importance)?;
+    }
+    Ok(())
+}
\ No newline at end of file
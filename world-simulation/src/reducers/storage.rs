@@ -0,0 +1,247 @@
+use spacetimedb::{ReducerContext, Table};
+use log;
+use crate::tables::*;
+use crate::types::*;
+use crate::systems::modifiers::*;
+use crate::tables::storage::{warehouse, ration_book};
+use crate::tables::city::{city, city_policy, trade_route};
+use crate::tables::building::{building, workplace_data};
+use crate::tables::events::simulation_time;
+use crate::reducers::city::enact_policy;
+
+/// Daily pass: workplaces deliver their output, trade routes flow and perishables spoil
+#[spacetimedb::reducer]
+pub fn process_city_storage(ctx: &ReducerContext, city_id: u32) -> Result<(), String> {
+    let current_hour = ctx.db.simulation_time().id().find(&1)
+        .ok_or("Simulation not initialized")?
+        .current_hour;
+
+    let mut city = ctx.db.city().id().find(&city_id)
+        .ok_or("City not found")?;
+
+    // Workplaces deliver what they made; farms deliver at harvest instead
+    let workplaces: Vec<Building> = ctx.db.building().iter()
+        .filter(|b| b.city_id == city_id && !b.destroyed && matches!(b.building_type, BuildingType::Workplace(_)))
+        .collect();
+    for workplace in workplaces {
+        if let Some(mut output) = ctx.db.workplace_data().building_id().find(&workplace.id) {
+            if output.resource_type == ResourceType::Food || output.inventory <= 0.0 {
+                continue;
+            }
+            let stored = deposit_stock(ctx, city_id, output.resource_type.clone(), output.inventory, current_hour);
+            output.inventory -= stored;
+            ctx.db.workplace_data().building_id().update(output);
+        }
+    }
+
+    // Imports are paid for out of city funds; exports earn
+    let routes: Vec<TradeRoute> = ctx.db.trade_route().iter()
+        .filter(|r| r.city_id == city_id)
+        .collect();
+    let mut imported = 0.0;
+    let mut exported = 0.0;
+    for route in routes {
+        let volume = route.rate_per_hour * warehousing::TRADE_HOURS_PER_DAY;
+        if route.is_import {
+            let affordable = if route.price_per_unit > 0.0 {
+                city.tax_reserve.max(0.0) / route.price_per_unit
+            } else {
+                volume
+            };
+            let stored = deposit_stock(ctx, city_id, route.resource_type.clone(), volume.min(affordable), current_hour);
+            city.tax_reserve -= stored * route.price_per_unit;
+            imported += stored;
+        } else if let Some(sent) = withdraw_stock(ctx, city_id, &route.resource_type, volume) {
+            city.tax_reserve += sent * route.price_per_unit;
+            exported += sent;
+        }
+    }
+    city.import_rate = imported / warehousing::TRADE_HOURS_PER_DAY;
+    city.export_rate = exported / warehousing::TRADE_HOURS_PER_DAY;
+
+    let warehouses: Vec<Warehouse> = ctx.db.warehouse().iter()
+        .filter(|w| w.city_id == city_id)
+        .collect();
+    for mut store in warehouses {
+        let days = current_hour.saturating_sub(store.last_update_hour) as f32 / 24.0;
+        let spoiled = store.stock * (1.0 - (1.0 - store.spoilage_rate).powf(days));
+        store.stock -= spoiled;
+        store.spoiled_total += spoiled;
+        store.last_update_hour = current_hour;
+        ctx.db.warehouse().id().update(store);
+    }
+
+    ctx.db.city().id().update(city);
+    Ok(())
+}
+
+/// Switch food rationing on at the given intensity (0-1), or off with zero
+#[spacetimedb::reducer]
+pub fn set_rationing(ctx: &ReducerContext, city_id: u32, intensity: f32) -> Result<(), String> {
+    let current_hour = ctx.db.simulation_time().id().find(&1)
+        .ok_or("Simulation not initialized")?
+        .current_hour;
+
+    ctx.db.city().id().find(&city_id)
+        .ok_or("City not found")?;
+
+    enact_policy(ctx, city_id, PolicyType::Rationing, intensity, current_hour);
+    if intensity <= 0.0 {
+        ctx.db.ration_book().city_id().delete(&city_id);
+    }
+
+    log::info!("City {} set rationing to {:.2}", city_id, intensity);
+    Ok(())
+}
+
+/// Build more warehouse space for a resource out of city funds
+#[spacetimedb::reducer]
+pub fn expand_warehouse(ctx: &ReducerContext, city_id: u32, resource_type: ResourceType, amount: f32) -> Result<(), String> {
+    let current_hour = ctx.db.simulation_time().id().find(&1)
+        .ok_or("Simulation not initialized")?
+        .current_hour;
+
+    let mut city = ctx.db.city().id().find(&city_id)
+        .ok_or("City not found")?;
+
+    let cost = amount * warehousing::EXPANSION_COST_PER_UNIT;
+    if amount <= 0.0 || city.tax_reserve < cost {
+        return Err("City cannot afford the expansion".to_string());
+    }
+
+    let mut store = open_warehouse(ctx, city_id, resource_type, current_hour);
+    store.capacity += amount;
+    ctx.db.warehouse().id().update(store);
+
+    city.tax_reserve -= cost;
+    ctx.db.city().id().update(city);
+
+    Ok(())
+}
+
+/// Quantity of a resource a city holds; None if the city keeps no warehouse for it
+pub(crate) fn stock_level(ctx: &ReducerContext, city_id: u32, resource_type: &ResourceType) -> Option<f32> {
+    ctx.db.warehouse().iter()
+        .find(|w| w.city_id == city_id && w.resource_type == *resource_type)
+        .map(|w| w.stock)
+}
+
+/// Add goods to a city's warehouse, opening one if needed; returns what fitted
+pub(crate) fn deposit_stock(ctx: &ReducerContext, city_id: u32, resource_type: ResourceType, amount: f32, hour: u64) -> f32 {
+    let mut store = open_warehouse(ctx, city_id, resource_type, hour);
+    let stored = amount.min(store.capacity - store.stock).max(0.0);
+    store.stock += stored;
+    ctx.db.warehouse().id().update(store);
+    stored
+}
+
+/// Take up to `amount` from a city's warehouse; returns what was taken, or None if the city keeps no warehouse for it
+pub(crate) fn withdraw_stock(ctx: &ReducerContext, city_id: u32, resource_type: &ResourceType, amount: f32) -> Option<f32> {
+    let mut store = ctx.db.warehouse().iter()
+        .find(|w| w.city_id == city_id && w.resource_type == *resource_type)?;
+
+    let taken = amount.min(store.stock).max(0.0);
+    store.stock -= taken;
+    ctx.db.warehouse().id().update(store);
+    Some(taken)
+}
+
+/// Eat one meal from the city's food warehouse; returns the share of a full portion served, zero if queued
+pub(crate) fn serve_meal(ctx: &ReducerContext, city_id: u32, hour: u64) -> f32 {
+    // Cities that keep no food warehouse are not modelled as going hungry
+    if stock_level(ctx, city_id, &ResourceType::Food).is_none() {
+        return 1.0;
+    }
+
+    let rationing = ctx.db.city_policy().iter()
+        .find(|p| p.city_id == city_id && p.policy_type == PolicyType::Rationing && p.intensity > 0.0)
+        .map(|p| p.intensity);
+
+    let intensity = match rationing {
+        Some(intensity) => intensity,
+        None => {
+            return match withdraw_stock(ctx, city_id, &ResourceType::Food, 1.0) {
+                Some(served) if served >= 1.0 => 1.0,
+                Some(served) => served.max(farming::FAMINE_MEAL_SHARE),
+                None => 1.0,
+            };
+        }
+    };
+
+    let mut book = todays_ration_book(ctx, city_id, intensity, hour);
+    if book.served_today + book.portion > book.daily_quota {
+        book.queued_today += 1;
+        ctx.db.ration_book().city_id().update(book);
+        return 0.0;
+    }
+
+    let served = withdraw_stock(ctx, city_id, &ResourceType::Food, book.portion).unwrap_or(0.0);
+    book.served_today += served;
+    let portion = book.portion;
+    ctx.db.ration_book().city_id().update(book);
+
+    if served >= portion {
+        portion
+    } else {
+        served.max(farming::FAMINE_MEAL_SHARE)
+    }
+}
+
+/// The ration book for the current day, opened fresh each morning
+fn todays_ration_book(ctx: &ReducerContext, city_id: u32, intensity: f32, hour: u64) -> RationBook {
+    let day = hour / 24;
+    let existing = ctx.db.ration_book().city_id().find(&city_id);
+    let is_new = existing.is_none();
+    if let Some(book) = existing {
+        if book.day == day {
+            return book;
+        }
+    }
+
+    let population = ctx.db.city().id().find(&city_id)
+        .map(|c| c.population)
+        .unwrap_or(0);
+    let portion = 1.0 - intensity.clamp(0.0, 1.0) * warehousing::RATION_CUT;
+    let book = RationBook {
+        city_id,
+        day,
+        portion,
+        daily_quota: population as f32 * farming::MEALS_PER_PERSON_PER_DAY * portion,
+        served_today: 0.0,
+        queued_today: 0,
+    };
+
+    if is_new {
+        ctx.db.ration_book().insert(book)
+    } else {
+        ctx.db.ration_book().city_id().update(book)
+    }
+}
+
+fn open_warehouse(ctx: &ReducerContext, city_id: u32, resource_type: ResourceType, hour: u64) -> Warehouse {
+    if let Some(store) = ctx.db.warehouse().iter()
+        .find(|w| w.city_id == city_id && w.resource_type == resource_type)
+    {
+        return store;
+    }
+
+    let population = ctx.db.city().id().find(&city_id)
+        .map(|c| c.population)
+        .unwrap_or(0);
+    let spoilage_rate = match resource_type {
+        ResourceType::Food => warehousing::FOOD_SPOILAGE,
+        ResourceType::Healthcare => warehousing::HEALTHCARE_SPOILAGE,
+        _ => 0.0,
+    };
+
+    ctx.db.warehouse().insert(Warehouse {
+        id: ctx.db.warehouse().iter().count() as u32 + 1,
+        city_id,
+        resource_type,
+        stock: 0.0,
+        capacity: warehousing::BASE_CAPACITY.max(population as f32 * warehousing::CAPACITY_PER_RESIDENT),
+        spoilage_rate,
+        spoiled_total: 0.0,
+        last_update_hour: hour,
+    })
+}
\ No newline at end of file
@@ -0,0 +1,451 @@
+use spacetimedb::{ReducerContext, Table};
+use log;
+use rand::Rng;
+use rand::seq::SliceRandom;
+use crate::tables::*;
+use crate::types::*;
+use crate::systems::modifiers::*;
+use crate::tables::unrest::{city_unrest, unrest_event, strike};
+use crate::tables::city::{city, city_policy};
+use crate::tables::individual::individual;
+use crate::tables::building::building;
+use crate::tables::events::{simulation_time, city_event};
+use crate::political::{PoliticalEventType, create_political_event, faction, political_office};
+use crate::narrative::{create_narrative_event, EventCategory};
+use crate::reducers::city::city_residents;
+use crate::reducers::migration::world_for_city;
+use crate::reducers::budget::tax_rates;
+use crate::reducers::crime::{police_officers, calculate_police_coverage};
+
+/// Daily unrest pass: grievances move the meter, and each stage it reaches takes effect
+#[spacetimedb::reducer]
+pub fn process_city_unrest(ctx: &ReducerContext, city_id: u32) -> Result<(), String> {
+    let current_hour = ctx.db.simulation_time().id().find(&1)
+        .ok_or("Simulation not initialized")?
+        .current_hour;
+
+    let mut city = ctx.db.city().id().find(&city_id)
+        .ok_or("City not found")?;
+    let mut unrest = unrest_record(ctx, city_id, current_hour);
+
+    // Grievances set the pressure; the meter closes part of the gap each day
+    let residents = city_residents(ctx, city_id);
+    let (pressure, cause) = assess_pressure(ctx, &city, &residents, &unrest);
+    let aggrieved: Vec<Individual> = residents.into_iter()
+        .filter(|i| i.age >= 18 && i.health > 0.0 && is_aggrieved(i))
+        .collect();
+    unrest.level = (unrest.level + (pressure - unrest.level) * disorder::ADJUSTMENT_RATE).clamp(0.0, 100.0);
+    unrest.repression = (unrest.repression - disorder::REPRESSION_DECAY).max(0.0);
+    if unrest.level < disorder::PROTEST_LEVEL {
+        unrest.instigator_faction_id = None;
+    }
+
+    let stage = stage_for(unrest.level);
+    if stage != unrest.stage {
+        let escalating = stage > unrest.stage;
+        announce_stage(ctx, &city, stage, escalating, aggrieved.len() as u32, &cause, unrest.instigator_faction_id, current_hour)?;
+        if stage == UnrestStage::Rebellion {
+            declare_rebellion(ctx, &city, &unrest, &cause, current_hour)?;
+        }
+        unrest.stage = stage;
+        unrest.stage_since_hour = current_hour;
+    }
+
+    let mut buildings_damaged = 0;
+    let mut injured = 0;
+    if unrest.stage >= UnrestStage::Protest {
+        city.stability = (city.stability + disorder::PROTEST_STABILITY_LOSS).max(0.0);
+    }
+    if unrest.stage >= UnrestStage::Strike {
+        call_strikes(ctx, city_id, current_hour);
+    } else {
+        end_strikes(ctx, city_id, current_hour);
+    }
+    if unrest.stage >= UnrestStage::Riot {
+        (buildings_damaged, injured) = riot(ctx, &mut city, &aggrieved);
+    }
+    if unrest.stage == UnrestStage::Rebellion {
+        city.stability = (city.stability + disorder::REBELLION_STABILITY_LOSS).max(0.0);
+        if current_hour >= unrest.stage_since_hour + disorder::REBELLION_DAYS * 24 {
+            decide_rebellion(ctx, &mut city, &mut unrest, aggrieved.len(), current_hour)?;
+        }
+    }
+
+    if unrest.stage != UnrestStage::Calm {
+        let id = ctx.db.unrest_event().iter().count() as u32 + 1;
+        ctx.db.unrest_event().insert(UnrestEvent {
+            id,
+            city_id,
+            hour: current_hour,
+            stage: unrest.stage,
+            level: unrest.level,
+            participants: aggrieved.len() as u32,
+            cause,
+            faction_id: unrest.instigator_faction_id,
+            buildings_damaged,
+            injured,
+        });
+    }
+
+    unrest.last_update_hour = current_hour;
+    ctx.db.city_unrest().city_id().update(unrest);
+    ctx.db.city().id().update(city);
+    Ok(())
+}
+
+/// Whether a workplace's staff are out on strike
+pub(crate) fn on_strike(ctx: &ReducerContext, building_id: u32) -> bool {
+    ctx.db.strike().iter()
+        .any(|s| s.building_id == building_id && s.ended_hour.is_none())
+}
+
+/// A city's current unrest level, 0 if it has never been measured
+pub(crate) fn unrest_level(ctx: &ReducerContext, city_id: u32) -> f32 {
+    ctx.db.city_unrest().city_id().find(&city_id)
+        .map_or(0.0, |u| u.level)
+}
+
+/// A faction stirs the crowds, adding to unrest and taking credit for it; returns the new level
+pub(crate) fn incite(ctx: &ReducerContext, city_id: u32, faction_id: u32, hour: u64) -> f32 {
+    let mut unrest = unrest_record(ctx, city_id, hour);
+    unrest.level = (unrest.level + disorder::INCITEMENT).min(100.0);
+    unrest.instigator_faction_id = Some(faction_id);
+    let level = unrest.level;
+    ctx.db.city_unrest().city_id().update(unrest);
+    level
+}
+
+/// Police break up the crowds: unrest falls with coverage but repression rises; returns the unrest removed
+pub(crate) fn crack_down(ctx: &ReducerContext, city_id: u32, hour: u64) -> f32 {
+    let mut unrest = unrest_record(ctx, city_id, hour);
+    let officers = police_officers(ctx, city_id).len();
+    let coverage = calculate_police_coverage(ctx, city_id, officers, city_residents(ctx, city_id).len());
+
+    let relief = (disorder::CRACKDOWN_RELIEF * coverage).min(unrest.level);
+    unrest.level -= relief;
+    unrest.repression = (unrest.repression + disorder::CRACKDOWN_REPRESSION).min(100.0);
+    ctx.db.city_unrest().city_id().update(unrest);
+    relief
+}
+
+fn unrest_record(ctx: &ReducerContext, city_id: u32, hour: u64) -> CityUnrest {
+    if let Some(unrest) = ctx.db.city_unrest().city_id().find(&city_id) {
+        return unrest;
+    }
+    ctx.db.city_unrest().insert(CityUnrest {
+        city_id,
+        level: 0.0,
+        stage: UnrestStage::Calm,
+        repression: 0.0,
+        instigator_faction_id: None,
+        stage_since_hour: hour,
+        last_update_hour: hour,
+    })
+}
+
+fn is_aggrieved(individual: &Individual) -> bool {
+    individual.stress > thresholds::STRESS_CRITICAL
+        || individual.food_water < thresholds::NEED_CRITICAL_LOW
+        || individual.workplace_id.is_none()
+}
+
+fn stage_for(level: f32) -> UnrestStage {
+    if level >= disorder::REBELLION_LEVEL {
+        UnrestStage::Rebellion
+    } else if level >= disorder::RIOT_LEVEL {
+        UnrestStage::Riot
+    } else if level >= disorder::STRIKE_LEVEL {
+        UnrestStage::Strike
+    } else if level >= disorder::PROTEST_LEVEL {
+        UnrestStage::Protest
+    } else {
+        UnrestStage::Calm
+    }
+}
+
+/// The pressure unrest is moving toward, and a description of what drives it, largest first
+fn assess_pressure(ctx: &ReducerContext, city: &City, residents: &[Individual], unrest: &CityUnrest) -> (f32, String) {
+    let adults: Vec<&Individual> = residents.iter().filter(|i| i.age >= 18 && i.health > 0.0).collect();
+    if adults.is_empty() {
+        return (0.0, String::new());
+    }
+    let share = |f: &dyn Fn(&Individual) -> bool| adults.iter().filter(|i| f(i)).count() as f32 / adults.len() as f32;
+
+    let stressed = share(&|i| i.stress > thresholds::STRESS_CRITICAL);
+    let hungry = share(&|i| i.food_water < thresholds::NEED_CRITICAL_LOW);
+    let unemployment = city.unemployment_rate / 100.0;
+    let tax_level = tax_rates(ctx, city.id).income / taxation::MAX_INCOME_TAX;
+    let security = ctx.db.city_policy().iter()
+        .find(|p| p.city_id == city.id && p.policy_type == PolicyType::SecurityFunding)
+        .map_or(0.0, |p| p.intensity);
+    let repression = unrest.repression.max(security * disorder::SECURITY_REPRESSION) / 100.0;
+
+    let mut pressures = vec![
+        (stressed * disorder::STRESS_WEIGHT, format!("{:.0}% of residents under critical stress", stressed * 100.0)),
+        (unemployment * disorder::UNEMPLOYMENT_WEIGHT, format!("unemployment at {:.0}%", unemployment * 100.0)),
+        (hungry * disorder::HUNGER_WEIGHT, format!("{:.0}% going hungry", hungry * 100.0)),
+        (tax_level * disorder::TAX_WEIGHT, format!("taxes at {:.0}% of the maximum", tax_level * 100.0)),
+        (repression * disorder::REPRESSION_WEIGHT, format!("resentment of repression ({:.0})", repression * 100.0)),
+    ];
+    let damping = city.stability / 100.0 * disorder::STABILITY_DAMPING;
+    let pressure = (pressures.iter().map(|p| p.0).sum::<f32>() - damping).clamp(0.0, 100.0);
+
+    pressures.sort_by(|a, b| b.0.total_cmp(&a.0));
+    let cause = pressures.iter()
+        .filter(|p| p.0 >= 1.0)
+        .map(|p| format!("{} (+{:.0})", p.1, p.0))
+        .collect::<Vec<_>>()
+        .join(", ");
+    (pressure, cause)
+}
+
+fn announce_stage(
+    ctx: &ReducerContext,
+    city: &City,
+    stage: UnrestStage,
+    escalating: bool,
+    participants: u32,
+    cause: &str,
+    instigator_id: Option<u32>,
+    hour: u64,
+) -> Result<(), String> {
+    let instigator = instigator_id
+        .and_then(|id| ctx.db.faction().id().find(&id))
+        .map_or(String::new(), |f| format!(", stirred up by {}", f.name));
+
+    let (title, description, impact_stability, importance) = match (stage, escalating) {
+        (UnrestStage::Calm, _) => (
+            format!("Calm returns to {}", city.name),
+            format!("The protests in {} have died down", city.name),
+            2.0, 2,
+        ),
+        (UnrestStage::Protest, true) => (
+            format!("Protests in {}", city.name),
+            format!("{} residents take to the streets of {} over {}{}", participants, city.name, cause, instigator),
+            -2.0, 3,
+        ),
+        (UnrestStage::Strike, true) => (
+            format!("Strikes spread in {}", city.name),
+            format!("Workers in {} walk out over {}{}", city.name, cause, instigator),
+            -5.0, 4,
+        ),
+        (UnrestStage::Riot, true) => (
+            format!("Riots in {}", city.name),
+            format!("Protest in {} turns violent as {} residents riot over {}{}", city.name, participants, cause, instigator),
+            -10.0, 5,
+        ),
+        (UnrestStage::Rebellion, _) => (
+            format!("Rebellion in {}", city.name),
+            format!("{} is in open revolt: {} rebels rise over {}{}", city.name, participants, cause, instigator),
+            -20.0, 6,
+        ),
+        (stage, false) => (
+            format!("Unrest eases in {}", city.name),
+            format!("Unrest in {} subsides to {:?}; grievances remain: {}", city.name, stage, cause),
+            1.0, 2,
+        ),
+    };
+
+    log::info!("City {}: unrest now {:?} ({})", city.id, stage, cause);
+    let id = ctx.db.city_event().iter().count() as u32 + 1;
+    ctx.db.city_event().insert(CityEvent {
+        id,
+        city_id: city.id,
+        hour,
+        event_type: CityEventType::Unrest,
+        description: description.clone(),
+        participants,
+        impact_stability,
+        impact_culture: 0.0,
+    });
+
+    if let Some(world_id) = world_for_city(ctx, city.id) {
+        let category = if stage >= UnrestStage::Riot { EventCategory::Political } else { EventCategory::Social };
+        create_narrative_event(ctx, world_id, 1, category, title, description, importance)?;
+    }
+    Ok(())
+}
+
+/// Walk out at the workplaces with the most aggrieved staff
+fn call_strikes(ctx: &ReducerContext, city_id: u32, hour: u64) {
+    let workplaces: Vec<Building> = ctx.db.building().iter()
+        .filter(|b| b.city_id == city_id && !b.destroyed && matches!(b.building_type, BuildingType::Workplace(_)))
+        .collect();
+    let wanted = (workplaces.len() as f32 * disorder::STRIKE_WORKPLACE_SHARE).ceil() as usize;
+    let striking = ctx.db.strike().iter()
+        .filter(|s| s.city_id == city_id && s.ended_hour.is_none())
+        .count();
+    if striking >= wanted {
+        return;
+    }
+
+    let mut candidates: Vec<(u32, u32, usize)> = workplaces.iter()
+        .filter(|b| !on_strike(ctx, b.id))
+        .map(|b| {
+            let staff: Vec<Individual> = ctx.db.individual().iter()
+                .filter(|i| i.workplace_id == Some(b.id) && i.health > 0.0)
+                .collect();
+            let aggrieved = staff.iter().filter(|i| i.stress > thresholds::STRESS_CRITICAL).count();
+            (b.id, staff.len() as u32, aggrieved)
+        })
+        .filter(|(_, staff, _)| *staff > 0)
+        .collect();
+    candidates.sort_by(|a, b| b.2.cmp(&a.2));
+
+    for (building_id, strikers, _) in candidates.into_iter().take(wanted - striking) {
+        let id = ctx.db.strike().iter().count() as u32 + 1;
+        ctx.db.strike().insert(Strike {
+            id,
+            city_id,
+            building_id,
+            strikers,
+            started_hour: hour,
+            ended_hour: None,
+        });
+    }
+}
+
+fn end_strikes(ctx: &ReducerContext, city_id: u32, hour: u64) {
+    let open: Vec<Strike> = ctx.db.strike().iter()
+        .filter(|s| s.city_id == city_id && s.ended_hour.is_none())
+        .collect();
+    for mut strike in open {
+        strike.ended_hour = Some(hour);
+        ctx.db.strike().id().update(strike);
+    }
+}
+
+/// Rioters wreck buildings and some are hurt; returns (buildings damaged, injured)
+fn riot(ctx: &ReducerContext, city: &mut City, rioters: &[Individual]) -> (u32, u32) {
+    let mut rng = rand::thread_rng();
+
+    let mut buildings: Vec<Building> = ctx.db.building().iter()
+        .filter(|b| b.city_id == city.id && !b.destroyed)
+        .collect();
+    buildings.shuffle(&mut rng);
+    let targets = (city.population as f32 / 100.0 * disorder::RIOT_BUILDINGS_PER_100).ceil() as usize;
+    let mut damaged = 0;
+    for mut building in buildings.into_iter().take(targets) {
+        building.maintenance = (building.maintenance - disorder::RIOT_DAMAGE).max(0.0);
+        ctx.db.building().id().update(building);
+        damaged += 1;
+    }
+
+    let mut injured = 0;
+    for rioter in rioters {
+        if rng.gen::<f32>() >= disorder::RIOT_INJURY_CHANCE {
+            continue;
+        }
+        if let Some(mut hurt) = ctx.db.individual().id().find(&rioter.id) {
+            hurt.health = (hurt.health + disorder::RIOT_INJURY_HEALTH_LOSS).max(0.0);
+            hurt.threat = (hurt.threat + disaster_damage::INJURY_THREAT_GAIN).min(thresholds::NEED_MAX);
+            ctx.db.individual().id().update(hurt);
+            injured += 1;
+        }
+    }
+
+    city.safety = (city.safety + disorder::RIOT_SAFETY_LOSS).max(0.0);
+    (damaged, injured)
+}
+
+/// The faction leading the rebels: the instigator, or the best-supported faction based here that does not hold the city
+fn rebel_faction(ctx: &ReducerContext, city_id: u32, unrest: &CityUnrest) -> Option<u32> {
+    if unrest.instigator_faction_id.is_some() {
+        return unrest.instigator_faction_id;
+    }
+    let ruling = chief_office(ctx, city_id).and_then(|o| o.faction_id);
+    ctx.db.faction().iter()
+        .filter(|f| f.is_active && f.base_city_id == city_id && Some(f.id) != ruling)
+        .max_by(|a, b| a.public_support.total_cmp(&b.public_support))
+        .map(|f| f.id)
+}
+
+fn chief_office(ctx: &ReducerContext, city_id: u32) -> Option<crate::political::PoliticalOffice> {
+    ctx.db.political_office().iter()
+        .filter(|o| o.city_id == city_id)
+        .max_by(|a, b| a.power_level.total_cmp(&b.power_level))
+}
+
+fn declare_rebellion(ctx: &ReducerContext, city: &City, unrest: &CityUnrest, cause: &str, hour: u64) -> Result<(), String> {
+    let (Some(world_id), Some(rebel_id)) = (world_for_city(ctx, city.id), rebel_faction(ctx, city.id, unrest)) else {
+        return Ok(());
+    };
+    let ruling = chief_office(ctx, city.id).and_then(|o| o.faction_id);
+    create_political_event(ctx, world_id, PoliticalEventType::Rebellion, rebel_id, ruling, hour,
+        format!("Rebellion in {} over {}", city.name, cause))?;
+    Ok(())
+}
+
+/// After a few days the rising is won or crushed, depending on rebels against police
+fn decide_rebellion(ctx: &ReducerContext, city: &mut City, unrest: &mut CityUnrest, rebels: usize, hour: u64) -> Result<(), String> {
+    let rebel_id = rebel_faction(ctx, city.id, unrest);
+    let rebel = rebel_id.and_then(|id| ctx.db.faction().id().find(&id));
+    let backing = rebel.as_ref().map_or(1.0, |f| 1.0 + f.public_support / 100.0);
+    let security = ctx.db.city_policy().iter()
+        .find(|p| p.city_id == city.id && p.policy_type == PolicyType::SecurityFunding)
+        .map_or(0.0, |p| p.intensity);
+    let defenders = police_officers(ctx, city.id).len() as f32 * disorder::DEFENDERS_PER_OFFICER * (1.0 + security);
+    let rebel_strength = rebels as f32 * backing;
+
+    let (title, description) = if rebel_strength > defenders {
+        // The rebels take the city: its chief office passes to their leader, or falls vacant for an election
+        let new_ruler = match (chief_office(ctx, city.id), &rebel) {
+            (Some(mut office), Some(faction)) => {
+                office.holder_id = faction.leader_id;
+                office.faction_id = Some(faction.id);
+                office.term_start_hour = hour;
+                office.approval_rating = faction.public_support;
+                let office_name = office.office_name.clone();
+                ctx.db.political_office().id().update(office);
+                format!("{} seizes the office of {}", faction.name, office_name)
+            },
+            (Some(mut office), None) => {
+                office.holder_id = 0;
+                office.faction_id = None;
+                let office_name = office.office_name.clone();
+                ctx.db.political_office().id().update(office);
+                format!("the {} is driven out", office_name)
+            },
+            (None, _) => "the city's rulers are overthrown".to_string(),
+        };
+        if let Some(mut faction) = rebel {
+            faction.influence = (faction.influence + 15.0).min(100.0);
+            faction.public_support = (faction.public_support + 10.0).min(100.0);
+            ctx.db.faction().id().update(faction);
+        }
+        unrest.repression = 0.0;
+        (format!("Rebels take {}", city.name),
+            format!("{} rebels overwhelm {:.0} defenders in {}; {}", rebels, defenders, city.name, new_ruler))
+    } else {
+        if let Some(mut faction) = rebel {
+            faction.influence = (faction.influence - 10.0).max(0.0);
+            faction.stability = (faction.stability - 15.0).max(0.0);
+            ctx.db.faction().id().update(faction);
+        }
+        unrest.repression = (unrest.repression + disorder::CRUSHED_REPRESSION).min(100.0);
+        (format!("Rebellion crushed in {}", city.name),
+            format!("{:.0} defenders put down {} rebels in {}; a harsh crackdown follows", defenders, rebels, city.name))
+    };
+
+    unrest.level = disorder::AFTERMATH_LEVEL;
+    unrest.instigator_faction_id = None;
+    unrest.stage = stage_for(unrest.level);
+    unrest.stage_since_hour = hour;
+
+    log::info!("City {}: {}", city.id, description);
+    let id = ctx.db.city_event().iter().count() as u32 + 1;
+    ctx.db.city_event().insert(CityEvent {
+        id,
+        city_id: city.id,
+        hour,
+        event_type: CityEventType::Unrest,
+        description: description.clone(),
+        participants: rebels as u32,
+        impact_stability: -10.0,
+        impact_culture: 0.0,
+    });
+    if let Some(world_id) = world_for_city(ctx, city.id) {
+        create_narrative_event(ctx, world_id, 1, EventCategory::Political, title, description, 6)?;
+    }
+    Ok(())
+}
\ No newline at end of file
@@ -4,9 +4,19 @@ use spacetimedb::{ReducerContext, Table};
 use log;
 use crate::world::game_world::GameWorld;
 use crate::narrative::{create_narrative_event, EventCategory};
-use crate::economics::{update_market_prices, process_trade_routes, generate_economic_events};
+use crate::economics::generate_economic_events;
+use crate::economics::markets::clear_markets;
+use crate::economics::trade_routes::process_trade_routes;
 use crate::political::{update_faction_status, generate_political_events, process_political_events};
+use crate::political::faction_membership::update_faction_membership;
+use crate::political::elections::update_offices;
+use crate::political::warfare::update_warfare;
+use crate::political::faction_relationships::update_diplomacy;
+use crate::political::faction_ai::plan_faction_actions;
+use crate::political::religion::update_religion;
 use crate::natural::{update_climate_conditions, generate_natural_events, process_natural_events};
+use crate::natural::weather::{generate_weather_fronts, update_weather_fronts};
+use crate::natural::disasters::{assess_disaster_risks, execute_disaster_events};
 
 // Scheduler configuration
 #[spacetimedb::table(name = scheduler_config)]
@@ -161,8 +171,8 @@ fn process_single_world(
     let current_hour = world.total_hours + hours_to_advance as u64;
 
     // 2. Update economic systems
-    if let Err(e) = update_market_prices(ctx, world_id, current_hour) {
-        log::warn!("Failed to update market prices for world {}: {}", world_id, e);
+    if let Err(e) = clear_markets(ctx, world_id, current_hour) {
+        log::warn!("Failed to clear markets for world {}: {}", world_id, e);
     }
 
     if let Err(e) = process_trade_routes(ctx, world_id, current_hour) {
@@ -197,6 +207,30 @@ fn process_single_world(
         log::warn!("Failed to update faction status for world {}: {}", world_id, e);
     }
 
+    if let Err(e) = update_faction_membership(ctx, world_id, current_hour) {
+        log::warn!("Failed to update faction membership for world {}: {}", world_id, e);
+    }
+
+    if let Err(e) = update_religion(ctx, world_id, current_hour) {
+        log::warn!("Failed to update religion for world {}: {}", world_id, e);
+    }
+
+    if let Err(e) = update_offices(ctx, world_id, current_hour) {
+        log::warn!("Failed to update political offices for world {}: {}", world_id, e);
+    }
+
+    if let Err(e) = update_diplomacy(ctx, world_id, current_hour) {
+        log::warn!("Failed to update diplomacy for world {}: {}", world_id, e);
+    }
+
+    if let Err(e) = update_warfare(ctx, world_id, current_hour) {
+        log::warn!("Failed to update warfare for world {}: {}", world_id, e);
+    }
+
+    if let Err(e) = plan_faction_actions(ctx, world_id, current_hour) {
+        log::warn!("Failed to plan faction actions for world {}: {}", world_id, e);
+    }
+
     // Generate political events
     match generate_political_events(ctx, world_id, current_hour) {
         Ok(event_ids) => {
@@ -230,6 +264,15 @@ fn process_single_world(
         log::warn!("Failed to update climate conditions for world {}: {}", world_id, e);
     }
 
+    // Move weather fronts across the regions
+    if let Err(e) = generate_weather_fronts(ctx, world_id, current_hour) {
+        log::warn!("Failed to generate weather fronts for world {}: {}", world_id, e);
+    }
+
+    if let Err(e) = update_weather_fronts(ctx, world_id, current_hour) {
+        log::warn!("Failed to update weather fronts for world {}: {}", world_id, e);
+    }
+
     // Generate natural events
     match generate_natural_events(ctx, world_id, current_hour) {
         Ok(event_ids) => {
@@ -238,6 +281,18 @@ fn process_single_world(
         Err(e) => log::warn!("Failed to generate natural events for world {}: {}", world_id, e),
     }
 
+    // Forecast disasters and let warned ones strike
+    if let Err(e) = assess_disaster_risks(ctx, world_id, current_hour) {
+        log::warn!("Failed to assess disaster risks for world {}: {}", world_id, e);
+    }
+
+    match execute_disaster_events(ctx, world_id, current_hour) {
+        Ok(event_ids) => {
+            stats.natural_events += event_ids.len() as u32;
+        },
+        Err(e) => log::warn!("Failed to execute disaster events for world {}: {}", world_id, e),
+    }
+
     // Process ongoing natural events
     if let Err(e) = process_natural_events(ctx, world_id, current_hour) {
         log::warn!("Failed to process natural events for world {}: {}", world_id, e);
@@ -252,6 +307,14 @@ fn process_single_world(
         if let Err(e) = crate::natural::seasonal_cycles::update_phenological_phases(ctx, world_id, current_hour) {
             log::warn!("Failed to update phenological phases for world {}: {}", world_id, e);
         }
+
+        if let Err(e) = crate::natural::wildlife::update_wildlife(ctx, world_id, current_hour) {
+            log::warn!("Failed to update wildlife for world {}: {}", world_id, e);
+        }
+
+        if let Err(e) = crate::natural::deposits::update_resource_deposits(ctx, world_id, current_hour) {
+            log::warn!("Failed to update resource deposits for world {}: {}", world_id, e);
+        }
     }
 
     // 5. Update world statistics
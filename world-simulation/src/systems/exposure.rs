@@ -0,0 +1,58 @@
+use crate::natural::WeatherPattern;
+use crate::systems::modifiers::weather_effects;
+
+/// Weather as felt by one individual, read from the climate of their own region
+#[derive(Debug, Clone, Copy)]
+pub struct LocalWeather {
+    pub temperature: f32,   // Celsius, including urban heat island
+    pub precipitation: f32, // mm/hour
+    pub wind_speed: f32,    // km/h
+    pub air_quality: f32,   // 0-100 index
+    pub pattern: WeatherPattern,
+    pub outdoors: bool,     // In transit or at an open-air location
+}
+
+impl LocalWeather {
+    /// Mild conditions used when a region has no climate record
+    pub fn mild(outdoors: bool) -> Self {
+        Self {
+            temperature: 18.0,
+            precipitation: 0.0,
+            wind_speed: 10.0,
+            air_quality: 80.0,
+            pattern: WeatherPattern::Clear,
+            outdoors,
+        }
+    }
+    
+    pub fn is_storm(&self) -> bool {
+        self.pattern == WeatherPattern::Stormy || self.wind_speed >= weather_effects::STORM_WIND_SPEED
+    }
+    
+    /// Degrees outside the comfortable band, whether cold or hot
+    pub fn thermal_stress(&self) -> f32 {
+        if self.temperature < weather_effects::COLD_THRESHOLD {
+            weather_effects::COLD_THRESHOLD - self.temperature
+        } else if self.temperature > weather_effects::HEAT_THRESHOLD {
+            self.temperature - weather_effects::HEAT_THRESHOLD
+        } else {
+            0.0
+        }
+    }
+    
+    /// Share of thermal stress that reaches the individual (buildings shelter most of it)
+    pub fn exposure(&self) -> f32 {
+        if self.outdoors { 1.0 } else { weather_effects::INDOOR_EXPOSURE }
+    }
+    
+    /// Multiplier on travel time from storms and heavy rain
+    pub fn travel_multiplier(&self) -> f32 {
+        if self.is_storm() {
+            weather_effects::STORM_TRAVEL_MULTIPLIER
+        } else if self.precipitation >= weather_effects::HEAVY_RAIN {
+            weather_effects::RAIN_TRAVEL_MULTIPLIER
+        } else {
+            1.0
+        }
+    }
+}
\ No newline at end of file
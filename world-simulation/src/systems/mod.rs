@@ -1,6 +1,8 @@
 pub mod needs;
 pub mod modifiers;
 pub mod priorities;
+pub mod exposure;
 
 pub use modifiers::*;
-pub use priorities::*;
\ No newline at end of file
+pub use priorities::*;
+pub use exposure::*;
\ No newline at end of file
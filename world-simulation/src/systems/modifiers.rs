@@ -80,8 +80,6 @@ pub mod building_depletion {
 // City Level Depletion Rates (per week)
 pub mod city_depletion {
     pub const PUBLIC_WORKS_PER_CITIZEN: f32 = -0.01;
-    pub const PUBLIC_SERVICE_COST_PER_100: f32 = -1.0;
-    pub const IMPORT_COST: f32 = -10.0;
     pub const EXPORT_REVENUE: f32 = 15.0;
     pub const STABILITY_PER_STRESSED: f32 = -0.1;
     pub const ARTIST_CULTURE_RATE: f32 = 0.5;
@@ -128,10 +126,12 @@ pub mod thresholds {
     
     pub const WASTE_CRITICAL: f32 = 80.0;
     pub const STRESS_CRITICAL: f32 = 70.0;
+    pub const HEALTH_CRITICAL: f32 = 40.0;
 }
 
 // Priority weights
 pub mod priority_weights {
+    pub const HEALTH_LOW: f32 = 9.0;
     pub const WASTE_HIGH: f32 = 10.0;
     pub const FOOD_CRITICAL: f32 = 8.0;
     pub const REST_CRITICAL: f32 = 7.0;
@@ -150,6 +150,10 @@ pub mod upgrades {
     pub const PRESTIGE_RENT_MULTIPLIER: f32 = 1.2;  // Per level
     pub const UPGRADE_WORK_HOURS_EFFICIENCY: f32 = 100.0;
     pub const UPGRADE_WORK_HOURS_PRESTIGE: f32 = 200.0;
+    pub const UPGRADE_WORK_HOURS_CAPACITY: f32 = 150.0;
+    pub const CAPACITY_UPGRADE_BONUS: f32 = 0.25;  // Share of capacity added
+    pub const REPAIR_WORK_HOURS_PER_POINT: f32 = 1.0;  // Per maintenance point restored
+    pub const RECONSTRUCTION_WORK_HOURS: f32 = 300.0;
 }
 
 // Location modifiers
@@ -167,4 +171,285 @@ pub mod location {
     
     pub const DANGEROUS_THREAT_PENALTY: f32 = -2.0;
     pub const DANGEROUS_STRESS_INCREASE: f32 = 1.0;
+}
+
+// Crime and public safety (per hour)
+pub mod public_safety {
+    pub const OFFENCE_BASE_CHANCE: f32 = 0.0005;
+    pub const OFFENCE_STRESS_FACTOR: f32 = 0.004;      // At stress 100
+    pub const OFFENCE_DEPRIVATION_FACTOR: f32 = 0.006; // At zero income
+    pub const POLICE_DETERRENCE: f32 = 0.7;            // Max reduction at full coverage
+    pub const POLICE_OFFICERS_PER_100: f32 = 2.0;      // Staffing for full coverage
+    
+    pub const THEFT_SHARE: f32 = 0.3;    // Share of victim income stolen
+    pub const FRAUD_SHARE: f32 = 0.2;
+    pub const ASSAULT_SAFETY_LOSS: f32 = -25.0;
+    pub const ASSAULT_THREAT_GAIN: f32 = 30.0;
+    pub const VICTIM_STRESS_GAIN: f32 = 10.0;
+    
+    pub const ARREST_BASE_CHANCE: f32 = 0.1;
+    pub const ARREST_COVERAGE_FACTOR: f32 = 0.6;
+    pub const ARREST_FINE: f32 = 50.0;
+    pub const ARREST_STRESS_GAIN: f32 = 20.0;
+    
+    pub const FACTION_RECRUIT_CHANCE: f32 = 0.2;
+    pub const FACTION_CUT: f32 = 0.25;   // Share of proceeds paid to the faction
+    pub const FACTION_TREASURY_SCALE: f32 = 10.0; // Individual income to faction treasury units
+    
+    pub const CRIME_RATE_WINDOW_HOURS: u64 = 168;
+}
+
+
+// Disease and treatment
+pub mod disease {
+    pub const TREATMENT_RECOVERY_MULTIPLIER: f32 = 2.0;   // Recovery speed in a hospital
+    pub const TREATMENT_SEVERITY_REDUCTION: f32 = -2.0;  // Severity change per hour of treatment
+    pub const HEALER_BONUS: f32 = 0.25;                  // Extra recovery per Healer on staff
+    pub const HEALTHCARE_STAFF_BONUS: f32 = 0.1;         // Extra recovery per Healthcare worker
+    pub const MAX_STAFF_BONUS: f32 = 1.5;
+    
+    pub const HEALTH_RECOVERY: f32 = 1.0;                // Per hour when not symptomatic
+    pub const SYMPTOM_HEALTH_LOSS: f32 = -0.05;          // Per severity point per hour
+    pub const SYMPTOM_REST_LOSS: f32 = -0.5;
+    pub const SYMPTOM_STRESS_GAIN: f32 = 0.5;
+    
+    pub const PLAGUE_SEED_SHARE: f32 = 0.02;             // Residents infected when a plague arrives
+    pub const PLAGUE_MIN_SEED: u32 = 1;
+    
+    pub const SANITATION_PROTECTION: f32 = 0.8;          // Dysentery avoided at full sanitation
+}
+
+
+// Schooling, skills and job qualification
+pub mod schooling {
+    pub const SCHOOL_AGE_MIN: u32 = 6;
+    pub const SCHOOL_AGE_MAX: u32 = 17;
+    pub const SCHOOL_START_HOUR: u64 = 8;
+    pub const SCHOOL_END_HOUR: u64 = 15;
+    pub const SCHOOL_DAYS_PER_WEEK: u64 = 5;
+    pub const RETRAINING_START_HOUR: u64 = 18;
+    pub const RETRAINING_END_HOUR: u64 = 21;
+    pub const RETRAINING_TUITION: f32 = 20.0;
+    
+    pub const SCHOOLING_SKILL_GAIN: f32 = 0.05;   // Per hour, every job type
+    pub const RETRAINING_SKILL_GAIN: f32 = 0.4;   // Per hour, focused job type
+    pub const WORK_SKILL_GAIN: f32 = 0.02;        // Per hour worked in the job
+    pub const SKILL_MAX: f32 = 100.0;
+    
+    pub const STUDENTS_PER_EDUCATOR: f32 = 20.0;
+    pub const EDUCATOR_ROLE_BONUS: f32 = 0.5;     // Educators count as 1.5 teachers
+    pub const UNFUNDED_QUALITY: f32 = 0.6;        // Quality multiplier without EducationFunding
+    pub const MIN_QUALITY: f32 = 0.2;
+    
+    pub const PRODUCTIVITY_MIN_SKILL_FACTOR: f32 = 0.6;  // At skill 0
+    pub const PRODUCTIVITY_MAX_SKILL_FACTOR: f32 = 1.4;  // At skill 100
+    pub const WAGE_SKILL_BONUS: f32 = 0.5;        // Extra wage share at skill 100
+    
+    pub const EXCELLENCE_ENROLLMENT: f32 = 0.9;   // Share of school-age children enrolled
+    pub const EXCELLENCE_QUALITY: f32 = 0.8;
+}
+
+// Migration between cities (evaluated weekly)
+pub mod relocation {
+    pub const UNMET_NEEDS_PRESSURE: f32 = 1.0;    // Per week with a critical need
+    pub const UNEMPLOYMENT_PRESSURE: f32 = 1.5;   // Per week without work
+    pub const DISASTER_PRESSURE: f32 = 4.0;       // Per week after a harmful event
+    pub const PRESSURE_DECAY: f32 = 0.5;          // Per week without push factors
+    pub const PRESSURE_THRESHOLD: f32 = 4.0;
+    pub const DISASTER_EXPOSURE_HOURS: u64 = 336; // Events this recent still push people out
+    
+    pub const JOB_OPENING_WEIGHT: f32 = 2.0;      // Per open position, capped
+    pub const MAX_JOB_SCORE: f32 = 20.0;
+    pub const HOUSING_WEIGHT: f32 = 1.0;          // Per vacant home place, capped
+    pub const MAX_HOUSING_SCORE: f32 = 10.0;
+    pub const SAFETY_WEIGHT: f32 = 0.2;
+    pub const HEALTH_WEIGHT: f32 = 0.1;
+    pub const DISTANCE_PENALTY: f32 = 0.05;       // Per map unit between city centres
+    pub const MIN_IMPROVEMENT: f32 = 5.0;         // Destination must beat home by this much
+    
+    pub const MIN_TRAVEL_HOURS: u64 = 24;
+    pub const TRAVEL_HOURS_PER_UNIT: f32 = 0.5;
+    pub const EXODUS_NARRATIVE_THRESHOLD: u32 = 5; // Disaster departures in one week
+}
+
+// Disaster damage to buildings and people
+pub mod disaster_damage {
+    pub const MITIGATION_EFFECT: f32 = 0.8;       // Damage reduction at 100% mitigation
+    pub const MAX_BUILDING_DAMAGE: f32 = 100.0;   // Maintenance lost at the epicentre at full intensity
+    pub const DESTROY_THRESHOLD: f32 = 70.0;      // Damage at or above this destroys the building
+    
+    pub const INJURY_CHANCE: f32 = 0.6;           // At the epicentre at full intensity
+    pub const INJURY_HEALTH_LOSS: f32 = -50.0;
+    pub const INJURY_SAFETY_LOSS: f32 = -30.0;
+    pub const INJURY_THREAT_GAIN: f32 = 30.0;
+    pub const DISPLACED_STRESS_GAIN: f32 = 20.0;
+    
+    pub const RESPONDERS_PER_SITE: usize = 4;
+    pub const RESPONDER_COST: f32 = 10.0;         // City funds per responder dispatched
+    pub const RESCUE_HEALTH_GAIN: f32 = 20.0;
+    pub const EVACUATION_SAFETY_GAIN: f32 = 20.0;
+}
+
+// Weather felt by individuals and buildings (per hour unless noted)
+pub mod weather_effects {
+    pub const COLD_THRESHOLD: f32 = 5.0;          // Celsius
+    pub const HEAT_THRESHOLD: f32 = 30.0;
+    pub const STORM_WIND_SPEED: f32 = 60.0;       // km/h
+    pub const HEAVY_RAIN: f32 = 5.0;              // mm/hour
+    pub const INDOOR_EXPOSURE: f32 = 0.25;        // Share of thermal stress felt indoors
+    
+    pub const FOOD_WATER_PER_DEGREE: f32 = -0.05;
+    pub const REST_PER_DEGREE: f32 = -0.05;
+    pub const ENVIRONMENT_PER_DEGREE: f32 = -0.1;  // Outdoors only
+    pub const STORM_ENVIRONMENT: f32 = -2.0;       // Outdoors only
+    
+    pub const POOR_AIR_QUALITY: f32 = 50.0;
+    pub const AIR_HEALTH_PER_POINT: f32 = -0.01;   // Per index point below poor
+    
+    pub const STORM_TRAVEL_MULTIPLIER: f32 = 1.5;
+    pub const RAIN_TRAVEL_MULTIPLIER: f32 = 1.2;
+    pub const STORM_OUTING_PENALTY: f32 = 3.0;     // Score penalty for parks during storms
+    
+    pub const STORM_MAINTENANCE_DECAY: f32 = -3.0; // Per day
+    pub const RAIN_MAINTENANCE_DECAY: f32 = -1.0;
+}
+
+// Farming calendar, food reserves and famine (per day unless noted)
+pub mod farming {
+    pub const GROWING_DAYS: f32 = 90.0;           // Days to ripen with full labour and a neutral season
+    pub const UNRIPE_GROWTH_CAP: f32 = 0.8;       // Crops wait here until their fruiting phase begins
+    pub const YIELD_PER_WORKER: f32 = 2800.0;     // Meals per worker per harvest at full crop health
+    pub const DROUGHT_CROP_DAMAGE: f32 = 0.03;    // Crop health lost per day
+    pub const FLOOD_CROP_DAMAGE: f32 = 0.05;
+    
+    pub const MEALS_PER_PERSON_PER_DAY: f32 = 2.0;
+    pub const WINTER_DAYS: f32 = 90.0;            // Reserve wanted going into winter
+    pub const SURPLUS_SHARE: f32 = 1.5;           // Stock above this multiple of the winter target is sold
+    pub const STOCKPILE_BUDGET_SHARE: f32 = 0.2;  // Share of city funds spent buying food each autumn day
+    pub const MEALS_PER_MARKET_UNIT: f32 = 10.0;
+    
+    pub const FAMINE_MEAL_SHARE: f32 = 0.3;       // Share of a meal scavenged from empty stores
+    pub const FAMINE_STABILITY_LOSS: f32 = -5.0;
+    pub const FAMINE_RECOVERY_DAYS: f32 = 7.0;    // Days of supply that end a famine
+}
+
+// City warehouses, trade flows and rationing (per day unless noted)
+pub mod warehousing {
+    pub const BASE_CAPACITY: f32 = 10000.0;
+    pub const CAPACITY_PER_RESIDENT: f32 = 250.0;  // Enough for a winter's meals
+    pub const EXPANSION_COST_PER_UNIT: f32 = 0.1;
+    
+    pub const FOOD_SPOILAGE: f32 = 0.01;
+    pub const HEALTHCARE_SPOILAGE: f32 = 0.005;
+    
+    pub const TRADE_HOURS_PER_DAY: f32 = 24.0;
+    
+    pub const RATION_CUT: f32 = 0.5;              // Portion reduction at full policy intensity
+    pub const RATION_QUEUE_STRESS: f32 = 3.0;     // Stress from being turned away
+}
+
+// City taxes and the weekly budget; rates are at full TaxRate policy intensity
+pub mod taxation {
+    pub const DEFAULT_TAX_LEVEL: f32 = 0.5;       // Policy intensity assumed without a TaxRate policy
+    pub const MAX_INCOME_TAX: f32 = 0.4;          // Share of wages withheld
+    pub const MAX_PROPERTY_TAX: f32 = 0.1;        // Share of a home's weekly rent
+    pub const MAX_SALES_TAX: f32 = 0.1;           // Share of each market trade, paid by the seller
+    pub const MAX_TARIFF: f32 = 0.2;              // Share of arriving cargo value
+    
+    pub const SERVICE_HOURS_PER_WEEK: f32 = 168.0;
+    pub const PUBLIC_WORKS_COST_PER_POINT: f32 = 20.0;
+    pub const PUBLIC_WORKS_BUDGET_SHARE: f32 = 0.2; // Of revenue, scaled up by PublicSpending
+    pub const POLICY_COST_PER_100: f32 = 5.0;     // Per 100 residents at full intensity
+    pub const DEBT_INTEREST_RATE: f32 = 0.01;     // Per week on a negative reserve
+}
+
+// Public works projects: proposal triggers, crews and completion effects
+pub mod construction {
+    pub const ROADS_PUBLIC_WORKS_THRESHOLD: f32 = 60.0;
+    pub const SEWERS_HEALTH_THRESHOLD: f32 = 60.0;
+    pub const WALLS_SAFETY_THRESHOLD: f32 = 50.0;
+    pub const AQUEDUCT_POPULATION: u32 = 500;
+    pub const MAX_OPEN_PROJECTS: usize = 2;
+    
+    pub const RESERVE_FUNDING_SHARE: f32 = 0.25;  // Of a positive reserve, allocated weekly
+    pub const MATERIALS_PER_HOUR: f32 = 2.0;      // Per crew work hour
+    pub const CREW_SHIFT_HOURS: f32 = 8.0;        // Per crew member per day
+    
+    pub const PUBLIC_WORKS_RESTORED: f32 = 30.0;  // On completing any project
+    pub const ROUTE_SAFETY_GAIN: f32 = 10.0;      // Roads, on every trade route touching the city
+    pub const SANITATION_QUALITY_GAIN: f32 = 20.0; // Sewers
+    pub const SANITATION_COVERAGE_GAIN: f32 = 40.0; // Aqueduct
+    pub const WALL_SAFETY_BONUS: f32 = 15.0;
+}
+
+// City unrest: pressures on the 0-100 meter, stage thresholds and stage effects (per day unless noted)
+pub mod disorder {
+    pub const STRESS_WEIGHT: f32 = 40.0;          // At every resident critically stressed
+    pub const UNEMPLOYMENT_WEIGHT: f32 = 30.0;    // At full unemployment
+    pub const HUNGER_WEIGHT: f32 = 40.0;          // At every resident critically hungry
+    pub const TAX_WEIGHT: f32 = 20.0;             // At full TaxRate intensity
+    pub const REPRESSION_WEIGHT: f32 = 20.0;      // At full repression
+    pub const STABILITY_DAMPING: f32 = 20.0;      // Pressure removed at full city stability
+    pub const ADJUSTMENT_RATE: f32 = 0.2;         // Share of the gap to the pressure closed each day
+    
+    pub const PROTEST_LEVEL: f32 = 30.0;
+    pub const STRIKE_LEVEL: f32 = 50.0;
+    pub const RIOT_LEVEL: f32 = 70.0;
+    pub const REBELLION_LEVEL: f32 = 85.0;
+    
+    pub const PROTEST_STABILITY_LOSS: f32 = -1.0;
+    pub const STRIKE_WORKPLACE_SHARE: f32 = 0.3;  // Of the city's workplaces, the most aggrieved first
+    pub const PICKET_COMMUNITY_GAIN: f32 = 3.0;   // Per shift on the picket line
+    pub const RIOT_BUILDINGS_PER_100: f32 = 2.0;  // Buildings damaged per 100 residents
+    pub const RIOT_DAMAGE: f32 = 30.0;            // Maintenance lost
+    pub const RIOT_INJURY_CHANCE: f32 = 0.05;     // Per rioter
+    pub const RIOT_INJURY_HEALTH_LOSS: f32 = -20.0;
+    pub const RIOT_SAFETY_LOSS: f32 = -5.0;
+    pub const REBELLION_STABILITY_LOSS: f32 = -10.0;
+    pub const REBELLION_DAYS: u64 = 3;            // Before the rising is won or crushed
+    pub const DEFENDERS_PER_OFFICER: f32 = 5.0;   // Rebels each police officer can hold off
+    
+    pub const REPRESSION_DECAY: f32 = 2.0;
+    pub const SECURITY_REPRESSION: f32 = 30.0;    // Repression felt at full SecurityFunding
+    pub const CRACKDOWN_RELIEF: f32 = 25.0;       // Unrest removed at full police coverage
+    pub const CRACKDOWN_REPRESSION: f32 = 20.0;
+    pub const CRUSHED_REPRESSION: f32 = 40.0;     // After a failed rebellion
+    pub const INCITEMENT: f32 = 10.0;             // Unrest a faction adds by stirring the crowds
+    pub const AFTERMATH_LEVEL: f32 = 40.0;        // Unrest left once a rebellion is decided
+}
+
+// Disaster early warning: who acts on a warning, what cities spend getting ready and how trust moves
+pub mod early_warning {
+    pub const INITIAL_TRUST: f32 = 60.0;
+    pub const HEED_WATCH: f32 = 0.2;              // Chance a fully trusting resident acts on each level
+    pub const HEED_ADVISORY: f32 = 0.45;
+    pub const HEED_WARNING: f32 = 0.75;
+    pub const HEED_EMERGENCY: f32 = 0.95;
+    pub const VULNERABLE_AGE: u32 = 65;
+    pub const VULNERABLE_HEED_BONUS: f32 = 0.15;  // Old or sick residents act more readily
+    pub const WAGE_WORRY: f32 = 0.3;              // Chance a hard-up worker stays home rather than evacuate
+    pub const HARD_UP_INCOME: f32 = 20.0;
+    
+    pub const STOCK_MEALS: u32 = 3;               // Meals bought in from the city's stores
+    pub const STOCK_MEAL_COST: f32 = 5.0;
+    pub const HOLD_HOURS: u64 = 4;                // Sheltering in place before looking around again
+    pub const SHELTER_CAPACITY_SHARE: f32 = 2.0;  // Evacuees per place of everyday capacity
+    pub const SHELTER_PROTECTION: f32 = 0.7;      // Share of injury chance removed inside a shelter
+    
+    pub const PREPARATION_PER_RESIDENT_WATCH: f32 = 0.5;
+    pub const PREPARATION_PER_RESIDENT_ADVISORY: f32 = 1.0;
+    pub const PREPARATION_PER_RESIDENT_WARNING: f32 = 2.0;
+    pub const PREPARATION_PER_RESIDENT_EMERGENCY: f32 = 4.0;
+    pub const MITIGATION_COST_PER_RESIDENT: f32 = 0.1; // Per point, rising as mitigation nears 100
+    
+    pub const FALSE_ALARM_TRUST_LOSS: f32 = 15.0; // For an Emergency; lesser levels cost less
+    pub const CONFIRMED_TRUST_GAIN: f32 = 5.0;
+}
+
+// Mines, quarries and logging camps (per day)
+pub mod extractive {
+    pub const MINING_PER_WORKER: f32 = 8.0;       // Ore or coal brought up per worker from an easy, untouched seam
+    pub const QUARRYING_PER_WORKER: f32 = 12.0;
+    pub const LOGGING_PER_WORKER: f32 = 10.0;
+    pub const DEPLETION_SLOWDOWN: f32 = 0.6;      // Share of output lost by the time a deposit is nearly worked out
 }
\ No newline at end of file
@@ -1,6 +1,7 @@
 use crate::tables::*;
 use crate::types::*;
 use crate::systems::modifiers::*;
+use crate::systems::exposure::LocalWeather;
 
 impl Individual {
     /// Check if a higher level need is active (lower level must be adequate)
@@ -35,14 +36,15 @@ impl Individual {
         self.achievements
     }
     
-    /// Update all needs based on time passed and current status
-    pub fn update_needs(&mut self, hours_passed: u64, location: &LocationCapability) {
+    /// Update all needs based on time passed, current status and local weather
+    pub fn update_needs(&mut self, hours_passed: u64, location: &LocationCapability, weather: &LocalWeather) {
         // Level 1: Physiological needs
-        self.update_food_water(hours_passed);
-        self.update_environment(hours_passed, location);
+        self.update_food_water(hours_passed, weather);
+        self.update_environment(hours_passed, location, weather);
         self.update_intimacy(hours_passed);
-        self.update_rest(hours_passed);
+        self.update_rest(hours_passed, weather);
         self.update_waste(hours_passed);
+        self.update_air_exposure(hours_passed, weather);
         
         // Level 2: Safety & Security (only if Level 1 is adequate)
         if self.is_need_level_active(2) {
@@ -63,17 +65,21 @@ impl Individual {
         }
     }
     
-    fn update_food_water(&mut self, hours_passed: u64) {
+    fn update_food_water(&mut self, hours_passed: u64, weather: &LocalWeather) {
         let depletion = match &self.status {
             IndividualStatus::Working { .. } => individual_depletion::FOOD_WATER_WORKING,
             IndividualStatus::Sleeping { .. } => individual_depletion::FOOD_WATER_RESTING,
             _ => individual_depletion::FOOD_WATER_BASE,
         };
-        self.food_water = (self.food_water + depletion * hours_passed as f32)
+        
+        // Keeping warm or cool burns through food and water
+        let weather_modifier = weather.thermal_stress() * weather.exposure() * weather_effects::FOOD_WATER_PER_DEGREE;
+        
+        self.food_water = (self.food_water + (depletion + weather_modifier) * hours_passed as f32)
             .clamp(0.0, thresholds::NEED_MAX);
     }
     
-    fn update_environment(&mut self, hours_passed: u64, location: &LocationCapability) {
+    fn update_environment(&mut self, hours_passed: u64, location: &LocationCapability, weather: &LocalWeather) {
         let depletion = if location.environmental_quality > 0.0 {
             individual_depletion::ENVIRONMENT_HEALING
         } else if location.environmental_quality < -1.0 {
@@ -81,7 +87,16 @@ impl Individual {
         } else {
             individual_depletion::ENVIRONMENT_NEUTRAL
         };
-        self.environment = (self.environment + depletion * hours_passed as f32)
+        
+        // Cold, heat and storms only bite when out in them
+        let weather_modifier = if weather.outdoors {
+            let storm = if weather.is_storm() { weather_effects::STORM_ENVIRONMENT } else { 0.0 };
+            weather.thermal_stress() * weather_effects::ENVIRONMENT_PER_DEGREE + storm
+        } else {
+            0.0
+        };
+        
+        self.environment = (self.environment + (depletion + weather_modifier) * hours_passed as f32)
             .clamp(0.0, thresholds::NEED_MAX);
     }
     
@@ -91,7 +106,7 @@ impl Individual {
             .clamp(0.0, thresholds::NEED_MAX);
     }
     
-    fn update_rest(&mut self, hours_passed: u64) {
+    fn update_rest(&mut self, hours_passed: u64, weather: &LocalWeather) {
         let depletion = match &self.status {
             IndividualStatus::Sleeping { .. } => individual_depletion::REST_SLEEPING,
             IndividualStatus::Working { .. } => individual_depletion::REST_WORKING,
@@ -101,7 +116,10 @@ impl Individual {
         // Stress affects rest depletion
         let stress_modifier = (self.stress / 10.0) * individual_depletion::STRESS_TO_REST_FACTOR;
         
-        self.rest = (self.rest + (depletion + stress_modifier) * hours_passed as f32)
+        // Cold and heat are tiring
+        let weather_modifier = weather.thermal_stress() * weather.exposure() * weather_effects::REST_PER_DEGREE;
+        
+        self.rest = (self.rest + (depletion + stress_modifier + weather_modifier) * hours_passed as f32)
             .clamp(0.0, thresholds::NEED_MAX);
     }
     
@@ -111,6 +129,15 @@ impl Individual {
             .clamp(0.0, thresholds::NEED_MAX);
     }
     
+    fn update_air_exposure(&mut self, hours_passed: u64, weather: &LocalWeather) {
+        if weather.air_quality >= weather_effects::POOR_AIR_QUALITY {
+            return;
+        }
+        let damage = (weather_effects::POOR_AIR_QUALITY - weather.air_quality) * weather_effects::AIR_HEALTH_PER_POINT;
+        self.health = (self.health + damage * hours_passed as f32)
+            .clamp(0.0, thresholds::NEED_MAX);
+    }
+    
     fn update_threat(&mut self, hours_passed: u64, location: &LocationCapability) {
         let depletion = if location.provides_healthcare || location.provides_rest {
             individual_depletion::THREAT_SAFE_BUILDING
@@ -207,6 +234,12 @@ impl Individual {
                        (thresholds::NEED_MAX - self.rest) * priority_weights::REST_CRITICAL));
         }
         
+        // Sick individuals look for somewhere to recover (hospitals score highest)
+        if self.health < thresholds::HEALTH_CRITICAL {
+            needs.push((FundamentalNeed::Environment, 
+                       (thresholds::NEED_MAX - self.health) * priority_weights::HEALTH_LOW));
+        }
+        
         if self.environment < thresholds::NEED_CRITICAL_LOW {
             needs.push((FundamentalNeed::Environment, 
                        (thresholds::NEED_MAX - self.environment) * priority_weights::ENVIRONMENT_LOW));
@@ -1,14 +1,16 @@
 use crate::tables::*;
 use crate::types::*;
+use crate::systems::modifiers::{thresholds, schooling, weather_effects};
+use crate::systems::exposure::LocalWeather;
 
 /// Calculate distance between two locations
 pub fn calculate_distance(x1: f32, y1: f32, x2: f32, y2: f32) -> f32 {
     ((x2 - x1).powi(2) + (y2 - y1).powi(2)).sqrt()
 }
 
-/// Calculate travel time based on distance (1 hour per 10 units)
-pub fn calculate_travel_time(distance: f32) -> u64 {
-    ((distance / 10.0).ceil() as u64).max(1)
+/// Calculate travel time based on distance (1 hour per 10 units), slowed by storms and heavy rain
+pub fn calculate_travel_time(distance: f32, weather: &LocalWeather) -> u64 {
+    ((distance / 10.0 * weather.travel_multiplier()).ceil() as u64).max(1)
 }
 
 /// Find the best location for fulfilling a specific need
@@ -19,12 +21,13 @@ pub fn find_best_location_for_need(
     locations: &[LocationCapability],
     current_x: f32,
     current_y: f32,
+    weather: &LocalWeather,
 ) -> Option<(u32, f32)> { // Returns (building_id, score)
     let mut candidates = Vec::new();
     
     for (building, location) in buildings.iter().zip(locations.iter()) {
-        // Check if building has capacity
-        if building.current_occupants >= building.max_capacity {
+        // Check if building is standing and has capacity
+        if building.destroyed || building.current_occupants >= building.max_capacity {
             continue;
         }
         
@@ -47,7 +50,7 @@ pub fn find_best_location_for_need(
         
         // Calculate score based on distance and quality
         let distance = calculate_distance(current_x, current_y, building.location_x, building.location_y);
-        let travel_time = calculate_travel_time(distance);
+        let travel_time = calculate_travel_time(distance, weather);
         
         // Base score from location quality and building prestige
         let quality_score = location.environmental_quality + (building.prestige_level as f32 * 0.2);
@@ -58,8 +61,23 @@ pub fn find_best_location_for_need(
         // Special bonuses
         let home_bonus = if individual.home_id == Some(building.id) { 2.0 } else { 0.0 };
         let work_bonus = if individual.workplace_id == Some(building.id) { 1.0 } else { 0.0 };
+        let treatment_bonus = if individual.health < thresholds::HEALTH_CRITICAL && location.provides_healthcare {
+            3.0
+        } else {
+            0.0
+        };
+        
+        // Nobody goes out to socialize in a storm, and parks lose their appeal
+        if weather.is_storm() && matches!(need, FundamentalNeed::Connection) && distance > 0.0 {
+            continue;
+        }
+        let storm_penalty = if weather.is_storm() && matches!(building.building_type, BuildingType::Park) {
+            weather_effects::STORM_OUTING_PENALTY
+        } else {
+            0.0
+        };
         
-        let total_score = quality_score + home_bonus + work_bonus - distance_penalty;
+        let total_score = quality_score + home_bonus + work_bonus + treatment_bonus - distance_penalty - storm_penalty;
         
         candidates.push((building.id, total_score, travel_time));
     }
@@ -100,10 +118,45 @@ pub fn can_afford_action(individual: &Individual, action: &IndividualAction) ->
     }
 }
 
-/// Calculate productivity based on individual's needs
-pub fn calculate_productivity(individual: &Individual) -> f32 {
-    // Base productivity
-    let mut productivity: f32 = 1.0;
+/// The job a building's staff do, if it employs anyone
+pub fn staff_job_type(building_type: &BuildingType) -> Option<JobType> {
+    match building_type {
+        BuildingType::Workplace(config) => Some(config.job_type.clone()),
+        BuildingType::Hospital => Some(JobType::Healthcare),
+        BuildingType::School => Some(JobType::Education),
+        BuildingType::PoliceStation | BuildingType::CityHall => Some(JobType::Government),
+        BuildingType::ResearchLab => Some(JobType::Research),
+        BuildingType::CultureCenter => Some(JobType::Culture),
+        BuildingType::Restaurant => Some(JobType::Retail),
+        BuildingType::Park => Some(JobType::Utilities),
+        BuildingType::Home(_) => None,
+    }
+}
+
+/// Minimum skill level needed to be hired for a job type
+pub fn required_skill_for_job(job_type: &JobType) -> f32 {
+    match job_type {
+        JobType::Factory | JobType::Retail | JobType::Agriculture => 0.0,
+        JobType::Mining | JobType::Quarrying | JobType::Logging => 0.0,
+        JobType::Utilities | JobType::Office => 20.0,
+        JobType::Government | JobType::Culture => 30.0,
+        JobType::Education => 40.0,
+        JobType::Healthcare => 50.0,
+        JobType::Research => 60.0,
+    }
+}
+
+/// Check if a skill level qualifies for a job type
+pub fn is_qualified_for_job(skill_level: f32, job_type: &JobType) -> bool {
+    skill_level >= required_skill_for_job(job_type)
+}
+
+/// Calculate productivity based on individual's needs and skill in their job
+pub fn calculate_productivity(individual: &Individual, skill_level: f32) -> f32 {
+    // Base productivity scales with skill
+    let skill_share = (skill_level / schooling::SKILL_MAX).clamp(0.0, 1.0);
+    let mut productivity: f32 = schooling::PRODUCTIVITY_MIN_SKILL_FACTOR +
+        (schooling::PRODUCTIVITY_MAX_SKILL_FACTOR - schooling::PRODUCTIVITY_MIN_SKILL_FACTOR) * skill_share;
     
     // Level 1 needs affect productivity
     if individual.food_water < 30.0 {
@@ -115,6 +168,9 @@ pub fn calculate_productivity(individual: &Individual) -> f32 {
     if individual.environment < 40.0 {
         productivity *= 0.8;
     }
+    if individual.health < thresholds::HEALTH_CRITICAL {
+        productivity *= 0.6;
+    }
     
     // Level 2 needs affect productivity
     if individual.is_need_level_active(2) {
@@ -0,0 +1,35 @@
+use spacetimedb::{ReducerContext, Table, SpacetimeType};
+use serde::{Deserialize, Serialize};
+
+// Crop cycle of one farm, following the planting and harvest calendar
+#[spacetimedb::table(name = farm_plot)]
+pub struct FarmPlot {
+    #[primary_key]
+    pub building_id: u32,
+    pub stage: CropStage,
+    pub planted_hour: u64,
+    pub growth: f32,                // 0-1, ripe at 1
+    pub crop_health: f32,           // 0-1, lost to drought and flood
+    pub last_harvest_hour: u64,
+    pub last_yield: f32,            // Meals brought in at the last harvest
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, SpacetimeType)]
+pub enum CropStage {
+    Fallow,
+    Growing,
+    Ripe,
+}
+
+// Whether a city can feed itself from its reserves
+#[spacetimedb::table(name = food_security)]
+pub struct FoodSecurity {
+    #[primary_key]
+    pub city_id: u32,
+    pub daily_consumption: f32,     // Meals eaten per day
+    pub days_of_supply: f32,
+    pub winter_target: f32,         // Stock wanted before winter sets in
+    pub famine: bool,
+    pub famine_since_hour: Option<u64>,
+    pub last_update_hour: u64,
+}
\ No newline at end of file
@@ -0,0 +1,50 @@
+use spacetimedb::{ReducerContext, Table, SpacetimeType};
+use serde::{Deserialize, Serialize};
+
+// Revenue a city has collected during the current week, settled by the weekly budget
+#[spacetimedb::table(name = budget_ledger)]
+pub struct BudgetLedger {
+    #[primary_key]
+    pub city_id: u32,
+    pub week_start_hour: u64,
+    pub income_tax: f32,        // Withheld from wages
+    pub property_tax: f32,
+    pub sales_tax: f32,         // On market trades in the city
+    pub tariffs: f32,           // On merchant cargo arriving over trade routes
+}
+
+// One week of a city's budget
+#[spacetimedb::table(name = budget_report)]
+pub struct BudgetReport {
+    #[primary_key]
+    pub id: u32,
+    pub city_id: u32,
+    pub week_start_hour: u64,
+    pub hour: u64,
+    
+    // Revenue
+    pub income_tax: f32,
+    pub property_tax: f32,
+    pub sales_tax: f32,
+    pub tariffs: f32,
+    pub total_revenue: f32,
+    
+    // Spending
+    pub services: f32,
+    pub public_works: f32,
+    pub policies: f32,
+    pub debt_interest: f32,
+    pub total_spending: f32,
+    
+    pub balance: f32,           // Revenue less spending
+    pub reserve: f32,           // tax_reserve after settling
+    pub debt: f32,              // Borrowing carried into next week
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, SpacetimeType)]
+pub enum RevenueLine {
+    IncomeTax,
+    PropertyTax,
+    SalesTax,
+    Tariff,
+}
\ No newline at end of file
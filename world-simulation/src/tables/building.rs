@@ -17,6 +17,7 @@ pub struct Building {
     pub cleanliness: f32,       // 0-100, affects environment and waste
     pub efficiency_level: u8,   // 0-5, upgrade stages
     pub prestige_level: u8,     // 0-5, upgrade stages
+    pub destroyed: bool,        // Unusable until rebuilt
     
     // Occupancy
     pub current_occupants: u32,
@@ -71,6 +72,7 @@ pub enum UpgradeType {
     Prestige,
     Capacity,
     Maintenance,
+    Reconstruction,
 }
 
 // Location capabilities (what needs can be fulfilled here)
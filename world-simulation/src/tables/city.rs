@@ -8,6 +8,8 @@ pub struct City {
     pub name: String,
     pub founded_hour: u64,
     pub population: u32,
+    pub latitude: f32,          // Degrees; sets the city's climate
+    pub longitude: f32,
     
     // Level 1: Infrastructure & Economy
     pub public_works: f32,      // 0-100, infrastructure health
@@ -118,4 +120,5 @@ pub enum PolicyType {
     EducationFunding,
     HealthcareFunding,
     SecurityFunding,
+    Rationing,
 }
\ No newline at end of file
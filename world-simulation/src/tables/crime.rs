@@ -0,0 +1,27 @@
+use spacetimedb::{ReducerContext, Table, SpacetimeType};
+use serde::{Deserialize, Serialize};
+
+// Offences committed by individuals
+#[spacetimedb::table(name = crime_event)]
+pub struct CrimeEvent {
+    #[primary_key]
+    pub id: u32,
+    pub city_id: u32,
+    pub offender_id: u32,
+    pub victim_id: Option<u32>,
+    pub location_id: u32,
+    pub hour: u64,
+    pub crime_type: CrimeType,
+    pub amount_taken: f32,      // Income moved from victim to offender
+    pub harm: f32,              // Safety lost by the victim
+    pub faction_id: Option<u32>, // Criminal faction that profited
+    pub arrested: bool,
+    pub arresting_officer_id: Option<u32>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, SpacetimeType)]
+pub enum CrimeType {
+    Theft,
+    Assault,
+    Fraud,
+}
@@ -0,0 +1,50 @@
+use spacetimedb::{ReducerContext, Table, SpacetimeType};
+use serde::{Deserialize, Serialize};
+
+// Area of a city struck by a disaster and what it did there
+#[spacetimedb::table(name = disaster_footprint)]
+pub struct DisasterFootprint {
+    #[primary_key]
+    pub id: u32,
+    pub natural_event_id: u32,
+    pub city_id: u32,
+    pub center_x: f32,
+    pub center_y: f32,
+    pub radius: f32,
+    pub intensity: f32,             // 0-1 after mitigation
+    pub mitigation_level: f32,      // 0-100% preparedness applied
+    pub hour: u64,
+    pub buildings_damaged: u32,
+    pub buildings_destroyed: u32,
+    pub injured: u32,
+    pub displaced: u32,
+}
+
+// Damage done to one building by a disaster
+#[spacetimedb::table(name = building_damage)]
+pub struct BuildingDamage {
+    #[primary_key]
+    pub id: u32,
+    pub footprint_id: u32,
+    pub building_id: u32,
+    pub maintenance_lost: f32,
+    pub destroyed: bool,
+    pub repair: RepairStatus,
+}
+
+// Someone injured by a disaster, until rescuers reach them
+#[spacetimedb::table(name = disaster_casualty)]
+pub struct DisasterCasualty {
+    #[primary_key]
+    pub id: u32,
+    pub footprint_id: u32,
+    pub individual_id: u32,
+    pub rescued: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, SpacetimeType)]
+pub enum RepairStatus {
+    Pending,
+    InProgress,
+    Repaired,
+}
\ No newline at end of file
@@ -0,0 +1,33 @@
+use spacetimedb::{ReducerContext, Table, SpacetimeType};
+use serde::{Deserialize, Serialize};
+use crate::types::*;
+
+// Skill level an individual has built up for a kind of job
+#[spacetimedb::table(name = skill)]
+pub struct Skill {
+    #[primary_key]
+    pub id: u32,
+    pub individual_id: u32,
+    pub job_type: JobType,
+    pub level: f32,             // 0-100, sets job eligibility and productivity
+    pub last_trained_hour: u64,
+}
+
+// A student's place at a school
+#[spacetimedb::table(name = enrollment)]
+pub struct Enrollment {
+    #[primary_key]
+    pub id: u32,
+    pub individual_id: u32,
+    pub school_id: u32,
+    pub kind: EnrollmentKind,
+    pub enrolled_hour: u64,
+    pub hours_attended: f32,
+    pub is_active: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, SpacetimeType)]
+pub enum EnrollmentKind {
+    Schooling,                  // Children: broad skills during the school day
+    Retraining(JobType),        // Adults: one job type in evening classes
+}
\ No newline at end of file
@@ -111,6 +111,7 @@ pub enum CityEventType {
     MilestoneReached,
     TradeAgreement,
     InfrastructureProject,
+    Unrest,
 }
 
 // Global simulation time
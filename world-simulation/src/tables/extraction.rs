@@ -0,0 +1,13 @@
+use spacetimedb::{ReducerContext, Table};
+
+// A mine, quarry or logging camp and the deposit it is working
+#[spacetimedb::table(name = extraction_site)]
+pub struct ExtractionSite {
+    #[primary_key]
+    pub building_id: u32,
+    pub city_id: u32,
+    pub deposit_id: Option<u32>,    // None until a workable deposit is found
+    pub extracted_total: f32,
+    pub last_output: f32,           // Brought out on the last working day
+    pub last_update_hour: u64,
+}
\ No newline at end of file
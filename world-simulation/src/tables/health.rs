@@ -0,0 +1,49 @@
+use spacetimedb::{ReducerContext, Table, SpacetimeType};
+use serde::{Deserialize, Serialize};
+
+// An individual's case of a disease
+#[spacetimedb::table(name = illness)]
+pub struct Illness {
+    #[primary_key]
+    pub id: u32,
+    pub individual_id: u32,
+    pub city_id: u32,
+    pub disease: DiseaseType,
+    pub stage: IllnessStage,
+    pub severity: f32,              // 0-100, health lost per day while symptomatic scales with this
+    pub infected_hour: u64,
+    pub symptoms_hour: u64,         // End of incubation
+    pub recovery_progress: f32,     // Hours of recovery accumulated
+    pub source_individual_id: Option<u32>, // None when seeded by an outbreak
+    pub treated_at: Option<u32>,    // Hospital building providing treatment
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, SpacetimeType)]
+pub enum DiseaseType {
+    Cold,
+    Fever,
+    Dysentery,
+    Plague,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, SpacetimeType)]
+pub enum IllnessStage {
+    Incubating,
+    Symptomatic,
+    Recovered,
+}
+
+// Daily epidemic curve point per city and disease
+#[spacetimedb::table(name = epidemic_snapshot)]
+pub struct EpidemicSnapshot {
+    #[primary_key]
+    pub id: u32,
+    pub city_id: u32,
+    pub disease: DiseaseType,
+    pub hour: u64,
+    pub susceptible: u32,
+    pub incubating: u32,
+    pub symptomatic: u32,
+    pub recovered: u32,
+    pub new_cases: u32,         // Infections since the previous snapshot
+}
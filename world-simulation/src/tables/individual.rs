@@ -18,6 +18,7 @@ pub struct Individual {
     pub intimacy: f32,
     pub rest: f32,
     pub waste: f32,
+    pub health: f32,            // Reduced by illness, restored by recovery and treatment
     
     // Level 2: Safety & Security (0-100, only active if Level 1 > 50%)
     pub threat: f32,
@@ -0,0 +1,36 @@
+use spacetimedb::{ReducerContext, Table, SpacetimeType};
+use serde::{Deserialize, Serialize};
+
+// A move between cities, one row per person travelling
+#[spacetimedb::table(name = migration_event)]
+pub struct MigrationEvent {
+    #[primary_key]
+    pub id: u32,
+    pub individual_id: u32,
+    pub household_head_id: u32,     // Whoever decided to move; equals individual_id for the head
+    pub from_city_id: u32,
+    pub to_city_id: u32,
+    pub from_home_id: Option<u32>,
+    pub to_home_id: u32,
+    pub reason: MigrationReason,
+    pub departed_hour: u64,
+    pub arrival_hour: u64,
+    pub arrived: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, SpacetimeType)]
+pub enum MigrationReason {
+    UnmetNeeds,
+    Unemployment,
+    Disaster,
+}
+
+// Accumulated pressure to leave; builds while push factors persist and fades otherwise
+#[spacetimedb::table(name = migration_pressure)]
+pub struct MigrationPressure {
+    #[primary_key]
+    pub individual_id: u32,
+    pub pressure: f32,              // Moves once above the threshold
+    pub main_reason: MigrationReason,
+    pub last_evaluated_hour: u64,
+}
\ No newline at end of file
@@ -2,8 +2,32 @@ pub mod individual;
 pub mod building;
 pub mod city;
 pub mod events;
+pub mod crime;
+pub mod health;
+pub mod education;
+pub mod migration;
+pub mod damage;
+pub mod storage;
+pub mod agriculture;
+pub mod budget;
+pub mod public_works;
+pub mod unrest;
+pub mod preparedness;
+pub mod extraction;
 
 pub use individual::*;
 pub use building::*;
 pub use city::*;
-pub use events::*;
\ No newline at end of file
+pub use events::*;
+pub use crime::*;
+pub use health::*;
+pub use education::*;
+pub use migration::*;
+pub use damage::*;
+pub use storage::*;
+pub use agriculture::*;
+pub use budget::*;
+pub use public_works::*;
+pub use unrest::*;
+pub use preparedness::*;
+pub use extraction::*;
\ No newline at end of file
@@ -0,0 +1,87 @@
+use spacetimedb::{ReducerContext, Table, SpacetimeType};
+use serde::{Deserialize, Serialize};
+
+// How far a city's residents believe its disaster warnings
+#[spacetimedb::table(name = warning_trust)]
+pub struct WarningTrust {
+    #[primary_key]
+    pub city_id: u32,
+    pub trust: f32,                 // 0-100
+    pub warnings_reviewed: u32,
+    pub false_alarms: u32,
+    pub last_review_hour: u64,
+}
+
+// What one resident did about a warning
+#[spacetimedb::table(name = warning_response)]
+pub struct WarningResponse {
+    #[primary_key]
+    pub id: u32,
+    pub warning_id: u32,
+    pub individual_id: u32,
+    pub city_id: u32,
+    pub action: PreparednessAction,
+    pub shelter_id: Option<u32>,    // Where evacuees were sent
+    pub stocked_food: bool,
+    pub meals_stored: f32,          // Portions laid in and not yet eaten
+    pub hour: u64,
+    pub released: bool,             // The warning has passed
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, SpacetimeType)]
+pub enum PreparednessAction {
+    Ignore,
+    StockFood,
+    StayHome,
+    Evacuate,
+}
+
+// A building the city has set aside to take in evacuees
+#[spacetimedb::table(name = shelter)]
+pub struct Shelter {
+    #[primary_key]
+    pub building_id: u32,
+    pub city_id: u32,
+    pub capacity: u32,
+    pub designated_hour: u64,
+}
+
+// What a city spent getting ready for one warning
+#[spacetimedb::table(name = warning_preparation)]
+pub struct WarningPreparation {
+    #[primary_key]
+    pub id: u32,
+    pub warning_id: u32,
+    pub city_id: u32,
+    pub spent: f32,
+    pub mitigation_before: f32,
+    pub mitigation_after: f32,
+    pub shelters: u32,
+    pub hour: u64,
+}
+
+// How a warning's response compared with what actually happened
+#[spacetimedb::table(name = warning_review)]
+pub struct WarningReview {
+    #[primary_key]
+    pub id: u32,
+    pub warning_id: u32,
+    pub city_id: u32,
+    pub natural_event_id: Option<u32>,
+    pub false_alarm: bool,
+    pub residents: u32,
+    pub heeded: u32,
+    pub evacuated: u32,
+    pub stayed_home: u32,
+    pub stocked_food: u32,
+    pub exposed_heeded: u32,        // Residents who acted but were still inside the footprint
+    pub exposed_ignored: u32,       // Residents who ignored the warning and were inside the footprint
+    pub injured: u32,
+    pub displaced: u32,
+    pub preparation_spent: f32,
+    pub mitigation_level: f32,
+    pub trust_before: f32,
+    pub trust_after: f32,
+    pub hour: u64,
+    pub finding: String,
+}
\ No newline at end of file
@@ -0,0 +1,48 @@
+use spacetimedb::{ReducerContext, Table, SpacetimeType};
+use serde::{Deserialize, Serialize};
+
+// An infrastructure project the city has proposed, funded from its budget and built by crews
+#[spacetimedb::table(name = public_works_project)]
+pub struct PublicWorksProject {
+    #[primary_key]
+    pub id: u32,
+    pub city_id: u32,
+    pub project_type: ProjectType,
+    pub status: ProjectStatus,
+    pub cost: f32,                  // Total budget the project needs
+    pub funds: f32,                 // Allocated and not yet spent
+    pub spent: f32,
+    pub work_hours_needed: f32,
+    pub work_hours_completed: f32,
+    pub workers_needed: u32,
+    pub proposed_hour: u64,
+    pub completed_hour: Option<u64>,
+    pub last_milestone: u8,         // Percent of work last announced
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, SpacetimeType)]
+pub enum ProjectType {
+    Roads,      // Restores public works and makes trade routes safer
+    Sewers,     // Restores public works and improves sanitation quality
+    Walls,      // Raises city safety
+    Aqueduct,   // Extends sanitation coverage
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, SpacetimeType)]
+pub enum ProjectStatus {
+    Proposed,           // Waiting for its first funds
+    UnderConstruction,
+    Stalled,            // Out of funds part-way through
+    Completed,
+}
+
+// A resident working on a project's crew
+#[spacetimedb::table(name = public_works_crew)]
+pub struct PublicWorksCrew {
+    #[primary_key]
+    pub id: u32,
+    pub project_id: u32,
+    pub individual_id: u32,
+    pub joined_hour: u64,
+    pub is_active: bool,
+}
\ No newline at end of file
@@ -0,0 +1,28 @@
+use spacetimedb::{ReducerContext, Table, SpacetimeType};
+use crate::types::*;
+
+// A city's warehouse for one resource
+#[spacetimedb::table(name = warehouse)]
+pub struct Warehouse {
+    #[primary_key]
+    pub id: u32,
+    pub city_id: u32,
+    pub resource_type: ResourceType,
+    pub stock: f32,                 // Food is counted in meals
+    pub capacity: f32,
+    pub spoilage_rate: f32,         // Share of stock lost per day
+    pub spoiled_total: f32,
+    pub last_update_hour: u64,
+}
+
+// Daily food release while a city's rationing policy is in force
+#[spacetimedb::table(name = ration_book)]
+pub struct RationBook {
+    #[primary_key]
+    pub city_id: u32,
+    pub day: u64,
+    pub portion: f32,               // Share of a normal meal handed out
+    pub daily_quota: f32,           // Meals released from the warehouse today
+    pub served_today: f32,
+    pub queued_today: u32,          // Turned away to wait for tomorrow's release
+}
\ No newline at end of file
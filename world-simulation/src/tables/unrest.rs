@@ -0,0 +1,52 @@
+use spacetimedb::{ReducerContext, Table, SpacetimeType};
+use serde::{Deserialize, Serialize};
+
+// A city's unrest meter and how far it has escalated
+#[spacetimedb::table(name = city_unrest)]
+pub struct CityUnrest {
+    #[primary_key]
+    pub city_id: u32,
+    pub level: f32,                 // 0-100
+    pub stage: UnrestStage,
+    pub repression: f32,            // 0-100, recent crackdowns; fades over time
+    pub instigator_faction_id: Option<u32>, // Faction currently stirring the crowds
+    pub stage_since_hour: u64,
+    pub last_update_hour: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Serialize, Deserialize, SpacetimeType)]
+pub enum UnrestStage {
+    Calm,
+    Protest,
+    Strike,     // Workplaces walk out and stop producing
+    Riot,       // Buildings are damaged and people hurt
+    Rebellion,  // Rebels contest control of the city
+}
+
+// A day of unrest in a city and what drove it
+#[spacetimedb::table(name = unrest_event)]
+pub struct UnrestEvent {
+    #[primary_key]
+    pub id: u32,
+    pub city_id: u32,
+    pub hour: u64,
+    pub stage: UnrestStage,
+    pub level: f32,
+    pub participants: u32,
+    pub cause: String,              // The pressures behind it, largest first
+    pub faction_id: Option<u32>,
+    pub buildings_damaged: u32,
+    pub injured: u32,
+}
+
+// A workplace whose staff have walked out
+#[spacetimedb::table(name = strike)]
+pub struct Strike {
+    #[primary_key]
+    pub id: u32,
+    pub city_id: u32,
+    pub building_id: u32,
+    pub strikers: u32,
+    pub started_hour: u64,
+    pub ended_hour: Option<u64>,
+}
\ No newline at end of file
@@ -18,6 +18,7 @@ pub enum IndividualStatus {
     InTransit(StatusData),
     Maintaining(StatusData),
     UsingFacilities(StatusData),
+    Studying(StatusData),
     Idle,
 }
 
@@ -60,6 +61,10 @@ pub enum JobType {
     Culture,
     Utilities,
     Government,
+    Agriculture,
+    Mining,
+    Quarrying,
+    Logging,
 }
 
 // Individual specialized roles (Level 5 self-actualization)
@@ -82,6 +87,8 @@ pub enum ResourceType {
     Culture,
     Science,
     Healthcare,
+    RawMaterials,
+    Energy,
 }
 
 // Actions individuals can take
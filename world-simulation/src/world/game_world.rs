@@ -34,6 +34,8 @@ pub struct WorldStats {
     pub total_trades: u64,
     pub total_conflicts: u32,
     pub total_disasters: u32,
+    pub total_migrations: u64,      // Individuals who settled in another city
+    pub disaster_migrations: u64,   // Of those, people displaced by disasters
     pub total_narrative_events: u64,
     pub average_happiness: f32,
     pub average_prosperity: f32,
@@ -86,6 +88,8 @@ pub fn create_game_world(
         total_trades: 0,
         total_conflicts: 0,
         total_disasters: 0,
+        total_migrations: 0,
+        disaster_migrations: 0,
         total_narrative_events: 0,
         average_happiness: 50.0,
         average_prosperity: 50.0,
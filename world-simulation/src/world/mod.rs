@@ -91,8 +91,8 @@ pub fn calculate_season(day_of_year: u16) -> Season {
 
 // Helper function to calculate season from hour
 pub fn calculate_season_from_hour(hour: u64) -> Season {
-    let day_of_year = ((hour / 24) % 365) as u16 + 1;
-    calculate_season(day_of_year)
+    let (day_of_year, _) = crate::natural::climate::calendar(hour);
+    calculate_season(day_of_year as u16)
 }
 
 // Get seasonal modifiers for various systems